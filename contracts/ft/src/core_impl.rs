@@ -1,10 +1,10 @@
 use crate::*;
 
-const GAS_FOR_RESOLVE_TRANSFER: Gas = 5_000_000_000_000;
-const GAS_FOR_FT_TRANSFER_CALL: Gas = 25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER;
-
 const NO_DEPOSIT: Balance = 0;
 
+/// Gas kept for the rest of ft_transfer_call once the promises are created
+const GAS_FOR_FT_TRANSFER_CALL: Gas = 25_000_000_000_000;
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct FungibleToken {
     /// AccountID -> Account balance.
@@ -12,6 +12,18 @@ pub struct FungibleToken {
 
     /// Total supply of the FT token.
     pub total_supply: Balance,
+
+    /// Cap on the total supply, set to the initial supply at init since there is no later mint path.
+    pub max_supply: Balance,
+
+    /// Tokens minted since deployment.
+    pub minted_total: Balance,
+
+    /// Tokens burnt since deployment.
+    pub burned_total: Balance,
+
+    /// Gas split and minimum prepaid gas for ft_transfer_call.
+    pub gas_config: GasConfig,
 }
 
 pub trait FungibleTokenCore {
@@ -39,6 +51,7 @@ impl FungibleTokenCore for FungibleToken {
         assert_one_yocto();
 
         let sender_id = env::predecessor_account_id();
+        let memo = validate_memo(memo);
 
         self.internal_transfer(&sender_id, &receiver_id, amount, memo);
     }
@@ -52,14 +65,26 @@ impl FungibleTokenCore for FungibleToken {
     ) -> PromiseOrValue<U128> {
         assert_one_yocto();
         require!(
-            env::prepaid_gas() > GAS_FOR_FT_TRANSFER_CALL + GAS_FOR_RESOLVE_TRANSFER,
-            "More gas is required"
+            env::prepaid_gas() >= self.gas_config.min_attached_gas,
+            format!(
+                "Not enough gas attached, ft_transfer_call requires at least {} gas",
+                self.gas_config.min_attached_gas
+            )
         );
 
         let sender_id = env::predecessor_account_id();
+        let memo = validate_memo(memo);
 
         self.internal_transfer(&sender_id, &receiver_id, amount, memo);
 
+        // The receiver gets all the gas which isn't needed for the callback, on_transfer_gas at least
+        let on_transfer_gas = std::cmp::max(
+            self.gas_config.on_transfer_gas,
+            env::prepaid_gas().saturating_sub(
+                env::used_gas() + self.gas_config.resolve_gas + GAS_FOR_FT_TRANSFER_CALL,
+            ),
+        );
+
         // Initiating receiver's call and the callback
 
         ext_fungible_token_receiver::ft_on_transfer(
@@ -68,7 +93,7 @@ impl FungibleTokenCore for FungibleToken {
             msg,
             &receiver_id,
             NO_DEPOSIT,
-            env::prepaid_gas() - GAS_FOR_FT_TRANSFER_CALL,
+            on_transfer_gas,
         )
         .then(ext_self::ft_resolve_transfer(
             sender_id,
@@ -76,7 +101,7 @@ impl FungibleTokenCore for FungibleToken {
             amount.into(),
             &env::current_account_id(),
             NO_DEPOSIT,
-            GAS_FOR_RESOLVE_TRANSFER,
+            self.gas_config.resolve_gas,
         ))
         .into()
     }
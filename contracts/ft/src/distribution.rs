@@ -0,0 +1,453 @@
+use crate::*;
+
+pub type DistributionId = u64;
+
+pub type MerkleHash = [u8; 32];
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Distribution {
+    /// A/c which funded the distribution and receives unclaimed funds after expiry
+    pub owner_id: AccountId,
+    pub merkle_root: MerkleHash,
+    pub total: Balance,
+    pub claimed: Balance,
+    /// Unix epoch in nanoseconds
+    pub expires_at: u64,
+    /// NEAR attached at creation used to register claimers who aren't registered yet
+    pub registration_funds: Balance,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonDistribution {
+    pub distribution_id: DistributionId,
+    pub owner_id: AccountId,
+    pub merkle_root: MerkleHash,
+    pub total: U128,
+    pub claimed: U128,
+    pub expires_at: u64,
+    pub registration_funds: U128,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DistributionLog {
+    pub distribution_id: DistributionId,
+    pub account_id: AccountId,
+    pub amount: U128,
+}
+
+/// Leaf of the distribution tree is sha256("<account_id>:<amount>"),
+/// ':' can't be part of an A/c id so the encoding is unambiguous
+pub(crate) fn merkle_leaf(account_id: &AccountId, amount: Balance) -> MerkleHash {
+    hash_bytes(format!("{}:{}", account_id, amount).as_bytes())
+}
+
+/// Nodes are hashed as sha256(min(a, b) ++ max(a, b)) so proofs don't need to carry the position of the sibling
+pub(crate) fn merkle_parent(a: &MerkleHash, b: &MerkleHash) -> MerkleHash {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+
+    let mut node = Vec::with_capacity(64);
+    node.extend_from_slice(left);
+    node.extend_from_slice(right);
+
+    hash_bytes(&node)
+}
+
+/// Walks the proof from leaf to root keeping only the running hash, i.e. constant space
+pub(crate) fn verify_merkle_proof(
+    root: &MerkleHash,
+    leaf: MerkleHash,
+    proof: &[MerkleHash],
+) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf, |node, sibling| merkle_parent(&node, sibling));
+
+    &computed == root
+}
+
+fn hash_bytes(bytes: &[u8]) -> MerkleHash {
+    let mut hash = MerkleHash::default();
+    hash.copy_from_slice(&env::sha256(bytes));
+    hash
+}
+
+/**********************************/
+/*  MERKLE CLAIM DISTRIBUTIONS    */
+/**********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, attach NEAR to cover registration of claimers who aren't registered yet
+    ///
+    /// Moves `total` from the owner's balance into the distribution pool, expires_at is in nanoseconds
+    #[payable]
+    pub fn create_distribution(
+        &mut self,
+        merkle_root: MerkleHash,
+        total: U128,
+        expires_at: u64,
+    ) -> DistributionId {
+        self.assert_owner();
+
+        let total: Balance = total.into();
+        require!(total > 0, "The amount should be a positive number");
+        require!(
+            expires_at > env::block_timestamp(),
+            "Expiry of the distribution should be in the future"
+        );
+
+        let owner_id = env::predecessor_account_id();
+        let escrow_id = self.internal_escrow_account_id();
+
+        let distribution_id = self.next_distribution_id;
+        self.next_distribution_id += 1;
+
+        self.token.internal_transfer(
+            &owner_id,
+            &escrow_id,
+            total,
+            Some(format!("distribution {}", distribution_id)),
+        );
+
+        self.distributions.insert(
+            &distribution_id,
+            &Distribution {
+                owner_id: owner_id.clone(),
+                merkle_root,
+                total,
+                claimed: 0,
+                expires_at,
+                registration_funds: env::attached_deposit(),
+            },
+        );
+
+        CatchEventLogVariant::DistributionCreated(DistributionLog {
+            distribution_id,
+            account_id: owner_id,
+            amount: total.into(),
+        })
+        .emit();
+
+        distribution_id
+    }
+
+    /// Claims `amount` for the predecessor if the proof for ("<account_id>:<amount>") matches the merkle root,
+    /// registers the claimer with the distribution's registration funds if needed
+    pub fn claim(
+        &mut self,
+        distribution_id: DistributionId,
+        amount: U128,
+        proof: Vec<MerkleHash>,
+    ) -> U128 {
+        let mut distribution = self
+            .distributions
+            .get(&distribution_id)
+            .unwrap_or_else(|| env::panic(b"Distribution does not exist"));
+
+        require!(
+            env::block_timestamp() < distribution.expires_at,
+            "Distribution has expired"
+        );
+
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        let leaf = merkle_leaf(&account_id, amount);
+
+        require!(
+            verify_merkle_proof(&distribution.merkle_root, leaf, &proof),
+            "Invalid merkle proof"
+        );
+
+        require!(
+            self.claimed_leaves.insert(&(distribution_id, leaf)),
+            "Already claimed"
+        );
+
+        distribution.claimed = distribution
+            .claimed
+            .checked_add(amount)
+            .filter(|claimed| claimed <= &distribution.total)
+            .unwrap_or_else(|| env::panic(b"Distribution doesn't have enough funds left"));
+
+        if !self.token.accounts.contains_key(&account_id) {
+            let registration_cost = self.storage_balance_bounds().min.0;
+            distribution.registration_funds = distribution
+                .registration_funds
+                .checked_sub(registration_cost)
+                .unwrap_or_else(|| {
+                    env::panic(b"Distribution can't pay for registration, please call storage_deposit first")
+                });
+
+            self.internal_register_account(&account_id, &distribution.owner_id, registration_cost);
+        }
+
+        self.distributions.insert(&distribution_id, &distribution);
+
+        let escrow_id = env::current_account_id();
+        self.token.internal_transfer(
+            &escrow_id,
+            &account_id,
+            amount,
+            Some(format!("distribution {}", distribution_id)),
+        );
+
+        CatchEventLogVariant::DistributionClaimed(DistributionLog {
+            distribution_id,
+            account_id,
+            amount: amount.into(),
+        })
+        .emit();
+
+        amount.into()
+    }
+
+    /// Returns unclaimed tokens and unused registration funds to the distribution owner once it has expired,
+    /// anyone can call it as funds can only go back to the owner
+    pub fn sweep_distribution(&mut self, distribution_id: DistributionId) -> U128 {
+        let distribution = self
+            .distributions
+            .get(&distribution_id)
+            .unwrap_or_else(|| env::panic(b"Distribution does not exist"));
+
+        require!(
+            env::block_timestamp() >= distribution.expires_at,
+            "Distribution has not expired yet"
+        );
+
+        self.distributions.remove(&distribution_id);
+
+        let unclaimed = distribution.total - distribution.claimed;
+
+        if unclaimed > 0 {
+            let escrow_id = env::current_account_id();
+
+            // owner may have unregistered in the meantime
+            require!(
+                self.token.accounts.contains_key(&distribution.owner_id),
+                "Distribution owner is not registered"
+            );
+
+            self.token.internal_transfer(
+                &escrow_id,
+                &distribution.owner_id,
+                unclaimed,
+                Some(format!("distribution {} sweep", distribution_id)),
+            );
+        }
+
+        if distribution.registration_funds > 0 {
+            Promise::new(distribution.owner_id.clone()).transfer(distribution.registration_funds);
+        }
+
+        CatchEventLogVariant::DistributionSwept(DistributionLog {
+            distribution_id,
+            account_id: distribution.owner_id,
+            amount: unclaimed.into(),
+        })
+        .emit();
+
+        unclaimed.into()
+    }
+
+    pub fn get_distribution(&self, distribution_id: DistributionId) -> Option<JsonDistribution> {
+        self.distributions
+            .get(&distribution_id)
+            .map(|distribution| JsonDistribution {
+                distribution_id,
+                owner_id: distribution.owner_id,
+                merkle_root: distribution.merkle_root,
+                total: distribution.total.into(),
+                claimed: distribution.claimed.into(),
+                expires_at: distribution.expires_at,
+                registration_funds: distribution.registration_funds.into(),
+            })
+    }
+
+    pub fn is_claimed(
+        &self,
+        distribution_id: DistributionId,
+        account_id: ValidAccountId,
+        amount: U128,
+    ) -> bool {
+        let leaf = merkle_leaf(account_id.as_ref(), amount.into());
+        self.claimed_leaves.contains(&(distribution_id, leaf))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod distribution_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env, VMContext};
+
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+    const EXPIRES_AT: u64 = 1_000;
+
+    /// Known tree of 4 leaves
+    ///
+    ///            root
+    ///         /        \
+    ///      n01          n23
+    ///     /   \        /   \
+    ///  alice  bob   carol  nft
+    struct Tree {
+        leaves: Vec<MerkleHash>,
+        n01: MerkleHash,
+        n23: MerkleHash,
+        root: MerkleHash,
+    }
+
+    fn tree() -> Tree {
+        let leaves = vec![
+            merkle_leaf(&alice().into(), 100),
+            merkle_leaf(&bob().into(), 200),
+            merkle_leaf(&carol().into(), 300),
+            merkle_leaf(&nft().into(), 400),
+        ];
+        let n01 = merkle_parent(&leaves[0], &leaves[1]);
+        let n23 = merkle_parent(&leaves[2], &leaves[3]);
+        let root = merkle_parent(&n01, &n23);
+
+        Tree {
+            leaves,
+            n01,
+            n23,
+            root,
+        }
+    }
+
+    fn context_at(account_id: ValidAccountId, deposit: Balance, timestamp: u64) -> VMContext {
+        let mut context = get_context(account_id.to_string(), deposit);
+        context.block_timestamp = timestamp;
+        context
+    }
+
+    fn setup(registration_funds: Balance) -> (Contract, Tree) {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(alice()));
+
+        let tree = tree();
+        testing_env!(get_context(dex().to_string(), registration_funds));
+        assert_eq!(
+            contract.create_distribution(tree.root, U128(1_000), EXPIRES_AT),
+            0
+        );
+
+        (contract, tree)
+    }
+
+    #[test]
+    fn known_tree_proofs() {
+        testing_env!(get_context(dex().to_string(), 0));
+        let tree = tree();
+
+        assert!(verify_merkle_proof(
+            &tree.root,
+            tree.leaves[0],
+            &[tree.leaves[1], tree.n23]
+        ));
+        assert!(verify_merkle_proof(
+            &tree.root,
+            tree.leaves[3],
+            &[tree.leaves[2], tree.n01]
+        ));
+        // wrong sibling
+        assert!(!verify_merkle_proof(
+            &tree.root,
+            tree.leaves[0],
+            &[tree.leaves[2], tree.n23]
+        ));
+        // leaf with a different amount
+        assert!(!verify_merkle_proof(
+            &tree.root,
+            merkle_leaf(&alice().into(), 101),
+            &[tree.leaves[1], tree.n23]
+        ));
+    }
+
+    #[test]
+    fn claim_registered_and_unregistered_accounts() {
+        let (mut contract, tree) = setup(STORAGE_COST);
+        let owner_balance = contract.ft_balance_of(dex()).0;
+
+        testing_env!(context_at(alice(), 0, 10));
+        contract.claim(0, U128(100), vec![tree.leaves[1], tree.n23]);
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"distribution_claimed","data":{"distribution_id":0,"account_id":"alice.near","amount":"100"}}"#
+        );
+        assert_eq!(contract.ft_balance_of(alice()).0, 100);
+        assert!(contract.is_claimed(0, alice(), U128(100)));
+
+        // bob isn't registered, registration is paid from the distribution
+        testing_env!(context_at(bob(), 0, 10));
+        contract.claim(0, U128(200), vec![tree.leaves[0], tree.n23]);
+        assert_eq!(contract.ft_balance_of(bob()).0, 200);
+
+        let distribution = contract.get_distribution(0).unwrap();
+        assert_eq!(distribution.claimed.0, 300);
+        assert_eq!(distribution.registration_funds.0, 0);
+
+        // after expiry the rest goes back to the owner
+        testing_env!(context_at(carol(), 0, EXPIRES_AT));
+        assert_eq!(contract.sweep_distribution(0).0, 700);
+        assert_eq!(contract.ft_balance_of(dex()).0, owner_balance + 700);
+        // nothing is left in escrow
+        assert_eq!(contract.token.ft_balance_of(env::current_account_id()).0, 0);
+        assert!(contract.get_distribution(0).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Already claimed")]
+    fn double_claim_fails() {
+        let (mut contract, tree) = setup(0);
+
+        testing_env!(context_at(alice(), 0, 10));
+        contract.claim(0, U128(100), vec![tree.leaves[1], tree.n23]);
+        contract.claim(0, U128(100), vec![tree.leaves[1], tree.n23]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid merkle proof")]
+    fn claim_with_wrong_amount_fails() {
+        let (mut contract, tree) = setup(0);
+
+        testing_env!(context_at(alice(), 0, 10));
+        contract.claim(0, U128(400), vec![tree.leaves[1], tree.n23]);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Distribution can't pay for registration, please call storage_deposit first"
+    )]
+    fn claim_without_registration_funds_fails() {
+        let (mut contract, tree) = setup(0);
+
+        testing_env!(context_at(bob(), 0, 10));
+        contract.claim(0, U128(200), vec![tree.leaves[0], tree.n23]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Distribution has expired")]
+    fn claim_after_expiry_fails() {
+        let (mut contract, tree) = setup(0);
+
+        testing_env!(context_at(alice(), 0, EXPIRES_AT));
+        contract.claim(0, U128(100), vec![tree.leaves[1], tree.n23]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Distribution has not expired yet")]
+    fn sweep_before_expiry_fails() {
+        let (mut contract, _) = setup(0);
+
+        testing_env!(context_at(dex(), 0, EXPIRES_AT - 1));
+        contract.sweep_distribution(0);
+    }
+}
@@ -9,6 +9,10 @@
 
 use std::fmt;
 
+use crate::distribution::DistributionLog;
+use crate::staking::NftStakeLog;
+use crate::streams::StreamLog;
+use crate::treasury::BuybackLog;
 use crate::*;
 
 pub const FT_STANDARD_NAME: &str = "nep141";
@@ -111,6 +115,90 @@ impl FtBurnLog {
     }
 }
 
+/*********************************************/
+/*  CATCH SPECIFIC EVENTS (NON NEP-141)      */
+/*********************************************/
+
+pub const CATCH_STANDARD_NAME: &str = "catch";
+
+/// Standard of the registration events, kept apart so indexers can follow storage alone
+pub const CATCH_STORAGE_STANDARD_NAME: &str = "catch_storage";
+
+pub const CATCH_EVENT_SPEC: &str = "1.0.0";
+
+/// Enum that represents the Catch specific events emitted by this contract.
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[serde(crate = "near_sdk::serde")]
+#[non_exhaustive]
+pub enum CatchEventLogVariant {
+    ExemptAccountAdded(AccountLog),
+    ExemptAccountRemoved(AccountLog),
+    NonCirculatingAccountAdded(AccountLog),
+    NonCirculatingAccountRemoved(AccountLog),
+    TreasuryUpdated(AccountLog),
+    CatchBuyback(BuybackLog),
+    DistributionCreated(DistributionLog),
+    DistributionClaimed(DistributionLog),
+    DistributionSwept(DistributionLog),
+    StreamCreated(StreamLog),
+    StreamWithdrawn(StreamLog),
+    StreamCancelled(StreamLog),
+    AccountRegistered(AccountRegisteredLog),
+    AccountUnregistered(AccountUnregisteredLog),
+    DepositRefunded(DepositRefundedLog),
+    NftStaked(NftStakeLog),
+    NftUnstaked(NftStakeLog),
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CatchEventLog {
+    pub standard: String,
+    pub version: String,
+
+    #[serde(flatten)]
+    pub event: CatchEventLogVariant,
+}
+
+impl fmt::Display for CatchEventLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "EVENT_JSON:{}",
+            &serde_json::to_string(self).map_err(|_| fmt::Error)?
+        ))
+    }
+}
+
+impl CatchEventLogVariant {
+    fn standard(&self) -> &'static str {
+        match self {
+            Self::AccountRegistered(_)
+            | Self::AccountUnregistered(_)
+            | Self::DepositRefunded(_) => CATCH_STORAGE_STANDARD_NAME,
+            _ => CATCH_STANDARD_NAME,
+        }
+    }
+
+    /// Wraps the variant in the Catch envelope and logs it
+    pub fn emit(self) {
+        let event_log = CatchEventLog {
+            standard: self.standard().to_string(),
+            version: CATCH_EVENT_SPEC.to_string(),
+            event: self,
+        };
+
+        env::log(event_log.to_string().as_bytes());
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountLog {
+    pub account_id: AccountId,
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod events {
@@ -0,0 +1,131 @@
+use crate::*;
+
+/****************************************************/
+/*  FEE & BURN EXEMPTIONS FOR CATCH SYSTEM ACCOUNTS  */
+/****************************************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Exempts the A/c (vesting pool, staking pool, marketplace settlement etc.) from transfer fees and burns,
+    /// returns false if the A/c was already exempted
+    #[payable]
+    pub fn add_exempt_account(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        let added = self.exempt_accounts.insert(&account_id);
+
+        if added {
+            CatchEventLogVariant::ExemptAccountAdded(AccountLog { account_id }).emit();
+        }
+
+        added
+    }
+
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Removes the A/c from the exemption list, returns false if the A/c was not exempted
+    #[payable]
+    pub fn remove_exempt_account(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        let removed = self.exempt_accounts.remove(&account_id);
+
+        if removed {
+            CatchEventLogVariant::ExemptAccountRemoved(AccountLog { account_id }).emit();
+        }
+
+        removed
+    }
+
+    /// Returns true if the given A/c is exempted from transfer fees and burns
+    pub fn is_exempt_account(&self, account_id: ValidAccountId) -> bool {
+        self.exempt_accounts.contains(account_id.as_ref())
+    }
+
+    /// Returns paginated view of exempted A/c's
+    pub fn get_exempt_accounts(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.exempt_accounts
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Fee and burn logic must skip a movement of tokens if this returns true,
+    /// it checks both sides so that flows into and out of system A/c's are never taxed.
+    ///
+    /// Refunds in ft_resolve_transfer move tokens back from receiver to sender and hence are covered as well
+    pub fn internal_is_transfer_exempt(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+    ) -> bool {
+        self.exempt_accounts.contains(sender_id) || self.exempt_accounts.contains(receiver_id)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod exemption_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const ONE_YOCTO: Balance = 1;
+
+    #[test]
+    fn add_and_remove_exempt_account() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        let mut contract = create_contract();
+
+        assert!(contract.add_exempt_account(alice()));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"exempt_account_added","data":{"account_id":"alice.near"}}"#
+        );
+        assert!(!contract.add_exempt_account(alice()));
+        assert!(contract.is_exempt_account(alice()));
+        assert_eq!(
+            contract.get_exempt_accounts(None, None),
+            vec![alice().to_string()]
+        );
+
+        assert!(contract.internal_is_transfer_exempt(&alice().into(), &bob().into()));
+        assert!(contract.internal_is_transfer_exempt(&bob().into(), &alice().into()));
+        assert!(!contract.internal_is_transfer_exempt(&bob().into(), &carol().into()));
+
+        assert!(contract.remove_exempt_account(alice()));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"exempt_account_removed","data":{"account_id":"alice.near"}}"#
+        );
+        assert!(!contract.remove_exempt_account(alice()));
+        assert!(!contract.is_exempt_account(alice()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only Owner of the Contract can call this method")]
+    fn add_exempt_account_fails_for_non_owner() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), ONE_YOCTO));
+        contract.add_exempt_account(carol());
+    }
+}
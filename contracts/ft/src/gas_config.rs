@@ -0,0 +1,161 @@
+use crate::*;
+
+const DEFAULT_ON_TRANSFER_GAS: Gas = 25_000_000_000_000;
+const DEFAULT_RESOLVE_GAS: Gas = 5_000_000_000_000;
+const DEFAULT_MIN_ATTACHED_GAS: Gas = 40_000_000_000_000;
+
+/// Gas split used by ft_transfer_call
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasConfig {
+    /// Minimum gas attached to ft_on_transfer of the receiver, which gets all the prepaid gas
+    /// the callback and ft_transfer_call itself don't need
+    pub on_transfer_gas: Gas,
+
+    /// Gas attached to ft_resolve_transfer, refunds fail if this is too low
+    pub resolve_gas: Gas,
+
+    /// Minimum prepaid gas for ft_transfer_call, has to cover both promises and the call itself
+    pub min_attached_gas: Gas,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            on_transfer_gas: DEFAULT_ON_TRANSFER_GAS,
+            resolve_gas: DEFAULT_RESOLVE_GAS,
+            min_attached_gas: DEFAULT_MIN_ATTACHED_GAS,
+        }
+    }
+}
+
+/*********************************/
+/*  GAS CONFIG FOR TRANSFER CALL */
+/*********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    #[payable]
+    pub fn set_gas_config(&mut self, gas_config: GasConfig) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            gas_config.resolve_gas > 0 && gas_config.on_transfer_gas > 0,
+            "Gas for the promises should be a positive number"
+        );
+        require!(
+            gas_config.min_attached_gas > gas_config.on_transfer_gas + gas_config.resolve_gas,
+            "min_attached_gas should be greater than on_transfer_gas + resolve_gas"
+        );
+
+        self.token.gas_config = gas_config;
+    }
+
+    /// Returns the gas split used by ft_transfer_call, SDKs should attach at least min_attached_gas
+    pub fn get_gas_config(&self) -> GasConfig {
+        self.token.gas_config.clone()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod gas_config_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+    const TGAS: Gas = 1_000_000_000_000;
+
+    fn gas_config(on_transfer: u64, resolve: u64, min_attached: u64) -> GasConfig {
+        GasConfig {
+            on_transfer_gas: on_transfer * TGAS,
+            resolve_gas: resolve * TGAS,
+            min_attached_gas: min_attached * TGAS,
+        }
+    }
+
+    #[test]
+    fn set_and_get_gas_config() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        let mut contract = create_contract();
+        assert_eq!(contract.get_gas_config(), GasConfig::default());
+
+        contract.set_gas_config(gas_config(50, 10, 80));
+        assert_eq!(contract.get_gas_config(), gas_config(50, 10, 80));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "min_attached_gas should be greater than on_transfer_gas + resolve_gas"
+    )]
+    fn set_gas_config_fails_with_low_minimum() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        let mut contract = create_contract();
+
+        contract.set_gas_config(gas_config(50, 10, 60));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only Owner of the Contract can call this method")]
+    fn set_gas_config_fails_for_non_owner() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), ONE_YOCTO));
+        contract.set_gas_config(gas_config(50, 10, 80));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Not enough gas attached, ft_transfer_call requires at least 80000000000000 gas"
+    )]
+    fn ft_transfer_call_fails_with_insufficient_gas() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(nft()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.set_gas_config(gas_config(50, 10, 80));
+
+        let mut context = get_context(dex().to_string(), ONE_YOCTO);
+        context.prepaid_gas = 79 * TGAS;
+        testing_env!(context);
+        contract.ft_transfer_call(nft(), U128(100), None, "".to_string());
+    }
+
+    //gas attached to ft_on_transfer by a ft_transfer_call with the prepaid gas
+    fn on_transfer_gas(prepaid_gas: Gas) -> Gas {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(nft()));
+
+        let mut context = get_context(dex().to_string(), ONE_YOCTO);
+        context.prepaid_gas = prepaid_gas;
+        testing_env!(context);
+        contract.ft_transfer_call(nft(), U128(100), None, "".to_string());
+
+        let receipts =
+            near_sdk::serde_json::to_string(&near_sdk::test_utils::get_created_receipts()).unwrap();
+        let gas = receipts.split(r#""gas":"#).nth(1).unwrap();
+        gas[..gas.find(',').unwrap()].parse().unwrap()
+    }
+
+    #[test]
+    fn ft_on_transfer_gets_the_gas_left() {
+        let gas = on_transfer_gas(300 * TGAS);
+
+        assert!(gas > 250 * TGAS);
+        assert!(gas <= (300 - 5 - 25) * TGAS);
+    }
+
+    #[test]
+    fn ft_on_transfer_gets_at_least_on_transfer_gas() {
+        assert_eq!(on_transfer_gas(50 * TGAS), DEFAULT_ON_TRANSFER_GAS);
+    }
+}
@@ -31,6 +31,31 @@ impl FungibleToken {
         }
     }
 
+    /// Every mint path must go through here so that minted_total stays reconcilable with ft_mint events
+    pub fn internal_record_mint(&mut self, amount: Balance) {
+        self.total_supply = self
+            .total_supply
+            .checked_add(amount)
+            .unwrap_or_else(|| env::panic(b"Total supply overflow"));
+
+        require!(
+            self.total_supply <= self.max_supply,
+            "Total supply can't exceed the max supply"
+        );
+
+        self.minted_total += amount;
+    }
+
+    /// Every burn path must go through here so that burned_total stays reconcilable with ft_burn events
+    pub fn internal_record_burn(&mut self, amount: Balance) {
+        self.total_supply = self
+            .total_supply
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic(b"Total supply underflow"));
+
+        self.burned_total += amount;
+    }
+
     pub fn internal_transfer(
         &mut self,
         sender_id: &AccountId,
@@ -57,6 +82,11 @@ impl FungibleToken {
         .emit();
     }
 
+    /// The receiver may have re-entered this contract and moved the tokens onward before the resolve,
+    /// so the refund is clamped to min(unused, receiver's current balance) and never credits more than remains.
+    ///
+    /// If the sender unregistered in the meantime the refund is burnt, if the sender registered again it's refunded.
+    /// Total supply is conserved across the flow, apart from tokens burnt here
     pub fn internal_resolve_transfer(
         &mut self,
         sender_id: &AccountId,
@@ -76,42 +106,63 @@ impl FungibleToken {
             PromiseResult::Failed => amount,
         };
 
-        if unused_amount > 0 {
-            let receiver_balance = self.accounts.get(&receiver_id).unwrap_or(0);
+        if unused_amount == 0 {
+            return amount;
+        }
 
-            if receiver_balance > 0 {
-                let refund_amount = std::cmp::min(receiver_balance, unused_amount);
+        let receiver_balance = self.accounts.get(&receiver_id).unwrap_or(0);
+        let refund_amount = std::cmp::min(receiver_balance, unused_amount);
 
-                self.accounts
-                    .insert(&receiver_id, &(receiver_balance - refund_amount));
+        if refund_amount == 0 {
+            return amount;
+        }
 
-                if let Some(sender_balance) = self.accounts.get(&sender_id) {
-                    self.accounts
-                        .insert(&sender_id, &(sender_balance + refund_amount));
+        self.internal_withdraw(receiver_id, refund_amount);
 
-                    FtTransferLog {
-                        old_owner_id: receiver_id.to_string(),
-                        new_owner_id: sender_id.to_string(),
-                        amount: U128::from(refund_amount),
-                        memo: Some("refund".to_string()),
-                    }
-                    .emit();
-                    return (amount - refund_amount).into();
-                } else {
-                    // Sender's account was deleted, so we need to burn tokens.
-                    self.total_supply -= refund_amount;
+        if self.accounts.contains_key(&sender_id) {
+            self.internal_deposit(sender_id, refund_amount);
 
-                    log!("The account of the sender was deleted");
+            FtTransferLog {
+                old_owner_id: receiver_id.to_string(),
+                new_owner_id: sender_id.to_string(),
+                amount: U128::from(refund_amount),
+                memo: Some("refund".to_string()),
+            }
+            .emit();
 
-                    FtBurnLog {
-                        owner_id: receiver_id.to_string(),
-                        amount: U128::from(refund_amount),
-                        memo: Some("burn".to_string()),
-                    }
-                    .emit();
-                }
+            amount - refund_amount
+        } else {
+            // Sender's account was deleted, so we need to burn tokens.
+            self.internal_record_burn(refund_amount);
+
+            log!("The account of the sender was deleted");
+
+            FtBurnLog {
+                owner_id: receiver_id.to_string(),
+                amount: U128::from(refund_amount),
+                memo: Some("burn".to_string()),
             }
+            .emit();
+
+            amount
+        }
+    }
+}
+
+/*********************************/
+/*  INTERNAL FUNCTIONS - ESCROW  */
+/*********************************/
+
+impl Contract {
+    /// Tokens locked by the contract (e.g. claim distributions) are held on the contract's own A/c,
+    /// which is registered on first use with storage paid by the contract (hence no deposit is recorded)
+    pub(crate) fn internal_escrow_account_id(&mut self) -> AccountId {
+        let escrow_id = env::current_account_id();
+
+        if !self.token.accounts.contains_key(&escrow_id) {
+            self.internal_register_account(&escrow_id, &escrow_id, 0);
         }
-        amount
+
+        escrow_id
     }
 }
@@ -8,40 +8,83 @@
  * storage_impl.rs implements NEP-145 standard for allocating storage per account
  * ft_metadata.rs implements NEP-148 standard for providing token-specific metadata.
  * events.rs extends NEP-297 for better indexing
+ * gas_config.rs holds the configurable gas split for ft_transfer_call
+ * supply.rs contains supply accounting views such as circulating supply and supply stats
+ * distribution.rs implements merkle-proof based claim distributions (airdrops)
+ * staking.rs lets holders stake NFTs of the Catch NFT contract to earn FT rewards
+ * nft_gate.rs caches whether A/c's hold an NFT of the gate NFT contract for NFT gated features
+ * streams.rs implements salary-style token streams from a sender to a receiver
+ * treasury.rs lets the treasury A/c burn its own tokens (buyback and burn)
+ * exemptions.rs manages Catch system A/c's which are exempted from transfer fees and burns
  * internal.rs contains internal methods for fungible token core.
  **/
 mod core_impl;
+mod distribution;
 mod events;
+mod exemptions;
 mod ft_metadata;
+mod gas_config;
 mod internal;
+mod nft_gate;
 mod receiver;
 mod resolver;
+mod staking;
 mod storage_impl;
+mod streams;
+mod supply;
+mod treasury;
 mod utils;
 
 pub use crate::core_impl::{FungibleToken, FungibleTokenCore};
-pub use crate::events::{FtBurnLog, FtMintLog, FtTransferLog};
+pub use crate::distribution::{Distribution, DistributionId, JsonDistribution, MerkleHash};
+pub use crate::events::{AccountLog, CatchEventLogVariant, FtBurnLog, FtMintLog, FtTransferLog};
 pub use crate::ft_metadata::FungibleTokenMetadata;
+pub use crate::gas_config::GasConfig;
+pub use crate::nft_gate::NftGate;
 pub use crate::receiver::ext_fungible_token_receiver;
 pub use crate::resolver::{ext_self, FungibleTokenResolver};
-pub use crate::storage_impl::StorageManager;
+pub use crate::staking::{JsonNftStake, NftStake};
+pub use crate::storage_impl::{
+    AccountRegisteredLog, AccountStorage, AccountUnregisteredLog, DepositRefundedLog,
+    StorageManager,
+};
+pub use crate::streams::{JsonStream, Stream, StreamId};
+pub use crate::supply::SupplyStats;
+use crate::utils::*;
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedSet};
 use near_sdk::json_types::{Base64VecU8, ValidAccountId, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    assert_one_yocto, env, ext_contract, log, near_bindgen, AccountId, Balance, Gas,
+    assert_one_yocto, env, ext_contract, log, near_bindgen, AccountId, Balance, CryptoHash, Gas,
     PanicOnDefault, Promise, PromiseOrValue, PromiseResult, StorageUsage,
 };
 
+#[cfg(target_arch = "wasm32")]
 #[global_allocator]
 static ALLOC: near_sdk::wee_alloc::WeeAlloc<'_> = near_sdk::wee_alloc::WeeAlloc::INIT;
 
 #[derive(BorshSerialize)]
 pub enum StorageKey {
     Accounts,
+    AccountStorage,
     Metadata,
+    ExemptAccounts,
+    NonCirculatingAccounts,
+    Distributions,
+    ClaimedLeaves,
+    Streams,
+    StreamsBySender,
+    StreamsBySenderInner { account_id_hash: CryptoHash },
+    StreamsByReceiver,
+    StreamsByReceiverInner { account_id_hash: CryptoHash },
+    NftRewardRates,
+    NftStakes,
+    NftStakesByOwner,
+    NftStakesByOwnerInner { account_id_hash: CryptoHash },
+    GateCache,
+    UnclaimedStreamFunds,
 }
 
 #[near_bindgen]
@@ -52,11 +95,69 @@ pub struct Contract {
     //// Fungible Token
     pub token: FungibleToken,
 
-    /// The storage size in bytes for one account.
+    /// The storage size in bytes for one account with the longest possible id.
     pub account_storage_usage: StorageUsage,
 
+    /// Storage deposit and measured usage per registered A/c
+    pub account_storage: LookupMap<AccountId, AccountStorage>,
+
     /// Metadata for the Fungible Token
     pub ft_metadata: LazyOption<FungibleTokenMetadata>,
+
+    /// Catch system A/c's which are exempted from transfer fees and burns
+    pub exempt_accounts: UnorderedSet<AccountId>,
+
+    /// Treasury, vesting, staking and escrow A/c's whose balances are excluded from circulating supply
+    pub non_circulating_accounts: UnorderedSet<AccountId>,
+
+    /// Treasury A/c allowed to burn its own balance
+    pub treasury_id: Option<AccountId>,
+
+    /// Tokens burnt by the treasury since deployment.
+    pub treasury_burned_total: Balance,
+
+    /// Merkle claim distributions by id
+    pub distributions: LookupMap<DistributionId, Distribution>,
+
+    /// Leaves which have already been claimed per distribution
+    pub claimed_leaves: LookupSet<(DistributionId, MerkleHash)>,
+
+    pub next_distribution_id: DistributionId,
+
+    /// Token streams by id
+    pub streams: LookupMap<StreamId, Stream>,
+
+    /// Stream ids for every sender
+    pub streams_by_sender: LookupMap<AccountId, UnorderedSet<StreamId>>,
+
+    /// Stream ids for every receiver
+    pub streams_by_receiver: LookupMap<AccountId, UnorderedSet<StreamId>>,
+
+    pub next_stream_id: StreamId,
+
+    /// Stream payouts of A/c's which weren't registered when paid, held in escrow until claimed
+    pub unclaimed_stream_funds: LookupMap<AccountId, Balance>,
+
+    /// NFT contract whose tokens can be staked
+    pub staking_nft_contract_id: Option<AccountId>,
+
+    /// Daily reward per staked NFT class
+    pub nft_reward_rates: LookupMap<String, Balance>,
+
+    /// Staked NFTs by token id
+    pub nft_stakes: LookupMap<String, NftStake>,
+
+    /// Staked token ids for every staker
+    pub nft_stakes_by_owner: LookupMap<AccountId, UnorderedSet<String>>,
+
+    /// Tokens held in escrow for staking rewards
+    pub staking_pool: Balance,
+
+    /// NFT contract whose holders get NFT gated features
+    pub nft_gate: Option<NftGate>,
+
+    /// Whether the A/c holds a gate NFT and when it was checked (nanoseconds)
+    pub gate_cache: LookupMap<AccountId, (bool, u64)>,
 }
 
 #[near_bindgen]
@@ -75,7 +176,11 @@ impl Contract {
 
         let token = FungibleToken {
             accounts: LookupMap::new(StorageKey::Accounts.try_to_vec().unwrap()),
-            total_supply: total_supply.into(),
+            total_supply: 0,
+            max_supply: total_supply.into(),
+            minted_total: 0,
+            burned_total: 0,
+            gas_config: GasConfig::default(),
         };
 
         let ft_metadata =
@@ -85,20 +190,50 @@ impl Contract {
             owner_id: owner_id.clone(),
             token,
             account_storage_usage: 0,
+            account_storage: LookupMap::new(StorageKey::AccountStorage.try_to_vec().unwrap()),
             ft_metadata,
+            exempt_accounts: UnorderedSet::new(StorageKey::ExemptAccounts.try_to_vec().unwrap()),
+            non_circulating_accounts: UnorderedSet::new(
+                StorageKey::NonCirculatingAccounts.try_to_vec().unwrap(),
+            ),
+            treasury_id: None,
+            treasury_burned_total: 0,
+            distributions: LookupMap::new(StorageKey::Distributions.try_to_vec().unwrap()),
+            claimed_leaves: LookupSet::new(StorageKey::ClaimedLeaves.try_to_vec().unwrap()),
+            next_distribution_id: 0,
+            streams: LookupMap::new(StorageKey::Streams.try_to_vec().unwrap()),
+            streams_by_sender: LookupMap::new(StorageKey::StreamsBySender.try_to_vec().unwrap()),
+            streams_by_receiver: LookupMap::new(
+                StorageKey::StreamsByReceiver.try_to_vec().unwrap(),
+            ),
+            next_stream_id: 0,
+            unclaimed_stream_funds: LookupMap::new(
+                StorageKey::UnclaimedStreamFunds.try_to_vec().unwrap(),
+            ),
+            staking_nft_contract_id: None,
+            nft_reward_rates: LookupMap::new(StorageKey::NftRewardRates.try_to_vec().unwrap()),
+            nft_stakes: LookupMap::new(StorageKey::NftStakes.try_to_vec().unwrap()),
+            nft_stakes_by_owner: LookupMap::new(StorageKey::NftStakesByOwner.try_to_vec().unwrap()),
+            staking_pool: 0,
+            nft_gate: None,
+            gate_cache: LookupMap::new(StorageKey::GateCache.try_to_vec().unwrap()),
         };
         // Determine cost of insertion into LookupMap
 
         let initial_storage_usage = env::storage_usage();
         let tmp_account_id = unsafe { String::from_utf8_unchecked(vec![b'a'; 64]) };
         this.token.accounts.insert(&tmp_account_id, &0u128);
+        this.account_storage
+            .insert(&tmp_account_id, &AccountStorage { total: 0, usage: 0 });
         this.account_storage_usage = env::storage_usage() - initial_storage_usage;
         this.token.accounts.remove(&tmp_account_id);
+        this.account_storage.remove(&tmp_account_id);
 
         // Make owner have total supply
 
         let total_supply_u128: u128 = total_supply.into();
         this.token.accounts.insert(&owner_id, &total_supply_u128);
+        this.token.internal_record_mint(total_supply_u128);
 
         FtMintLog {
             owner_id: owner_id.to_string(),
@@ -117,6 +252,41 @@ impl Contract {
             .ft_transfer(receiver_id.into(), amount.into(), memo)
     }
 
+    /// Same as ft_transfer but registers the receiver if needed, paid by the sender
+    ///
+    /// Attach at least the storage minimum, excess is refunded and if the receiver
+    /// is already registered the full deposit is refunded
+    #[payable]
+    pub fn ft_transfer_auto_register(
+        &mut self,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        let deposit = env::attached_deposit();
+        let min_balance = self.storage_balance_bounds().min.0;
+        require!(
+            deposit >= min_balance,
+            format!("Please Attach a deposit of {} Yocto Near", min_balance)
+        );
+
+        let sender_id = env::predecessor_account_id();
+        let receiver_id: AccountId = receiver_id.into();
+        let memo = validate_memo(memo);
+
+        let refund = if self.token.accounts.contains_key(&receiver_id) {
+            deposit
+        } else {
+            self.internal_register_account(&receiver_id, &sender_id, min_balance);
+            deposit - min_balance
+        };
+
+        self.token
+            .internal_transfer(&sender_id, &receiver_id, amount.into(), memo);
+
+        self.internal_refund_deposit(&receiver_id, &sender_id, refund);
+    }
+
     /// Transfer Fungible tokens to a Contract and call on_transfer function of the contract
     /// returns the amount of tokens used by the contract
     #[payable]
@@ -155,7 +325,6 @@ impl Contract {
     pub fn ft_balance_of(&self, account_id: ValidAccountId) -> U128 {
         self.token.ft_balance_of(account_id.into())
     }
-
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -165,10 +334,10 @@ mod ft_core_tests {
     use utils::test_utils::*;
 
     use near_sdk::MockedBlockchain;
-    use near_sdk::{testing_env, Balance};
+    use near_sdk::{test_utils, testing_env, Balance};
 
     const ONE_YOCTO: Balance = 1;
-    const STORAGE_COST: Balance = 1_250_000_000_000_000_000_000; // 1 Near = 10^24 Yocto Near
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000; // 1 Near = 10^24 Yocto Near
 
     // Test for new()
 
@@ -211,6 +380,83 @@ mod ft_core_tests {
         assert_eq!(contract.ft_balance_of(dex()), remaining_balance);
     }
 
+    #[test]
+    fn test_ft_transfer_logs_sanitized_memo() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(carol(), U128::from(100), Some("quest\n#7".to_string()));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":{"old_owner_id":"dex.near","new_owner_id":"carol.near","amount":"100","memo":"quest#7"}}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Memo can't be longer than 256 bytes")]
+    fn test_ft_transfer_fails_with_long_memo() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(carol(), U128::from(100), Some("a".repeat(257)));
+    }
+
+    #[test]
+    fn test_ft_transfer_auto_register_registers_receiver() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+
+        let mut contract = create_contract();
+        contract.ft_transfer_auto_register(carol(), U128::from(100), None);
+
+        assert_eq!(contract.ft_balance_of(carol()).0, 100);
+        assert!(contract.storage_balance_of(carol()).is_some());
+
+        let logs = test_utils::get_logs();
+        assert_eq!(
+            logs[logs.len() - 2],
+            r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"account_registered","data":{"account_id":"carol.near","payer_id":"dex.near","deposit":"2580000000000000000000"}}"#
+        );
+        assert_eq!(
+            logs[logs.len() - 1],
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":{"old_owner_id":"dex.near","new_owner_id":"carol.near","amount":"100"}}"#
+        );
+    }
+
+    #[test]
+    fn test_ft_transfer_auto_register_to_registered_receiver() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        contract.ft_transfer_auto_register(carol(), U128::from(100), None);
+
+        assert_eq!(contract.ft_balance_of(carol()).0, 100);
+        let logs = test_utils::get_logs();
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].contains(r#""event":"ft_transfer""#));
+        assert_eq!(
+            logs[1],
+            r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"deposit_refunded","data":{"account_id":"carol.near","payer_id":"dex.near","amount":"2580000000000000000000"}}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Please Attach a deposit of 2580000000000000000000 Yocto Near")]
+    fn test_ft_transfer_auto_register_fails_without_deposit() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+
+        let mut contract = create_contract();
+        contract.ft_transfer_auto_register(carol(), U128::from(100), None);
+    }
+
     #[test]
     #[should_panic(expected = "The account is not registered")]
     fn test_ft_transfer_fails() {
@@ -0,0 +1,278 @@
+use crate::*;
+
+const GAS_FOR_GATE_QUERY: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_GATE: Gas = 10_000_000_000_000;
+
+const BASIS_POINTS: u128 = 10_000;
+
+#[ext_contract(ext_nft_gate)]
+pub trait NonFungibleTokenEnumeration {
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+}
+
+#[ext_contract(ext_gate_self)]
+pub trait NftGateResolver {
+    fn resolve_refresh_gate(&mut self, account_id: AccountId) -> bool;
+}
+
+/// Features which only apply to holders of an NFT of the gate NFT contract
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftGate {
+    /// NFT contract queried with nft_supply_for_owner
+    pub nft_contract_id: AccountId,
+
+    /// Seconds a cached result is trusted, the A/c is treated as not holding after that
+    pub cache_ttl_sec: u64,
+
+    /// Extra staking rewards of holders in basis points, 5000 pays 1.5x
+    pub staking_boost_bps: u32,
+}
+
+impl Contract {
+    /// True if the cached result says the A/c holds the gate NFT and hasn't expired
+    pub(crate) fn internal_is_gate_holder(&self, account_id: &AccountId) -> bool {
+        let gate = if let Some(gate) = &self.nft_gate {
+            gate
+        } else {
+            return false;
+        };
+
+        match self.gate_cache.get(account_id) {
+            Some((holds, checked_at)) => {
+                holds
+                    && env::block_timestamp().saturating_sub(checked_at)
+                        <= gate.cache_ttl_sec * 1_000_000_000
+            }
+            None => false,
+        }
+    }
+
+    /// Staking rewards with the boost of the gate applied for holders
+    pub(crate) fn internal_boosted_rewards(
+        &self,
+        account_id: &AccountId,
+        rewards: Balance,
+    ) -> Balance {
+        match &self.nft_gate {
+            Some(gate) if self.internal_is_gate_holder(account_id) => {
+                rewards + rewards * gate.staking_boost_bps as u128 / BASIS_POINTS
+            }
+            _ => rewards,
+        }
+    }
+}
+
+/*********************************/
+/*  NFT GATED FT OPERATIONS      */
+/*********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// None removes the gate, cached results are kept but ignored until a gate is set again
+    #[payable]
+    pub fn set_nft_gate(&mut self, nft_gate: Option<NftGate>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.nft_gate = nft_gate;
+    }
+
+    pub fn get_nft_gate(&self) -> Option<NftGate> {
+        self.nft_gate.clone()
+    }
+
+    /// Queries the gate NFT contract and caches whether the A/c holds one of its tokens,
+    /// anyone can call it but only registered A/c's are cached
+    pub fn refresh_gate(&mut self, account_id: ValidAccountId) -> Promise {
+        let gate = self
+            .nft_gate
+            .as_ref()
+            .unwrap_or_else(|| env::panic(b"NFT gate is not set"));
+
+        let account_id: AccountId = account_id.into();
+        require!(
+            self.token.accounts.contains_key(&account_id),
+            format!("The account {} is not registered", &account_id)
+        );
+
+        ext_nft_gate::nft_supply_for_owner(
+            account_id.clone(),
+            &gate.nft_contract_id,
+            0,
+            GAS_FOR_GATE_QUERY,
+        )
+        .then(ext_gate_self::resolve_refresh_gate(
+            account_id,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_GATE,
+        ))
+    }
+
+    /// Caches the result of nft_supply_for_owner, if the NFT contract is unavailable the cache
+    /// is left untouched and expires as usual
+    #[private]
+    pub fn resolve_refresh_gate(&mut self, account_id: AccountId) -> bool {
+        let supply = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<U128>(&value).ok()
+            }
+            _ => None,
+        };
+
+        if let Some(supply) = supply {
+            self.gate_cache
+                .insert(&account_id, &(supply.0 > 0, env::block_timestamp()));
+        } else {
+            log!(
+                "NFT gate contract is unavailable, the gate of {} isn't refreshed",
+                &account_id
+            );
+        }
+
+        self.internal_is_gate_holder(&account_id)
+    }
+
+    /// True if the A/c holds the gate NFT according to a cached result which hasn't expired
+    pub fn is_gate_holder(&self, account_id: ValidAccountId) -> bool {
+        self.internal_is_gate_holder(account_id.as_ref())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod nft_gate_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env, VMContext};
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+    const SECOND: u64 = 1_000_000_000;
+
+    fn context_at(account_id: AccountId, deposit: Balance, timestamp: u64) -> VMContext {
+        let mut context = get_context(account_id, deposit);
+        context.block_timestamp = timestamp;
+        context
+    }
+
+    /// holders of nft.catchlabs.near tokens are cached for 60 seconds and get 50% extra staking rewards
+    fn setup() -> Contract {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.set_nft_gate(Some(NftGate {
+            nft_contract_id: nft().to_string(),
+            cache_ttl_sec: 60,
+            staking_boost_bps: 5_000,
+        }));
+
+        contract
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult, timestamp: u64) -> bool {
+        testing_env!(
+            context_at("mike.near".to_string(), 0, timestamp),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_refresh_gate(carol().to_string())
+    }
+
+    fn supply(supply: u128) -> PromiseResult {
+        PromiseResult::Successful(near_sdk::serde_json::to_vec(&U128(supply)).unwrap())
+    }
+
+    #[test]
+    fn holder_is_cached_until_expiry() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.refresh_gate(carol());
+
+        assert!(resolve(&mut contract, supply(2), 0));
+
+        testing_env!(context_at(bob().to_string(), 0, 60 * SECOND));
+        assert!(contract.is_gate_holder(carol()));
+
+        testing_env!(context_at(bob().to_string(), 0, 61 * SECOND));
+        assert!(!contract.is_gate_holder(carol()));
+    }
+
+    #[test]
+    fn non_holder_is_cached() {
+        let mut contract = setup();
+
+        assert!(!resolve(&mut contract, supply(0), 0));
+        assert!(!contract.is_gate_holder(carol()));
+    }
+
+    #[test]
+    fn unavailable_nft_contract_keeps_cache() {
+        let mut contract = setup();
+        resolve(&mut contract, supply(1), 0);
+
+        assert!(resolve(&mut contract, PromiseResult::Failed, 30 * SECOND));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec!["NFT gate contract is unavailable, the gate of carol.near isn't refreshed"]
+        );
+
+        // the old result still expires
+        assert!(!resolve(&mut contract, PromiseResult::Failed, 61 * SECOND));
+    }
+
+    #[test]
+    #[should_panic(expected = "The account bob.near is not registered")]
+    fn refresh_unregistered_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.refresh_gate(bob());
+    }
+
+    #[test]
+    fn staking_rewards_are_boosted_for_holders() {
+        let mut contract = setup();
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.set_staking_nft_contract(nft());
+        contract.set_nft_reward_rate("genesis.hero".to_string(), Some(U128(100)));
+        contract.fund_staking_pool(U128(1_000));
+
+        testing_env!(context_at(nft().to_string(), 0, 0));
+        contract.nft_on_lock(
+            carol().to_string(),
+            "genesis.hero.carol.near".to_string(),
+            "".to_string(),
+        );
+
+        let day = 86_400 * SECOND;
+        resolve(&mut contract, supply(1), 2 * day);
+
+        testing_env!(context_at(carol().to_string(), 0, 2 * day));
+        assert_eq!(
+            contract.get_staked_nfts(carol(), None, None)[0]
+                .pending_rewards
+                .0,
+            300
+        );
+
+        testing_env!(context_at(carol().to_string(), ONE_YOCTO, 2 * day));
+        contract.unstake_nft("genesis.hero.carol.near".to_string());
+        assert_eq!(contract.get_staking_pool().0, 700);
+
+        // expired cache, no boost
+        testing_env!(context_at(carol().to_string(), 0, 3 * day));
+        assert!(!contract.is_gate_holder(carol()));
+    }
+}
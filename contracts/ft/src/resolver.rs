@@ -32,3 +32,154 @@ impl FungibleTokenResolver for FungibleToken {
             .into()
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod resolver_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env, PromiseResult};
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+    const TOTAL_SUPPLY: Balance = 1_000_000_000_000_000;
+
+    /// dex -> ft_transfer_call of `amount` to the nft contract, alice and nft are registered
+    fn setup(amount: Balance) -> Contract {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(alice()));
+        contract.storage_deposit(Some(nft()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer_call(nft(), U128(amount), None, "".to_string());
+
+        contract
+    }
+
+    fn resolve(
+        contract: &mut Contract,
+        sender_id: ValidAccountId,
+        amount: Balance,
+        result: PromiseResult,
+    ) -> Balance {
+        testing_env!(
+            get_context("mike.near".to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract
+            .ft_resolve_transfer(sender_id.into(), nft().into(), U128(amount))
+            .0
+    }
+
+    fn unused(amount: Balance) -> PromiseResult {
+        PromiseResult::Successful(near_sdk::serde_json::to_vec(&U128(amount)).unwrap())
+    }
+
+    fn assert_supply_conserved(contract: &Contract, burned: Balance) {
+        let balances: Balance = [dex(), alice(), nft()]
+            .iter()
+            .map(|account_id| contract.ft_balance_of(account_id.clone()).0)
+            .sum();
+        let stats = contract.supply_stats();
+
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - burned);
+        assert_eq!(balances, contract.ft_total_supply().0);
+        assert_eq!(
+            stats.minted_total.0 - stats.burned_total.0,
+            stats.total_supply.0
+        );
+    }
+
+    #[test]
+    fn malicious_receiver_moves_funds_before_resolve() {
+        let mut contract = setup(1_000);
+
+        // receiver re-enters and moves the tokens onward, then claims everything is unused
+        testing_env!(get_context(nft().to_string(), ONE_YOCTO));
+        contract.ft_transfer(alice(), U128(900), None);
+
+        let used = resolve(&mut contract, dex(), 1_000, unused(u128::MAX));
+
+        assert_eq!(used, 900);
+        assert_eq!(contract.ft_balance_of(nft()).0, 0);
+        assert_eq!(contract.ft_balance_of(alice()).0, 900);
+        assert_eq!(contract.ft_balance_of(dex()).0, TOTAL_SUPPLY - 900);
+        assert_supply_conserved(&contract, 0);
+    }
+
+    #[test]
+    fn receiver_moves_all_funds_before_resolve() {
+        let mut contract = setup(1_000);
+
+        testing_env!(get_context(nft().to_string(), ONE_YOCTO));
+        contract.ft_transfer(alice(), U128(1_000), None);
+
+        assert_eq!(resolve(&mut contract, dex(), 1_000, unused(1_000)), 1_000);
+        assert!(test_utils::get_logs().is_empty());
+        assert_supply_conserved(&contract, 0);
+    }
+
+    #[test]
+    fn failed_receiver_call_refunds_everything() {
+        let mut contract = setup(1_000);
+
+        assert_eq!(
+            resolve(&mut contract, dex(), 1_000, PromiseResult::Failed),
+            0
+        );
+        assert_eq!(contract.ft_balance_of(dex()).0, TOTAL_SUPPLY);
+        assert_eq!(contract.ft_balance_of(nft()).0, 0);
+        assert_supply_conserved(&contract, 0);
+    }
+
+    #[test]
+    fn unregistered_sender_refund_is_burnt() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(alice()));
+        contract.storage_deposit(Some(nft()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(alice(), U128(1_000), None);
+
+        testing_env!(get_context(alice().to_string(), ONE_YOCTO));
+        contract.ft_transfer_call(nft(), U128(1_000), None, "".to_string());
+        contract.storage_unregister(None);
+
+        assert_eq!(resolve(&mut contract, alice(), 1_000, unused(400)), 1_000);
+        assert_eq!(contract.ft_balance_of(nft()).0, 600);
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":{"owner_id":"nft.catchlabs.near","amount":"400","memo":"burn"}}"#
+        );
+        assert_supply_conserved(&contract, 400);
+    }
+
+    #[test]
+    fn re_registered_sender_is_refunded() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(alice()));
+        contract.storage_deposit(Some(nft()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(alice(), U128(1_000), None);
+
+        testing_env!(get_context(alice().to_string(), ONE_YOCTO));
+        contract.ft_transfer_call(nft(), U128(1_000), None, "".to_string());
+        contract.storage_unregister(None);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_COST));
+        contract.storage_deposit(None);
+
+        assert_eq!(resolve(&mut contract, alice(), 1_000, unused(400)), 600);
+        assert_eq!(contract.ft_balance_of(alice()).0, 400);
+        assert_supply_conserved(&contract, 0);
+    }
+}
@@ -0,0 +1,481 @@
+use crate::*;
+
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+const GAS_FOR_NFT_UNLOCK: Gas = 15_000_000_000_000;
+const GAS_FOR_RESOLVE_UNSTAKE: Gas = 15_000_000_000_000;
+
+#[ext_contract(ext_nft)]
+pub trait NonFungibleToken {
+    fn nft_unlock(&mut self, token_id: String);
+}
+
+#[ext_contract(ext_staking_self)]
+pub trait NftStakingResolver {
+    fn resolve_unstake(&mut self, token_id: String, rewards: U128);
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct NftStake {
+    pub owner_id: AccountId,
+    pub class_id: String,
+    /// Unix epoch in nanoseconds
+    pub staked_at: u64,
+    /// Reward rate of the class at the time of staking
+    pub rate_per_day: Balance,
+    /// Set while the NFT is being unlocked
+    pub unstaking: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonNftStake {
+    pub token_id: String,
+    pub class_id: String,
+    pub staked_at: u64,
+    pub pending_rewards: U128,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftStakeLog {
+    pub account_id: AccountId,
+    pub token_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewards: Option<U128>,
+}
+
+impl NftStake {
+    /// Rewards accrued until `now`, rate is per day
+    pub fn pending_rewards(&self, now: u64) -> Balance {
+        let elapsed = now.saturating_sub(self.staked_at) as u128;
+        let day = NANOS_PER_DAY as u128;
+
+        (self.rate_per_day / day)
+            .saturating_mul(elapsed)
+            .saturating_add((self.rate_per_day % day) * elapsed / day)
+    }
+}
+
+/// Class of the token, for Catch NFTs `event_id.token_id.owner_id` it's `event_id.token_id`
+pub(crate) fn nft_class_id(token_id: &str) -> String {
+    token_id
+        .splitn(3, '.')
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/***********************************/
+/*  NFT STAKING FOR FT REWARDS     */
+/***********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Called by the staking NFT contract on nft_lock_call, returns true if the NFT should be unlocked.
+    /// The NFT stays with its owner, locked in place until it is unstaked, so every copy of a class is
+    /// staked under its own token id `event_id.token_id.owner_id`
+    pub fn nft_on_lock(
+        &mut self,
+        owner_id: AccountId,
+        token_id: String,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        // the NFT is always staked for its owner, msg isn't needed
+        let _ = msg;
+
+        require!(
+            Some(env::predecessor_account_id()) == self.staking_nft_contract_id,
+            "Only NFTs of the staking NFT contract can be staked"
+        );
+
+        let class_id = nft_class_id(&token_id);
+
+        let rate_per_day = if let Some(rate_per_day) = self.nft_reward_rates.get(&class_id) {
+            rate_per_day
+        } else {
+            log!("{} can't be staked, unlocking the token", &class_id);
+            return PromiseOrValue::Value(true);
+        };
+
+        if !self.token.accounts.contains_key(&owner_id) {
+            log!(
+                "{} is not registered for rewards, unlocking the token",
+                &owner_id
+            );
+            return PromiseOrValue::Value(true);
+        }
+
+        self.nft_stakes.insert(
+            &token_id,
+            &NftStake {
+                owner_id: owner_id.clone(),
+                class_id,
+                staked_at: env::block_timestamp(),
+                rate_per_day,
+                unstaking: false,
+            },
+        );
+
+        let mut token_ids = self.nft_stakes_by_owner.get(&owner_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::NftStakesByOwnerInner {
+                    account_id_hash: hash_id(&owner_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        token_ids.insert(&token_id);
+        self.nft_stakes_by_owner.insert(&owner_id, &token_ids);
+
+        CatchEventLogVariant::NftStaked(NftStakeLog {
+            account_id: owner_id,
+            token_id,
+            rewards: None,
+        })
+        .emit();
+
+        PromiseOrValue::Value(false)
+    }
+
+    /// Staker only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Unlocks the NFT and pays the accrued rewards (capped by the pool) once the NFT is unlocked
+    #[payable]
+    pub fn unstake_nft(&mut self, token_id: String) -> Promise {
+        assert_one_yocto();
+
+        let mut stake = self
+            .nft_stakes
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token is not staked"));
+
+        require!(
+            env::predecessor_account_id() == stake.owner_id,
+            "Only the staker can unstake the token"
+        );
+        require!(!stake.unstaking, "Token is already being unstaked");
+
+        let rewards = std::cmp::min(
+            self.internal_boosted_rewards(
+                &stake.owner_id,
+                stake.pending_rewards(env::block_timestamp()),
+            ),
+            self.staking_pool,
+        );
+        self.staking_pool -= rewards;
+
+        stake.unstaking = true;
+        self.nft_stakes.insert(&token_id, &stake);
+
+        ext_nft::nft_unlock(
+            token_id.clone(),
+            self.staking_nft_contract_id.as_ref().unwrap(),
+            0,
+            GAS_FOR_NFT_UNLOCK,
+        )
+        .then(ext_staking_self::resolve_unstake(
+            token_id,
+            rewards.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_UNSTAKE,
+        ))
+    }
+
+    /// Pays the rewards if the NFT was unlocked, otherwise the token stays staked and the rewards go back to the pool
+    #[private]
+    pub fn resolve_unstake(&mut self, token_id: String, rewards: U128) {
+        let mut stake = self.nft_stakes.get(&token_id).unwrap();
+        let rewards: Balance = rewards.into();
+
+        let unlocked = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        if !unlocked {
+            log!("Unlocking {} failed, the token stays staked", &token_id);
+
+            stake.unstaking = false;
+            self.nft_stakes.insert(&token_id, &stake);
+            self.staking_pool += rewards;
+            return;
+        }
+
+        self.nft_stakes.remove(&token_id);
+        if let Some(mut token_ids) = self.nft_stakes_by_owner.get(&stake.owner_id) {
+            token_ids.remove(&token_id);
+            if token_ids.is_empty() {
+                self.nft_stakes_by_owner.remove(&stake.owner_id);
+            } else {
+                self.nft_stakes_by_owner.insert(&stake.owner_id, &token_ids);
+            }
+        }
+
+        let paid = if rewards > 0 && self.token.accounts.contains_key(&stake.owner_id) {
+            self.token.internal_transfer(
+                &env::current_account_id(),
+                &stake.owner_id,
+                rewards,
+                Some(format!("staking rewards for {}", &token_id)),
+            );
+            rewards
+        } else {
+            self.staking_pool += rewards;
+            0
+        };
+
+        CatchEventLogVariant::NftUnstaked(NftStakeLog {
+            account_id: stake.owner_id,
+            token_id,
+            rewards: Some(paid.into()),
+        })
+        .emit();
+    }
+
+    /// Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Moves tokens of the predecessor into the pool which pays staking rewards
+    #[payable]
+    pub fn fund_staking_pool(&mut self, amount: U128) {
+        assert_one_yocto();
+
+        let escrow_id = self.internal_escrow_account_id();
+        self.token.internal_transfer(
+            &env::predecessor_account_id(),
+            &escrow_id,
+            amount.into(),
+            Some("staking pool".to_string()),
+        );
+
+        self.staking_pool += amount.0;
+    }
+
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    #[payable]
+    pub fn set_staking_nft_contract(&mut self, nft_contract_id: ValidAccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        self.staking_nft_contract_id = Some(nft_contract_id.into());
+    }
+
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Sets the daily reward of a class (`event_id.token_id` for Catch NFTs), None stops new stakes of the class
+    #[payable]
+    pub fn set_nft_reward_rate(&mut self, class_id: String, rate_per_day: Option<U128>) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        match rate_per_day {
+            Some(rate_per_day) => self.nft_reward_rates.insert(&class_id, &rate_per_day.0),
+            None => self.nft_reward_rates.remove(&class_id),
+        };
+    }
+
+    pub fn get_nft_reward_rate(&self, class_id: String) -> Option<U128> {
+        self.nft_reward_rates.get(&class_id).map(U128)
+    }
+
+    pub fn get_staking_pool(&self) -> U128 {
+        self.staking_pool.into()
+    }
+
+    /// Returns paginated view of the NFTs staked by the A/c with pending rewards
+    pub fn get_staked_nfts(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonNftStake> {
+        let token_ids = if let Some(token_ids) = self.nft_stakes_by_owner.get(account_id.as_ref()) {
+            token_ids
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        token_ids
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|token_id| {
+                let stake = self.nft_stakes.get(&token_id).unwrap();
+                JsonNftStake {
+                    pending_rewards: self
+                        .internal_boosted_rewards(
+                            &stake.owner_id,
+                            stake.pending_rewards(env::block_timestamp()),
+                        )
+                        .into(),
+                    class_id: stake.class_id,
+                    staked_at: stake.staked_at,
+                    token_id,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod staking_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env, VMContext};
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+    const DAY: u64 = NANOS_PER_DAY;
+    const TOKEN_ID: &str = "genesis.hero.carol.near";
+
+    fn context_at(account_id: AccountId, deposit: Balance, timestamp: u64) -> VMContext {
+        let mut context = get_context(account_id, deposit);
+        context.block_timestamp = timestamp;
+        context
+    }
+
+    /// carol stakes genesis.hero earning 100 per day, the pool has 1000
+    fn setup() -> Contract {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.set_staking_nft_contract(nft());
+        contract.set_nft_reward_rate("genesis.hero".to_string(), Some(U128(100)));
+        contract.fund_staking_pool(U128(1_000));
+
+        testing_env!(context_at(nft().to_string(), 0, 0));
+        let result =
+            contract.nft_on_lock(carol().to_string(), TOKEN_ID.to_string(), "".to_string());
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+
+        contract
+    }
+
+    fn resolve(contract: &mut Contract, rewards: Balance, result: PromiseResult) {
+        testing_env!(
+            get_context("mike.near".to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_unstake(TOKEN_ID.to_string(), U128(rewards));
+    }
+
+    #[test]
+    fn class_id_of_catch_token() {
+        assert_eq!(nft_class_id("event.token.owner.near"), "event.token");
+        assert_eq!(nft_class_id("token"), "token");
+    }
+
+    #[test]
+    fn stake_and_unstake_pays_rewards() {
+        let mut contract = setup();
+
+        testing_env!(context_at(carol().to_string(), 0, DAY + DAY / 2));
+        let staked = contract.get_staked_nfts(carol(), None, None);
+        assert_eq!(staked[0].token_id, TOKEN_ID);
+        assert_eq!(staked[0].pending_rewards.0, 150);
+
+        testing_env!(context_at(carol().to_string(), ONE_YOCTO, 2 * DAY));
+        contract.unstake_nft(TOKEN_ID.to_string());
+        assert_eq!(contract.get_staking_pool().0, 800);
+
+        resolve(&mut contract, 200, PromiseResult::Successful(vec![]));
+
+        assert_eq!(contract.ft_balance_of(carol()).0, 200);
+        assert!(contract.get_staked_nfts(carol(), None, None).is_empty());
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"nft_unstaked","data":{"account_id":"carol.near","token_id":"genesis.hero.carol.near","rewards":"200"}}"#
+        );
+    }
+
+    #[test]
+    fn copies_of_one_class_are_staked_by_their_owners() {
+        let mut contract = setup();
+
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        contract.storage_deposit(Some(bob()));
+
+        testing_env!(context_at(nft().to_string(), 0, DAY));
+        let result = contract.nft_on_lock(
+            bob().to_string(),
+            "genesis.hero.bob.near".to_string(),
+            "".to_string(),
+        );
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+
+        testing_env!(context_at(bob().to_string(), 0, 2 * DAY));
+        assert_eq!(
+            contract.get_staked_nfts(carol(), None, None)[0]
+                .pending_rewards
+                .0,
+            200
+        );
+        let staked = contract.get_staked_nfts(bob(), None, None);
+        assert_eq!(staked[0].token_id, "genesis.hero.bob.near");
+        assert_eq!(staked[0].pending_rewards.0, 100);
+
+        testing_env!(context_at(bob().to_string(), ONE_YOCTO, 2 * DAY));
+        contract.unstake_nft("genesis.hero.bob.near".to_string());
+        assert_eq!(contract.get_staked_nfts(carol(), None, None).len(), 1);
+    }
+
+    #[test]
+    fn failed_unlock_keeps_token_staked() {
+        let mut contract = setup();
+
+        testing_env!(context_at(carol().to_string(), ONE_YOCTO, 20 * DAY));
+        contract.unstake_nft(TOKEN_ID.to_string());
+        // rewards are capped by the pool
+        assert_eq!(contract.get_staking_pool().0, 0);
+
+        resolve(&mut contract, 1_000, PromiseResult::Failed);
+
+        assert_eq!(contract.get_staking_pool().0, 1_000);
+        assert_eq!(contract.ft_balance_of(carol()).0, 0);
+        assert_eq!(contract.get_staked_nfts(carol(), None, None).len(), 1);
+
+        // unstaking can be retried
+        testing_env!(context_at(carol().to_string(), ONE_YOCTO, 20 * DAY));
+        contract.unstake_nft(TOKEN_ID.to_string());
+    }
+
+    #[test]
+    fn unknown_class_is_unlocked() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        let result = contract.nft_on_lock(
+            bob().to_string(),
+            "genesis.villain.bob.near".to_string(),
+            "".to_string(),
+        );
+        assert!(matches!(result, PromiseOrValue::Value(true)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only NFTs of the staking NFT contract can be staked")]
+    fn stake_from_other_contract_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.nft_on_lock(bob().to_string(), TOKEN_ID.to_string(), "".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the staker can unstake the token")]
+    fn unstake_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), ONE_YOCTO));
+        contract.unstake_nft(TOKEN_ID.to_string());
+    }
+}
@@ -1,12 +1,47 @@
 use crate::*;
 
-#[derive(Serialize)]
+#[derive(Serialize, Debug, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StorageBalance {
     total: U128,
     available: U128,
 }
 
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountRegisteredLog {
+    pub account_id: AccountId,
+    /// Set only if someone else paid for the registration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer_id: Option<AccountId>,
+    pub deposit: U128,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountUnregisteredLog {
+    pub account_id: AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub burned_amount: Option<U128>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepositRefundedLog {
+    pub account_id: AccountId,
+    /// Set only if the refund goes to someone else than the A/c, e.g. a third party registration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer_id: Option<AccountId>,
+    pub amount: U128,
+}
+
+/// NEAR paid for storage by an A/c and the bytes its registration actually occupies
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AccountStorage {
+    pub total: Balance,
+    pub usage: StorageUsage,
+}
+
 #[derive(Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StorageBalanceBounds {
@@ -18,6 +53,11 @@ pub trait StorageManager {
     /// Deposit Near for the purpose of storage costs
     fn storage_deposit(&mut self, account_id: Option<ValidAccountId>) -> StorageBalance;
 
+    /// Wallet UX Security -> Attach 1 Yocto,
+    ///
+    /// Withdraws the surplus of the deposit over the measured usage of the A/c, all of it if amount is None
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+
     /// Wallet UX Security -> Attach 1 Yocto,
     ///
     /// Removes the A/c if no tokens present, burns token only if force = true
@@ -25,10 +65,10 @@ pub trait StorageManager {
 
     /// Returns min and max NEAR that can be deposited for storage,
     ///
-    /// Here min = max
+    /// Here min = max, quoted for the longest possible A/c id so that every A/c is covered
     fn storage_balance_bounds(&self) -> StorageBalanceBounds;
 
-    /// Returns Storage Balance of a given A/c,here it's the Same for Every Registered A/c,
+    /// Returns Storage Balance of a given A/c, available is the deposit not used by the A/c's measured storage
     ///
     /// None is returned for Unregistered A/c
     fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance>;
@@ -49,11 +89,10 @@ impl StorageManager for Contract {
             None => env::predecessor_account_id().into(),
         };
 
+        let payer_id = env::predecessor_account_id();
+
         if self.token.accounts.contains_key(&account_id) {
-            log!("The account is already registered, refunding the deposit");
-            if amount > 0 {
-                Promise::new(env::predecessor_account_id()).transfer(amount);
-            }
+            self.internal_refund_deposit(&account_id, &payer_id, amount);
         } else {
             let min_balance = self.storage_balance_bounds().min.0;
             require!(
@@ -61,14 +100,37 @@ impl StorageManager for Contract {
                 format!("Please Attach a deposit of {} Yocto Near", min_balance)
             );
 
-            self.token.accounts.insert(&account_id, &0);
+            self.internal_register_account(&account_id, &payer_id, min_balance);
+            self.internal_refund_deposit(&account_id, &payer_id, amount - min_balance);
+        }
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
 
-            let refund = amount - min_balance;
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
 
-            if refund > 0 {
-                Promise::new(env::predecessor_account_id()).transfer(refund);
-            }
+        let account_id = env::predecessor_account_id();
+        let storage_balance = self
+            .internal_storage_balance_of(&account_id)
+            .unwrap_or_else(|| env::panic(b"The account is not registered"));
+        let available = storage_balance.available.0;
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+
+        require!(
+            amount <= available,
+            "The amount is greater than the available storage balance"
+        );
+
+        if amount > 0 {
+            // available > 0 implies the A/c has a measured storage record
+            let mut account_storage = self.account_storage.get(&account_id).unwrap();
+            account_storage.total -= amount;
+            self.account_storage.insert(&account_id, &account_storage);
+
+            self.internal_refund_deposit(&account_id, &account_id, amount);
         }
+
         self.internal_storage_balance_of(&account_id).unwrap()
     }
 
@@ -96,6 +158,63 @@ impl StorageManager for Contract {
 /*********************************************/
 
 impl Contract {
+    /// Registers the A/c with zero balance and records the deposit paid for it along with the measured usage,
+    /// the caller must make sure storage is paid for
+    pub fn internal_register_account(
+        &mut self,
+        account_id: &AccountId,
+        payer_id: &AccountId,
+        deposit: Balance,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+
+        self.token.accounts.insert(account_id, &0);
+        self.account_storage.insert(
+            account_id,
+            &AccountStorage {
+                total: deposit,
+                usage: 0,
+            },
+        );
+
+        let usage = env::storage_usage() - initial_storage_usage;
+        self.account_storage.insert(
+            account_id,
+            &AccountStorage {
+                total: deposit,
+                usage,
+            },
+        );
+
+        CatchEventLogVariant::AccountRegistered(AccountRegisteredLog {
+            account_id: account_id.clone(),
+            payer_id: Some(payer_id.clone()).filter(|payer_id| payer_id != account_id),
+            deposit: deposit.into(),
+        })
+        .emit();
+    }
+
+    /// Transfers the refund to the payer, logging the A/c the deposit was made for
+    pub fn internal_refund_deposit(
+        &self,
+        account_id: &AccountId,
+        payer_id: &AccountId,
+        amount: Balance,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        Promise::new(payer_id.clone()).transfer(amount);
+
+        CatchEventLogVariant::DepositRefunded(DepositRefundedLog {
+            account_id: account_id.clone(),
+            payer_id: Some(payer_id.clone()).filter(|payer_id| payer_id != account_id),
+            amount: amount.into(),
+        })
+        .emit();
+    }
+
     pub fn internal_storage_unregister(
         &mut self,
         force: Option<bool>,
@@ -106,9 +225,13 @@ impl Contract {
         if let Some(balance) = self.token.accounts.get(&account_id) {
             if balance == 0 || force {
                 self.token.accounts.remove(&account_id);
+                let storage_deposit = self
+                    .account_storage
+                    .remove(&account_id)
+                    .map(|account_storage| account_storage.total)
+                    .unwrap_or_else(|| self.storage_balance_bounds().min.0);
 
-                // no need to check as balance subtracted will always be valid
-                self.token.total_supply -= balance;
+                self.token.internal_record_burn(balance);
 
                 FtBurnLog {
                     owner_id: account_id.to_string(),
@@ -117,7 +240,13 @@ impl Contract {
                 }
                 .emit();
 
-                Promise::new(account_id.clone()).transfer(self.storage_balance_bounds().min.0 + 1);
+                CatchEventLogVariant::AccountUnregistered(AccountUnregisteredLog {
+                    account_id: account_id.clone(),
+                    burned_amount: Some(balance.into()).filter(|_| balance > 0),
+                })
+                .emit();
+
+                self.internal_refund_deposit(&account_id, &account_id, storage_deposit + 1);
                 Some((account_id, balance))
             } else {
                 env::panic(b"Can't unregister the account with the positive balance without force")
@@ -129,14 +258,24 @@ impl Contract {
     }
 
     pub fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
-        if self.token.accounts.contains_key(account_id) {
-            Some(StorageBalance {
+        if !self.token.accounts.contains_key(account_id) {
+            return None;
+        }
+
+        // A/c's registered without a measured record (e.g. the owner at init) are quoted at the bounds
+        Some(match self.account_storage.get(account_id) {
+            Some(account_storage) => StorageBalance {
+                total: account_storage.total.into(),
+                available: account_storage
+                    .total
+                    .saturating_sub(Balance::from(account_storage.usage) * env::storage_byte_cost())
+                    .into(),
+            },
+            None => StorageBalance {
                 total: self.storage_balance_bounds().min,
                 available: 0.into(),
-            })
-        } else {
-            None
-        }
+            },
+        })
     }
 }
 
@@ -146,15 +285,15 @@ mod storage_tests {
     use super::*;
     use utils::test_utils::*;
 
-    use near_sdk::testing_env;
     use near_sdk::Balance;
     use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
 
     const ONE_YOCTO: Balance = 1;
-    const STORAGE_COST: Balance = 1_250_000_000_000_000_000_000; // 1 Near = 10^24 Yocto Near
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000; // 1 Near = 10^24 Yocto Near
 
     #[test]
-    #[should_panic(expected = "Please Attach a deposit of 1250000000000000000000 Yocto Near")]
+    #[should_panic(expected = "Please Attach a deposit of 2580000000000000000000 Yocto Near")]
     fn storage_deposit_fails() {
         testing_env!(get_context(carol().to_string(), 500));
         let mut contract = create_contract();
@@ -199,4 +338,115 @@ mod storage_tests {
         assert_eq!(contract.ft_balance_of(carol()).0, 0);
         assert!(!contract.storage_balance_of(carol()).is_some());
     }
+
+    #[test]
+    fn storage_balance_reflects_account_id_length() {
+        testing_env!(get_context(dex().to_string(), 0));
+        let mut contract = create_contract();
+        let min_balance = contract.storage_balance_bounds().min.0;
+
+        let short_id = ValidAccountId::try_from("ab").unwrap();
+        let long_id = ValidAccountId::try_from("a".repeat(64)).unwrap();
+
+        testing_env!(get_context(dex().to_string(), min_balance));
+        contract.storage_deposit(Some(short_id.clone()));
+        testing_env!(get_context(dex().to_string(), min_balance));
+        contract.storage_deposit(Some(long_id.clone()));
+
+        let short_balance = contract.storage_balance_of(short_id.clone()).unwrap();
+        let long_balance = contract.storage_balance_of(long_id).unwrap();
+
+        // bounds are quoted for the longest id, the short id occupies 62 bytes less in each of the two maps
+        assert_eq!(short_balance.total.0, min_balance);
+        assert_eq!(long_balance.total.0, min_balance);
+        assert_eq!(long_balance.available.0, 0);
+        assert_eq!(
+            short_balance.available.0,
+            Balance::from(2 * 62_u64) * env::storage_byte_cost()
+        );
+
+        testing_env!(get_context(short_id.to_string(), ONE_YOCTO));
+        let short_balance = contract.storage_withdraw(None);
+        assert_eq!(short_balance.available.0, 0);
+        assert_eq!(
+            short_balance.total.0,
+            min_balance - Balance::from(2 * 62_u64) * env::storage_byte_cost()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount is greater than the available storage balance")]
+    fn storage_withdraw_fails_above_surplus() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(carol().to_string(), ONE_YOCTO));
+        let available = contract.storage_balance_of(carol()).unwrap().available.0;
+        contract.storage_withdraw(Some(U128(available + 1)));
+    }
+
+    #[test]
+    fn storage_deposit_logs_registration_and_refund() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST + 10));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        // the first log is ft_mint of the initial supply
+        assert_eq!(
+            test_utils::get_logs()[1..],
+            vec![
+                r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"account_registered","data":{"account_id":"carol.near","payer_id":"dex.near","deposit":"2580000000000000000000"}}"#,
+                r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"deposit_refunded","data":{"account_id":"carol.near","payer_id":"dex.near","amount":"10"}}"#,
+            ]
+        );
+
+        // self registration of an already registered A/c refunds everything
+        testing_env!(get_context(carol().to_string(), STORAGE_COST));
+        contract.storage_deposit(None);
+
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"deposit_refunded","data":{"account_id":"carol.near","amount":"2580000000000000000000"}}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn storage_unregister_logs_burned_amount() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(carol(), U128::from(1000), None);
+
+        testing_env!(get_context(carol().to_string(), ONE_YOCTO));
+        contract.storage_unregister(Some(true));
+
+        let logs = test_utils::get_logs();
+        assert_eq!(
+            logs[1],
+            r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"account_unregistered","data":{"account_id":"carol.near","burned_amount":"1000"}}"#
+        );
+        assert_eq!(
+            logs[2],
+            r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"deposit_refunded","data":{"account_id":"carol.near","amount":"2580000000000000000001"}}"#
+        );
+    }
+
+    #[test]
+    fn storage_unregister_without_balance_logs_no_burn() {
+        testing_env!(get_context(carol().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(None);
+
+        testing_env!(get_context(carol().to_string(), ONE_YOCTO));
+        contract.storage_unregister(None);
+
+        assert_eq!(
+            test_utils::get_logs()[1],
+            r#"EVENT_JSON:{"standard":"catch_storage","version":"1.0.0","event":"account_unregistered","data":{"account_id":"carol.near"}}"#
+        );
+    }
 }
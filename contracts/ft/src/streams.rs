@@ -0,0 +1,585 @@
+use crate::*;
+
+pub type StreamId = u64;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Stream {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub total: Balance,
+    pub withdrawn: Balance,
+    /// Unix epoch in nanoseconds
+    pub start: u64,
+    /// Duration in nanoseconds
+    pub duration: u64,
+    /// NEAR paid by the sender for storing the stream, refunded once the stream is removed
+    pub storage_cost: Balance,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonStream {
+    pub stream_id: StreamId,
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub total: U128,
+    pub withdrawn: U128,
+    pub withdrawable: U128,
+    pub start: u64,
+    pub duration: u64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StreamLog {
+    pub stream_id: StreamId,
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund: Option<U128>,
+}
+
+impl Stream {
+    /// Amount streamed to the receiver until `now`, never more than total
+    ///
+    /// total * elapsed / duration is computed as q * elapsed + r * elapsed / duration
+    /// where total = q * duration + r, so that nothing overflows u128
+    pub fn vested(&self, now: u64) -> Balance {
+        let elapsed = now.saturating_sub(self.start).min(self.duration) as u128;
+        let duration = self.duration as u128;
+
+        (self.total / duration) * elapsed + (self.total % duration) * elapsed / duration
+    }
+
+    pub fn withdrawable(&self, now: u64) -> Balance {
+        self.vested(now) - self.withdrawn
+    }
+}
+
+/**********************************/
+/*  STREAMING PAYMENTS            */
+/**********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Escrows `total` from the predecessor which then flows to the receiver over `duration_sec`,
+    /// attach NEAR to cover the storage of the stream, excess is refunded
+    #[payable]
+    pub fn create_stream(
+        &mut self,
+        receiver_id: ValidAccountId,
+        total: U128,
+        duration_sec: u64,
+    ) -> StreamId {
+        // the escrow A/c is registered at the expense of the contract, not of the first sender
+        let escrow_id = self.internal_escrow_account_id();
+        let initial_storage = env::storage_usage();
+
+        let sender_id = env::predecessor_account_id();
+        let receiver_id: AccountId = receiver_id.into();
+        let total: Balance = total.into();
+
+        require!(
+            self.token.accounts.contains_key(&receiver_id),
+            "The receiver is not registered"
+        );
+        require!(duration_sec > 0, "Duration should be a positive number");
+
+        let duration = duration_sec
+            .checked_mul(1_000_000_000)
+            .unwrap_or_else(|| env::panic(b"Duration is too long"));
+
+        let stream_id = self.next_stream_id;
+        self.next_stream_id += 1;
+
+        self.token.internal_transfer(
+            &sender_id,
+            &escrow_id,
+            total,
+            Some(format!("stream {}", stream_id)),
+        );
+
+        let mut stream = Stream {
+            sender_id: sender_id.clone(),
+            receiver_id: receiver_id.clone(),
+            total,
+            withdrawn: 0,
+            start: env::block_timestamp(),
+            duration,
+            storage_cost: 0,
+        };
+        self.streams.insert(&stream_id, &stream);
+        self.internal_add_stream_to_index(true, &sender_id, stream_id);
+        self.internal_add_stream_to_index(false, &receiver_id, stream_id);
+
+        stream.storage_cost =
+            Balance::from(env::storage_usage() - initial_storage) * env::storage_byte_cost();
+        require!(
+            env::attached_deposit() >= stream.storage_cost,
+            format!(
+                "Please Attach a deposit of {} Yocto Near",
+                stream.storage_cost
+            )
+        );
+        self.streams.insert(&stream_id, &stream);
+
+        let refund = env::attached_deposit() - stream.storage_cost;
+        if refund > 0 {
+            Promise::new(sender_id.clone()).transfer(refund);
+        }
+
+        CatchEventLogVariant::StreamCreated(StreamLog {
+            stream_id,
+            sender_id,
+            receiver_id,
+            amount: total.into(),
+            refund: None,
+        })
+        .emit();
+
+        stream_id
+    }
+
+    /// Receiver only, transfers the amount streamed so far which hasn't been withdrawn yet,
+    /// it is held for claim_stream_funds if the receiver isn't registered
+    pub fn withdraw_from_stream(&mut self, stream_id: StreamId) -> U128 {
+        let mut stream = self
+            .streams
+            .get(&stream_id)
+            .unwrap_or_else(|| env::panic(b"Stream does not exist"));
+
+        require!(
+            env::predecessor_account_id() == stream.receiver_id,
+            "Only the receiver of the stream can withdraw"
+        );
+
+        let amount = stream.withdrawable(env::block_timestamp());
+        require!(amount > 0, "Nothing to withdraw yet");
+
+        stream.withdrawn += amount;
+
+        self.internal_pay_out_stream(
+            &stream.receiver_id,
+            amount,
+            Some(format!("stream {}", stream_id)),
+        );
+
+        CatchEventLogVariant::StreamWithdrawn(StreamLog {
+            stream_id,
+            sender_id: stream.sender_id.clone(),
+            receiver_id: stream.receiver_id.clone(),
+            amount: amount.into(),
+            refund: None,
+        })
+        .emit();
+
+        if stream.withdrawn == stream.total {
+            self.internal_remove_stream(stream_id, &stream);
+        } else {
+            self.streams.insert(&stream_id, &stream);
+        }
+
+        amount.into()
+    }
+
+    /// Sender or receiver, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Pays out what has been streamed until now to the receiver and refunds the rest to the sender,
+    /// the share of a party which isn't registered is held for claim_stream_funds
+    #[payable]
+    pub fn cancel_stream(&mut self, stream_id: StreamId) {
+        assert_one_yocto();
+
+        let stream = self
+            .streams
+            .get(&stream_id)
+            .unwrap_or_else(|| env::panic(b"Stream does not exist"));
+
+        let predecessor_id = env::predecessor_account_id();
+        require!(
+            predecessor_id == stream.sender_id || predecessor_id == stream.receiver_id,
+            "Only the sender or the receiver of the stream can cancel it"
+        );
+
+        let vested = stream.vested(env::block_timestamp());
+        let receiver_amount = vested - stream.withdrawn;
+        let sender_amount = stream.total - vested;
+
+        let memo = Some(format!("stream {} cancelled", stream_id));
+
+        if receiver_amount > 0 {
+            self.internal_pay_out_stream(&stream.receiver_id, receiver_amount, memo.clone());
+        }
+        if sender_amount > 0 {
+            self.internal_pay_out_stream(&stream.sender_id, sender_amount, memo);
+        }
+
+        CatchEventLogVariant::StreamCancelled(StreamLog {
+            stream_id,
+            sender_id: stream.sender_id.clone(),
+            receiver_id: stream.receiver_id.clone(),
+            amount: receiver_amount.into(),
+            refund: Some(sender_amount.into()),
+        })
+        .emit();
+
+        self.internal_remove_stream(stream_id, &stream);
+    }
+
+    /// Transfers the stream payouts held while the predecessor wasn't registered
+    pub fn claim_stream_funds(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+
+        require!(
+            self.token.accounts.contains_key(&account_id),
+            "Register with storage_deposit to claim the stream funds"
+        );
+
+        let amount = self.unclaimed_stream_funds.remove(&account_id).unwrap_or(0);
+        require!(amount > 0, "No stream funds to claim");
+
+        self.token.internal_transfer(
+            &env::current_account_id(),
+            &account_id,
+            amount,
+            Some("unclaimed stream funds".to_string()),
+        );
+
+        amount.into()
+    }
+
+    pub fn get_unclaimed_stream_funds(&self, account_id: ValidAccountId) -> U128 {
+        self.unclaimed_stream_funds
+            .get(account_id.as_ref())
+            .unwrap_or(0)
+            .into()
+    }
+
+    pub fn get_stream(&self, stream_id: StreamId) -> Option<JsonStream> {
+        self.streams
+            .get(&stream_id)
+            .map(|stream| self.internal_stream_to_json(stream_id, stream))
+    }
+
+    /// Returns paginated view of the streams sent by the A/c with live withdrawable amounts
+    pub fn get_streams_by_sender(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonStream> {
+        self.internal_get_streams(
+            self.streams_by_sender.get(account_id.as_ref()),
+            from_index,
+            limit,
+        )
+    }
+
+    /// Returns paginated view of the streams received by the A/c with live withdrawable amounts
+    pub fn get_streams_by_receiver(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonStream> {
+        self.internal_get_streams(
+            self.streams_by_receiver.get(account_id.as_ref()),
+            from_index,
+            limit,
+        )
+    }
+}
+
+impl Contract {
+    fn internal_stream_to_json(&self, stream_id: StreamId, stream: Stream) -> JsonStream {
+        JsonStream {
+            stream_id,
+            withdrawable: stream.withdrawable(env::block_timestamp()).into(),
+            sender_id: stream.sender_id,
+            receiver_id: stream.receiver_id,
+            total: stream.total.into(),
+            withdrawn: stream.withdrawn.into(),
+            start: stream.start,
+            duration: stream.duration,
+        }
+    }
+
+    fn internal_get_streams(
+        &self,
+        stream_ids: Option<UnorderedSet<StreamId>>,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonStream> {
+        let stream_ids = if let Some(stream_ids) = stream_ids {
+            stream_ids
+        } else {
+            return vec![];
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        stream_ids
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|stream_id| self.get_stream(stream_id).unwrap())
+            .collect()
+    }
+
+    fn internal_add_stream_to_index(
+        &mut self,
+        by_sender: bool,
+        account_id: &AccountId,
+        stream_id: StreamId,
+    ) {
+        let index = if by_sender {
+            &mut self.streams_by_sender
+        } else {
+            &mut self.streams_by_receiver
+        };
+
+        let mut stream_ids = index.get(account_id).unwrap_or_else(|| {
+            let account_id_hash = hash_id(account_id);
+            let key = if by_sender {
+                StorageKey::StreamsBySenderInner { account_id_hash }
+            } else {
+                StorageKey::StreamsByReceiverInner { account_id_hash }
+            };
+            UnorderedSet::new(key.try_to_vec().unwrap())
+        });
+
+        stream_ids.insert(&stream_id);
+        index.insert(account_id, &stream_ids);
+    }
+
+    fn internal_remove_stream_from_index(
+        &mut self,
+        by_sender: bool,
+        account_id: &AccountId,
+        stream_id: StreamId,
+    ) {
+        let index = if by_sender {
+            &mut self.streams_by_sender
+        } else {
+            &mut self.streams_by_receiver
+        };
+
+        if let Some(mut stream_ids) = index.get(account_id) {
+            stream_ids.remove(&stream_id);
+
+            if stream_ids.is_empty() {
+                index.remove(account_id);
+            } else {
+                index.insert(account_id, &stream_ids);
+            }
+        }
+    }
+
+    /// Transfers stream funds out of the escrow, the funds of an A/c which unregistered meanwhile
+    /// stay in escrow until it registers again and claims them
+    fn internal_pay_out_stream(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        if self.token.accounts.contains_key(account_id) {
+            self.token
+                .internal_transfer(&env::current_account_id(), account_id, amount, memo);
+            return;
+        }
+
+        log!(
+            "{} isn't registered, {} is held until claimed",
+            account_id,
+            amount
+        );
+
+        let unclaimed = self.unclaimed_stream_funds.get(account_id).unwrap_or(0);
+        self.unclaimed_stream_funds
+            .insert(account_id, &(unclaimed + amount));
+    }
+
+    fn internal_remove_stream(&mut self, stream_id: StreamId, stream: &Stream) {
+        self.streams.remove(&stream_id);
+        self.internal_remove_stream_from_index(true, &stream.sender_id, stream_id);
+        self.internal_remove_stream_from_index(false, &stream.receiver_id, stream_id);
+
+        if stream.storage_cost > 0 {
+            Promise::new(stream.sender_id.clone()).transfer(stream.storage_cost);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod stream_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{testing_env, VMContext};
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+    const STREAM_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
+    const SEC: u64 = 1_000_000_000;
+
+    fn context_at(account_id: ValidAccountId, deposit: Balance, timestamp: u64) -> VMContext {
+        let mut context = get_context(account_id.to_string(), deposit);
+        context.block_timestamp = timestamp;
+        context
+    }
+
+    /// dex streams 1000 tokens to carol over 100 seconds starting at 0
+    fn setup() -> Contract {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(context_at(dex(), STREAM_DEPOSIT, 0));
+        assert_eq!(contract.create_stream(carol(), U128(1_000), 100), 0);
+
+        contract
+    }
+
+    #[test]
+    fn vested_never_exceeds_total() {
+        let stream = Stream {
+            sender_id: dex().into(),
+            receiver_id: carol().into(),
+            total: u128::MAX,
+            withdrawn: 0,
+            start: 0,
+            duration: 3,
+            storage_cost: 0,
+        };
+
+        assert_eq!(stream.vested(1), u128::MAX / 3);
+        assert_eq!(stream.vested(2), u128::MAX / 3 * 2);
+        assert_eq!(stream.vested(3), u128::MAX);
+        assert_eq!(stream.vested(u64::MAX), u128::MAX);
+    }
+
+    #[test]
+    fn withdraw_pro_rata() {
+        let mut contract = setup();
+
+        testing_env!(context_at(carol(), 0, 25 * SEC));
+        assert_eq!(contract.withdraw_from_stream(0).0, 250);
+        assert_eq!(contract.ft_balance_of(carol()).0, 250);
+
+        let stream = &contract.get_streams_by_receiver(carol(), None, None)[0];
+        assert_eq!(stream.withdrawn.0, 250);
+        assert_eq!(stream.withdrawable.0, 0);
+
+        testing_env!(context_at(carol(), 0, 60 * SEC));
+        assert_eq!(
+            contract.get_streams_by_sender(dex(), None, None)[0]
+                .withdrawable
+                .0,
+            350
+        );
+
+        // after the end everything is withdrawable and the stream is removed
+        testing_env!(context_at(carol(), 0, 500 * SEC));
+        assert_eq!(contract.withdraw_from_stream(0).0, 750);
+        assert_eq!(contract.ft_balance_of(carol()).0, 1_000);
+        assert!(contract.get_stream(0).is_none());
+        assert!(contract.get_streams_by_sender(dex(), None, None).is_empty());
+        assert!(contract
+            .get_streams_by_receiver(carol(), None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn cancel_splits_remaining_funds() {
+        let mut contract = setup();
+        let sender_balance = contract.ft_balance_of(dex()).0;
+
+        testing_env!(context_at(carol(), 0, 10 * SEC));
+        contract.withdraw_from_stream(0);
+
+        testing_env!(context_at(dex(), ONE_YOCTO, 40 * SEC));
+        contract.cancel_stream(0);
+
+        assert_eq!(contract.ft_balance_of(carol()).0, 400);
+        assert_eq!(contract.ft_balance_of(dex()).0, sender_balance + 600);
+        assert!(contract.get_stream(0).is_none());
+    }
+
+    #[test]
+    fn escrow_registration_is_paid_by_the_contract() {
+        let mut contract = setup();
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(carol(), U128(1_000), None);
+
+        // the same stream the other way round, without registering the escrow
+        testing_env!(context_at(carol(), STREAM_DEPOSIT, 0));
+        let stream_id = contract.create_stream(dex(), U128(1_000), 100);
+
+        assert_eq!(
+            contract.streams.get(&0).unwrap().storage_cost,
+            contract.streams.get(&stream_id).unwrap().storage_cost
+        );
+    }
+
+    //carol unregisters from the token while the stream runs
+    fn unregister_carol(contract: &mut Contract) {
+        testing_env!(context_at(carol(), ONE_YOCTO, 0));
+        assert!(contract.storage_unregister(None));
+    }
+
+    #[test]
+    fn share_of_unregistered_receiver_is_held_until_claimed() {
+        let mut contract = setup();
+        let sender_balance = contract.ft_balance_of(dex()).0;
+        unregister_carol(&mut contract);
+
+        testing_env!(context_at(carol(), 0, 10 * SEC));
+        assert_eq!(contract.withdraw_from_stream(0).0, 100);
+
+        // the sender still gets the rest back
+        testing_env!(context_at(dex(), ONE_YOCTO, 40 * SEC));
+        contract.cancel_stream(0);
+        assert_eq!(contract.ft_balance_of(dex()).0, sender_balance + 600);
+        assert_eq!(contract.get_unclaimed_stream_funds(carol()).0, 400);
+
+        testing_env!(get_context(carol().to_string(), STORAGE_COST));
+        contract.storage_deposit(None);
+        assert_eq!(contract.claim_stream_funds().0, 400);
+        assert_eq!(contract.ft_balance_of(carol()).0, 400);
+        assert_eq!(contract.get_unclaimed_stream_funds(carol()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Register with storage_deposit to claim the stream funds")]
+    fn claim_by_unregistered_account_fails() {
+        let mut contract = setup();
+        unregister_carol(&mut contract);
+
+        testing_env!(context_at(carol(), 0, 10 * SEC));
+        contract.withdraw_from_stream(0);
+        contract.claim_stream_funds();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the receiver of the stream can withdraw")]
+    fn withdraw_by_sender_fails() {
+        let mut contract = setup();
+
+        testing_env!(context_at(dex(), 0, 10 * SEC));
+        contract.withdraw_from_stream(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the sender or the receiver of the stream can cancel it")]
+    fn cancel_by_third_party_fails() {
+        let mut contract = setup();
+
+        testing_env!(context_at(bob(), ONE_YOCTO, 10 * SEC));
+        contract.cancel_stream(0);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::*;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SupplyStats {
+    pub total_supply: U128,
+    pub minted_total: U128,
+    pub burned_total: U128,
+    pub max_supply: U128,
+    pub treasury_burned_total: U128,
+}
+
+/**********************************/
+/*  SUPPLY ACCOUNTING & TRACKING  */
+/**********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Marks the A/c (treasury, vesting pool, staking pool, bridge escrow) as non circulating,
+    /// returns false if the A/c was already marked
+    #[payable]
+    pub fn add_non_circulating_account(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        let added = self.non_circulating_accounts.insert(&account_id);
+
+        if added {
+            CatchEventLogVariant::NonCirculatingAccountAdded(AccountLog { account_id }).emit();
+        }
+
+        added
+    }
+
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Removes the A/c from the non circulating list, returns false if the A/c was not marked
+    #[payable]
+    pub fn remove_non_circulating_account(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        let removed = self.non_circulating_accounts.remove(&account_id);
+
+        if removed {
+            CatchEventLogVariant::NonCirculatingAccountRemoved(AccountLog { account_id }).emit();
+        }
+
+        removed
+    }
+
+    /// Returns paginated view of non circulating A/c's
+    pub fn get_non_circulating_accounts(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.non_circulating_accounts
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    /// Returns total supply minus the balances held by non circulating A/c's
+    ///
+    /// The set is expected to stay small (a handful of system A/c's) so iterating it is cheap
+    pub fn circulating_supply(&self) -> U128 {
+        let locked: Balance = self
+            .non_circulating_accounts
+            .iter()
+            .map(|account_id| self.token.accounts.get(&account_id).unwrap_or(0))
+            .sum();
+
+        // balances of a subset of A/c's can never exceed the total supply
+        (self.token.total_supply - locked).into()
+    }
+
+    /// Returns minted-to-date, burned-to-date and net supply in one call,
+    /// minted_total - burned_total == total_supply always holds
+    pub fn supply_stats(&self) -> SupplyStats {
+        SupplyStats {
+            total_supply: self.token.total_supply.into(),
+            minted_total: self.token.minted_total.into(),
+            burned_total: self.token.burned_total.into(),
+            max_supply: self.token.max_supply.into(),
+            treasury_burned_total: self.treasury_burned_total.into(),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod supply_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env, PromiseResult};
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+
+    #[test]
+    fn circulating_supply_excludes_non_circulating_accounts() {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(carol()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.ft_transfer(carol(), U128::from(1_000), None);
+
+        let total_supply = contract.ft_total_supply().0;
+        assert_eq!(contract.circulating_supply().0, total_supply);
+
+        // dex acts as treasury here
+        assert!(contract.add_non_circulating_account(dex()));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"non_circulating_account_added","data":{"account_id":"dex.near"}}"#
+        );
+        assert_eq!(contract.circulating_supply().0, 1_000);
+
+        // unregistered A/c's hold nothing and do not affect the figure
+        assert!(contract.add_non_circulating_account(alice()));
+        assert_eq!(contract.circulating_supply().0, 1_000);
+
+        assert!(contract.remove_non_circulating_account(dex()));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"non_circulating_account_removed","data":{"account_id":"dex.near"}}"#
+        );
+        assert!(!contract.remove_non_circulating_account(dex()));
+        assert_eq!(contract.circulating_supply().0, total_supply);
+        assert_eq!(
+            contract.get_non_circulating_accounts(None, None),
+            vec![alice().to_string()]
+        );
+    }
+
+    fn account(index: u64) -> ValidAccountId {
+        ValidAccountId::try_from(format!("acc{}.near", index)).unwrap()
+    }
+
+    fn assert_supply_invariant(contract: &Contract, holders: &[ValidAccountId]) {
+        let stats = contract.supply_stats();
+        assert_eq!(
+            stats.minted_total.0 - stats.burned_total.0,
+            stats.total_supply.0
+        );
+
+        let balances: Balance = holders
+            .iter()
+            .map(|account_id| contract.ft_balance_of(account_id.clone()).0)
+            .sum();
+        assert_eq!(balances, stats.total_supply.0);
+    }
+
+    #[test]
+    fn supply_stats_after_init() {
+        testing_env!(get_context(dex().to_string(), 0));
+        let contract = create_contract();
+
+        assert_eq!(
+            contract.supply_stats(),
+            SupplyStats {
+                total_supply: U128(1_000_000_000_000_000),
+                minted_total: U128(1_000_000_000_000_000),
+                burned_total: U128(0),
+                max_supply: U128(1_000_000_000_000_000),
+                treasury_burned_total: U128(0),
+            }
+        );
+    }
+
+    #[test]
+    fn supply_invariant_holds_across_random_operations() {
+        testing_env!(get_context(dex().to_string(), 0));
+        let mut contract = create_contract();
+
+        let mut holders: Vec<ValidAccountId> = (0..6).map(account).collect();
+        holders.push(dex());
+
+        // simple LCG so that the sequence is random looking but reproducible
+        let mut seed: u64 = 0x5eed;
+        let mut next = |bound: u64| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        };
+
+        for _ in 0..200 {
+            let sender = holders[next(holders.len() as u64) as usize].clone();
+            let receiver = holders[next(holders.len() as u64) as usize].clone();
+
+            match next(4) {
+                // registration
+                0 => {
+                    testing_env!(get_context(dex().to_string(), STORAGE_COST));
+                    contract.storage_deposit(Some(receiver));
+                }
+                // plain transfer
+                1 => {
+                    let balance = contract.ft_balance_of(sender.clone()).0;
+                    if balance == 0
+                        || sender == receiver
+                        || contract.storage_balance_of(receiver.clone()).is_none()
+                    {
+                        continue;
+                    }
+                    testing_env!(get_context(sender.to_string(), ONE_YOCTO));
+                    contract.ft_transfer(receiver, U128(next(balance as u64) as u128 + 1), None);
+                }
+                // forced unregister burns the remaining balance
+                2 => {
+                    if sender == dex() {
+                        continue;
+                    }
+                    testing_env!(get_context(sender.to_string(), ONE_YOCTO));
+                    contract.storage_unregister(Some(true));
+                }
+                // transfer call where the sender may vanish before the refund
+                _ => {
+                    let balance = contract.ft_balance_of(sender.clone()).0;
+                    if balance == 0
+                        || sender == receiver
+                        || contract.storage_balance_of(receiver.clone()).is_none()
+                    {
+                        continue;
+                    }
+                    let amount = next(balance as u64) as u128 + 1;
+                    contract.token.internal_transfer(
+                        &sender.to_string(),
+                        &receiver.to_string(),
+                        amount,
+                        None,
+                    );
+
+                    if sender != dex() && next(2) == 0 {
+                        testing_env!(get_context(sender.to_string(), ONE_YOCTO));
+                        contract.storage_unregister(Some(true));
+                    }
+
+                    let unused = U128(next(amount as u64 + 1) as u128);
+                    testing_env!(
+                        get_context("mike.near".to_string(), 0),
+                        Default::default(),
+                        Default::default(),
+                        Default::default(),
+                        vec![PromiseResult::Successful(
+                            near_sdk::serde_json::to_vec(&unused).unwrap()
+                        )]
+                    );
+                    contract.ft_resolve_transfer(sender.into(), receiver.into(), U128(amount));
+                }
+            }
+
+            assert_supply_invariant(&contract, &holders);
+        }
+
+        assert!(contract.supply_stats().burned_total.0 > 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only Owner of the Contract can call this method")]
+    fn add_non_circulating_account_fails_for_non_owner() {
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), ONE_YOCTO));
+        contract.add_non_circulating_account(carol());
+    }
+}
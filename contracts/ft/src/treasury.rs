@@ -0,0 +1,146 @@
+use crate::*;
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BuybackLog {
+    pub treasury_id: AccountId,
+    pub amount: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/**********************************/
+/*  TREASURY BUYBACK & BURN       */
+/**********************************/
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Sets the treasury A/c which is allowed to burn its own tokens through treasury_burn
+    #[payable]
+    pub fn set_treasury(&mut self, account_id: ValidAccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        self.treasury_id = Some(account_id.clone());
+
+        CatchEventLogVariant::TreasuryUpdated(AccountLog { account_id }).emit();
+    }
+
+    /// Returns the configured treasury A/c if any
+    pub fn get_treasury(&self) -> Option<AccountId> {
+        self.treasury_id.clone()
+    }
+
+    /// Treasury only, Wallet UX Security -> Attach 1 Yocto
+    ///
+    /// Burns tokens held by the treasury, memo should reference the governance proposal
+    #[payable]
+    pub fn treasury_burn(&mut self, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+
+        let treasury_id = env::predecessor_account_id();
+        require!(
+            self.treasury_id.as_ref() == Some(&treasury_id),
+            "Only the treasury A/c can call this method"
+        );
+
+        let amount: Balance = amount.into();
+        require!(amount > 0, "The amount should be a positive number");
+
+        let memo = validate_memo(memo);
+
+        self.token.internal_withdraw(&treasury_id, amount);
+        self.token.internal_record_burn(amount);
+        self.treasury_burned_total += amount;
+
+        FtBurnLog {
+            owner_id: treasury_id.clone(),
+            amount: U128::from(amount),
+            memo: memo.clone(),
+        }
+        .emit();
+
+        CatchEventLogVariant::CatchBuyback(BuybackLog {
+            treasury_id,
+            amount: U128::from(amount),
+            memo,
+        })
+        .emit();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod treasury_tests {
+    use super::*;
+    use utils::test_utils::*;
+
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const ONE_YOCTO: Balance = 1;
+    const STORAGE_COST: Balance = 2_580_000_000_000_000_000_000;
+
+    fn setup_treasury() -> Contract {
+        testing_env!(get_context(dex().to_string(), STORAGE_COST));
+        let mut contract = create_contract();
+        contract.storage_deposit(Some(bob()));
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.set_treasury(bob());
+        contract.ft_transfer(bob(), U128(10_000), None);
+
+        contract
+    }
+
+    #[test]
+    fn treasury_burn() {
+        let mut contract = setup_treasury();
+        let total_supply = contract.ft_total_supply().0;
+
+        testing_env!(get_context(bob().to_string(), ONE_YOCTO));
+        contract.treasury_burn(U128(4_000), Some("proposal-12".to_string()));
+
+        let logs = test_utils::get_logs();
+        assert_eq!(
+            logs[logs.len() - 2],
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":{"owner_id":"bob.near","amount":"4000","memo":"proposal-12"}}"#
+        );
+        assert_eq!(
+            logs[logs.len() - 1],
+            r#"EVENT_JSON:{"standard":"catch","version":"1.0.0","event":"catch_buyback","data":{"treasury_id":"bob.near","amount":"4000","memo":"proposal-12"}}"#
+        );
+
+        assert_eq!(contract.ft_balance_of(bob()).0, 6_000);
+        assert_eq!(contract.ft_total_supply().0, total_supply - 4_000);
+
+        let stats = contract.supply_stats();
+        assert_eq!(stats.burned_total.0, 4_000);
+        assert_eq!(stats.treasury_burned_total.0, 4_000);
+        assert_eq!(
+            stats.minted_total.0 - stats.burned_total.0,
+            stats.total_supply.0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the treasury A/c can call this method")]
+    fn treasury_burn_fails_for_other_accounts() {
+        let mut contract = setup_treasury();
+
+        testing_env!(get_context(dex().to_string(), ONE_YOCTO));
+        contract.treasury_burn(U128(1), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn treasury_burn_fails_above_balance() {
+        let mut contract = setup_treasury();
+
+        testing_env!(get_context(bob().to_string(), ONE_YOCTO));
+        contract.treasury_burn(U128(10_001), None);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::Contract;
+use near_sdk::CryptoHash;
 
 #[macro_export]
 macro_rules! require {
@@ -9,6 +10,40 @@ macro_rules! require {
     };
 }
 
+/// Max length of a transfer memo in bytes
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// Removes control characters (newlines, tabs, escape sequences ...) so that user supplied
+/// strings can be safely logged and rendered by indexers
+pub(crate) fn sanitize(input: &str) -> String {
+    input.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Panics if the memo is longer than MAX_MEMO_LEN bytes, otherwise returns the sanitized memo
+pub(crate) fn validate_memo(memo: Option<String>) -> Option<String> {
+    memo.and_then(|memo| {
+        require!(
+            memo.len() <= MAX_MEMO_LEN,
+            format!("Memo can't be longer than {} bytes", MAX_MEMO_LEN)
+        );
+
+        let memo = sanitize(&memo);
+
+        if memo.is_empty() {
+            None
+        } else {
+            Some(memo)
+        }
+    })
+}
+
+//used to generate a unique prefix in our storage collections (this is to avoid data collisions)
+pub(crate) fn hash_id(id: &str) -> CryptoHash {
+    let mut hash = CryptoHash::default();
+    hash.copy_from_slice(&near_sdk::env::sha256(id.as_bytes()));
+    hash
+}
+
 impl Contract {
     pub fn assert_owner(&self) {
         require!(
@@ -82,3 +117,46 @@ pub mod test_utils {
         Contract::new(dex(), total_supply, metadata)
     }
 }
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod utils_tests {
+    use super::*;
+    use test_utils::*;
+
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    #[test]
+    fn memo_at_limit_with_multi_byte_chars() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        // '€' is 3 bytes in UTF-8, 85 * 3 + 1 = 256 bytes
+        let memo = format!("{}a", "€".repeat(85));
+        assert_eq!(memo.len(), MAX_MEMO_LEN);
+        assert_eq!(validate_memo(Some(memo.clone())), Some(memo));
+    }
+
+    #[test]
+    #[should_panic(expected = "Memo can't be longer than 256 bytes")]
+    fn memo_over_limit_with_multi_byte_chars() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        // 85 * 3 + 2 = 257 bytes but only 86 chars
+        let memo = format!("{}é", "€".repeat(85));
+        assert_eq!(memo.len(), MAX_MEMO_LEN + 1);
+        validate_memo(Some(memo));
+    }
+
+    #[test]
+    fn memo_control_chars_are_stripped() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        assert_eq!(
+            validate_memo(Some("reward\n\u{1b}[31m #42\t".to_string())),
+            Some("reward[31m #42".to_string())
+        );
+        assert_eq!(validate_memo(Some("\n\r".to_string())), None);
+        assert_eq!(validate_memo(None), None);
+    }
+}
@@ -1,6 +1,6 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
-use near_sdk::json_types::{U128, U64};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     assert_one_yocto, env, ext_contract, near_bindgen, require, AccountId, Balance,
@@ -10,12 +10,14 @@ use std::collections::HashMap;
 
 use crate::external::*;
 use crate::internal::*;
+use crate::nft_contracts::*;
 use crate::sale::*;
 use near_sdk::env::STORAGE_PRICE_PER_BYTE;
 
 mod external;
 mod internal;
 mod nft_callbacks;
+mod nft_contracts;
 mod sale;
 mod sale_views;
 
@@ -62,6 +64,9 @@ pub struct Contract {
 
     //keep track of the storage that accounts have payed
     pub storage_deposits: LookupMap<AccountId, Balance>,
+
+    //NFT contracts which registered the marketplace as an approved marketplace
+    pub nft_contracts: UnorderedMap<AccountId, CollectionMetadata>,
 }
 
 /// Helper structure to for keys of the persistent collections.
@@ -74,6 +79,7 @@ pub enum StorageKey {
     ByNFTContractIdInner { account_id_hash: CryptoHash },
     FTTokenIds,
     StorageDeposits,
+    NftContracts,
 }
 
 #[near_bindgen]
@@ -95,6 +101,7 @@ impl Contract {
             by_nft_contract_id: LookupMap::new(StorageKey::ByNFTContractId),
             approved_ft_tokens: LookupSet::new(StorageKey::FTTokenIds),
             storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            nft_contracts: UnorderedMap::new(StorageKey::NftContracts),
         };
 
         //return the Contract object
@@ -0,0 +1,48 @@
+use crate::*;
+
+/// NFT contract metadata sent by the NFT contract when it whitelists the marketplace
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub base_uri: String,
+    pub reference: String,
+    pub reference_hash: Base64VecU8,
+}
+
+#[near_bindgen]
+impl Contract {
+    //called by an NFT contract once it has approved this marketplace
+    pub fn register_nft_contract(&mut self, collection_metadata: CollectionMetadata) {
+        let nft_contract_id = env::predecessor_account_id();
+
+        self.nft_contracts
+            .insert(&nft_contract_id, &collection_metadata);
+    }
+
+    //called by an NFT contract once it has removed this marketplace from its approved marketplaces,
+    //its sales can't be settled anymore
+    pub fn on_marketplace_removed(&mut self) {
+        let nft_contract_id = env::predecessor_account_id();
+
+        self.nft_contracts.remove(&nft_contract_id);
+    }
+
+    //returns paginated view of the NFT contracts served by the marketplace
+    pub fn get_nft_contracts(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, CollectionMetadata)> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.nft_contracts
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
@@ -0,0 +1,42 @@
+#!/usr/bin/zsh
+
+source ~/.zshrc
+
+# run after local_near_deploy_and_init.sh, local_near_create_helper_acc.sh and local_near_create_event_no_dependencies.sh
+
+# deploy the CATCH FT contract and give the total supply to event_owner.test.near
+
+local_near create-account ft.test.near --masterAccount test.near && local_near send test.near ft.test.near 50 &&
+local_near deploy --accountId ft.test.near --wasmFile ../ft/res/ft.wasm &&
+local_near call ft.test.near new '{"owner_id": "event_owner.test.near","total_supply": "1000000000","metadata": { "spec": "ft-1.0.0","name": "CAT Token","symbol": "CATCH","icon": "C-A-T-C-H","reference": "ipfs://metadata/example.link","reference_hash": "AK3YRHqKhCJNmKfV6SrutnlWW/icN5J8NUPtKsNXR1M=","decimals": 0}}' --accountId ft.test.near &&
+
+# register the nft contract and darshan on the FT contract and fund darshan
+
+local_near call ft.test.near storage_deposit '{"account_id": "contract.test.near"}' --accountId event_owner.test.near --deposit 0.01 &&
+local_near call ft.test.near storage_deposit '{"account_id": "darshan.test.near"}' --accountId event_owner.test.near --deposit 0.01 &&
+local_near call ft.test.near ft_transfer '{"receiver_id": "darshan.test.near","amount": "1000"}' --accountId event_owner.test.near --depositYocto 1 &&
+
+# accept the FT as payment and price the trophy at 100 CATCH
+
+local_near call contract.test.near set_payment_token '{"payment_token_id": "ft.test.near"}' --accountId contract.test.near --depositYocto 1 &&
+local_near call contract.test.near set_token_ft_price '{"token_id": "CATCH-CON2 new.trophy","price": "100"}' --accountId event_owner.test.near &&
+local_near view contract.test.near get_ft_priced_tokens '{}' &&
+
+# darshan pays the storage of the mints from a storage balance on the NFT contract
+local_near call contract.test.near storage_deposit '{}' --accountId darshan.test.near --deposit 0.1 &&
+
+# success : exact price, darshan balance 1000 -> 900
+
+local_near call ft.test.near ft_transfer_call '{"receiver_id": "contract.test.near","amount": "100","msg": "{\"action\":\"mint\",\"token_id\":\"CATCH-CON2 new.trophy\"}"}' --accountId darshan.test.near --depositYocto 1 --gas 100000000000000 &&
+local_near view ft.test.near ft_balance_of '{"account_id": "darshan.test.near"}' &&
+
+# partial refund : 150 sent for rahul, 50 is refunded, darshan balance 900 -> 800
+
+local_near call ft.test.near ft_transfer_call '{"receiver_id": "contract.test.near","amount": "150","msg": "{\"action\":\"mint\",\"token_id\":\"CATCH-CON2 new.trophy\",\"receiver_id\":\"rahul.test.near\"}"}' --accountId darshan.test.near --depositYocto 1 --gas 100000000000000 &&
+local_near view ft.test.near ft_balance_of '{"account_id": "darshan.test.near"}' &&
+
+# failure : darshan already owns the trophy so the mint panics and everything is refunded, balance stays 800
+
+local_near call ft.test.near ft_transfer_call '{"receiver_id": "contract.test.near","amount": "100","msg": "{\"action\":\"mint\",\"token_id\":\"CATCH-CON2 new.trophy\"}"}' --accountId darshan.test.near --depositYocto 1 --gas 100000000000000 ;
+local_near view ft.test.near ft_balance_of '{"account_id": "darshan.test.near"}' &&
+local_near view contract.test.near get_ft_proceeds '{"event_id": "CATCH-CON2 new"}'
@@ -0,0 +1,169 @@
+use crate::*;
+
+const GAS_FOR_RESOLVE_KEY_ROTATION: Gas = 5_000_000_000_000;
+
+#[ext_contract(ext_account_keys_self)]
+pub trait AccountKeysResolver {
+    fn resolve_key_rotation(
+        &mut self,
+        account_id: AccountId,
+        old_public_key: Base58PublicKey,
+        new_public_key: Base58PublicKey,
+    );
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, replaces the full access key of a player sub-account created at mint, used
+    /// when the device of the player is lost. The outcome is logged by resolve_key_rotation
+    #[payable]
+    pub fn rotate_account_key(
+        &mut self,
+        account_id: ValidAccountId,
+        old_public_key: Base58PublicKey,
+        new_public_key: Base58PublicKey,
+    ) -> Promise {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        assert_catch_sub_account(&account_id);
+        require!(
+            old_public_key != new_public_key,
+            "New key is the same as the old key"
+        );
+
+        Promise::new(account_id.clone())
+            .add_full_access_key(new_public_key.clone().into())
+            .delete_key(old_public_key.clone().into())
+            .then(ext_account_keys_self::resolve_key_rotation(
+                account_id,
+                old_public_key,
+                new_public_key,
+                &env::current_account_id(),
+                0,
+                GAS_FOR_RESOLVE_KEY_ROTATION,
+            ))
+    }
+
+    #[private]
+    pub fn resolve_key_rotation(
+        &mut self,
+        account_id: AccountId,
+        old_public_key: Base58PublicKey,
+        new_public_key: Base58PublicKey,
+    ) {
+        let log = KeyRotationLog {
+            account_id,
+            old_public_key,
+            new_public_key,
+        };
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => CatchEventLogVariant::AccountKeyRotated(log).emit(),
+            _ => CatchEventLogVariant::AccountKeyRotationFailed(log).emit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod account_keys_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const OLD_KEY: &str = "ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN";
+    const NEW_KEY: &str = "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp";
+
+    fn key(key: &str) -> Base58PublicKey {
+        Base58PublicKey::try_from(key).unwrap()
+    }
+
+    fn rotate(contract: &mut Contract, account_id: &str) {
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.rotate_account_key(
+            ValidAccountId::try_from(account_id).unwrap(),
+            key(OLD_KEY),
+            key(NEW_KEY),
+        );
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_key_rotation(
+            "player.nft.catchlabs.near".to_string(),
+            key(OLD_KEY),
+            key(NEW_KEY),
+        );
+    }
+
+    #[test]
+    fn rotation_swaps_the_keys() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        rotate(&mut contract, "player.nft.catchlabs.near");
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("AddKey") && receipts.contains("DeleteKey"));
+        assert!(receipts.contains("resolve_key_rotation"));
+
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            &format!(
+                r#"EVENT_JSON:{{"standard":"catch","version":"1.1.0","event":"account_key_rotated","data":{{"account_id":"player.nft.catchlabs.near","old_public_key":"{}","new_public_key":"{}"}}}}"#,
+                OLD_KEY, NEW_KEY
+            )
+        );
+    }
+
+    #[test]
+    fn failed_rotation_is_logged() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        resolve(&mut contract, PromiseResult::Failed);
+        assert!(test_utils::get_logs()
+            .last()
+            .unwrap()
+            .contains(r#""event":"account_key_rotation_failed""#));
+    }
+
+    #[test]
+    #[should_panic(expected = "player.near is not a Catch sub-account")]
+    fn rotation_of_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        rotate(&mut contract, "player.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "a.player.nft.catchlabs.near is not a Catch sub-account")]
+    fn rotation_of_nested_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        rotate(&mut contract, "a.player.nft.catchlabs.near");
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn rotation_by_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.rotate_account_key(
+            ValidAccountId::try_from("player.nft.catchlabs.near").unwrap(),
+            key(OLD_KEY),
+            key(NEW_KEY),
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use crate::*;
+
+const GAS_FOR_AFFILIATE_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_AFFILIATE: Gas = 10_000_000_000_000;
+
+const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+#[ext_contract(ext_affiliate_self)]
+pub trait AffiliateResolver {
+    fn resolve_affiliate_reward(
+        &mut self,
+        token_id: TokenId,
+        affiliate_id: AccountId,
+        amount: U128,
+    );
+}
+
+/// Referrers of a sale earn bps of the sale balance, converted to the payment token at ft_per_near
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AffiliateProgram {
+    pub enabled: bool,
+    pub bps: u32,
+    //payment token paid for a sale balance worth 1 NEAR
+    pub ft_per_near: U128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonAffiliateProgram {
+    pub program: Option<AffiliateProgram>,
+    pub pool: U128,
+}
+
+//reward of the affiliate in the payment token for a sale balance in yoctoNEAR
+pub(crate) fn affiliate_reward(balance: Balance, program: &AffiliateProgram) -> Balance {
+    let near_value = balance * program.bps as u128 / ROYALTY_BASIS_POINTS as u128;
+    let rate = program.ft_per_near.0;
+
+    near_value / ONE_NEAR * rate + near_value % ONE_NEAR * rate / ONE_NEAR
+}
+
+impl Contract {
+    //adds FT sent by the owner with an affiliate_funding msg to the affiliate pool
+    pub(crate) fn internal_fund_affiliate_pool(&mut self, sender_id: AccountId, amount: Balance) {
+        require!(
+            sender_id == self.owner_id,
+            "Only the owner can fund the affiliate pool"
+        );
+
+        self.affiliate_pool += amount;
+        self.ft_liabilities += amount;
+
+        CatchEventLogVariant::AffiliatePoolFunded(AffiliatePoolLog {
+            amount: amount.into(),
+            pool: self.affiliate_pool.into(),
+        })
+        .emit();
+    }
+
+    //takes the reward of the sale out of the pool and pays it to the affiliate, the reward is
+    //queued for claim_affiliate_reward if the sale can't spare the gas
+    pub(crate) fn internal_reward_affiliate(
+        &mut self,
+        token_id: &TokenId,
+        affiliate_id: AccountId,
+        balance: Balance,
+    ) {
+        let program = match &self.affiliate_program {
+            Some(program) if program.enabled => program,
+            _ => return,
+        };
+
+        let amount = affiliate_reward(balance, program);
+        if amount == 0 {
+            return;
+        }
+
+        if self.affiliate_pool < amount {
+            CatchEventLogVariant::CatchAffiliate(AffiliateLog {
+                token_id: token_id.clone(),
+                affiliate_id,
+                amount: amount.into(),
+                status: AffiliateRewardStatus::Unfunded,
+            })
+            .emit();
+            return;
+        }
+
+        self.affiliate_pool -= amount;
+
+        let remaining_gas = env::prepaid_gas().saturating_sub(env::used_gas());
+        if remaining_gas < GAS_FOR_AFFILIATE_TRANSFER + GAS_FOR_RESOLVE_AFFILIATE {
+            self.internal_queue_affiliate_reward(token_id.clone(), affiliate_id, amount);
+            return;
+        }
+
+        self.internal_transfer_affiliate_reward(token_id.clone(), affiliate_id, amount);
+    }
+
+    fn internal_queue_affiliate_reward(
+        &mut self,
+        token_id: TokenId,
+        affiliate_id: AccountId,
+        amount: Balance,
+    ) {
+        let pending = self.affiliate_rewards.get(&affiliate_id).unwrap_or(0);
+        self.affiliate_rewards
+            .insert(&affiliate_id, &(pending + amount));
+
+        CatchEventLogVariant::CatchAffiliate(AffiliateLog {
+            token_id,
+            affiliate_id,
+            amount: amount.into(),
+            status: AffiliateRewardStatus::Queued,
+        })
+        .emit();
+    }
+
+    fn internal_transfer_affiliate_reward(
+        &self,
+        token_id: TokenId,
+        affiliate_id: AccountId,
+        amount: Balance,
+    ) -> Promise {
+        ext_ft::ft_transfer(
+            affiliate_id.clone(),
+            amount.into(),
+            Some(format!("Affiliate reward for {}", &token_id)),
+            self.payment_token_id.as_ref().unwrap(),
+            1,
+            GAS_FOR_AFFILIATE_TRANSFER,
+        )
+        .then(ext_affiliate_self::resolve_affiliate_reward(
+            token_id,
+            affiliate_id,
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_AFFILIATE,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, configures the affiliate program, the pool is funded with an
+    /// affiliate_funding ft_transfer_call on the payment token
+    pub fn set_affiliate_program(&mut self, enabled: bool, bps: u32, ft_per_near: U128) {
+        self.assert_owner();
+
+        require!(self.payment_token_id.is_some(), "Payment token is not set");
+        require!(
+            bps <= ROYALTY_BASIS_POINTS,
+            format!("Affiliate bps can't exceed {}", ROYALTY_BASIS_POINTS)
+        );
+
+        let program = AffiliateProgram {
+            enabled,
+            bps,
+            ft_per_near,
+        };
+
+        CatchEventLogVariant::AffiliateProgramUpdated(program.clone()).emit();
+
+        self.affiliate_program = Some(program);
+    }
+
+    pub fn get_affiliate_program(&self) -> JsonAffiliateProgram {
+        JsonAffiliateProgram {
+            program: self.affiliate_program.clone(),
+            pool: self.affiliate_pool.into(),
+        }
+    }
+
+    //affiliate rewards which couldn't be delivered to the account
+    pub fn get_affiliate_reward(&self, account_id: AccountId) -> U128 {
+        self.affiliate_rewards.get(&account_id).unwrap_or(0).into()
+    }
+
+    /// Transfers the affiliate rewards which couldn't be delivered, e.g. the affiliate
+    /// wasn't registered on the payment token
+    pub fn claim_affiliate_reward(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+
+        let amount = self.affiliate_rewards.remove(&account_id).unwrap_or(0);
+        require!(amount > 0, "No affiliate reward to claim");
+
+        self.internal_transfer_affiliate_reward("claim".to_string(), account_id, amount)
+    }
+
+    //queues the reward for claim_affiliate_reward if the transfer failed
+    #[private]
+    pub fn resolve_affiliate_reward(
+        &mut self,
+        token_id: TokenId,
+        affiliate_id: AccountId,
+        amount: U128,
+    ) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            CatchEventLogVariant::CatchAffiliate(AffiliateLog {
+                token_id,
+                affiliate_id,
+                amount,
+                status: AffiliateRewardStatus::Paid,
+            })
+            .emit();
+            self.internal_settle_ft_liability(amount.0);
+            return;
+        }
+
+        self.internal_queue_affiliate_reward(token_id, affiliate_id, amount.0);
+    }
+}
+
+#[cfg(test)]
+mod affiliate_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    /// 5% of the sale balance is paid at 100 FT per NEAR, the pool holds 1000 FT
+    /// and alice listed her gold pass on the marketplace
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_affiliate_program(true, 500, U128(100));
+        contract.approve_marketplaces(vec![marketplace()]);
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = r#"{"action":"affiliate_funding"}"#;
+        contract.ft_on_transfer(nft(), U128(1000), msg.to_string());
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        contract
+    }
+
+    fn sell(contract: &mut Contract, affiliate_id: Option<AccountId>) {
+        testing_env!(get_context(marketplace().to_string(), 1));
+        contract.nft_transfer_payout(
+            bob().to_string(),
+            PASS.to_string(),
+            None,
+            U128(20 * ONE_NEAR),
+            None,
+            10,
+            affiliate_id,
+        );
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_affiliate_reward(PASS.to_string(), carol().into(), U128(100));
+    }
+
+    #[test]
+    fn sale_pays_affiliate() {
+        let mut contract = setup();
+
+        sell(&mut contract, Some(carol().into()));
+        assert_eq!(contract.get_affiliate_program().pool.0, 900);
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("resolve_affiliate_reward"));
+
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"catch_affiliate","data":{"token_id":"catchcon.gold.alice.near","affiliate_id":"carol.near","amount":"100","status":"paid"}}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_reward_is_claimable() {
+        let mut contract = setup();
+
+        sell(&mut contract, Some(carol().into()));
+        resolve(&mut contract, PromiseResult::Failed);
+        assert_eq!(contract.get_affiliate_reward(carol().into()).0, 100);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.claim_affiliate_reward();
+        assert_eq!(contract.get_affiliate_reward(carol().into()).0, 0);
+    }
+
+    #[test]
+    fn no_reward_when_program_is_disabled() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_affiliate_program(false, 500, U128(100));
+
+        sell(&mut contract, Some(carol().into()));
+        assert_eq!(contract.get_affiliate_program().pool.0, 1000);
+    }
+
+    #[test]
+    fn unfunded_reward_is_not_paid() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_affiliate_program(true, 10_000, U128(100));
+
+        sell(&mut contract, Some(carol().into()));
+        assert_eq!(contract.get_affiliate_program().pool.0, 1000);
+        assert!(test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains(r#""amount":"2000","status":"unfunded""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The buyer can't be the affiliate of the sale")]
+    fn buyer_as_affiliate_fails() {
+        let mut contract = setup();
+
+        sell(&mut contract, Some(bob().into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The token owner can't be the affiliate of the sale")]
+    fn token_owner_as_affiliate_fails() {
+        let mut contract = setup();
+
+        sell(&mut contract, Some(alice().into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can fund the affiliate pool")]
+    fn funding_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = r#"{"action":"affiliate_funding"}"#;
+        contract.ft_on_transfer(carol(), U128(1000), msg.to_string());
+    }
+}
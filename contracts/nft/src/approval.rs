@@ -1,7 +1,9 @@
 use crate::*;
 
+//gas kept for the rest of nft_approve when nft_on_approve is called
 const GAS_FOR_NFT_APPROVE: Gas = 10_000_000_000_000;
 const NO_DEPOSIT: Balance = 0;
+pub(crate) const DEFAULT_MAX_APPROVALS_PER_TOKEN: u32 = 10;
 
 #[derive(BorshDeserialize, BorshSerialize, Clone, Default)]
 pub struct ApprovalInfo {
@@ -9,9 +11,81 @@ pub struct ApprovalInfo {
     pub next_approval_id: u64,
 }
 
+//prefix of the approvals of the owners of a class kept inside it before approvals_by_token_id
+pub(crate) fn legacy_approvals_prefix(class_token_id: &TokenId) -> Vec<u8> {
+    StorageKey::ApprovedAccountsPerToken {
+        token_id_hash: hash_id(class_token_id),
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+/// Whitelist entry of a marketplace, trial marketplaces are approved until expires_at
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketplaceApproval {
+    //Unix epoch in milliseconds, None never expires
+    pub expires_at: Option<u64>,
+    pub added_at_block: BlockHeight,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonMarketplaceApproval {
+    pub marketplace_id: AccountId,
+    pub expires_at: Option<u64>,
+    pub added_at_block: BlockHeight,
+    pub expired: bool,
+}
+
+impl MarketplaceApproval {
+    pub(crate) fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now_ms())
+    }
+}
+
+impl Contract {
+    //whitelisted marketplaces whose approval hasn't expired
+    pub(crate) fn internal_is_approved_marketplace(&self, account_id: &AccountId) -> bool {
+        self.approved_marketplaces
+            .get(account_id)
+            .is_some_and(|approval| !approval.is_expired())
+    }
+
+    //false if the marketplace is already approved, an expired approval is replaced
+    pub(crate) fn internal_approve_marketplace(
+        &mut self,
+        account_id: AccountId,
+        expires_at: Option<u64>,
+    ) -> bool {
+        if self.internal_is_approved_marketplace(&account_id) {
+            return false;
+        }
+
+        self.approved_marketplaces.insert(
+            &account_id,
+            &MarketplaceApproval {
+                expires_at,
+                added_at_block: env::block_index(),
+            },
+        );
+
+        CatchEventLogVariant::MarketplaceChange(MarketplaceChangeLog {
+            marketplace_id: account_id,
+            approved: true,
+        })
+        .emit();
+
+        true
+    }
+}
+
 pub trait NonFungibleTokenCore {
-    //approve an account ID to transfer a token on your behalf, here it will be only for marketplaces
-    fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>);
+    //approve an account ID to transfer a token on your behalf, here it will be only for marketplaces,
+    //returns the approval id which nft_transfer_payout checks against
+    fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>)
+        -> u64;
 
     //check if the passed in account has access to approve the token ID
     fn nft_is_approved(
@@ -43,20 +117,52 @@ trait NonFungibleTokenApprovalsReceiver {
 #[near_bindgen]
 impl NonFungibleTokenCore for Contract {
     #[payable]
-    fn nft_approve(&mut self, token_id: TokenId, account_id: AccountId, msg: Option<String>) {
+    fn nft_approve(
+        &mut self,
+        token_id: TokenId,
+        account_id: AccountId,
+        msg: Option<String>,
+    ) -> u64 {
         assert_at_least_one_yocto();
+        self.assert_not_paused(PausableFeature::Approve);
+
+        require!(
+            self.internal_active_lease(&token_id).is_none(),
+            "Token is leased"
+        );
+        require!(
+            self.parent_by_token_id.get(&token_id).is_none(),
+            "Token is attached"
+        );
 
-        let (token_id, owner_id) = resolve_token_id(token_id);
+        let full_token_id = token_id;
+        let (token_id, owner_id) = resolve_token_id(full_token_id.clone());
 
-        let mut token = self.tokens_by_id.get(&token_id).expect("No token");
+        let token = self.tokens_by_id.get(&token_id).expect("No token");
 
+        require!(!token.soulbound, "Token is soulbound");
+        require!(
+            self.internal_is_transferable_pass(&token_id),
+            "Passes of the event aren't transferable"
+        );
+        self.assert_not_blacklisted(&owner_id);
+        self.assert_not_blacklisted(&account_id);
+        require!(
+            self.frozen_tokens.get(&full_token_id).is_none(),
+            "Token is frozen"
+        );
+        require!(
+            self.locks_by_token_id.get(&full_token_id).is_none(),
+            "Token is locked"
+        );
+        require!(self.internal_is_active(&token_id), "Token not yet active");
         require!(
-            self.approved_marketplaces.contains(&account_id),
+            self.internal_is_approved_marketplace(&account_id),
             "You cannot list on other marketplaces other than Catch Approved Marketplace"
         );
 
         require!(
-            !internal_is_token_expired(&token),
+            !self.internal_is_copy_expired(&full_token_id, &token),
             "Can't approve other account since Token has already expired"
         );
 
@@ -75,39 +181,55 @@ impl NonFungibleTokenCore for Contract {
             "Predecessor must be the token owner."
         );
 
-        let mut approval_info = token
-            .account_approval_info_per_owner
-            .get(&owner_id)
+        let mut approval_info = self
+            .approvals_by_token_id
+            .get(&full_token_id)
             .unwrap_or_default();
 
+        //ids grow with every approval of the copy, re-approving an account replaces its id
+        //so listings made with the old id are stale
         let approval_id: u64 = approval_info.next_approval_id;
 
-        let is_new_approval = approval_info
-            .approved_account_ids
-            .insert(account_id.clone(), approval_id)
-            .is_none();
+        require!(
+            approval_info.approved_account_ids.contains_key(&account_id)
+                || approval_info.approved_account_ids.len() < self.max_approvals_per_token as usize,
+            format!(
+                "Can't approve more than {} accounts for a token, revoke an approval first",
+                self.max_approvals_per_token
+            )
+        );
 
-        //if it was a new approval, we need to calculate how much storage is being used to add the account.
-        let storage_used = if is_new_approval {
-            bytes_for_approved_account_id(&account_id)
-        //if it was not a new approval, we used no storage.
-        } else {
-            0
-        };
+        approval_info
+            .approved_account_ids
+            .insert(account_id.clone(), approval_id);
 
         approval_info.next_approval_id += 1;
 
-        token
-            .account_approval_info_per_owner
-            .insert(&owner_id, &approval_info);
+        //the first approval of a copy also creates its record, so the storage is measured instead of
+        //derived from the account ID. The record carries next_approval_id and follows the copy
+        let initial_storage = env::storage_usage();
+        self.approvals_by_token_id
+            .insert(&full_token_id, &approval_info);
 
-        self.tokens_by_id.insert(&token_id, &token);
+        self.internal_pay_storage(env::storage_usage().saturating_sub(initial_storage));
 
-        refund_deposit(storage_used);
+        CatchEventLogVariant::NftApprove(NftApproveLog {
+            token_id: full_token_id,
+            owner_id: owner_id.clone(),
+            account_id: account_id.clone(),
+            approval_id,
+        })
+        .emit();
 
         //if some message was passed into the function, we initiate a cross contract call on the
-        //account we're giving access to.
-        if let Some(msg) = msg {
+        //marketplace we're giving access to, nothing here depends on its result
+        if let Some(msg) = msg.filter(|msg| !msg.is_empty()) {
+            let remaining_gas = env::prepaid_gas().saturating_sub(env::used_gas());
+            require!(
+                remaining_gas > GAS_FOR_NFT_APPROVE,
+                "Not enough gas to notify the marketplace"
+            );
+
             ext_non_fungible_approval_receiver::nft_on_approve(
                 build_full_token_id(token_id, owner_id.clone()),
                 owner_id,
@@ -115,28 +237,33 @@ impl NonFungibleTokenCore for Contract {
                 msg,
                 &account_id, // contract account we're calling
                 NO_DEPOSIT,  // NEAR deposit we attach to the call
-                env::prepaid_gas() - GAS_FOR_NFT_APPROVE, // GAS we're attaching
-            )
-            .as_return(); // Returning this promise
+                std::cmp::min(
+                    remaining_gas - GAS_FOR_NFT_APPROVE,
+                    GAS_FOR_NFT_APPROVE * 10,
+                ), // GAS we're attaching, at most 100 Tgas
+            );
         }
+
+        approval_id
     }
 
-    //check if the passed in account has access to approve the token ID
+    //check if the passed in account has access to approve the token ID, unknown or burnt tokens
+    //and copies the owner no longer holds aren't approved
     fn nft_is_approved(
         &self,
         token_id: TokenId,
         approved_account_id: AccountId,
         approval_id: Option<u64>,
     ) -> bool {
-        let (token_id, owner_id) = resolve_token_id(token_id);
+        let full_token_id = token_id;
+        let (token_id, owner_id) = resolve_token_id(full_token_id.clone());
 
-        let token = self
-            .tokens_by_id
-            .get(&token_id)
-            .unwrap_or_else(|| env::panic(b"No token"));
+        if !self.internal_owns_token(&owner_id, &token_id) {
+            return false;
+        }
 
         let approved_account_ids =
-            if let Some(approval_info) = token.account_approval_info_per_owner.get(&owner_id) {
+            if let Some(approval_info) = self.approvals_by_token_id.get(&full_token_id) {
                 approval_info.approved_account_ids
             } else {
                 return false;
@@ -165,9 +292,10 @@ impl NonFungibleTokenCore for Contract {
     fn nft_revoke(&mut self, token_id: TokenId, account_id: AccountId) {
         assert_one_yocto();
 
-        let (token_id, owner_id) = resolve_token_id(token_id);
+        let full_token_id = token_id;
+        let (token_id, owner_id) = resolve_token_id(full_token_id.clone());
 
-        let mut token = self.tokens_by_id.get(&token_id).expect("No token");
+        require!(self.tokens_by_id.get(&token_id).is_some(), "No token");
 
         let predecessor_account_id = env::predecessor_account_id();
 
@@ -186,19 +314,26 @@ impl NonFungibleTokenCore for Contract {
             "Revoke can only be performed by owner of NFT"
         );
 
-        if let Some(mut approval_info) = token.account_approval_info_per_owner.get(&owner_id) {
+        if let Some(mut approval_info) = self.approvals_by_token_id.get(&full_token_id) {
             if approval_info
                 .approved_account_ids
                 .remove(&account_id)
                 .is_some()
             {
-                refund_approved_account_ids_iter(predecessor_account_id, [account_id].iter());
-
-                token
-                    .account_approval_info_per_owner
-                    .insert(&owner_id, &approval_info);
-
-                self.tokens_by_id.insert(&token_id, &token);
+                self.refund_approved_account_ids_iter(
+                    predecessor_account_id,
+                    [&account_id].into_iter(),
+                );
+
+                self.approvals_by_token_id
+                    .insert(&full_token_id, &approval_info);
+
+                CatchEventLogVariant::NftRevoke(RevokeLog {
+                    token_id: full_token_id,
+                    owner_id,
+                    account_ids: vec![account_id],
+                })
+                .emit();
             }
         }
     }
@@ -208,9 +343,10 @@ impl NonFungibleTokenCore for Contract {
     fn nft_revoke_all(&mut self, token_id: TokenId) {
         assert_one_yocto();
 
-        let (token_id, owner_id) = resolve_token_id(token_id);
+        let full_token_id = token_id;
+        let (token_id, owner_id) = resolve_token_id(full_token_id.clone());
 
-        let mut token = self.tokens_by_id.get(&token_id).expect("No token");
+        require!(self.tokens_by_id.get(&token_id).is_some(), "No token");
 
         let predecessor_account_id = env::predecessor_account_id();
 
@@ -230,7 +366,7 @@ impl NonFungibleTokenCore for Contract {
         );
 
         let (mut approved_account_ids, next_approval_id) =
-            if let Some(approval_info) = token.account_approval_info_per_owner.get(&owner_id) {
+            if let Some(approval_info) = self.approvals_by_token_id.get(&full_token_id) {
                 (
                     approval_info.approved_account_ids,
                     approval_info.next_approval_id,
@@ -241,18 +377,28 @@ impl NonFungibleTokenCore for Contract {
 
         //only revoke if the approved account IDs for the token is not empty
         if !approved_account_ids.is_empty() {
-            refund_approved_account_ids(predecessor_account_id, &approved_account_ids);
-            approved_account_ids.clear();
+            self.refund_approved_account_ids(predecessor_account_id, &approved_account_ids);
 
-            token.account_approval_info_per_owner.insert(
-                &owner_id,
+            let mut account_ids: Vec<AccountId> = approved_account_ids
+                .drain()
+                .map(|(account_id, _)| account_id)
+                .collect();
+            account_ids.sort();
+
+            self.approvals_by_token_id.insert(
+                &full_token_id,
                 &ApprovalInfo {
                     approved_account_ids,
                     next_approval_id,
                 },
             );
 
-            self.tokens_by_id.insert(&token_id, &token);
+            CatchEventLogVariant::NftRevoke(RevokeLog {
+                token_id: full_token_id,
+                owner_id,
+                account_ids,
+            })
+            .emit();
         }
     }
 }
@@ -269,9 +415,609 @@ impl Contract {
         let mut added = Vec::with_capacity(marketplaces.len());
 
         for marketplace in marketplaces {
-            added.push(self.approved_marketplaces.insert(&marketplace.into()));
+            added.push(self.internal_approve_marketplace(marketplace.into(), None));
         }
 
         added
     }
+
+    /// Owner only, allows listing on the marketplace until expires_at (ms) or indefinitely,
+    /// false if it was already approved
+    #[payable]
+    pub fn add_approved_marketplace(
+        &mut self,
+        account_id: ValidAccountId,
+        expires_at: Option<u64>,
+    ) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            expires_at.is_none_or(|expires_at| expires_at > now_ms()),
+            "Expiry must be in the future"
+        );
+
+        self.internal_approve_marketplace(account_id.into(), expires_at)
+    }
+
+    /// Owner only, applies to new approvals, tokens which already have more approvals keep them
+    pub fn set_max_approvals_per_token(&mut self, max_approvals: u32) {
+        self.assert_owner();
+
+        require!(max_approvals > 0, "At least one approval must be allowed");
+
+        self.max_approvals_per_token = max_approvals;
+    }
+
+    pub fn get_max_approvals_per_token(&self) -> u32 {
+        self.max_approvals_per_token
+    }
+
+    /// Owner only, moves the expiry of an approved marketplace, None approves it indefinitely
+    #[payable]
+    pub fn extend_marketplace_approval(
+        &mut self,
+        account_id: ValidAccountId,
+        new_expiry: Option<u64>,
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let marketplace_id: AccountId = account_id.into();
+        let mut approval = self
+            .approved_marketplaces
+            .get(&marketplace_id)
+            .unwrap_or_else(|| env::panic(b"Marketplace is not approved"));
+
+        if let Some(new_expiry) = new_expiry {
+            require!(new_expiry > now_ms(), "Expiry must be in the future");
+            require!(
+                approval
+                    .expires_at
+                    .is_some_and(|expires_at| new_expiry > expires_at),
+                "New expiry must be later than the current one"
+            );
+        }
+
+        approval.expires_at = new_expiry;
+        self.approved_marketplaces
+            .insert(&marketplace_id, &approval);
+    }
+
+    /// Owner only, moves the whitelist kept before approvals had an expiry into approval records
+    /// without expiry, returns how many marketplaces were moved. The length of the old set was
+    /// part of the old contract state, so its elements are read until the first missing index
+    pub fn migrate_approved_marketplaces(&mut self) -> u64 {
+        self.assert_owner();
+
+        let prefix = StorageKey::ApprovedMarketplaces.try_to_vec().unwrap();
+        let element_key = |index: u64| [&prefix[..], b"e", &index.to_le_bytes()].concat();
+
+        let mut migrated = 0;
+        while let Some(raw_marketplace_id) = env::storage_read(&element_key(migrated)) {
+            env::storage_remove(&element_key(migrated));
+            env::storage_remove(&[&prefix[..], b"i", &raw_marketplace_id].concat());
+
+            let marketplace_id = AccountId::try_from_slice(&raw_marketplace_id).unwrap();
+            if self.approved_marketplaces.get(&marketplace_id).is_none() {
+                self.internal_approve_marketplace(marketplace_id, None);
+            }
+
+            migrated += 1;
+        }
+
+        migrated
+    }
+
+    /// Owner only, rewrites the token classes stored while approvals were kept inside them and
+    /// moves the approvals of their copies to approvals_by_token_id, returns how many classes and
+    /// copies were migrated. token_ids lists classes (event_id.token_id) and copies
+    /// (event_id.token_id.owner_id), the holders come from the indexer since the old approvals
+    /// can't be enumerated. Migrated entries are skipped, so the call can be repeated in pages
+    pub fn migrate_token_approvals(&mut self, token_ids: Vec<TokenId>) -> u64 {
+        self.assert_owner();
+
+        let mut migrated = 0;
+        for token_id in token_ids {
+            if self.tokens_by_id.migrate_v1(&token_id) {
+                migrated += 1;
+                continue;
+            }
+
+            let (class_token_id, owner_id) = match try_resolve_token_id(&token_id) {
+                Some(resolved) => resolved,
+                None => continue,
+            };
+            let mut legacy_approvals: LookupMap<AccountId, ApprovalInfo> =
+                LookupMap::new(legacy_approvals_prefix(&class_token_id));
+            let legacy_approval_info = match legacy_approvals.remove(&owner_id) {
+                Some(approval_info) => approval_info,
+                None => continue,
+            };
+
+            //approvals granted since the upgrade are kept, ids given out before aren't reused
+            let approval_info = match self.approvals_by_token_id.get(&token_id) {
+                Some(mut approval_info) => {
+                    approval_info.next_approval_id = std::cmp::max(
+                        approval_info.next_approval_id,
+                        legacy_approval_info.next_approval_id,
+                    );
+                    approval_info
+                }
+                None => legacy_approval_info,
+            };
+            self.approvals_by_token_id.insert(&token_id, &approval_info);
+            migrated += 1;
+        }
+
+        migrated
+    }
+
+    /// Owner only, stops listing on the marketplace, false if it wasn't approved. Unlike
+    /// remove_marketplace the marketplace isn't notified, the event lets indexers drop its listings
+    #[payable]
+    pub fn remove_approved_marketplace(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let marketplace_id: AccountId = account_id.into();
+        if self.approved_marketplaces.remove(&marketplace_id).is_none() {
+            return false;
+        }
+
+        CatchEventLogVariant::MarketplaceChange(MarketplaceChangeLog {
+            marketplace_id,
+            approved: false,
+        })
+        .emit();
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod approval_tests {
+    use super::*;
+    use crate::nft_core::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        contract.internal_approve_marketplace(bob().into(), None);
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        contract
+    }
+
+    #[test]
+    fn approval_ids_grow_and_replace() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), marketplace().into(), None),
+            0
+        );
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), bob().into(), None),
+            1
+        );
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), marketplace().into(), None),
+            2
+        );
+
+        let approvals = contract
+            .nft_token(PASS.to_string())
+            .unwrap()
+            .approved_account_ids;
+        assert_eq!(approvals.len(), 2);
+        assert_eq!(approvals[&marketplace().to_string()], 2);
+
+        assert!(!contract.nft_is_approved(PASS.to_string(), marketplace().into(), Some(0)));
+        assert!(contract.nft_is_approved(PASS.to_string(), marketplace().into(), Some(2)));
+    }
+
+    #[test]
+    fn ids_are_not_reused_after_revoke() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_revoke_all(PASS.to_string());
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), marketplace().into(), None),
+            1
+        );
+    }
+
+    #[test]
+    fn approve_logs_the_approval() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_approve","data":{"token_id":"catchcon.gold.alice.near","owner_id":"alice.near","account_id":"marketplace.near","approval_id":0}}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach")]
+    fn approval_without_storage_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    fn revoke_logs_the_account() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+        contract.nft_approve(PASS.to_string(), bob().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_revoke(PASS.to_string(), bob().into());
+
+        assert!(!contract.nft_is_approved(PASS.to_string(), bob().into(), None));
+        assert!(contract.nft_is_approved(PASS.to_string(), marketplace().into(), None));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_revoke","data":{"token_id":"catchcon.gold.alice.near","owner_id":"alice.near","account_ids":["bob.near"]}}"#
+            ]
+        );
+        // the storage of the approval is refunded
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+    }
+
+    #[test]
+    fn revoke_all_keeps_approvals_of_other_owners() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve(
+            "catchcon.gold.bob.near".to_string(),
+            marketplace().into(),
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+        contract.nft_approve(PASS.to_string(), bob().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_revoke_all(PASS.to_string());
+
+        assert!(contract
+            .nft_token(PASS.to_string())
+            .unwrap()
+            .approved_account_ids
+            .is_empty());
+        assert!(contract.nft_is_approved(
+            "catchcon.gold.bob.near".to_string(),
+            marketplace().into(),
+            None
+        ));
+        assert!(
+            test_utils::get_logs()[0].contains(r#""account_ids":["bob.near","marketplace.near"]"#)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Revoke can only be performed by owner of NFT")]
+    fn revoke_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.nft_revoke(PASS.to_string(), marketplace().into());
+    }
+
+    #[test]
+    fn approval_ends_with_the_transfer() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        let approval_id = contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+
+        assert!(!contract.nft_is_approved(
+            PASS.to_string(),
+            marketplace().into(),
+            Some(approval_id)
+        ));
+        assert!(!contract.nft_is_approved(
+            "catchcon.gold.bob.near".to_string(),
+            marketplace().into(),
+            None
+        ));
+    }
+
+    #[test]
+    fn unknown_token_is_not_approved() {
+        let contract = setup();
+
+        assert!(!contract.nft_is_approved(
+            "catchcon.silver.alice.near".to_string(),
+            marketplace().into(),
+            None
+        ));
+        assert!(!contract.nft_is_approved(
+            "catchcon.gold.carol.near".to_string(),
+            marketplace().into(),
+            None
+        ));
+    }
+
+    #[test]
+    fn approval_msg_is_forwarded_to_marketplace() {
+        let mut contract = setup();
+
+        // the refund of the excess deposit and nft_on_approve
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(
+            PASS.to_string(),
+            marketplace().into(),
+            Some(r#"{"price":"100"}"#.to_string()),
+        );
+        assert_eq!(test_utils::get_created_receipts().len(), 2);
+
+        // empty messages aren't forwarded
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), Some(String::new()));
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "You cannot list on other marketplaces other than Catch Approved Marketplace"
+    )]
+    fn approval_of_other_marketplace_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(
+            PASS.to_string(),
+            carol().into(),
+            Some(r#"{"price":"100"}"#.to_string()),
+        );
+    }
+
+    #[test]
+    fn owner_manages_the_whitelist() {
+        testing_env!(get_context(nft().to_string(), 1));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        assert!(contract.add_approved_marketplace(carol(), None));
+        assert!(!contract.add_approved_marketplace(carol(), None));
+        assert!(contract
+            .get_approved_marketplaces(None, None)
+            .contains(&carol().to_string()));
+
+        assert!(contract.remove_approved_marketplace(carol()));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"marketplace_change","data":{"marketplace_id":"carol.near","approved":true}}"#,
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"marketplace_change","data":{"marketplace_id":"carol.near","approved":false}}"#
+            ]
+        );
+        assert!(!contract.remove_approved_marketplace(carol()));
+        assert!(!contract
+            .get_approved_marketplaces(None, None)
+            .contains(&carol().to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn whitelist_by_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.add_approved_marketplace(carol(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn whitelist_without_yocto_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        contract.add_approved_marketplace(carol(), None);
+    }
+
+    fn context_at(predecessor_id: AccountId, deposit: Balance, timestamp_ms: u64) {
+        let mut context = get_context(predecessor_id, deposit);
+        context.block_timestamp = timestamp_ms * 1_000_000;
+        testing_env!(context);
+    }
+
+    #[test]
+    fn trial_marketplace_expires() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.add_approved_marketplace(carol(), Some(1_000));
+
+        context_at(alice().to_string(), STORAGE_DEPOSIT, 999);
+        contract.nft_approve(PASS.to_string(), carol().into(), None);
+
+        context_at(alice().to_string(), 0, 1_000);
+        assert!(!contract
+            .get_approved_marketplaces(None, None)
+            .contains(&carol().to_string()));
+        let approval = contract
+            .get_marketplace_approvals(None, None)
+            .into_iter()
+            .find(|approval| approval.marketplace_id == carol().to_string())
+            .unwrap();
+        assert!(approval.expired);
+        assert_eq!(approval.expires_at, Some(1_000));
+
+        context_at(nft().to_string(), 1, 1_000);
+        contract.extend_marketplace_approval(carol(), Some(2_000));
+        assert!(contract
+            .get_approved_marketplaces(None, None)
+            .contains(&carol().to_string()));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "You cannot list on other marketplaces other than Catch Approved Marketplace"
+    )]
+    fn approval_of_expired_marketplace_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.add_approved_marketplace(carol(), Some(1_000));
+
+        context_at(alice().to_string(), STORAGE_DEPOSIT, 1_000);
+        contract.nft_approve(PASS.to_string(), carol().into(), None);
+    }
+
+    #[test]
+    fn legacy_whitelist_is_migrated() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        let mut legacy_marketplaces: UnorderedSet<AccountId> =
+            UnorderedSet::new(StorageKey::ApprovedMarketplaces.try_to_vec().unwrap());
+        legacy_marketplaces.insert(&carol().into());
+        legacy_marketplaces.insert(&bob().into());
+
+        assert_eq!(contract.migrate_approved_marketplaces(), 2);
+        assert_eq!(contract.migrate_approved_marketplaces(), 0);
+        assert!(!legacy_marketplaces.contains(&carol().into()));
+
+        let migrated = contract.get_approved_marketplaces(None, None);
+        assert!(migrated.contains(&carol().to_string()));
+        assert!(migrated.contains(&bob().to_string()));
+    }
+
+    #[test]
+    fn legacy_token_approvals_are_migrated() {
+        let mut contract = setup();
+
+        let class_token_id = "catchcon.gold".to_string();
+        //the class is only stored in the layout written before approvals_by_token_id
+        let token = contract.tokens_by_id.remove(&class_token_id).unwrap();
+        let legacy = TokenV1 {
+            token_id: token.token_id,
+            copies_minted: token.copies_minted,
+            max_copies: token.max_copies,
+            expires_at: token.expires_at,
+            token_dependency_by_id: token.token_dependency_by_id,
+            event_dependency_by_id: token.event_dependency_by_id,
+            external_dependencies: token.external_dependencies,
+            approvals_prefix: legacy_approvals_prefix(&class_token_id),
+            royalty: token.royalty,
+            metadata_frozen: token.metadata_frozen,
+            soulbound: token.soulbound,
+        };
+        env::storage_write(
+            &[
+                &StorageKey::TokensById.try_to_vec().unwrap()[..],
+                &class_token_id.try_to_vec().unwrap(),
+            ]
+            .concat(),
+            &legacy.try_to_vec().unwrap(),
+        );
+
+        let mut legacy_approvals: LookupMap<AccountId, ApprovalInfo> =
+            LookupMap::new(legacy_approvals_prefix(&class_token_id));
+        legacy_approvals.insert(
+            &alice().into(),
+            &ApprovalInfo {
+                approved_account_ids: [(marketplace().into(), 3)].into_iter().collect(),
+                next_approval_id: 4,
+            },
+        );
+
+        assert_eq!(
+            contract.nft_token(PASS.to_string()).unwrap().copies_minted,
+            1
+        );
+
+        testing_env!(get_context(nft().to_string(), 0));
+        let token_ids = vec![class_token_id, PASS.to_string()];
+        assert_eq!(contract.migrate_token_approvals(token_ids.clone()), 2);
+        assert_eq!(contract.migrate_token_approvals(token_ids), 0);
+
+        assert!(contract.nft_is_approved(PASS.to_string(), marketplace().into(), Some(3)));
+        assert_eq!(
+            contract.nft_token(PASS.to_string()).unwrap().copies_minted,
+            1
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), bob().into(), None),
+            4
+        );
+    }
+
+    #[test]
+    fn approvals_per_token_are_capped() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_max_approvals_per_token(1);
+        assert_eq!(contract.get_max_approvals_per_token(), 1);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+        // re-approving replaces the approval
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_revoke(PASS.to_string(), marketplace().into());
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), bob().into(), None);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Can't approve more than 1 accounts for a token, revoke an approval first"
+    )]
+    fn approval_over_the_cap_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_max_approvals_per_token(1);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+        contract.nft_approve(PASS.to_string(), bob().into(), None);
+    }
 }
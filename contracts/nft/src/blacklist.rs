@@ -0,0 +1,146 @@
+use crate::*;
+
+impl Contract {
+    pub(crate) fn assert_not_blacklisted(&self, account_id: &AccountId) {
+        if self.blacklist.contains(account_id) {
+            env::panic(format!("{} is blacklisted", account_id).as_bytes());
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, the account can't send, receive or approve tokens anymore,
+    /// tokens it holds stay with it
+    #[payable]
+    pub fn add_to_blacklist(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        if !self.blacklist.insert(&account_id) {
+            return false;
+        }
+
+        CatchEventLogVariant::AccountBlacklisted(BlacklistLog { account_id }).emit();
+
+        true
+    }
+
+    /// Owner only
+    #[payable]
+    pub fn remove_from_blacklist(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let account_id: AccountId = account_id.into();
+        if !self.blacklist.remove(&account_id) {
+            return false;
+        }
+
+        CatchEventLogVariant::AccountUnblacklisted(BlacklistLog { account_id }).emit();
+
+        true
+    }
+
+    pub fn get_blacklist(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.blacklist
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod blacklist_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    /// alice holds a gold pass, bob is blacklisted
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        testing_env!(get_context(nft().to_string(), 1));
+        assert!(contract.add_to_blacklist(bob()));
+
+        contract
+    }
+
+    #[test]
+    fn blacklist_is_listed() {
+        let mut contract = setup();
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"account_blacklisted","data":{"account_id":"bob.near"}}"#
+            ]
+        );
+        assert!(!contract.add_to_blacklist(bob()));
+        assert_eq!(contract.get_blacklist(None, None), vec![bob().to_string()]);
+
+        assert!(contract.remove_from_blacklist(bob()));
+        assert!(contract.get_blacklist(None, None).is_empty());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near is blacklisted")]
+    fn transfer_to_blacklisted_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near is blacklisted")]
+    fn transfer_by_blacklisted_holder_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.add_to_blacklist(alice());
+
+        // the holder keeps the token but can't move it
+        assert!(contract.nft_token(PASS.to_string()).is_some());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(carol().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "marketplace.near is blacklisted")]
+    fn approve_of_blacklisted_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.add_to_blacklist(marketplace());
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near is blacklisted")]
+    fn mint_to_blacklisted_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+    }
+}
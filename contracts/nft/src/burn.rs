@@ -0,0 +1,272 @@
+use crate::*;
+
+impl Contract {
+    //token classes which list the token class in their token_dependency_by_id
+    pub(crate) fn internal_dependents_of(&self, class_token_id: &TokenId) -> Vec<TokenId> {
+        self.token_metadata_by_id
+            .keys()
+            .filter(|token_id| {
+                self.tokens_by_id
+                    .get(token_id)
+                    .is_some_and(|token| token.token_dependency_by_id.contains(class_token_id))
+            })
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, destroys the copy of form event_id.token_id.owner_id and refunds the freed
+    /// storage to the owner. A standalone token of nft_mint is removed entirely, the class of an
+    /// event pass or an edition stays for the other copies. Tokens other tokens depend on can't
+    /// be burnt. A pass of create_event burnt before the event starts frees its seat, which goes
+    /// to the head of the waitlist of the event
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+
+        let full_token_id = token_id;
+        let (token_id, owner_id) = resolve_token_id(full_token_id.clone());
+
+        require!(
+            env::predecessor_account_id() == owner_id
+                && self.internal_owns_token(&owner_id, &token_id),
+            "Only the token owner can burn it"
+        );
+        require!(!self.internal_is_pending(&token_id), "Token is pending");
+        require!(
+            self.frozen_tokens.get(&full_token_id).is_none(),
+            "Token is frozen"
+        );
+        require!(
+            self.locks_by_token_id.get(&full_token_id).is_none(),
+            "Token is locked"
+        );
+        require!(
+            self.internal_active_lease(&full_token_id).is_none(),
+            "Token is leased"
+        );
+        if let Some(parent_token_id) = self.parent_by_token_id.get(&full_token_id) {
+            env::panic(format!("Token is attached to {}", parent_token_id).as_bytes());
+        }
+        require!(
+            self.children_by_token_id.get(&full_token_id).is_none(),
+            "Detach the attached tokens before burning"
+        );
+
+        let dependents = self.internal_dependents_of(&token_id);
+        require!(
+            dependents.is_empty(),
+            format!(
+                "Token {} is a dependency of {}",
+                &token_id,
+                dependents.join(", ")
+            )
+        );
+
+        let token = self.tokens_by_id.get(&token_id).unwrap();
+
+        // approvals are paid from the storage balance, they go back through it
+        if let Some(approval_info) = self.approvals_by_token_id.get(&full_token_id) {
+            self.refund_approved_account_ids(owner_id.clone(), &approval_info.approved_account_ids);
+        }
+
+        // so are an expired lease and an unused lease approval
+        let lease_storage = env::storage_usage();
+        self.internal_remove_lease(&full_token_id);
+        self.lease_approvals.remove(&full_token_id);
+        self.internal_release_storage(
+            owner_id.clone(),
+            lease_storage.saturating_sub(env::storage_usage()),
+        );
+
+        let initial_storage = env::storage_usage();
+
+        self.approvals_by_token_id.remove(&full_token_id);
+
+        self.internal_remove_token_from_owner(&owner_id, &token_id);
+
+        let event_id: EventId = token_id.split_once(".").unwrap().0.to_string();
+        let frees_seat = token_id == format!("{}.{}", &event_id, EVENT_PASS_TOKEN)
+            && self.events_by_id.get(&event_id).is_some_and(|event| {
+                !event.cancelled
+                    && event
+                        .metadata
+                        .is_some_and(|metadata| now_ms() < metadata.starts_at)
+            });
+        if frees_seat {
+            self.internal_release_copy(&token_id);
+        } else {
+            self.minted_count -= 1;
+        }
+
+        self.pass_expiry_by_token_id.remove(&full_token_id);
+        self.level_by_token_id.remove(&full_token_id);
+        self.recoveries_by_token_id.remove(&full_token_id);
+        self.metadata_history_by_token_id.remove(&full_token_id);
+        self.checked_in_at_by_token_id.remove(&full_token_id);
+
+        if self.events_by_id.get(&event_id).is_none() && token.max_copies == 1 {
+            self.tokens_by_id.remove(&token_id);
+            self.token_metadata_by_id.remove(&token_id);
+            self.internal_remove_from_series(&token_id);
+            self.upgrade_config_by_token_id.remove(&token_id);
+            self.renewal_config_by_token_id.remove(&token_id);
+            self.ft_price_by_token_id.remove(&token_id);
+        }
+
+        let storage_released = initial_storage.saturating_sub(env::storage_usage());
+
+        let burn_event = EventLogVariant::NftBurn(vec![NftBurnLog {
+            owner_id: owner_id.clone(),
+            token_ids: vec![token_id],
+            authorized_id: None,
+            memo: None,
+        }]);
+
+        self.internal_notify_hook(burn_event.clone());
+
+        burn_event.emit();
+
+        // storage of the promoted pass the escrow didn't cover is kept from the refund
+        let mut refund = Balance::from(storage_released) * env::storage_byte_cost();
+        if frees_seat {
+            refund = refund.saturating_sub(self.internal_promote_from_waitlist(&event_id));
+        }
+        if refund > 0 {
+            Promise::new(owner_id).transfer(refund);
+        }
+    }
+}
+
+#[cfg(test)]
+mod burn_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold", "silver"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        contract
+    }
+
+    #[test]
+    fn burn_removes_the_copy() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn(PASS.to_string());
+
+        assert!(contract.nft_token(PASS.to_string()).is_none());
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+        assert!(contract
+            .approvals_by_token_id
+            .get(&PASS.to_string())
+            .is_none());
+
+        // the class stays for the other copies
+        assert!(contract
+            .nft_token_by_id("catchcon.gold".to_string())
+            .is_some());
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_burn","data":[{"owner_id":"alice.near","token_ids":["catchcon.gold"]}]}"#
+        );
+
+        // the storage of the approval and the freed storage of the copy
+        assert_eq!(test_utils::get_created_receipts().len(), 2);
+    }
+
+    #[test]
+    fn burn_removes_standalone_token() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            "genesis.hero".to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+        contract.resolve_mint("genesis.hero".to_string());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn("genesis.hero.alice.near".to_string());
+
+        assert!(contract
+            .nft_token_by_id("genesis.hero".to_string())
+            .is_none());
+        assert_eq!(contract.nft_total_supply().0, 0);
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token catchcon.gold is a dependency of partnercon.vip")]
+    fn burn_of_dependency_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.organise_event(
+            "partnercon".to_string(),
+            vec![TokenInfo {
+                token_id: "vip".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec!["catchcon.gold".to_string()],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can burn it")]
+    fn burn_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.nft_burn(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn burn_without_yocto_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.nft_burn(PASS.to_string());
+    }
+}
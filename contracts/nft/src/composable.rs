@@ -0,0 +1,292 @@
+use crate::*;
+
+/// Most tokens that can travel together with a top-level parent, to keep its transfer within gas
+const MAX_ATTACHED_TOKENS: usize = 10;
+
+impl Contract {
+    //number of tokens attached to the token of form event_id.token_id.owner_id, directly or through its children
+    fn internal_count_attached(&self, token_id: &TokenId) -> usize {
+        self.children_by_token_id
+            .get(token_id)
+            .unwrap_or_default()
+            .iter()
+            .map(|child_token_id| 1 + self.internal_count_attached(child_token_id))
+            .sum()
+    }
+
+    //attached children move to the new owner along with the parent, which has been moved already
+    pub(crate) fn internal_move_children(
+        &mut self,
+        old_parent_token_id: &TokenId,
+        new_parent_token_id: &TokenId,
+        receiver_id: &AccountId,
+    ) {
+        let children = match self.children_by_token_id.remove(old_parent_token_id) {
+            Some(children) => children,
+            None => return,
+        };
+
+        let mut new_children = Vec::with_capacity(children.len());
+
+        for child_token_id in children {
+            let (class_token_id, owner_id) = resolve_token_id(child_token_id.clone());
+
+            require!(
+                self.frozen_tokens.get(&child_token_id).is_none(),
+                format!("The attached {} is frozen", &child_token_id)
+            );
+            require!(
+                self.locks_by_token_id.get(&child_token_id).is_none(),
+                format!("The attached {} is locked", &child_token_id)
+            );
+            require!(
+                !self.internal_owns_token(receiver_id, &class_token_id),
+                format!("The receiver already owns the attached {}", &class_token_id)
+            );
+
+            self.parent_by_token_id.remove(&child_token_id);
+
+            // the owner moves the child, its own children follow it
+            let (_, approval_info) =
+                self.internal_move_token(&owner_id, receiver_id, &child_token_id, None, None);
+            self.refund_approved_account_ids(owner_id, &approval_info.approved_account_ids);
+
+            let new_child_token_id = build_full_token_id(class_token_id, receiver_id.clone());
+            self.parent_by_token_id
+                .insert(&new_child_token_id, new_parent_token_id);
+            new_children.push(new_child_token_id);
+        }
+
+        self.children_by_token_id
+            .insert(new_parent_token_id, &new_children);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner of both tokens only, the child travels with the parent until detached,
+    /// it can't be transferred, approved or leased on its own meanwhile
+    #[payable]
+    pub fn nft_attach(&mut self, child_token_id: TokenId, parent_token_id: TokenId) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+
+        let (child_class_token_id, child_owner_id) = resolve_token_id(child_token_id.clone());
+        let (parent_class_token_id, parent_owner_id) = resolve_token_id(parent_token_id.clone());
+
+        require!(
+            child_owner_id == owner_id
+                && parent_owner_id == owner_id
+                && self.internal_owns_token(&owner_id, &child_class_token_id)
+                && self.internal_owns_token(&owner_id, &parent_class_token_id),
+            "Only the owner of both tokens can attach them"
+        );
+        require!(
+            self.parent_by_token_id.get(&child_token_id).is_none(),
+            "Token is already attached"
+        );
+        require!(
+            self.internal_active_lease(&child_token_id).is_none(),
+            "Token is leased"
+        );
+
+        let child = self.tokens_by_id.get(&child_class_token_id).unwrap();
+        require!(!child.soulbound, "Token is soulbound");
+        require!(
+            self.approvals_by_token_id
+                .get(&child_token_id)
+                .is_none_or(|approval_info| approval_info.approved_account_ids.is_empty()),
+            "Revoke the approvals of the child first"
+        );
+
+        // the parent can't be the child or one of its descendants
+        let mut root_token_id = parent_token_id.clone();
+        loop {
+            require!(
+                root_token_id != child_token_id,
+                "Attaching would create a cycle"
+            );
+
+            match self.parent_by_token_id.get(&root_token_id) {
+                Some(token_id) => root_token_id = token_id,
+                None => break,
+            }
+        }
+
+        require!(
+            self.internal_count_attached(&root_token_id)
+                + 1
+                + self.internal_count_attached(&child_token_id)
+                <= MAX_ATTACHED_TOKENS,
+            format!("At most {} tokens can be attached", MAX_ATTACHED_TOKENS)
+        );
+
+        let mut children = self
+            .children_by_token_id
+            .get(&parent_token_id)
+            .unwrap_or_default();
+        children.push(child_token_id.clone());
+        self.children_by_token_id
+            .insert(&parent_token_id, &children);
+        self.parent_by_token_id
+            .insert(&child_token_id, &parent_token_id);
+
+        CatchEventLogVariant::NftAttached(AttachLog {
+            owner_id,
+            child_token_id,
+            parent_token_id,
+        })
+        .emit();
+    }
+
+    /// Token owner only, the child can be transferred on its own again
+    #[payable]
+    pub fn nft_detach(&mut self, child_token_id: TokenId) {
+        assert_one_yocto();
+        let owner_id = env::predecessor_account_id();
+
+        let (_, child_owner_id) = resolve_token_id(child_token_id.clone());
+        require!(
+            child_owner_id == owner_id,
+            "Only the token owner can detach the token"
+        );
+
+        let parent_token_id = self
+            .parent_by_token_id
+            .remove(&child_token_id)
+            .unwrap_or_else(|| env::panic(b"Token is not attached"));
+
+        let mut children = self
+            .children_by_token_id
+            .get(&parent_token_id)
+            .unwrap_or_default();
+        children.retain(|token_id| token_id != &child_token_id);
+
+        if children.is_empty() {
+            self.children_by_token_id.remove(&parent_token_id);
+        } else {
+            self.children_by_token_id
+                .insert(&parent_token_id, &children);
+        }
+
+        CatchEventLogVariant::NftDetached(AttachLog {
+            owner_id,
+            child_token_id,
+            parent_token_id,
+        })
+        .emit();
+    }
+
+    pub fn children_of(&self, parent_token_id: TokenId) -> Vec<TokenId> {
+        self.children_by_token_id
+            .get(&parent_token_id)
+            .unwrap_or_default()
+    }
+
+    pub fn parent_of(&self, child_token_id: TokenId) -> Option<TokenId> {
+        self.parent_by_token_id.get(&child_token_id)
+    }
+}
+
+#[cfg(test)]
+mod composable_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const HERO: &str = "genesis.hero.alice.near";
+    const HAT: &str = "genesis.hat.alice.near";
+    const CAPE: &str = "genesis.cape.alice.near";
+
+    /// alice holds a hero, a hat and a cape
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "genesis", &["hero", "hat", "cape"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        for token_id in ["genesis.hero", "genesis.hat", "genesis.cape"] {
+            contract.nft_event_register(alice().to_string(), token_id.to_string());
+        }
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract
+    }
+
+    #[test]
+    fn children_move_with_the_parent() {
+        let mut contract = setup();
+
+        contract.nft_attach(HAT.to_string(), HERO.to_string());
+        contract.nft_attach(CAPE.to_string(), HAT.to_string());
+        assert!(test_utils::get_logs()[0].contains(r#""event":"nft_attached""#));
+
+        contract.nft_transfer(bob().into(), HERO.to_string(), None, None);
+
+        assert_eq!(
+            contract.children_of("genesis.hero.bob.near".to_string()),
+            vec!["genesis.hat.bob.near".to_string()]
+        );
+        assert_eq!(
+            contract.parent_of("genesis.cape.bob.near".to_string()),
+            Some("genesis.hat.bob.near".to_string())
+        );
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 3);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+        assert!(contract.children_of(HERO.to_string()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is attached to genesis.hero.alice.near")]
+    fn attached_child_transfer_fails() {
+        let mut contract = setup();
+
+        contract.nft_attach(HAT.to_string(), HERO.to_string());
+        contract.nft_transfer(bob().into(), HAT.to_string(), None, None);
+    }
+
+    #[test]
+    fn detached_child_transfers_on_its_own() {
+        let mut contract = setup();
+
+        contract.nft_attach(HAT.to_string(), HERO.to_string());
+        contract.nft_detach(HAT.to_string());
+        assert!(contract.parent_of(HAT.to_string()).is_none());
+
+        contract.nft_transfer(bob().into(), HAT.to_string(), None, None);
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is attached")]
+    fn attached_child_approval_fails() {
+        let mut contract = setup();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+
+        contract.nft_attach(HAT.to_string(), HERO.to_string());
+        contract.nft_approve(HAT.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attaching would create a cycle")]
+    fn cycle_fails() {
+        let mut contract = setup();
+
+        contract.nft_attach(HAT.to_string(), HERO.to_string());
+        contract.nft_attach(HERO.to_string(), HAT.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner of both tokens can attach them")]
+    fn attach_to_other_owner_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "genesis.hero".to_string());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_attach(HAT.to_string(), "genesis.hero.bob.near".to_string());
+    }
+}
@@ -0,0 +1,574 @@
+use crate::*;
+
+const GAS_FOR_NFT_TOKEN: Gas = 5_000_000_000_000;
+const GAS_FOR_RESOLVE_EXTERNAL_TRANSFER: Gas = 30_000_000_000_000;
+
+pub(crate) const MAX_EXTERNAL_DEPENDENCIES: usize = 4;
+
+/// (partner NFT contract, TokenId on that contract) which the receiver has to hold
+pub type ExternalDependency = (AccountId, TokenId);
+
+/// Part of the token returned by nft_token of a partner contract
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PartnerToken {
+    pub owner_id: AccountId,
+}
+
+#[ext_contract(ext_partner_nft)]
+pub trait PartnerNft {
+    fn nft_token(&self, token_id: TokenId) -> Option<PartnerToken>;
+}
+
+/// Transfer waiting for the partner contracts to confirm the external dependencies
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingTransfer {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub token_id: TokenId,
+    pub approval_id: Option<u64>,
+    pub memo: Option<String>,
+}
+
+#[ext_contract(ext_dependencies_self)]
+pub trait DependenciesResolver {
+    fn resolve_external_transfer(&mut self, transfer: PendingTransfer);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TransferStatus {
+    Transferable,
+    NotTransferable,
+    UnverifiableSynchronously,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferPreflight {
+    pub status: TransferStatus,
+    pub reason: Option<String>,
+    //verified with the partner contracts when the transfer is made
+    pub external_dependencies: Vec<ExternalDependency>,
+}
+
+/// Dependencies a receiver lacks to get a token, external dependencies aren't included
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferEligibility {
+    pub eligible: bool,
+    pub missing_token_ids: Vec<TokenId>,
+    pub missing_event_ids: Vec<EventId>,
+}
+
+impl Contract {
+    pub(crate) fn internal_assert_external_dependencies(
+        &self,
+        external_dependencies: &[ExternalDependency],
+    ) {
+        if external_dependencies.is_empty() {
+            return;
+        }
+
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the owner can set external dependencies"
+        );
+        require!(
+            external_dependencies.len() <= MAX_EXTERNAL_DEPENDENCIES,
+            format!(
+                "A token can't have more than {} external dependencies",
+                MAX_EXTERNAL_DEPENDENCIES
+            )
+        );
+    }
+
+    //external dependencies of the token of form event_id.token_id.owner_id
+    pub(crate) fn internal_external_dependencies(
+        &self,
+        token_id: &TokenId,
+    ) -> Vec<ExternalDependency> {
+        let (token_id, _) = resolve_token_id(token_id.clone());
+
+        self.tokens_by_id
+            .get(&token_id)
+            .map(|token| token.external_dependencies)
+            .unwrap_or_default()
+    }
+
+    //asks every partner contract for its token, the transfer is made by resolve_external_transfer
+    pub(crate) fn internal_verify_external_dependencies(
+        &self,
+        transfer: PendingTransfer,
+        external_dependencies: Vec<ExternalDependency>,
+    ) -> Promise {
+        let checks = external_dependencies
+            .into_iter()
+            .map(|(contract_id, token_id)| {
+                ext_partner_nft::nft_token(token_id, &contract_id, 0, GAS_FOR_NFT_TOKEN)
+            })
+            .reduce(|checks, check| checks.and(check))
+            .unwrap();
+
+        checks.then(ext_dependencies_self::resolve_external_transfer(
+            transfer,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_EXTERNAL_TRANSFER,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Preflight of nft_transfer, external dependencies are reported as unverifiable synchronously
+    /// as they are only checked with the partner contracts when the transfer is made
+    pub fn can_transfer(
+        &self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+    ) -> TransferPreflight {
+        let mut check =
+            self.internal_check_transfer(&sender_id, &receiver_id, &token_id, approval_id);
+
+        if check.is_ok()
+            && self.accounts_registry.is_some()
+            && !self.registry_players.contains(&receiver_id)
+        {
+            check = Err(format!("{} is not a Catch player", receiver_id));
+        }
+
+        let external_dependencies = self.internal_external_dependencies(&token_id);
+
+        let (status, reason) = match check {
+            Err(reason) => (TransferStatus::NotTransferable, Some(reason)),
+            Ok(_) if !external_dependencies.is_empty() => (
+                TransferStatus::UnverifiableSynchronously,
+                Some("External dependencies are unverifiable synchronously".to_string()),
+            ),
+            Ok(_) => (TransferStatus::Transferable, None),
+        };
+
+        TransferPreflight {
+            status,
+            reason,
+            external_dependencies,
+        }
+    }
+
+    /// Whether the receiver satisfies the token and event dependencies of the token of form
+    /// event_id.token_id.owner_id, so marketplaces can check a buyer before the sale
+    pub fn check_transfer_eligibility(
+        &self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+    ) -> TransferEligibility {
+        let (token_id, _) = resolve_token_id(token_id);
+        let token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+
+        let (missing_token_ids, missing_event_ids) =
+            self.internal_missing_dependencies(&receiver_id, &token);
+
+        TransferEligibility {
+            eligible: missing_token_ids.is_empty() && missing_event_ids.is_empty(),
+            missing_token_ids,
+            missing_event_ids,
+        }
+    }
+
+    /// Events of event_dependency_by_id of the token of form event_id.token_id.owner_id the
+    /// account holds no valid pass of
+    pub fn missing_event_dependencies(
+        &self,
+        account_id: AccountId,
+        token_id: TokenId,
+    ) -> Vec<EventId> {
+        self.check_transfer_eligibility(account_id, token_id)
+            .missing_event_ids
+    }
+
+    //makes the transfer if the receiver holds every external dependency, otherwise the token
+    //stays with its owner and the reason is logged
+    #[private]
+    pub fn resolve_external_transfer(&mut self, transfer: PendingTransfer) {
+        let external_dependencies = self.internal_external_dependencies(&transfer.token_id);
+
+        for (index, (contract_id, token_id)) in external_dependencies.iter().enumerate() {
+            let holder = match env::promise_result(index as u64) {
+                PromiseResult::Successful(value) => {
+                    near_sdk::serde_json::from_slice::<Option<PartnerToken>>(&value)
+                        .ok()
+                        .flatten()
+                        .map(|token| token.owner_id)
+                }
+                _ => {
+                    env::log(
+                        format!(
+                            "Couldn't verify {} on {}, the transfer of {} is cancelled",
+                            token_id, contract_id, transfer.token_id
+                        )
+                        .as_bytes(),
+                    );
+                    return;
+                }
+            };
+
+            if holder.as_ref() != Some(&transfer.receiver_id) {
+                env::log(
+                    format!(
+                        "{} doesn't hold {} on {}, the transfer of {} is cancelled",
+                        transfer.receiver_id, token_id, contract_id, transfer.token_id
+                    )
+                    .as_bytes(),
+                );
+                return;
+            }
+        }
+
+        //the token could have been moved while the partner contracts were called
+        if let Err(message) = self.internal_check_transfer(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            &transfer.token_id,
+            transfer.approval_id,
+        ) {
+            env::log(
+                format!(
+                    "{}, the transfer of {} is cancelled",
+                    message, transfer.token_id
+                )
+                .as_bytes(),
+            );
+            return;
+        }
+
+        let (old_owner_id, old_approval_info) = self.internal_transfer(
+            &transfer.sender_id,
+            &transfer.receiver_id,
+            &transfer.token_id,
+            transfer.approval_id,
+            transfer.memo,
+        );
+
+        self.refund_approved_account_ids(old_owner_id, &old_approval_info.approved_account_ids);
+    }
+}
+
+#[cfg(test)]
+mod dependencies_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "partnercon.vip.alice.near";
+
+    fn partner() -> AccountId {
+        "partner.near".to_string()
+    }
+
+    fn vip_pass(external_dependencies: Vec<ExternalDependency>) -> TokenInfo {
+        TokenInfo {
+            token_id: "vip".to_string(),
+            token_metadata: token_metadata(10),
+            token_dependency_by_id: vec![],
+            event_dependency_by_id: vec![],
+            external_dependencies,
+            royalty: Default::default(),
+            soulbound: false,
+        }
+    }
+
+    /// the owner organises partnercon whose vip pass requires badge-1 of the partner contract,
+    /// alice holds a vip pass
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.organise_event(
+            "partnercon".to_string(),
+            vec![vip_pass(vec![(partner(), "badge-1".to_string())])],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "partnercon.vip".to_string());
+
+        contract
+    }
+
+    fn transfer_to_bob(contract: &mut Contract) {
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().to_string(), PASS.to_string(), None, None);
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_external_transfer(PendingTransfer {
+            sender_id: alice().to_string(),
+            receiver_id: bob().to_string(),
+            token_id: PASS.to_string(),
+            approval_id: None,
+            memo: None,
+        });
+    }
+
+    fn partner_token(owner_id: &str) -> PromiseResult {
+        let token = format!(r#"{{"token_id":"badge-1","owner_id":"{}"}}"#, owner_id);
+        PromiseResult::Successful(token.into_bytes())
+    }
+
+    #[test]
+    fn transfer_waits_for_partner_contract() {
+        let mut contract = setup();
+
+        transfer_to_bob(&mut contract);
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("nft_token"));
+        assert!(receipts.contains("partner.near"));
+        assert!(contract.internal_owns_token(&alice().into(), &"partnercon.vip".to_string()));
+
+        resolve(&mut contract, partner_token("bob.near"));
+        assert!(contract.internal_owns_token(&bob().into(), &"partnercon.vip".to_string()));
+        assert!(!contract.internal_owns_token(&alice().into(), &"partnercon.vip".to_string()));
+    }
+
+    #[test]
+    fn transfer_is_cancelled_if_receiver_lacks_partner_token() {
+        let mut contract = setup();
+
+        resolve(&mut contract, partner_token("carol.near"));
+
+        assert!(contract.internal_owns_token(&alice().into(), &"partnercon.vip".to_string()));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec!["bob.near doesn't hold badge-1 on partner.near, the transfer of partnercon.vip.alice.near is cancelled"]
+        );
+    }
+
+    #[test]
+    fn transfer_is_cancelled_if_partner_contract_fails() {
+        let mut contract = setup();
+
+        resolve(&mut contract, PromiseResult::Failed);
+
+        assert!(contract.internal_owns_token(&alice().into(), &"partnercon.vip".to_string()));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec!["Couldn't verify badge-1 on partner.near, the transfer of partnercon.vip.alice.near is cancelled"]
+        );
+    }
+
+    #[test]
+    fn can_transfer_reports_external_dependencies() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        let preflight = contract.can_transfer(alice().into(), bob().into(), PASS.to_string(), None);
+        assert_eq!(preflight.status, TransferStatus::UnverifiableSynchronously);
+        assert_eq!(
+            preflight.external_dependencies,
+            vec![(partner(), "badge-1".to_string())]
+        );
+
+        let gold = "catchcon.gold.alice.near".to_string();
+        let preflight = contract.can_transfer(alice().into(), bob().into(), gold.clone(), None);
+        assert_eq!(preflight.status, TransferStatus::Transferable);
+
+        let preflight = contract.can_transfer(bob().into(), carol().into(), gold, None);
+        assert_eq!(preflight.status, TransferStatus::NotTransferable);
+        assert_eq!(
+            preflight.reason,
+            Some("Token Owner hasn't approved any account".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can set external dependencies")]
+    fn organiser_cannot_set_external_dependencies() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.organise_event(
+            "partnercon".to_string(),
+            vec![vip_pass(vec![(partner(), "badge-1".to_string())])],
+            None,
+        );
+    }
+
+    /// vipcon.vip requires catchcon.gold and a pass of catchcon, alice holds both
+    fn setup_token_dependencies() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+        contract.organise_event(
+            "vipcon".to_string(),
+            vec![TokenInfo {
+                token_id: "vip".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec!["catchcon.gold".to_string()],
+                event_dependency_by_id: vec!["catchcon".to_string()],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_event_register(alice().to_string(), "vipcon.vip".to_string());
+
+        contract
+    }
+
+    #[test]
+    fn check_transfer_eligibility_lists_missing_dependencies() {
+        let contract = setup_token_dependencies();
+
+        let vip = "vipcon.vip.alice.near".to_string();
+        assert_eq!(
+            contract.check_transfer_eligibility(bob().into(), vip.clone()),
+            TransferEligibility {
+                eligible: false,
+                missing_token_ids: vec!["catchcon.gold".to_string()],
+                missing_event_ids: vec!["catchcon".to_string()],
+            }
+        );
+        assert!(
+            contract
+                .check_transfer_eligibility(alice().into(), vip)
+                .eligible
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "receiver_id doesn't satisfy all dependencies for the token, missing catchcon.gold, a pass of catchcon"
+    )]
+    fn transfer_to_receiver_without_dependencies_fails() {
+        let mut contract = setup_token_dependencies();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            bob().to_string(),
+            "vipcon.vip.alice.near".to_string(),
+            None,
+            None,
+        );
+    }
+
+    /// clubcon.member requires a pass of catchcon, alice holds a gold pass
+    fn setup_event_dependency() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+        contract.organise_event(
+            "clubcon".to_string(),
+            vec![TokenInfo {
+                token_id: "member".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec!["catchcon".to_string()],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        contract
+    }
+
+    #[test]
+    fn pass_holders_follow_transfers() {
+        let mut contract = setup_event_dependency();
+        let member = "clubcon.member.alice.near".to_string();
+
+        assert!(contract
+            .missing_event_dependencies(alice().into(), member.clone())
+            .is_empty());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            bob().to_string(),
+            "catchcon.gold.alice.near".to_string(),
+            None,
+            None,
+        );
+
+        assert_eq!(
+            contract.missing_event_dependencies(alice().into(), member.clone()),
+            vec!["catchcon".to_string()]
+        );
+        assert!(contract
+            .missing_event_dependencies(bob().into(), member)
+            .is_empty());
+    }
+
+    #[test]
+    fn expired_pass_misses_event_dependency() {
+        let mut contract = setup_event_dependency();
+
+        contract
+            .pass_expiry_by_token_id
+            .insert(&"catchcon.gold.alice.near".to_string(), &0);
+
+        assert_eq!(
+            contract.missing_event_dependencies(
+                alice().into(),
+                "clubcon.member.alice.near".to_string()
+            ),
+            vec!["catchcon".to_string()]
+        );
+    }
+
+    #[test]
+    fn event_dependency_can_require_check_in() {
+        let mut contract = setup_event_dependency();
+        let member = "clubcon.member.alice.near".to_string();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_dependency_requires_check_in("catchcon".to_string(), true);
+        assert_eq!(
+            contract.missing_event_dependencies(alice().into(), member.clone()),
+            vec!["catchcon".to_string()]
+        );
+
+        contract.check_in("catchcon.gold.alice.near".to_string());
+        assert!(contract
+            .missing_event_dependencies(alice().into(), member)
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or the event organiser can change the event")]
+    fn check_in_requirement_by_other_account_fails() {
+        let mut contract = setup_event_dependency();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.set_dependency_requires_check_in("catchcon".to_string(), true);
+    }
+}
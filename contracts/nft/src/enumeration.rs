@@ -1,10 +1,29 @@
 use crate::*;
 
+//upper bound of the limit of upcoming_events_for
+const MAX_UPCOMING_EVENTS: u64 = 50;
+//upper bound of the limit of the event views
+pub const MAX_EVENTS_PAGE: u64 = 50;
+
 #[near_bindgen]
 impl Contract {
     //Query for the total supply of NFTs on the contract here it is type of passes issued
     pub fn nft_total_supply(&self) -> U128 {
-        U128(self.token_metadata_by_id.len() as u128)
+        let series_tokens: u64 = self
+            .series_by_id
+            .keys()
+            .map(|series_id| self.series_supply(series_id).0 as u64)
+            .sum();
+
+        U128((self.token_metadata_by_id.len() + series_tokens - self.pending_mints.len()) as u128)
+    }
+
+    //tokens minted and not burnt against the cap set at init, pending mints are counted
+    pub fn get_supply_info(&self) -> SupplyInfo {
+        SupplyInfo {
+            minted: self.minted_count,
+            max: self.max_total_supply,
+        }
     }
 
     //Query for nft tokens on the contract regardless of the owner using pagination [Common Data of all passes]
@@ -15,8 +34,20 @@ impl Contract {
     ) -> Vec<JsonTokenGeneral> {
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
+        //tokens of a series come after the other tokens, they are read one at a time
+        let series_tokens = self.series_by_id.keys().flat_map(|series_id| {
+            self.tokens_per_series
+                .get(&series_id)
+                .into_iter()
+                .flat_map(|tokens_set| {
+                    (0..tokens_set.len()).filter_map(move |index| tokens_set.as_vector().get(index))
+                })
+        });
+
         self.token_metadata_by_id
             .keys()
+            .chain(series_tokens)
+            .filter(|token_id| !self.internal_is_pending(token_id))
             .skip(start as usize)
             .take(limit.unwrap_or(50) as usize)
             .map(|token_id| self.nft_token_by_id(token_id.clone()).unwrap())
@@ -25,45 +56,41 @@ impl Contract {
 
     //get the total supply of NFTs for a given owner
     pub fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
-        let tokens_for_owner_set = self.tokens_per_owner.get(&account_id);
-
-        if let Some(tokens_for_owner_set) = tokens_for_owner_set {
-            U128(tokens_for_owner_set.len() as u128)
-        } else {
-            U128(0)
-        }
+        U128(self.internal_token_count(&account_id) as u128)
     }
 
-    //Query for all the tokens for an owner
+    //Query for the tokens of an owner, only the requested slice of the owner's set is read.
+    //The order is the insertion order of the set and is stable between calls as long as the
+    //owner doesn't gain or lose tokens, removing a token moves the last token into its place
     pub fn nft_tokens_for_owner(
         &self,
         account_id: AccountId,
         from_index: Option<U128>,
         limit: Option<u64>,
     ) -> Vec<JsonToken> {
-        let tokens_for_owner_set = self.tokens_per_owner.get(&account_id);
-        let tokens = if let Some(tokens_for_owner_set) = tokens_for_owner_set {
-            tokens_for_owner_set
-        } else {
-            return vec![];
+        let tokens = match self.tokens_per_owner.get(&account_id) {
+            Some(tokens_for_owner_set) => tokens_for_owner_set,
+            None => return vec![],
         };
 
-        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let start = u128::from(from_index.unwrap_or(U128(0))).min(tokens.len() as u128) as u64;
+        let end = start.saturating_add(limit.unwrap_or(50)).min(tokens.len());
 
-        tokens
-            .iter()
-            .skip(start as usize)
-            .take(limit.unwrap_or(50) as usize)
-            .map(|token_id| {
+        (start..end)
+            .filter_map(|index| tokens.as_vector().get(index))
+            .filter_map(|token_id| {
                 self.nft_token(build_full_token_id(token_id, account_id.clone()))
-                    .unwrap()
             })
             .collect()
     }
 
     pub fn nft_token_by_id(&self, token_id: TokenId) -> Option<JsonTokenGeneral> {
+        if self.internal_is_pending(&token_id) {
+            return None;
+        }
+
         if let Some(token) = self.tokens_by_id.get(&token_id) {
-            let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+            let metadata = self.internal_token_metadata(&token_id).unwrap();
             Some(JsonTokenGeneral {
                 token_id,
                 copies_minted: token.copies_minted,
@@ -71,6 +98,9 @@ impl Contract {
                 metadata,
                 token_dependency_by_id: token.token_dependency_by_id,
                 event_dependency_by_id: token.event_dependency_by_id,
+                external_dependencies: token.external_dependencies,
+                royalty: token.royalty,
+                soulbound: token.soulbound,
             })
         } else {
             //if there wasn't a token ID in the tokens_by_id collection, we return None
@@ -78,18 +108,181 @@ impl Contract {
         }
     }
 
-    //Query for all the events that were created on this contract
+    //how many copies of the token were minted out of its edition, e.g. 3 of 10
+    pub fn nft_copies_of(&self, original_token_id: TokenId) -> Option<CopiesView> {
+        if self.internal_is_pending(&original_token_id) {
+            return None;
+        }
+
+        self.tokens_by_id
+            .get(&original_token_id)
+            .map(|token| CopiesView {
+                token_id: original_token_id,
+                copies_minted: token.copies_minted,
+                max_copies: token.max_copies,
+            })
+    }
+
+    pub fn get_series(&self, series_id: SeriesId) -> Option<Series> {
+        self.series_by_id.get(&series_id)
+    }
+
+    //tokens of the series which exist, pending ones included
+    pub fn series_supply(&self, series_id: SeriesId) -> U128 {
+        self.tokens_per_series
+            .get(&series_id)
+            .map_or(U128(0), |tokens_set| U128(tokens_set.len() as u128))
+    }
+
+    //Query for the tokens of a series using pagination
+    pub fn nft_tokens_for_series(
+        &self,
+        series_id: SeriesId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonTokenGeneral> {
+        let tokens_set = match self.tokens_per_series.get(&series_id) {
+            Some(tokens_set) => tokens_set,
+            None => return vec![],
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        tokens_set
+            .iter()
+            .filter(|token_id| !self.internal_is_pending(token_id))
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|token_id| self.nft_token_by_id(token_id).unwrap())
+            .collect()
+    }
+
+    //Query for all the events that were created on this contract, at most MAX_EVENTS_PAGE at once
     pub fn get_events(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonEvent> {
         let start = u128::from(from_index.unwrap_or(U128(0)));
+        let limit = std::cmp::min(limit.unwrap_or(MAX_EVENTS_PAGE), MAX_EVENTS_PAGE);
 
         self.events_by_id
             .keys()
             .skip(start as usize)
-            .take(limit.unwrap_or(50) as usize)
+            .take(limit as usize)
             .map(|event_id| self.get_event_by_id(event_id.clone()).unwrap())
             .collect()
     }
 
+    //passes of the event held at the moment
+    pub fn nft_supply_for_event(&self, event_id: EventId) -> U128 {
+        self.tokens_per_event
+            .get(&event_id)
+            .map_or(U128(0), |tokens_set| U128(tokens_set.len() as u128))
+    }
+
+    //Query for the passes of an event and their holders, ordered like nft_tokens_for_owner
+    pub fn nft_tokens_for_event(
+        &self,
+        event_id: EventId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonToken> {
+        let tokens = match self.tokens_per_event.get(&event_id) {
+            Some(tokens_set) => tokens_set,
+            None => return vec![],
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0))).min(tokens.len() as u128) as u64;
+        let end = start.saturating_add(limit.unwrap_or(50)).min(tokens.len());
+
+        (start..end)
+            .filter_map(|index| tokens.as_vector().get(index))
+            .filter_map(|token_id| self.nft_token(token_id))
+            .collect()
+    }
+
+    pub fn events_total_supply(&self) -> U128 {
+        U128(self.events_by_id.len() as u128)
+    }
+
+    //events in the given status at the current block, from_index counts the matching events
+    pub fn get_events_by_status(
+        &self,
+        status: EventStatus,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonEvent> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let limit = std::cmp::min(limit.unwrap_or(MAX_EVENTS_PAGE), MAX_EVENTS_PAGE);
+
+        self.events_by_id
+            .iter()
+            .filter(|(_, event)| self.internal_event_status(event) == status)
+            .skip(start as usize)
+            .take(limit as usize)
+            .map(|(event_id, _)| self.get_event_by_id(event_id).unwrap())
+            .collect()
+    }
+
+    //events the account is the organiser of
+    pub fn events_for_organizer(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonEvent> {
+        let events_set = match self.events_per_organiser.get(&account_id) {
+            Some(events_set) => events_set,
+            None => return vec![],
+        };
+
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let limit = std::cmp::min(limit.unwrap_or(MAX_EVENTS_PAGE), MAX_EVENTS_PAGE);
+
+        events_set
+            .iter()
+            .skip(start as usize)
+            .take(limit as usize)
+            .map(|event_id| self.get_event_by_id(event_id).unwrap())
+            .collect()
+    }
+
+    //counters of the event, each one is kept up to date so no pass is iterated
+    pub fn get_event_stats(&self, event_id: EventId) -> Option<EventStats> {
+        let event = self.events_by_id.get(&event_id)?;
+
+        Some(EventStats {
+            passes_issued: event.passes_issued,
+            max_capacity: event
+                .metadata
+                .as_ref()
+                .map(|metadata| metadata.max_capacity),
+            checked_in_count: event.attendance,
+            unique_holders: self
+                .passes_per_event
+                .get(&event_id)
+                .map_or(0, |holders| holders.len()),
+            revenue_collected: event.revenue_collected,
+            waitlist_length: self.waitlist_length(event_id.clone()),
+            status: self.internal_event_status(&event),
+            event_id,
+        })
+    }
+
+    //stats of a page of the events, ordered like get_events
+    pub fn get_events_summary(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<EventStats> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+        let limit = std::cmp::min(limit.unwrap_or(MAX_EVENTS_PAGE), MAX_EVENTS_PAGE);
+
+        self.events_by_id
+            .keys()
+            .skip(start as usize)
+            .take(limit as usize)
+            .map(|event_id| self.get_event_stats(event_id.clone()).unwrap())
+            .collect()
+    }
+
     //reutrns all data related to a event
     pub fn get_event_by_id(&self, event_id: EventId) -> Option<JsonEvent> {
         if let Some(event) = self.events_by_id.get(&event_id) {
@@ -100,8 +293,10 @@ impl Contract {
                 .collect();
             let json_event = JsonEvent {
                 event_id,
+                status: self.internal_event_status(&event),
                 organiser: event.organiser,
                 event_tokens_metadata,
+                metadata: event.metadata,
             };
 
             Some(json_event)
@@ -110,19 +305,527 @@ impl Contract {
         }
     }
 
-    //Returns paginated view of approved marketplaces
+    //Returns paginated view of approved marketplaces with their sync status
     pub fn get_approved_marketplace(
         &self,
         from_index: Option<U128>,
         limit: Option<u64>,
+    ) -> Vec<JsonMarketplace> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.approved_marketplaces
+            .keys()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|marketplace_id| JsonMarketplace {
+                sync: self.marketplace_sync.get(&marketplace_id),
+                marketplace_id,
+            })
+            .collect()
+    }
+
+    //Accounts where tokens can be listed, marketplaces whose approval expired are left out,
+    //get_approved_marketplace adds their sync status
+    pub fn get_approved_marketplaces(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
     ) -> Vec<AccountId> {
         let start = u128::from(from_index.unwrap_or(U128(0)));
 
+        self.approved_marketplaces
+            .iter()
+            .filter(|(_, approval)| !approval.is_expired())
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(marketplace_id, _)| marketplace_id)
+            .collect()
+    }
+
+    //Whitelist records of the marketplaces, expired ones included
+    pub fn get_marketplace_approvals(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonMarketplaceApproval> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
         self.approved_marketplaces
             .iter()
             .skip(start as usize)
             .take(limit.unwrap_or(50) as usize)
-            .map(|marketplace_id| marketplace_id)
+            .map(|(marketplace_id, approval)| JsonMarketplaceApproval {
+                expired: approval.is_expired(),
+                marketplace_id,
+                expires_at: approval.expires_at,
+                added_at_block: approval.added_at_block,
+            })
             .collect()
     }
+
+    //Passes of the account for events which haven't started yet, soonest first, cancelled events are left out
+    pub fn upcoming_events_for(&self, account_id: AccountId, limit: Option<u64>) -> UpcomingEvents {
+        let limit =
+            std::cmp::min(limit.unwrap_or(MAX_UPCOMING_EVENTS), MAX_UPCOMING_EVENTS) as usize;
+
+        let tokens_set = match self.tokens_per_owner.get(&account_id) {
+            Some(tokens_set) => tokens_set,
+            None => {
+                return UpcomingEvents {
+                    events: vec![],
+                    has_more: false,
+                }
+            }
+        };
+
+        let now = env::block_timestamp() / 1_000_000;
+        let mut events = vec![];
+
+        //the passes of each event are looked up in the set of the account
+        for (event_id, event) in self.events_by_id.iter() {
+            if event.cancelled {
+                continue;
+            }
+
+            for class_token_id in event.event_passes.iter() {
+                if !tokens_set.contains(class_token_id) {
+                    continue;
+                }
+
+                let metadata = self.token_metadata_by_id.get(class_token_id).unwrap();
+
+                let starts_at = match metadata.starts_at {
+                    Some(starts_at) if starts_at > now => starts_at,
+                    _ => continue,
+                };
+
+                let token_id = build_full_token_id(class_token_id.clone(), account_id.clone());
+
+                events.push(UpcomingEventView {
+                    event_id: event_id.clone(),
+                    title: metadata.title,
+                    starts_at,
+                    tier: class_token_id.split_once('.').unwrap().1.to_string(),
+                    checked_in: self.checked_in_at_by_token_id.get(&token_id).is_some(),
+                    token_id,
+                });
+            }
+        }
+
+        events.sort_by_key(|event| event.starts_at);
+
+        let has_more = events.len() > limit;
+        events.truncate(limit);
+
+        UpcomingEvents { events, has_more }
+    }
+}
+
+#[cfg(test)]
+mod enumeration_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    /// organises the event with a gold pass starting at starts_at (ms) and registers alice for it
+    fn organise(contract: &mut Contract, event_id: &str, starts_at: u64) {
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        let mut token_metadata = token_metadata(10);
+        token_metadata.starts_at = Some(starts_at);
+        contract.organise_event(
+            event_id.to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata,
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), format!("{}.gold", event_id));
+    }
+
+    fn whitelist_at(contract_id: &str) -> Vec<AccountId> {
+        let mut context = get_context(nft().to_string(), 0);
+        context.current_account_id = contract_id.to_string();
+        testing_env!(context);
+
+        Contract::new_default_meta(nft(), None).get_approved_marketplaces(None, None)
+    }
+
+    #[test]
+    fn whitelist_starts_with_marketplace_of_network() {
+        assert_eq!(
+            whitelist_at("nft.catchlabs.near"),
+            vec!["marketplace.catchlabs.near".to_string()]
+        );
+        assert_eq!(
+            whitelist_at("nft.catchlabs.testnet"),
+            vec!["marketplace.catchlabs.testnet".to_string()]
+        );
+        assert_eq!(
+            whitelist_at("nft.catchlabs.test.near"),
+            vec!["marketplace.catchlabs.test.near".to_string()]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't tell the network of nft.catchlabs.betanet")]
+    fn init_on_unknown_network_fails() {
+        whitelist_at("nft.catchlabs.betanet");
+    }
+
+    fn event_ids_at(contract: &Contract, timestamp_ms: u64, status: EventStatus) -> Vec<EventId> {
+        let mut context = get_context(bob().to_string(), 0);
+        context.block_timestamp = timestamp_ms * 1_000_000;
+        testing_env!(context);
+
+        contract
+            .get_events_by_status(status, None, None)
+            .into_iter()
+            .map(|event| event.event_id)
+            .collect()
+    }
+
+    fn create_catalogue_event(contract: &mut Contract, event_id: &str) {
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.create_event(
+            event_id.to_string(),
+            EventMetadata {
+                title: "Catch Con".to_string(),
+                description: None,
+                venue: None,
+                starts_at: 1_000,
+                ends_at: 2_000,
+                max_capacity: 100,
+                media: None,
+                media_hash: None,
+                reference: None,
+                reference_hash: None,
+            },
+            None,
+        );
+    }
+
+    #[test]
+    fn events_filtered_by_status() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        organise(&mut contract, "finals", 3_000);
+        organise(&mut contract, "kickoff", 500);
+        create_catalogue_event(&mut contract, "catchcon");
+        create_catalogue_event(&mut contract, "meetup");
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.cancel_event("meetup".to_string(), "Venue unavailable".to_string());
+
+        assert_eq!(contract.events_total_supply().0, 4);
+        assert_eq!(
+            event_ids_at(&contract, 1_500, EventStatus::Upcoming),
+            vec!["finals".to_string()]
+        );
+        assert_eq!(
+            event_ids_at(&contract, 1_500, EventStatus::Live),
+            vec!["kickoff".to_string(), "catchcon".to_string()]
+        );
+        assert_eq!(
+            event_ids_at(&contract, 2_500, EventStatus::Ended),
+            vec!["catchcon".to_string()]
+        );
+        assert_eq!(
+            event_ids_at(&contract, 2_500, EventStatus::Cancelled),
+            vec!["meetup".to_string()]
+        );
+        assert_eq!(
+            contract
+                .get_event_by_id("finals".to_string())
+                .unwrap()
+                .status,
+            EventStatus::Upcoming
+        );
+    }
+
+    #[test]
+    fn events_follow_their_organiser() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        organise(&mut contract, "finals", 3_000);
+        create_catalogue_event(&mut contract, "catchcon");
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.transfer_event_organizer("catchcon".to_string(), carol());
+
+        let event_ids: Vec<EventId> = contract
+            .events_for_organizer(carol().into(), None, None)
+            .into_iter()
+            .map(|event| event.event_id)
+            .collect();
+        assert_eq!(
+            event_ids,
+            vec!["finals".to_string(), "catchcon".to_string()]
+        );
+        assert_eq!(
+            contract
+                .events_for_organizer(carol().into(), Some(U128(1)), Some(1))
+                .len(),
+            1
+        );
+        assert!(contract
+            .events_for_organizer(bob().into(), None, None)
+            .is_empty());
+    }
+
+    fn event_token_ids(contract: &Contract, event_id: &str) -> Vec<TokenId> {
+        contract
+            .nft_tokens_for_event(event_id.to_string(), None, None)
+            .into_iter()
+            .map(|token| token.token_id)
+            .collect()
+    }
+
+    #[test]
+    fn tokens_for_event_follow_mints_transfers_and_burns() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        organise(&mut contract, "finals", 0);
+        organise(&mut contract, "meetup", 0);
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "finals.gold".to_string());
+
+        assert_eq!(contract.nft_supply_for_event("finals".to_string()).0, 2);
+        assert_eq!(
+            event_token_ids(&contract, "finals"),
+            vec![
+                "finals.gold.alice.near".to_string(),
+                "finals.gold.bob.near".to_string()
+            ]
+        );
+        assert_eq!(
+            event_token_ids(&contract, "meetup"),
+            vec!["meetup.gold.alice.near".to_string()]
+        );
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            carol().into(),
+            "finals.gold.alice.near".to_string(),
+            None,
+            None,
+        );
+
+        assert_eq!(contract.nft_supply_for_event("finals".to_string()).0, 2);
+        assert_eq!(
+            event_token_ids(&contract, "finals"),
+            vec![
+                "finals.gold.bob.near".to_string(),
+                "finals.gold.carol.near".to_string()
+            ]
+        );
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.nft_burn("finals.gold.bob.near".to_string());
+
+        assert_eq!(contract.nft_supply_for_event("finals".to_string()).0, 1);
+        assert_eq!(
+            event_token_ids(&contract, "finals"),
+            vec!["finals.gold.carol.near".to_string()]
+        );
+        assert_eq!(contract.nft_supply_for_event("unknown".to_string()).0, 0);
+    }
+
+    fn upcoming_at(contract: &Contract, timestamp_ms: u64, limit: Option<u64>) -> UpcomingEvents {
+        let mut context = get_context(bob().to_string(), 0);
+        context.block_timestamp = timestamp_ms * 1_000_000;
+        testing_env!(context);
+
+        contract.upcoming_events_for(alice().into(), limit)
+    }
+
+    #[test]
+    fn upcoming_events_sorted_by_start() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        organise(&mut contract, "finals", 3_000);
+        organise(&mut contract, "meetup", 2_000);
+        organise(&mut contract, "kickoff", 500);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in("meetup.gold.alice.near".to_string());
+
+        let upcoming = upcoming_at(&contract, 1_000, None);
+        assert!(!upcoming.has_more);
+        assert_eq!(
+            upcoming.events,
+            vec![
+                UpcomingEventView {
+                    event_id: "meetup".to_string(),
+                    title: "Catch Pass".to_string(),
+                    starts_at: 2_000,
+                    token_id: "meetup.gold.alice.near".to_string(),
+                    tier: "gold".to_string(),
+                    checked_in: true,
+                },
+                UpcomingEventView {
+                    event_id: "finals".to_string(),
+                    title: "Catch Pass".to_string(),
+                    starts_at: 3_000,
+                    token_id: "finals.gold.alice.near".to_string(),
+                    tier: "gold".to_string(),
+                    checked_in: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn upcoming_events_leave_out_cancelled_and_cap() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        organise(&mut contract, "finals", 3_000);
+        organise(&mut contract, "meetup", 2_000);
+        organise(&mut contract, "kickoff", 1_500);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event("kickoff".to_string(), "Venue unavailable".to_string());
+
+        let upcoming = upcoming_at(&contract, 1_000, Some(1));
+        assert!(upcoming.has_more);
+        assert_eq!(upcoming.events.len(), 1);
+        assert_eq!(upcoming.events[0].event_id, "meetup");
+
+        assert_eq!(upcoming_at(&contract, 1_000, Some(5)).events.len(), 2);
+        assert!(contract
+            .upcoming_events_for(bob().into(), None)
+            .events
+            .is_empty());
+    }
+
+    #[test]
+    fn tokens_for_owner_are_paginated() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        for event_id in ["kickoff", "meetup", "finals"] {
+            organise(&mut contract, event_id, 1_000);
+        }
+
+        let token_ids = |from_index: Option<U128>, limit: Option<u64>| -> Vec<String> {
+            contract
+                .nft_tokens_for_owner(alice().into(), from_index, limit)
+                .into_iter()
+                .map(|token| token.token_id)
+                .collect()
+        };
+
+        assert_eq!(
+            token_ids(None, None),
+            vec![
+                "kickoff.gold.alice.near",
+                "meetup.gold.alice.near",
+                "finals.gold.alice.near"
+            ]
+        );
+        assert_eq!(
+            token_ids(Some(U128(1)), Some(1)),
+            vec!["meetup.gold.alice.near"]
+        );
+        assert_eq!(
+            token_ids(Some(U128(2)), Some(50)),
+            vec!["finals.gold.alice.near"]
+        );
+        assert!(token_ids(Some(U128(3)), None).is_empty());
+        assert!(token_ids(Some(U128(u128::MAX)), Some(u64::MAX)).is_empty());
+    }
+
+    #[test]
+    fn tokens_for_unknown_owner_are_empty() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let contract = create_contract();
+
+        assert!(contract
+            .nft_tokens_for_owner(bob().into(), None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn tokens_list_every_class() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        organise(&mut contract, "kickoff", 1_000);
+        organise(&mut contract, "finals", 1_000);
+
+        // approvals of a copy don't show up in the class, passes can be approved once they start
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        let mut context = get_context(alice().to_string(), STORAGE_DEPOSIT);
+        context.block_timestamp = 1_000 * 1_000_000;
+        testing_env!(context);
+        contract.nft_approve(
+            "kickoff.gold.alice.near".to_string(),
+            marketplace().into(),
+            None,
+        );
+
+        assert_eq!(contract.nft_total_supply().0, 2);
+
+        let tokens = contract.nft_tokens(None, None);
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].token_id, "kickoff.gold");
+        assert_eq!(tokens[0].copies_minted, 1);
+
+        let tokens = contract.nft_tokens(Some(U128(1)), Some(1));
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_id, "finals.gold");
+        assert!(contract.nft_tokens(Some(U128(2)), None).is_empty());
+    }
+
+    fn capped_contract(max_total_supply: u64) -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = Contract::new_default_meta(nft(), Some(max_total_supply));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        contract
+    }
+
+    fn register(contract: &mut Contract, account_id: ValidAccountId) {
+        testing_env!(get_context(account_id.to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(account_id.to_string(), "catchcon.gold".to_string());
+    }
+
+    #[test]
+    fn supply_info_counts_mints_and_burns() {
+        let mut contract = capped_contract(2);
+        register(&mut contract, alice());
+        register(&mut contract, bob());
+
+        assert_eq!(
+            contract.get_supply_info(),
+            SupplyInfo {
+                minted: 2,
+                max: Some(2),
+            }
+        );
+
+        // burning frees room under the cap
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn("catchcon.gold.alice.near".to_string());
+        assert_eq!(contract.get_supply_info().minted, 1);
+
+        register(&mut contract, carol());
+        assert_eq!(contract.get_supply_info().minted, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Max total supply reached")]
+    fn mint_beyond_max_total_supply_fails() {
+        let mut contract = capped_contract(1);
+        register(&mut contract, alice());
+        register(&mut contract, bob());
+    }
 }
@@ -9,9 +9,212 @@ pub type EventId = String;
 pub struct Event {
     pub organiser: AccountId,
     pub event_passes: Vec<TokenId>,
-    // pub event_metadata: EventMetadata     Will be included in Future version of contract
+    //royalty override of the event, merged over the default royalty of the contract
+    pub royalty: Royalty,
+    //no passes can be minted once cancelled, FT paid for passes can be claimed back
+    pub cancelled: bool,
+    //FT paid to attendees on check-in out of the reward pool of the event
+    pub checkin_reward: Option<U128>,
+    //no more check-ins once archived, the unspent reward pool goes back to the organiser
+    pub archived: bool,
+    //passes count as event dependency of other tokens only once checked in
+    pub dependency_requires_check_in: bool,
+    //None for events organised with organise_event
+    pub metadata: Option<EventMetadata>,
+    //copies of the pass class event_id.pass minted so far, bounded by max_capacity
+    pub passes_issued: u64,
+    //passes checked in at the door
+    pub attendance: u64,
+    //passes stay with their first holder unless set, they can still be burnt and checked in
+    pub transferable: bool,
+    //price in yoctoNEAR of a pass bought with register_for_event, None if passes aren't sold
+    pub price: Option<U128>,
+    //yoctoNEAR paid for passes bought with register_for_event or from the waitlist, fee included
+    pub revenue_collected: U128,
+    //account which paid the storage of the event, refunded when the event is deleted
+    pub storage_payer: AccountId,
 }
 
+impl Event {
+    //metadata of an event of create_event which still takes attendees
+    pub(crate) fn assert_open(&self) -> EventMetadata {
+        let metadata = self
+            .metadata
+            .clone()
+            .unwrap_or_else(|| env::panic(b"Passes are issued for events of create_event"));
+        require!(!self.cancelled, "Event is cancelled");
+        require!(now_ms() < metadata.ends_at, "Event has ended");
+        require!(
+            self.passes_issued < metadata.max_capacity,
+            "Event is at capacity"
+        );
+
+        metadata
+    }
+
+    //true once passes_issued reached max_capacity of an event of create_event
+    pub(crate) fn is_sold_out(&self) -> bool {
+        self.metadata
+            .as_ref()
+            .is_some_and(|metadata| self.passes_issued >= metadata.max_capacity)
+    }
+}
+
+/// Catalogue data of an event created with create_event, times are Unix epoch in milliseconds
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventMetadata {
+    pub title: String,
+    pub description: Option<String>,
+    pub venue: Option<String>,
+    pub starts_at: u64,
+    pub ends_at: u64,
+    //attendees the event can take
+    pub max_capacity: u64,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl EventMetadata {
+    /// media and reference come with their sha256 like in token metadata
+    pub fn assert_valid(&self) {
+        require!(!self.title.is_empty(), "Event title is empty");
+        require!(
+            self.ends_at > self.starts_at,
+            "ends_at has to be after starts_at"
+        );
+        require!(self.max_capacity > 0, "max_capacity must be positive");
+
+        match (&self.media, &self.media_hash) {
+            (Some(_), Some(media_hash)) => assert_valid_hash(media_hash, "media_hash"),
+            (Some(_), None) => env::panic(b"media requires media_hash"),
+            (None, Some(_)) => env::panic(b"media_hash is given without media"),
+            (None, None) => {}
+        }
+
+        match (&self.reference, &self.reference_hash) {
+            (Some(_), Some(reference_hash)) => assert_valid_hash(reference_hash, "reference_hash"),
+            (Some(_), None) => env::panic(b"reference requires reference_hash"),
+            (None, Some(_)) => env::panic(b"reference_hash is given without reference"),
+            (None, None) => {}
+        }
+    }
+
+    //metadata of the pass class of issue_event_pass
+    fn pass_metadata(&self) -> TokenMetadata {
+        TokenMetadata {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            media: self.media.clone().unwrap_or_default(),
+            media_hash: self
+                .media_hash
+                .clone()
+                .unwrap_or_else(|| Base64VecU8(vec![])),
+            copies: Some(self.max_capacity),
+            issued_at: None,
+            expires_at: Some(self.ends_at),
+            starts_at: None,
+            updated_at: None,
+            lat: None,
+            lng: None,
+            extra: None,
+            reference: self.reference.clone(),
+            reference_hash: self.reference_hash.clone(),
+        }
+    }
+}
+
+/// Fields of the metadata of an event to change, fields which are None are kept
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventMetadataUpdate {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub venue: Option<String>,
+    pub starts_at: Option<u64>,
+    pub ends_at: Option<u64>,
+    pub max_capacity: Option<u64>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl EventMetadataUpdate {
+    //writes the update to the metadata, returns the names of the fields which changed
+    fn apply(self, metadata: &mut EventMetadata) -> Vec<String> {
+        let mut changed = vec![];
+
+        update_field(&mut metadata.title, self.title, "title", &mut changed);
+        update_field(
+            &mut metadata.description,
+            self.description.map(Some),
+            "description",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.venue,
+            self.venue.map(Some),
+            "venue",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.starts_at,
+            self.starts_at,
+            "starts_at",
+            &mut changed,
+        );
+        update_field(&mut metadata.ends_at, self.ends_at, "ends_at", &mut changed);
+        update_field(
+            &mut metadata.max_capacity,
+            self.max_capacity,
+            "max_capacity",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.media,
+            self.media.map(Some),
+            "media",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.media_hash,
+            self.media_hash.map(Some),
+            "media_hash",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.reference,
+            self.reference.map(Some),
+            "reference",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.reference_hash,
+            self.reference_hash.map(Some),
+            "reference_hash",
+            &mut changed,
+        );
+
+        changed
+    }
+}
+
+/// Where the event stands at the current block
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EventStatus {
+    Upcoming,
+    Live,
+    Ended,
+    Cancelled,
+}
+
+//token part of the pass class of issue_event_pass, passes are minted as event_id.pass.owner_id
+pub const EVENT_PASS_TOKEN: &str = "pass";
+
 #[derive(Deserialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct TokenInfo {
@@ -19,12 +222,290 @@ pub struct TokenInfo {
     pub token_metadata: TokenMetadata,
     pub token_dependency_by_id: Vec<TokenId>,
     pub event_dependency_by_id: Vec<EventId>,
+    //tokens of partner NFT contracts the receiver has to hold, only the owner can set them
+    #[serde(default)]
+    pub external_dependencies: Vec<ExternalDependency>,
+    #[serde(default)]
+    pub royalty: Royalty,
+    //passes bound to their holder, like KYC passes
+    #[serde(default)]
+    pub soulbound: bool,
+}
+
+impl Contract {
+    //status of the event at the current block, events of organise_event run from the earliest
+    //starts_at to the latest expires_at of their passes
+    pub(crate) fn internal_event_status(&self, event: &Event) -> EventStatus {
+        if event.cancelled {
+            return EventStatus::Cancelled;
+        }
+
+        let (starts_at, ends_at) = match &event.metadata {
+            Some(metadata) => (Some(metadata.starts_at), Some(metadata.ends_at)),
+            None => {
+                let passes: Vec<TokenMetadata> = event
+                    .event_passes
+                    .iter()
+                    .filter_map(|token_id| self.token_metadata_by_id.get(token_id))
+                    .collect();
+                let starts_at = passes.iter().filter_map(|pass| pass.starts_at).min();
+                let ends_at = if passes.iter().all(|pass| pass.expires_at.is_some()) {
+                    passes.iter().filter_map(|pass| pass.expires_at).max()
+                } else {
+                    None
+                };
+                (starts_at, ends_at)
+            }
+        };
+
+        let now = now_ms();
+        if event.archived || ends_at.is_some_and(|ends_at| ends_at <= now) {
+            EventStatus::Ended
+        } else if starts_at.is_some_and(|starts_at| starts_at > now) {
+            EventStatus::Upcoming
+        } else {
+            EventStatus::Live
+        }
+    }
+
+    //keeps events_per_organiser in sync with the organiser of the event
+    pub(crate) fn internal_add_event_to_organiser(
+        &mut self,
+        account_id: &AccountId,
+        event_id: &EventId,
+    ) {
+        let mut events_set = self
+            .events_per_organiser
+            .get(account_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::EventsPerOrganiserInner {
+                        account_id_hash: hash_id(account_id),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        events_set.insert(event_id);
+        self.events_per_organiser.insert(account_id, &events_set);
+    }
+
+    pub(crate) fn internal_remove_event_from_organiser(
+        &mut self,
+        account_id: &AccountId,
+        event_id: &EventId,
+    ) {
+        if let Some(mut events_set) = self.events_per_organiser.get(account_id) {
+            events_set.remove(event_id);
+            if events_set.is_empty() {
+                self.events_per_organiser.remove(account_id);
+            } else {
+                self.events_per_organiser.insert(account_id, &events_set);
+            }
+        }
+    }
+
+    //false for passes of an event which isn't transferable, tokens outside events are transferable
+    pub(crate) fn internal_is_transferable_pass(&self, token_id: &TokenId) -> bool {
+        let event_id: EventId = token_id.split_once('.').unwrap().0.to_string();
+        self.events_by_id
+            .get(&event_id)
+            .is_none_or(|event| event.transferable)
+    }
+
+    //panics unless the caller is the contract owner or the organiser of the event
+    pub(crate) fn assert_event_manager(&self, event: &Event, action: &str) {
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == event.organiser || caller == self.owner_id,
+            format!("Only the owner or the event organiser can {}", action)
+        );
+    }
+
+    //keeps passes_issued of the event in step with the copies of its pass class, whichever way
+    //they are minted
+    pub(crate) fn internal_count_event_pass(&mut self, token_id: &TokenId, issued: bool) {
+        let event_id: EventId = token_id.split_once('.').unwrap().0.to_string();
+        if *token_id != format!("{}.{}", event_id, EVENT_PASS_TOKEN) {
+            return;
+        }
+
+        if let Some(mut event) = self.events_by_id.get(&event_id) {
+            if issued {
+                event.passes_issued += 1;
+            } else {
+                event.passes_issued -= 1;
+            }
+            self.events_by_id.insert(&event_id, &event);
+        }
+    }
+
+    //class token of the passes of the event, created with the first pass with metadata derived
+    //from the event
+    pub(crate) fn internal_event_pass_class(
+        &mut self,
+        event_id: &EventId,
+        mut event: Event,
+        metadata: &EventMetadata,
+    ) -> TokenId {
+        let token_id = format!("{}.{}", event_id, EVENT_PASS_TOKEN);
+        if self.tokens_by_id.get(&token_id).is_some() {
+            return token_id;
+        }
+
+        let token = Token {
+            token_id: token_id.clone(),
+            copies_minted: 0,
+            max_copies: metadata.max_capacity,
+            expires_at: Some(metadata.ends_at),
+            token_dependency_by_id: vec![],
+            event_dependency_by_id: vec![],
+            external_dependencies: vec![],
+            royalty: self.internal_merge_royalty(&event.royalty, Default::default()),
+            metadata_frozen: false,
+            soulbound: false,
+        };
+        self.tokens_by_id.insert(&token_id, &token);
+
+        let mut token_metadata = metadata.pass_metadata();
+        token_metadata.issued_at = Some(now_ms());
+        self.token_metadata_by_id.insert(&token_id, &token_metadata);
+
+        event.event_passes.push(token_id.clone());
+        self.events_by_id.insert(event_id, &event);
+
+        token_id
+    }
+
+    //carries an update of the event over to its pass class, if passes were issued already
+    fn internal_sync_event_pass_class(&mut self, event_id: &EventId, metadata: &EventMetadata) {
+        let token_id = format!("{}.{}", event_id, EVENT_PASS_TOKEN);
+        let mut token = match self.tokens_by_id.get(&token_id) {
+            Some(token) => token,
+            None => return,
+        };
+
+        token.max_copies = metadata.max_capacity;
+        token.expires_at = Some(metadata.ends_at);
+        self.tokens_by_id.insert(&token_id, &token);
+
+        let issued_at = self
+            .token_metadata_by_id
+            .get(&token_id)
+            .and_then(|token_metadata| token_metadata.issued_at);
+        let mut token_metadata = metadata.pass_metadata();
+        token_metadata.issued_at = issued_at;
+        token_metadata.updated_at = Some(now_ms());
+        self.token_metadata_by_id.insert(&token_id, &token_metadata);
+    }
+
+    //keeps passes_per_event and tokens_per_event in sync with the passes the account holds,
+    //tokens outside events are skipped
+    pub(crate) fn internal_index_pass(&mut self, account_id: &AccountId, token_id: &TokenId) {
+        let event_id: EventId = token_id.split_once('.').unwrap().0.to_string();
+        if self.events_by_id.get(&event_id).is_none() {
+            return;
+        }
+
+        let mut tokens_set = self.tokens_per_event.get(&event_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::TokensPerEventInner {
+                    event_id_hash: hash_id(&event_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        tokens_set.insert(&build_full_token_id(token_id.clone(), account_id.clone()));
+        self.tokens_per_event.insert(&event_id, &tokens_set);
+
+        let mut holders = self.passes_per_event.get(&event_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::PassesPerEventInner {
+                    event_id_hash: hash_id(&event_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        holders.insert(account_id);
+        self.passes_per_event.insert(&event_id, &holders);
+    }
+
+    //the account leaves passes_per_event once it holds no pass of the event
+    pub(crate) fn internal_unindex_pass(&mut self, account_id: &AccountId, token_id: &TokenId) {
+        let event_id: EventId = token_id.split_once('.').unwrap().0.to_string();
+        let event = match self.events_by_id.get(&event_id) {
+            Some(event) => event,
+            None => return,
+        };
+
+        if let Some(mut tokens_set) = self.tokens_per_event.get(&event_id) {
+            tokens_set.remove(&build_full_token_id(token_id.clone(), account_id.clone()));
+            self.tokens_per_event.insert(&event_id, &tokens_set);
+        }
+
+        if event
+            .event_passes
+            .iter()
+            .any(|pass| self.internal_owns_token(account_id, pass))
+        {
+            return;
+        }
+
+        if let Some(mut holders) = self.passes_per_event.get(&event_id) {
+            holders.remove(account_id);
+            self.passes_per_event.insert(&event_id, &holders);
+        }
+    }
+
+    //true if the account holds a pass of the event which isn't expired and is checked in
+    //if the event requires it
+    pub(crate) fn internal_holds_valid_pass(
+        &self,
+        account_id: &AccountId,
+        event_id: &EventId,
+    ) -> bool {
+        let holds_pass = self
+            .passes_per_event
+            .get(event_id)
+            .is_some_and(|holders| holders.contains(account_id));
+
+        let event = self.events_by_id.get(event_id).unwrap();
+
+        //a borrowed pass counts as held while the lease is active
+        event.event_passes.iter().any(|class_token_id| {
+            let token_id = if holds_pass {
+                self.internal_held_copy(account_id, class_token_id)
+            } else {
+                self.internal_borrowed_copy(account_id, class_token_id)
+            };
+            let token_id = match token_id {
+                Some(token_id) => token_id,
+                None => return false,
+            };
+
+            let token = self.tokens_by_id.get(class_token_id).unwrap();
+
+            !self.internal_is_copy_expired(&token_id, &token)
+                && self.internal_is_active(class_token_id)
+                && (!event.dependency_requires_check_in
+                    || self.checked_in_at_by_token_id.get(&token_id).is_some())
+        })
+    }
 }
 
 #[near_bindgen]
 impl Contract {
     #[payable]
-    pub fn organise_event(&mut self, event_id: EventId, tokens: Vec<TokenInfo>) {
+    pub fn organise_event(
+        &mut self,
+        event_id: EventId,
+        tokens: Vec<TokenInfo>,
+        royalty: Option<Royalty>,
+    ) {
+        self.assert_not_paused(PausableFeature::Events);
+
         let initial_storage = env::storage_usage();
 
         let mut event_passes: Vec<TokenId> = Vec::with_capacity(tokens.len());
@@ -33,15 +514,26 @@ impl Contract {
         let mut storage_required_for_token_ids = 0;
         let event: Event;
         let organiser = env::predecessor_account_id();
+        let royalty = royalty.unwrap_or_default();
 
         // assert_valid_catch_user_account_pattern(&organiser);
 
         assert_valid_id(&event_id);
+        require!(
+            self.series_by_id.get(&event_id).is_none(),
+            "A series with the same id exists"
+        );
 
         for token_info in tokens {
             assert_valid_id(&token_info.token_id);
+            token_info.token_metadata.assert_valid();
+            self.internal_assert_external_dependencies(&token_info.external_dependencies);
 
             token_id = format!("{}.{}", event_id, token_info.token_id); // TokenId = EventId.TokenId
+            require!(
+                !token_info.token_dependency_by_id.contains(&token_id),
+                format!("Token {} can't depend on itself", &token_id)
+            );
 
             event_passes.push(token_id.clone());
 
@@ -52,13 +544,10 @@ impl Contract {
                 expires_at: token_info.token_metadata.expires_at,
                 token_dependency_by_id: token_info.token_dependency_by_id,
                 event_dependency_by_id: token_info.event_dependency_by_id,
-                account_approval_info_per_owner: LookupMap::new(
-                    StorageKey::ApprovedAccountsPerToken {
-                        token_id_hash: hash_id(&token_id),
-                    }
-                    .try_to_vec()
-                    .unwrap(),
-                ),
+                external_dependencies: token_info.external_dependencies,
+                royalty: self.internal_merge_royalty(&royalty, token_info.royalty),
+                metadata_frozen: false,
+                soulbound: token_info.soulbound,
             };
 
             require!(
@@ -75,9 +564,23 @@ impl Contract {
 
         assert_valid_id(&event_id);
 
+        self.internal_add_event_to_organiser(&organiser, &event_id);
+
         event = Event {
             organiser,
             event_passes,
+            royalty,
+            cancelled: false,
+            checkin_reward: None,
+            archived: false,
+            dependency_requires_check_in: false,
+            metadata: None,
+            passes_issued: 0,
+            attendance: 0,
+            transferable: true,
+            price: None,
+            revenue_collected: U128(0),
+            storage_payer: env::predecessor_account_id(),
         };
 
         require!(
@@ -91,62 +594,148 @@ impl Contract {
         refund_deposit(total_storage_required);
     }
 
+    /// Creates an event without passes for the catalogue, the caller is its organiser and pays
+    /// its storage. Passes are added with add_tokens_to_event. Passes of an event which isn't
+    /// transferable are bound to the account they are issued to, events are transferable unless
+    /// told otherwise
     #[payable]
-    pub fn nft_event_register(&mut self, receiver_id: AccountId, token_id: TokenId) {
-        let mut token = self
-            .tokens_by_id
-            .get(&token_id)
-            .unwrap_or_else(|| env::panic(b"Token does not exist"));
+    pub fn create_event(
+        &mut self,
+        event_id: EventId,
+        metadata: EventMetadata,
+        transferable: Option<bool>,
+    ) {
+        self.assert_not_paused(PausableFeature::Events);
 
-        let account_id = receiver_id;
+        let initial_storage = env::storage_usage();
 
-        require!(!internal_is_token_expired(&token), "Token has expired");
+        assert_valid_id(&event_id);
+        metadata.assert_valid();
+        require!(
+            self.series_by_id.get(&event_id).is_none(),
+            "A series with the same id exists"
+        );
 
-        assert_token_availability(&token);
+        let organiser = env::predecessor_account_id();
+        let event = Event {
+            organiser: organiser.clone(),
+            event_passes: vec![],
+            royalty: Default::default(),
+            cancelled: false,
+            checkin_reward: None,
+            archived: false,
+            dependency_requires_check_in: false,
+            metadata: Some(metadata.clone()),
+            passes_issued: 0,
+            attendance: 0,
+            transferable: transferable.unwrap_or(true),
+            price: None,
+            revenue_collected: U128(0),
+            storage_payer: env::predecessor_account_id(),
+        };
 
         require!(
-            self.internal_is_eligible_to_mint_token(&account_id, &token),
-            format!(
-                "{} doesn't satisfy all the dependencies for the token {}",
-                &account_id, &token_id
-            )
+            self.events_by_id.insert(&event_id, &event).is_none(),
+            "Event Already Exists"
         );
+        self.internal_add_event_to_organiser(&organiser, &event_id);
 
-        token.copies_minted += 1;
+        CatchEventLogVariant::EventCreated(EventCreatedLog {
+            event_id,
+            organiser,
+            metadata,
+        })
+        .emit();
 
-        self.tokens_by_id.insert(&token_id, &token);
+        refund_deposit(env::storage_usage() - initial_storage);
+    }
 
-        if self.tokens_per_owner.get(&account_id).is_none() {
+    /// Owner or event organiser only, creates the account of the receiver and issues it a pass
+    /// of an event of create_event. Passes are copies of event_id.pass whose metadata is taken
+    /// from the event, they expire when the event ends and no more than max_capacity are issued
+    #[payable]
+    pub fn issue_event_pass(
+        &mut self,
+        event_id: EventId,
+        receiver_id: ValidAccountId,
+        public_key: Base58PublicKey,
+    ) -> Promise {
+        self.assert_not_paused(PausableFeature::Events);
+
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "issue passes");
+        let metadata = event.assert_open();
+
+        let receiver_id: AccountId = receiver_id.into();
+        assert_catch_sub_account(&receiver_id);
+
+        let initial_storage = env::storage_usage();
+
+        let token_id = self.internal_event_pass_class(&event_id, event, &metadata);
+
+        self.internal_reserve_copy(&receiver_id, &token_id);
+
+        let storage_used = env::storage_usage() - initial_storage;
+        self.internal_start_mint_copy(receiver_id, token_id, public_key, storage_used)
+    }
+
+    #[payable]
+    pub fn nft_event_register(&mut self, receiver_id: AccountId, token_id: TokenId) {
+        self.assert_not_paused(PausableFeature::Events);
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let account_id = receiver_id;
+
+        let storage_cost = if self.tokens_per_owner.get(&account_id).is_none() {
             let storage_used = bytes_for_token_or_event_or_account_id(&account_id);
             refund_deposit(storage_used);
+            env::storage_byte_cost() * Balance::from(storage_used)
         } else {
             Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit());
-        }
+            0
+        };
 
-        self.internal_add_token_to_owner(&account_id, &token_id);
+        // the copy is reserved now and given once the registry confirms the receiver
+        if self.internal_is_async_validation() {
+            self.internal_reserve_copy(&account_id, &token_id);
+            self.internal_check_player(&account_id)
+                .then(ext_registry_self::resolve_player_mint(
+                    account_id,
+                    token_id,
+                    env::predecessor_account_id(),
+                    storage_cost.into(),
+                    &env::current_account_id(),
+                    0,
+                    GAS_FOR_RESOLVE_PLAYER,
+                ));
+            return;
+        }
 
         let event_id: EventId = token_id.split_once(".").unwrap().0.to_string();
+        let memo = format!(
+            "{} has successfully registered for the event {}",
+            &account_id, &event_id
+        );
 
-        NftMintLog::emit(vec![NftMintLog {
-            owner_id: account_id.clone(),
-            token_ids: vec![token_id],
-            memo: Some(format!(
-                "{} has successfully registered for the event {}",
-                &account_id, &event_id
-            )),
-        }]);
+        self.internal_assert_catch_player(&account_id);
+        self.internal_mint_copy(&account_id, &token_id, memo);
 
         // todo!(); // Refund User if payed extra
     }
 
     #[payable]
     pub fn add_tokens_to_event(&mut self,event_id: EventId,new_token_set: Vec<TokenInfo>){
+        self.assert_not_paused(PausableFeature::Events);
 
         let initial_storage = env::storage_usage();
 
         let mut event = self.events_by_id.get(&event_id).unwrap_or_else(|| env::panic(b"Invalid event id"));
 
-        require!(env::predecessor_account_id() == event.organiser, "Only Owner can add new tokens to the event");
+        self.assert_event_manager(&event, "add tokens to the event");
 
         let mut token_id: TokenId;
         let mut token: Token;
@@ -154,9 +743,15 @@ impl Contract {
 
         for token_info in new_token_set {
             assert_valid_id(&token_info.token_id);
+            token_info.token_metadata.assert_valid();
+            self.internal_assert_external_dependencies(&token_info.external_dependencies);
 
             
             token_id = format!("{}.{}", event_id, token_info.token_id); // TokenId = EventId.TokenId
+            require!(
+                !token_info.token_dependency_by_id.contains(&token_id),
+                format!("Token {} can't depend on itself", &token_id)
+            );
             
             event.event_passes.push(token_id.clone());
 
@@ -167,13 +762,10 @@ impl Contract {
                 expires_at: token_info.token_metadata.expires_at,
                 token_dependency_by_id: token_info.token_dependency_by_id,
                 event_dependency_by_id: token_info.event_dependency_by_id,
-                account_approval_info_per_owner: LookupMap::new(
-                    StorageKey::ApprovedAccountsPerToken {
-                        token_id_hash: hash_id(&token_id),
-                    }
-                    .try_to_vec()
-                    .unwrap(),
-                ),
+                external_dependencies: token_info.external_dependencies,
+                royalty: self.internal_merge_royalty(&event.royalty, token_info.royalty),
+                metadata_frozen: false,
+                soulbound: token_info.soulbound,
             };
 
             require!(
@@ -195,4 +787,922 @@ impl Contract {
 
         refund_deposit(total_storage_required);
     }
+
+    /// Owner or event organiser only, changes the metadata of an event of create_event until it
+    /// starts. Capacity can only grow, issued passes follow the update. The storage the update
+    /// adds is paid from the deposit, storage it frees is refunded
+    #[payable]
+    pub fn update_event(&mut self, event_id: EventId, updates: EventMetadataUpdate) {
+        assert_at_least_one_yocto();
+        self.assert_not_paused(PausableFeature::Events);
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "update the event");
+        require!(!event.cancelled, "Event is cancelled");
+
+        let mut metadata = event
+            .metadata
+            .clone()
+            .unwrap_or_else(|| env::panic(b"Only events of create_event have metadata"));
+        require!(now_ms() < metadata.starts_at, "Event has already started");
+
+        let initial_storage = env::storage_usage();
+
+        let max_capacity = metadata.max_capacity;
+        let fields = updates.apply(&mut metadata);
+        require!(!fields.is_empty(), "Nothing to update");
+
+        metadata.assert_valid();
+        require!(
+            metadata.max_capacity >= max_capacity,
+            "Capacity can only be increased"
+        );
+
+        event.metadata = Some(metadata.clone());
+        self.events_by_id.insert(&event_id, &event);
+        self.internal_sync_event_pass_class(&event_id, &metadata);
+
+        let final_storage = env::storage_usage();
+        if final_storage > initial_storage {
+            refund_deposit(final_storage - initial_storage);
+        } else {
+            let storage_released = initial_storage - final_storage;
+            Promise::new(env::predecessor_account_id()).transfer(
+                env::attached_deposit()
+                    + Balance::from(storage_released) * env::storage_byte_cost(),
+            );
+        }
+
+        CatchEventLogVariant::EventUpdated(EventUpdatedLog { event_id, fields }).emit();
+    }
+
+    /// Owner or event organiser only, if required passes of the event satisfy the event
+    /// dependency of other tokens only once checked in
+    pub fn set_dependency_requires_check_in(&mut self, event_id: EventId, required: bool) {
+        self.assert_not_paused(PausableFeature::Events);
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "change the event");
+
+        event.dependency_requires_check_in = required;
+        self.events_by_id.insert(&event_id, &event);
+    }
+
+    /// Owner or event organiser only, stops the sale of passes, passes of the event can't be
+    /// transferred or checked in from then on and buyers claim their refunds with
+    /// claim_event_refund. Events of create_event can't be cancelled once they ended
+    pub fn cancel_event(&mut self, event_id: EventId, reason: String) {
+        self.assert_not_paused(PausableFeature::Events);
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "cancel the event");
+        require!(!event.cancelled, "Event is already cancelled");
+        require!(!reason.trim().is_empty(), "Cancellation reason is required");
+        require!(
+            event
+                .metadata
+                .as_ref()
+                .is_none_or(|metadata| now_ms() < metadata.ends_at),
+            "Event has already ended"
+        );
+
+        event.cancelled = true;
+        self.events_by_id.insert(&event_id, &event);
+
+        CatchEventLogVariant::EventCancelled(EventCancelledLog { event_id, reason }).emit();
+    }
+
+    /// Owner only, removes an event which ended or was cancelled once every pass of it is burnt,
+    /// together with its pass classes and indexes. Queued accounts get their escrow back and the
+    /// freed storage is refunded to the account which paid for the event
+    #[payable]
+    pub fn delete_event(&mut self, event_id: EventId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(
+            matches!(
+                self.internal_event_status(&event),
+                EventStatus::Ended | EventStatus::Cancelled
+            ),
+            "Event hasn't ended or been cancelled"
+        );
+        let passes = self
+            .tokens_per_event
+            .get(&event_id)
+            .map_or(0, |tokens_set| tokens_set.len());
+        require!(
+            passes == 0,
+            format!("{} passes of the event remain", passes)
+        );
+        require!(
+            self.ft_proceeds_by_event_id.get(&event_id).unwrap_or(0) == 0,
+            "FT proceeds of the event are still held"
+        );
+        require!(
+            self.near_proceeds_by_event_id.get(&event_id).unwrap_or(0) == 0,
+            "NEAR proceeds of the event are still held"
+        );
+        require!(
+            self.reward_pool_by_event_id.get(&event_id).unwrap_or(0) == 0,
+            "Reward pool of the event isn't empty"
+        );
+        for token_id in &event.event_passes {
+            let dependents: Vec<TokenId> = self
+                .internal_dependents_of(token_id)
+                .into_iter()
+                .filter(|dependent| !event.event_passes.contains(dependent))
+                .collect();
+            require!(
+                dependents.is_empty(),
+                format!(
+                    "Token {} is a dependency of {}",
+                    token_id,
+                    dependents.join(", ")
+                )
+            );
+        }
+
+        let initial_storage = env::storage_usage();
+
+        for token_id in &event.event_passes {
+            self.tokens_by_id.remove(token_id);
+            self.token_metadata_by_id.remove(token_id);
+            self.upgrade_config_by_token_id.remove(token_id);
+            self.renewal_config_by_token_id.remove(token_id);
+            self.ft_price_by_token_id.remove(token_id);
+        }
+
+        if let Some(mut tokens_set) = self.tokens_per_event.remove(&event_id) {
+            tokens_set.clear();
+        }
+        if let Some(mut holders) = self.passes_per_event.remove(&event_id) {
+            holders.clear();
+        }
+        if let Some(mut waitlist) = self.waitlists.remove(&event_id) {
+            for account_id in waitlist.iter() {
+                let escrow = self
+                    .waitlist_escrow
+                    .remove(&(event_id.clone(), account_id.clone()))
+                    .unwrap();
+                Promise::new(account_id).transfer(escrow);
+            }
+            waitlist.clear();
+        }
+        self.ft_proceeds_by_event_id.remove(&event_id);
+        self.reward_pool_by_event_id.remove(&event_id);
+
+        self.internal_remove_event_from_organiser(&event.organiser, &event_id);
+        self.events_by_id.remove(&event_id);
+
+        let storage_released = initial_storage.saturating_sub(env::storage_usage());
+        if storage_released > 0 {
+            Promise::new(event.storage_payer)
+                .transfer(Balance::from(storage_released) * env::storage_byte_cost());
+        }
+
+        CatchEventLogVariant::EventDeleted(EventDeletedLog { event_id }).emit();
+    }
+
+    /// Owner or event organiser only, marks the pass (event_id.token_id.owner_id) as used at
+    /// the door and pays the check-in reward of the event to the attendee. Passes of events of
+    /// create_event are checked in between starts_at and ends_at
+    pub fn check_in(&mut self, token_id: TokenId) {
+        self.assert_not_paused(PausableFeature::Events);
+
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+        let event_id: EventId = class_token_id.split_once(".").unwrap().0.to_string();
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "check in passes");
+        require!(!event.cancelled, "Event is cancelled");
+        require!(!event.archived, "Event is archived");
+
+        if let Some(metadata) = &event.metadata {
+            require!(now_ms() >= metadata.starts_at, "Event hasn't started");
+            require!(now_ms() < metadata.ends_at, "Event has ended");
+        }
+
+        require!(
+            self.internal_owns_token(&owner_id, &class_token_id),
+            "No token"
+        );
+        require!(
+            self.checked_in_at_by_token_id.get(&token_id).is_none(),
+            "Pass already redeemed"
+        );
+
+        self.checked_in_at_by_token_id.insert(&token_id, &now_ms());
+
+        event.attendance += 1;
+        self.events_by_id.insert(&event_id, &event);
+
+        if let Some(reward) = event.checkin_reward {
+            self.internal_issue_checkin_reward(&event_id, &owner_id, reward.0);
+        }
+    }
+
+    /// Owner or event organiser only, hands the event over to another organiser, who gets the
+    /// proceeds and the reward pool of the event from then on
+    #[payable]
+    pub fn transfer_event_organizer(&mut self, event_id: EventId, new_organizer: ValidAccountId) {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Events);
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "transfer the event");
+
+        let new_organiser: AccountId = new_organizer.into();
+        require!(
+            new_organiser != event.organiser,
+            "Account is already the event organiser"
+        );
+
+        let old_organiser = std::mem::replace(&mut event.organiser, new_organiser.clone());
+        self.events_by_id.insert(&event_id, &event);
+
+        self.internal_remove_event_from_organiser(&old_organiser, &event_id);
+        self.internal_add_event_to_organiser(&new_organiser, &event_id);
+
+        CatchEventLogVariant::EventOrganiserTransferred(EventOrganiserLog {
+            event_id,
+            old_organiser,
+            new_organiser,
+        })
+        .emit();
+    }
+
+    /// Owner or event organiser only, passes of an event can be made transferable at any time,
+    /// e.g. once identity checks are done, but an event can't be made non-transferable once
+    /// passes were issued
+    pub fn set_event_transferable(&mut self, event_id: EventId, transferable: bool) {
+        self.assert_not_paused(PausableFeature::Events);
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "change the event");
+        require!(
+            transferable
+                || event.event_passes.iter().all(|token_id| self
+                    .tokens_by_id
+                    .get(token_id)
+                    .is_some_and(|token| token.copies_minted == 0)),
+            "Passes of the event were issued, it can't be made non-transferable"
+        );
+
+        event.transferable = transferable;
+        self.events_by_id.insert(&event_id, &event);
+    }
+
+    //true once the pass of form event_id.token_id.owner_id was checked in
+    pub fn is_checked_in(&self, token_id: TokenId) -> bool {
+        self.checked_in_at_by_token_id.get(&token_id).is_some()
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const EVENT: &str = "catchcon";
+
+    fn event_metadata() -> EventMetadata {
+        EventMetadata {
+            title: "Catch Con".to_string(),
+            description: None,
+            venue: Some("Lisbon".to_string()),
+            starts_at: 1_000,
+            ends_at: 2_000,
+            max_capacity: 100,
+            media: None,
+            media_hash: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.create_event(EVENT.to_string(), event_metadata(), None);
+
+        contract
+    }
+
+    fn player() -> ValidAccountId {
+        ValidAccountId::try_from("alice.nft.catchlabs.near").unwrap()
+    }
+
+    fn issue_pass(contract: &mut Contract, receiver_id: ValidAccountId) {
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.issue_event_pass(
+            EVENT.to_string(),
+            receiver_id,
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+        );
+    }
+
+    fn resolve_pass(contract: &mut Contract, receiver_id: ValidAccountId, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_mint_copy(
+            format!("{}.pass.{}", EVENT, receiver_id),
+            carol().to_string(),
+            U128(STORAGE_DEPOSIT),
+            U128(0),
+        );
+    }
+
+    #[test]
+    fn created_event_is_in_the_catalogue() {
+        let mut contract = setup();
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"event_created","data":{"event_id":"catchcon","organiser":"carol.near","metadata":{"title":"Catch Con","description":null,"venue":"Lisbon","starts_at":1000,"ends_at":2000,"max_capacity":100,"media":null,"media_hash":null,"reference":null,"reference_hash":null}}}"#
+        );
+
+        let event = contract.get_event_by_id(EVENT.to_string()).unwrap();
+        assert_eq!(event.organiser, carol().to_string());
+        assert_eq!(event.metadata, Some(event_metadata()));
+        assert!(event.event_tokens_metadata.is_empty());
+        assert_eq!(contract.get_events(None, None).len(), 1);
+
+        // the organiser adds the passes
+        contract.add_tokens_to_event(
+            EVENT.to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+        );
+        assert_eq!(
+            contract
+                .get_event_by_id(EVENT.to_string())
+                .unwrap()
+                .event_tokens_metadata
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn issued_pass_is_derived_from_the_event() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"event_pass_issued","data":{"event_id":"catchcon","owner_id":"alice.nft.catchlabs.near","token_id":"catchcon.pass.alice.nft.catchlabs.near"}}"#
+        );
+
+        let tokens = contract.nft_tokens_for_owner(player().to_string(), None, None);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_id, "catchcon.pass.alice.nft.catchlabs.near");
+        assert_eq!(tokens[0].metadata.title, "Catch Con");
+        assert_eq!(tokens[0].metadata.expires_at, Some(2_000));
+        assert_eq!(tokens[0].max_copies, 100);
+
+        let event = contract.events_by_id.get(&EVENT.to_string()).unwrap();
+        assert_eq!(event.passes_issued, 1);
+        assert_eq!(event.event_passes, vec!["catchcon.pass".to_string()]);
+    }
+
+    #[test]
+    fn failed_account_creation_releases_the_pass() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Failed);
+
+        let event = contract.events_by_id.get(&EVENT.to_string()).unwrap();
+        assert_eq!(event.passes_issued, 0);
+        assert!(contract
+            .nft_tokens_for_owner(player().to_string(), None, None)
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is at capacity")]
+    fn pass_beyond_capacity_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        let mut metadata = event_metadata();
+        metadata.max_capacity = 1;
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.create_event(EVENT.to_string(), metadata, None);
+
+        issue_pass(&mut contract, player());
+        issue_pass(
+            &mut contract,
+            ValidAccountId::try_from("bob.nft.catchlabs.near").unwrap(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Event has ended")]
+    fn pass_after_the_event_fails() {
+        let mut contract = setup();
+
+        let mut context = get_context(carol().to_string(), STORAGE_DEPOSIT);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context);
+        contract.issue_event_pass(
+            EVENT.to_string(),
+            player(),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near is not a Catch sub-account")]
+    fn pass_to_other_account_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, alice());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event Already Exists")]
+    fn duplicate_event_fails() {
+        let mut contract = setup();
+
+        contract.create_event(EVENT.to_string(), event_metadata(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ends_at has to be after starts_at")]
+    fn event_ending_before_start_fails() {
+        let mut contract = setup();
+
+        let mut metadata = event_metadata();
+        metadata.ends_at = metadata.starts_at;
+        contract.create_event("othercon".to_string(), metadata, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "media requires media_hash")]
+    fn event_media_without_hash_fails() {
+        let mut contract = setup();
+
+        let mut metadata = event_metadata();
+        metadata.media = Some("ipfs://media".to_string());
+        contract.create_event("othercon".to_string(), metadata, None);
+    }
+
+    #[test]
+    fn check_in_redeems_the_pass() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        let pass = format!("{}.pass.{}", EVENT, player());
+        assert!(!contract.is_checked_in(pass.clone()));
+
+        // gate staff of the owner checks in during the event
+        let mut context = get_context(nft().to_string(), 0);
+        context.block_timestamp = 1_500 * 1_000_000;
+        testing_env!(context);
+        contract.check_in(pass.clone());
+
+        assert!(contract.is_checked_in(pass.clone()));
+        assert_eq!(contract.nft_token(pass).unwrap().checked_in_at, Some(1_500));
+        assert_eq!(
+            contract
+                .events_by_id
+                .get(&EVENT.to_string())
+                .unwrap()
+                .attendance,
+            1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or the event organiser can check in passes")]
+    fn check_in_of_other_organisers_event_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        // bob organises his own event, which gives him no say over the event of carol
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.create_event("othercon".to_string(), event_metadata(), None);
+
+        let mut context = get_context(bob().to_string(), 0);
+        context.block_timestamp = 1_500 * 1_000_000;
+        testing_env!(context);
+        contract.check_in(format!("{}.pass.{}", EVENT, player()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or the event organiser can issue passes")]
+    fn pass_of_other_organisers_event_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.create_event("othercon".to_string(), event_metadata(), None);
+        contract.issue_event_pass(
+            EVENT.to_string(),
+            player(),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn organiser_can_not_change_contract_settings() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.pause(vec![PausableFeature::Events]);
+    }
+
+    #[test]
+    fn transferred_event_is_managed_by_the_new_organiser() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.transfer_event_organizer(EVENT.to_string(), bob());
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"event_organiser_transferred","data":{"event_id":"catchcon","old_organiser":"carol.near","new_organiser":"bob.near"}}"#
+        );
+        assert_eq!(
+            contract
+                .get_event_by_id(EVENT.to_string())
+                .unwrap()
+                .organiser,
+            bob().to_string()
+        );
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.set_dependency_requires_check_in(EVENT.to_string(), true);
+        assert!(
+            contract
+                .events_by_id
+                .get(&EVENT.to_string())
+                .unwrap()
+                .dependency_requires_check_in
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or the event organiser can change the event")]
+    fn previous_organiser_can_not_change_the_event() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.transfer_event_organizer(EVENT.to_string(), bob());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_dependency_requires_check_in(EVENT.to_string(), true);
+    }
+
+    #[test]
+    fn cancelled_event_freezes_its_passes() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event(EVENT.to_string(), "Venue unavailable".to_string());
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"event_cancelled","data":{"event_id":"catchcon","reason":"Venue unavailable"}}"#
+        );
+
+        let pass = format!("{}.pass.{}", EVENT, player());
+        assert_eq!(
+            contract.internal_check_transfer(
+                &player().to_string(),
+                &bob().to_string(),
+                &pass,
+                None
+            ),
+            Err("Event is cancelled".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is cancelled")]
+    fn check_in_of_cancelled_event_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        let mut context = get_context(carol().to_string(), 0);
+        context.block_timestamp = 1_500 * 1_000_000;
+        testing_env!(context);
+        contract.cancel_event(EVENT.to_string(), "Venue unavailable".to_string());
+        contract.check_in(format!("{}.pass.{}", EVENT, player()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Event has already ended")]
+    fn cancel_of_ended_event_fails() {
+        let mut contract = setup();
+
+        let mut context = get_context(carol().to_string(), 0);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context);
+        contract.cancel_event(EVENT.to_string(), "Venue unavailable".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or the event organiser can cancel the event")]
+    fn cancel_by_other_organiser_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.cancel_event(EVENT.to_string(), "Venue unavailable".to_string());
+    }
+
+    #[test]
+    fn delete_event_removes_it_and_refunds_the_storage() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        let mut context = get_context(player().to_string(), 1);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context.clone());
+        contract.nft_burn(format!("{}.pass.{}", EVENT, player()));
+
+        context.predecessor_account_id = nft().to_string();
+        testing_env!(context);
+        contract.delete_event(EVENT.to_string());
+
+        assert!(contract.get_event_by_id(EVENT.to_string()).is_none());
+        assert!(contract
+            .nft_token_by_id(format!("{}.pass", EVENT))
+            .is_none());
+        assert!(contract
+            .events_for_organizer(carol().into(), None, None)
+            .is_empty());
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"event_deleted","data":{"event_id":"catchcon"}}"#
+        );
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains(r#""receiver_id":"carol.near""#));
+    }
+
+    #[test]
+    #[should_panic(expected = "1 passes of the event remain")]
+    fn delete_event_with_passes_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        let mut context = get_context(nft().to_string(), 1);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context);
+        contract.delete_event(EVENT.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event hasn't ended or been cancelled")]
+    fn delete_of_upcoming_event_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.delete_event(EVENT.to_string());
+    }
+
+    #[test]
+    fn update_event_changes_the_given_fields() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.update_event(
+            EVENT.to_string(),
+            EventMetadataUpdate {
+                title: Some("Catch Con 2".to_string()),
+                venue: Some("Lisbon".to_string()),
+                ends_at: Some(3_000),
+                max_capacity: Some(200),
+                ..Default::default()
+            },
+        );
+
+        // the venue didn't change
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"event_updated","data":{"event_id":"catchcon","fields":["title","ends_at","max_capacity"]}}"#
+        );
+
+        let metadata = contract
+            .get_event_by_id(EVENT.to_string())
+            .unwrap()
+            .metadata
+            .unwrap();
+        assert_eq!(metadata.title, "Catch Con 2");
+        assert_eq!(metadata.ends_at, 3_000);
+        assert_eq!(metadata.max_capacity, 200);
+
+        let pass = contract
+            .nft_token(format!("{}.pass.{}", EVENT, player()))
+            .unwrap();
+        assert_eq!(pass.metadata.title, "Catch Con 2");
+        assert_eq!(pass.expires_at, Some(3_000));
+        assert_eq!(pass.max_copies, 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Capacity can only be increased")]
+    fn update_event_lowering_capacity_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.update_event(
+            EVENT.to_string(),
+            EventMetadataUpdate {
+                max_capacity: Some(50),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Event has already started")]
+    fn update_of_started_event_fails() {
+        let mut contract = setup();
+
+        let mut context = get_context(nft().to_string(), 1);
+        context.block_timestamp = 1_000 * 1_000_000;
+        testing_env!(context);
+        contract.update_event(
+            EVENT.to_string(),
+            EventMetadataUpdate {
+                venue: Some("Porto".to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn setup_bound_pass() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.create_event(EVENT.to_string(), event_metadata(), Some(false));
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        contract
+    }
+
+    #[test]
+    fn bound_pass_stays_with_its_holder() {
+        let mut contract = setup_bound_pass();
+
+        let pass = format!("{}.pass.{}", EVENT, player());
+        assert!(!contract.nft_token(pass.clone()).unwrap().transferable);
+        assert_eq!(
+            contract.internal_check_transfer(
+                &player().to_string(),
+                &bob().to_string(),
+                &pass,
+                None
+            ),
+            Err("Passes of the event aren't transferable".to_string())
+        );
+
+        // the pass can still be used at the door
+        let mut context = get_context(carol().to_string(), 0);
+        context.block_timestamp = 1_500 * 1_000_000;
+        testing_env!(context);
+        contract.check_in(pass.clone());
+
+        // and is released once identity checks are done
+        contract.set_event_transferable(EVENT.to_string(), true);
+        assert!(contract.nft_token(pass.clone()).unwrap().transferable);
+        assert!(contract
+            .internal_check_transfer(&player().to_string(), &bob().to_string(), &pass, None)
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Passes of the event aren't transferable")]
+    fn approve_of_bound_pass_fails() {
+        let mut contract = setup_bound_pass();
+
+        testing_env!(get_context(player().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(
+            format!("{}.pass.{}", EVENT, player()),
+            marketplace().into(),
+            None,
+        );
+    }
+
+    #[test]
+    fn bound_pass_can_be_burnt() {
+        let mut contract = setup_bound_pass();
+
+        testing_env!(get_context(player().to_string(), 1));
+        contract.nft_burn(format!("{}.pass.{}", EVENT, player()));
+
+        assert_eq!(contract.nft_supply_for_event(EVENT.to_string()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Passes of the event were issued, it can't be made non-transferable")]
+    fn binding_issued_passes_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_event_transferable(EVENT.to_string(), false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event hasn't started")]
+    fn check_in_before_the_event_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in(format!("{}.pass.{}", EVENT, player()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Event has ended")]
+    fn check_in_after_the_event_fails() {
+        let mut contract = setup();
+
+        issue_pass(&mut contract, player());
+        resolve_pass(&mut contract, player(), PromiseResult::Successful(vec![]));
+
+        let mut context = get_context(carol().to_string(), 0);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context);
+        contract.check_in(format!("{}.pass.{}", EVENT, player()));
+    }
 }
@@ -0,0 +1,153 @@
+use crate::*;
+
+/// Why and since when a copy is frozen
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FreezeInfo {
+    pub reason: String,
+    //Unix epoch in milliseconds
+    pub frozen_at: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, the copy of form event_id.token_id.owner_id can't be transferred, approved or
+    /// burnt until it is unfrozen. Freezing a frozen copy replaces the reason
+    #[payable]
+    pub fn freeze_token(&mut self, token_id: TokenId, reason: String) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+        require!(
+            self.internal_owns_token(&owner_id, &class_token_id),
+            "No token"
+        );
+
+        let frozen_at = self
+            .frozen_tokens
+            .get(&token_id)
+            .map_or_else(now_ms, |freeze_info| freeze_info.frozen_at);
+        self.frozen_tokens.insert(
+            &token_id,
+            &FreezeInfo {
+                reason: reason.clone(),
+                frozen_at,
+            },
+        );
+
+        CatchEventLogVariant::NftFrozen(FreezeLog {
+            token_id,
+            reason: Some(reason),
+        })
+        .emit();
+    }
+
+    /// Owner only
+    #[payable]
+    pub fn unfreeze_token(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            self.frozen_tokens.remove(&token_id).is_some(),
+            "Token is not frozen"
+        );
+
+        CatchEventLogVariant::NftUnfrozen(FreezeLog {
+            token_id,
+            reason: None,
+        })
+        .emit();
+    }
+
+    pub fn get_freeze_info(&self, token_id: TokenId) -> Option<FreezeInfo> {
+        self.frozen_tokens.get(&token_id)
+    }
+}
+
+#[cfg(test)]
+mod freeze_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    /// alice's gold pass is frozen
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.freeze_token(PASS.to_string(), "Reported stolen".to_string());
+
+        contract
+    }
+
+    #[test]
+    fn refreeze_updates_the_reason() {
+        let mut contract = setup();
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_frozen","data":{"token_id":"catchcon.gold.alice.near","reason":"Reported stolen"}}"#
+            ]
+        );
+
+        contract.freeze_token(PASS.to_string(), "Under investigation".to_string());
+        assert_eq!(
+            contract.get_freeze_info(PASS.to_string()).unwrap().reason,
+            "Under investigation"
+        );
+
+        contract.unfreeze_token(PASS.to_string());
+        assert!(contract.get_freeze_info(PASS.to_string()).is_none());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is frozen")]
+    fn transfer_of_frozen_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is frozen")]
+    fn approve_of_frozen_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is frozen")]
+    fn burn_of_frozen_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn freeze_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.freeze_token(PASS.to_string(), "Mine".to_string());
+    }
+}
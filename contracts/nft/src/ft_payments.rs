@@ -0,0 +1,1159 @@
+use crate::*;
+
+const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_FT_TRANSFER: Gas = 10_000_000_000_000;
+
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[ext_contract(ext_ft_payments_self)]
+pub trait FtPaymentsResolver {
+    fn resolve_ft_withdraw(&mut self, event_id: EventId, amount: U128);
+    fn resolve_ft_refund(&mut self, event_id: EventId, account_id: AccountId, amount: U128);
+}
+
+/// msg passed by the payer in ft_transfer_call on the payment token
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum FtTransferMsg {
+    /// Mints a copy of the token to receiver_id (defaults to the payer)
+    Mint {
+        token_id: TokenId,
+        receiver_id: Option<ValidAccountId>,
+    },
+    /// Buys a pass of the tier (event_id.tier) for receiver_id (defaults to the payer),
+    /// everything is returned if the tier is sold out
+    BuyPass {
+        event_id: EventId,
+        tier: String,
+        receiver_id: Option<ValidAccountId>,
+    },
+    /// Event organiser only, adds the amount to the check-in reward pool of the event
+    EventFunding { event_id: EventId },
+    /// Owner only, adds the amount to the affiliate pool
+    AffiliateFunding,
+    /// Pays one period of the plan, the season pass is minted to the payer on the first payment
+    Subscribe { plan_id: PlanId },
+    /// Levels up the token (event_id.token_id.owner_id) of the payer, everything is returned
+    /// if the payer doesn't own it
+    Upgrade { token_id: TokenId },
+    /// Extends the expiry of the token (event_id.token_id.owner_id) of the payer by extend_sec
+    Renew { token_id: TokenId, extend_sec: u64 },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtPricedToken {
+    pub token_id: TokenId,
+    pub price: U128,
+}
+
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    /// Called by the payment token on ft_transfer_call, returns the unused amount which is refunded by the FT contract.
+    /// Panics if the mint fails, so the FT contract refunds everything
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        require!(
+            Some(env::predecessor_account_id()) == self.payment_token_id,
+            "Only the payment token is accepted"
+        );
+
+        let msg: FtTransferMsg =
+            near_sdk::serde_json::from_str(&msg).unwrap_or_else(|_| env::panic(b"Invalid msg"));
+
+        match msg {
+            FtTransferMsg::Mint {
+                token_id,
+                receiver_id,
+            } => {
+                let receiver_id: AccountId =
+                    receiver_id.map_or(sender_id.clone().into(), |id| id.into());
+
+                self.internal_buy_with_ft(sender_id.into(), receiver_id, token_id, amount.into())
+            }
+            FtTransferMsg::BuyPass {
+                event_id,
+                tier,
+                receiver_id,
+            } => {
+                let token_id: TokenId = format!("{}.{}", event_id, tier);
+                let receiver_id: AccountId =
+                    receiver_id.map_or(sender_id.clone().into(), |id| id.into());
+
+                let token = self
+                    .tokens_by_id
+                    .get(&token_id)
+                    .unwrap_or_else(|| env::panic(b"Token does not exist"));
+
+                if token.copies_minted >= token.max_copies {
+                    env::log(
+                        format!("{} is sold out, refunding {}", &token_id, amount.0).as_bytes(),
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+
+                self.internal_buy_with_ft(sender_id.into(), receiver_id, token_id, amount.into())
+            }
+            FtTransferMsg::EventFunding { event_id } => {
+                self.internal_fund_reward_pool(sender_id.into(), event_id, amount.into());
+
+                PromiseOrValue::Value(U128(0))
+            }
+            FtTransferMsg::AffiliateFunding => {
+                self.internal_fund_affiliate_pool(sender_id.into(), amount.into());
+
+                PromiseOrValue::Value(U128(0))
+            }
+            FtTransferMsg::Subscribe { plan_id } => {
+                self.internal_subscribe(sender_id.into(), plan_id, amount.into())
+            }
+            FtTransferMsg::Upgrade { token_id } => {
+                self.internal_upgrade(sender_id.into(), token_id, amount.into())
+            }
+            FtTransferMsg::Renew {
+                token_id,
+                extend_sec,
+            } => self.internal_renew_with_ft(sender_id.into(), token_id, extend_sec, amount.into()),
+        }
+    }
+}
+
+impl Contract {
+    /// Mints a copy of the token to receiver_id for its price, escrows the price as proceeds of the event
+    /// and records it as paid by the payer, returns the excess
+    fn internal_buy_with_ft(
+        &mut self,
+        payer_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        amount: Balance,
+    ) -> PromiseOrValue<U128> {
+        // panicking returns the FT to the payer
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let price = self
+            .ft_price_by_token_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token can't be bought with the payment token"));
+
+        require!(
+            amount >= price,
+            format!("Attached amount is less than the price {}", price)
+        );
+
+        // the copy is reserved now and given once the registry confirms the receiver, the storage
+        // of the reservation and of a new owner is paid upfront like in nft_event_register
+        if self.internal_is_async_validation() {
+            let initial_storage = env::storage_usage();
+            self.internal_reserve_copy(&receiver_id, &token_id);
+
+            let mut storage_used = env::storage_usage() - initial_storage;
+            if self.tokens_per_owner.get(&receiver_id).is_none() {
+                storage_used += bytes_for_token_or_event_or_account_id(&receiver_id);
+            }
+            let storage_cost = self.internal_spend_storage_balance(&payer_id, storage_used);
+
+            return PromiseOrValue::Promise(self.internal_check_player(&receiver_id).then(
+                ext_registry_self::resolve_player_ft_mint(
+                    PendingFtMint {
+                        payer_id,
+                        receiver_id,
+                        token_id,
+                        price: price.into(),
+                        amount: amount.into(),
+                        storage_cost: storage_cost.into(),
+                    },
+                    &env::current_account_id(),
+                    0,
+                    GAS_FOR_RESOLVE_PLAYER,
+                ),
+            ));
+        }
+
+        self.internal_assert_catch_player(&receiver_id);
+
+        // no NEAR comes with the FT, the storage is paid from the storage balance of the payer
+        let initial_storage = env::storage_usage();
+        self.internal_mint_copy(
+            &receiver_id,
+            &token_id,
+            format!("{} bought with the payment token", &token_id),
+        );
+        self.internal_record_ft_payment(&token_id, payer_id.clone(), price);
+        self.internal_spend_storage_balance(&payer_id, env::storage_usage() - initial_storage);
+
+        PromiseOrValue::Value(U128(amount - price))
+    }
+
+    //escrows the price as proceeds of the event of the token and records it as paid by the payer
+    pub(crate) fn internal_record_ft_payment(
+        &mut self,
+        token_id: &TokenId,
+        payer_id: AccountId,
+        price: Balance,
+    ) {
+        let event_id: EventId = token_id.split_once(".").unwrap().0.to_string();
+
+        let proceeds = self.ft_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        self.ft_proceeds_by_event_id
+            .insert(&event_id, &(proceeds + price));
+
+        let key = (event_id, payer_id);
+        let paid = self.ft_paid_by_event_account.get(&key).unwrap_or(0);
+        self.ft_paid_by_event_account.insert(&key, &(paid + price));
+
+        self.ft_liabilities += price;
+    }
+
+    //the FT owed left the contract, FT owed before the total was kept may be missing from it
+    pub(crate) fn internal_settle_ft_liability(&mut self, amount: Balance) {
+        self.ft_liabilities = self.ft_liabilities.saturating_sub(amount);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the FT contract accepted as payment for mints, the FT owed in the current payment
+    /// token has to be paid out first as the balances owed don't record their token
+    #[payable]
+    pub fn set_payment_token(&mut self, payment_token_id: ValidAccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            self.ft_liabilities == 0,
+            format!("{} of the payment token is still owed", self.ft_liabilities)
+        );
+
+        let payment_token_id: AccountId = payment_token_id.into();
+        self.payment_token_id = Some(payment_token_id.clone());
+
+        CatchEventLogVariant::PaymentTokenUpdated(PaymentTokenLog { payment_token_id }).emit();
+    }
+
+    pub fn get_payment_token(&self) -> Option<AccountId> {
+        self.payment_token_id.clone()
+    }
+
+    //FT of the payment token owed as proceeds, reward pools, rewards and upgrade payments
+    pub fn get_ft_liabilities(&self) -> U128 {
+        self.ft_liabilities.into()
+    }
+
+    /// Owner only, totals the FT owed before the total was kept from the proceeds and reward pools
+    /// of the listed events and the pending rewards of the listed accounts, which are listed from
+    /// the indexer. The total is recomputed from the listed entries on every call
+    pub fn migrate_ft_liabilities(
+        &mut self,
+        event_ids: Vec<EventId>,
+        pending_rewards: Vec<(EventId, AccountId)>,
+        affiliate_ids: Vec<AccountId>,
+    ) -> U128 {
+        self.assert_owner();
+
+        let events: Balance = event_ids
+            .iter()
+            .map(|event_id| {
+                self.ft_proceeds_by_event_id.get(event_id).unwrap_or(0)
+                    + self.reward_pool_by_event_id.get(event_id).unwrap_or(0)
+            })
+            .sum();
+        let rewards: Balance = pending_rewards
+            .iter()
+            .map(|key| self.pending_rewards.get(key).unwrap_or(0))
+            .sum();
+        let affiliates: Balance = affiliate_ids
+            .iter()
+            .map(|affiliate_id| self.affiliate_rewards.get(affiliate_id).unwrap_or(0))
+            .sum();
+
+        self.ft_liabilities =
+            events + rewards + affiliates + self.affiliate_pool + self.unrouted_upgrade_payments;
+
+        self.ft_liabilities.into()
+    }
+
+    /// Event organiser only, sets the price of the token in the payment token, None removes it
+    pub fn set_token_ft_price(&mut self, token_id: TokenId, price: Option<U128>) {
+        require!(
+            self.tokens_by_id.get(&token_id).is_some(),
+            "Token does not exist"
+        );
+
+        let event_id: EventId = token_id.split_once(".").unwrap().0.to_string();
+        let event = self.events_by_id.get(&event_id).unwrap();
+
+        require!(
+            env::predecessor_account_id() == event.organiser,
+            "Only the event organiser can set the price"
+        );
+
+        match price {
+            Some(price) => self.ft_price_by_token_id.insert(&token_id, &price.0),
+            None => self.ft_price_by_token_id.remove(&token_id),
+        };
+    }
+
+    //Returns paginated view of tokens which can be bought with the payment token and their prices
+    pub fn get_ft_priced_tokens(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<FtPricedToken> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.ft_price_by_token_id
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(token_id, price)| FtPricedToken {
+                token_id,
+                price: price.into(),
+            })
+            .collect()
+    }
+
+    //FT collected for an event which is owed to the organiser
+    pub fn get_ft_proceeds(&self, event_id: EventId) -> U128 {
+        self.ft_proceeds_by_event_id
+            .get(&event_id)
+            .unwrap_or(0)
+            .into()
+    }
+
+    //FT paid by the account for passes of the event
+    pub fn get_ft_paid(&self, event_id: EventId, account_id: AccountId) -> U128 {
+        self.ft_paid_by_event_account
+            .get(&(event_id, account_id))
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Event organiser only, transfers the FT proceeds of the event to the organiser,
+    /// the organiser has to be registered on the payment token
+    pub fn withdraw_ft_proceeds(&mut self, event_id: EventId) -> Promise {
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(
+            env::predecessor_account_id() == event.organiser,
+            "Only the event organiser can withdraw the proceeds"
+        );
+        require!(
+            !event.cancelled,
+            "Proceeds of a cancelled event are refunded to the buyers"
+        );
+
+        let amount = self.ft_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        require!(amount > 0, "No proceeds to withdraw");
+
+        self.ft_proceeds_by_event_id.insert(&event_id, &0);
+
+        ext_ft::ft_transfer(
+            event.organiser,
+            amount.into(),
+            Some(format!("Proceeds of the event {}", &event_id)),
+            self.payment_token_id.as_ref().unwrap(),
+            1,
+            GAS_FOR_FT_TRANSFER,
+        )
+        .then(ext_ft_payments_self::resolve_ft_withdraw(
+            event_id,
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_FT_TRANSFER,
+        ))
+    }
+
+    /// Transfers back the NEAR and the FT the caller paid for passes of a cancelled event, limited
+    /// to the proceeds still held for the event
+    pub fn claim_event_refund(&mut self, event_id: EventId) -> Promise {
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(event.cancelled, "Event is not cancelled");
+
+        let account_id = env::predecessor_account_id();
+        let key = (event_id.clone(), account_id.clone());
+
+        let near_paid = self.near_paid_by_event_account.get(&key).unwrap_or(0);
+        let near_proceeds = self.near_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        let near_amount = std::cmp::min(near_paid, near_proceeds);
+
+        let paid = self.ft_paid_by_event_account.get(&key).unwrap_or(0);
+        let proceeds = self.ft_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        let amount = std::cmp::min(paid, proceeds);
+        require!(amount > 0 || near_amount > 0, "Nothing to refund");
+
+        if near_amount > 0 {
+            self.near_paid_by_event_account
+                .insert(&key, &(near_paid - near_amount));
+            self.near_proceeds_by_event_id
+                .insert(&event_id, &(near_proceeds - near_amount));
+
+            let refund = Promise::new(account_id.clone()).transfer(near_amount);
+            if amount == 0 {
+                return refund;
+            }
+        }
+
+        self.ft_paid_by_event_account.insert(&key, &(paid - amount));
+        self.ft_proceeds_by_event_id
+            .insert(&event_id, &(proceeds - amount));
+
+        ext_ft::ft_transfer(
+            account_id.clone(),
+            amount.into(),
+            Some(format!("Refund for the cancelled event {}", &event_id)),
+            self.payment_token_id.as_ref().unwrap(),
+            1,
+            GAS_FOR_FT_TRANSFER,
+        )
+        .then(ext_ft_payments_self::resolve_ft_refund(
+            event_id,
+            account_id,
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_FT_TRANSFER,
+        ))
+    }
+
+    //restores the proceeds if the transfer to the organiser failed
+    #[private]
+    pub fn resolve_ft_withdraw(&mut self, event_id: EventId, amount: U128) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.internal_settle_ft_liability(amount.0);
+            return;
+        }
+
+        env::log(format!("Withdrawing {} failed, restoring the proceeds", amount.0).as_bytes());
+
+        let proceeds = self.ft_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        self.ft_proceeds_by_event_id
+            .insert(&event_id, &(proceeds + amount.0));
+    }
+
+    //restores the refund if the transfer to the buyer failed
+    #[private]
+    pub fn resolve_ft_refund(&mut self, event_id: EventId, account_id: AccountId, amount: U128) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.internal_settle_ft_liability(amount.0);
+            return;
+        }
+
+        env::log(
+            format!(
+                "Refunding {} to {} failed, it can be claimed again",
+                amount.0, &account_id
+            )
+            .as_bytes(),
+        );
+
+        let proceeds = self.ft_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        self.ft_proceeds_by_event_id
+            .insert(&event_id, &(proceeds + amount.0));
+
+        let key = (event_id, account_id);
+        let paid = self.ft_paid_by_event_account.get(&key).unwrap_or(0);
+        self.ft_paid_by_event_account
+            .insert(&key, &(paid + amount.0));
+    }
+}
+
+#[cfg(test)]
+mod ft_payments_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_token_ft_price("catchcon.gold".to_string(), Some(U128(100)));
+
+        deposit_storage(&mut contract);
+
+        contract
+    }
+
+    //the payers pay the storage of their mints from their storage balance
+    fn deposit_storage(contract: &mut Contract) {
+        for account_id in [alice(), bob()] {
+            testing_env!(get_context(account_id.to_string(), STORAGE_DEPOSIT));
+            contract.storage_deposit(None);
+        }
+    }
+
+    fn storage_total(contract: &Contract, account_id: ValidAccountId) -> Balance {
+        contract
+            .storage_deposits
+            .get(account_id.as_ref())
+            .unwrap()
+            .total
+    }
+
+    fn mint_msg(token_id: &str) -> String {
+        format!(r#"{{"action":"mint","token_id":"{}"}}"#, token_id)
+    }
+
+    fn unused(result: PromiseOrValue<U128>) -> u128 {
+        match result {
+            PromiseOrValue::Value(unused) => unused.0,
+            PromiseOrValue::Promise(_) => panic!("expected a value"),
+        }
+    }
+
+    #[test]
+    fn mint_with_ft_refunds_excess() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let result = contract.ft_on_transfer(alice(), U128(150), mint_msg("catchcon.gold"));
+
+        assert_eq!(unused(result), 50);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+        assert!(test_utils::get_logs()[0].contains(r#""event":"nft_mint""#));
+        assert_eq!(
+            contract.get_ft_priced_tokens(None, None)[0].token_id,
+            "catchcon.gold"
+        );
+    }
+
+    #[test]
+    fn mint_with_ft_spends_the_storage_balance_of_the_payer() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let initial_storage = env::storage_usage();
+        let msg = r#"{"action":"mint","token_id":"catchcon.gold","receiver_id":"bob.near"}"#;
+        contract.ft_on_transfer(alice(), U128(100), msg.to_string());
+
+        let storage_cost =
+            Balance::from(env::storage_usage() - initial_storage) * env::storage_byte_cost();
+        assert_eq!(
+            storage_total(&contract, alice()),
+            STORAGE_DEPOSIT - storage_cost
+        );
+        assert_eq!(storage_total(&contract, bob()), STORAGE_DEPOSIT);
+    }
+
+    #[test]
+    #[should_panic(expected = "carol.near must cover")]
+    fn mint_with_ft_without_storage_balance_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(carol(), U128(100), mint_msg("catchcon.gold"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn set_payment_token_without_yocto_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.set_payment_token(ft());
+    }
+
+    #[test]
+    fn set_payment_token_emits_event() {
+        testing_env!(get_context(nft().to_string(), 1));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"payment_token_updated","data":{"payment_token_id":"ft.catchlabs.near"}}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn mint_with_ft_for_another_receiver() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = r#"{"action":"mint","token_id":"catchcon.gold","receiver_id":"bob.near"}"#;
+        assert_eq!(
+            unused(contract.ft_on_transfer(alice(), U128(100), msg.to_string())),
+            0
+        );
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached amount is less than the price 100")]
+    fn mint_with_insufficient_ft_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(99), mint_msg("catchcon.gold"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the payment token is accepted")]
+    fn mint_from_other_ft_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), mint_msg("catchcon.gold"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token can't be bought with the payment token")]
+    fn mint_unpriced_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_token_ft_price("catchcon.gold".to_string(), None);
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), mint_msg("catchcon.gold"));
+    }
+
+    fn buy_pass_msg(tier: &str) -> String {
+        format!(
+            r#"{{"action":"buy_pass","event_id":"catchcon","tier":"{}"}}"#,
+            tier
+        )
+    }
+
+    fn resolve_context(result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn buy_pass_escrows_price_and_refunds_excess() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let result = contract.ft_on_transfer(alice(), U128(120), buy_pass_msg("gold"));
+
+        assert_eq!(unused(result), 20);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+        assert_eq!(
+            contract
+                .get_ft_paid("catchcon".to_string(), alice().into())
+                .0,
+            100
+        );
+    }
+
+    #[test]
+    fn buy_pass_sold_out_refunds_everything() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 1);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_token_ft_price("catchcon.gold".to_string(), Some(U128(100)));
+
+        deposit_storage(&mut contract);
+
+        testing_env!(get_context(ft().to_string(), 0));
+        assert_eq!(
+            unused(contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"))),
+            0
+        );
+        assert_eq!(
+            unused(contract.ft_on_transfer(bob(), U128(100), buy_pass_msg("gold"))),
+            100
+        );
+
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 0);
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+        assert_eq!(
+            contract.get_ft_paid("catchcon".to_string(), bob().into()).0,
+            0
+        );
+    }
+
+    #[test]
+    fn organiser_withdraws_proceeds() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.withdraw_ft_proceeds("catchcon".to_string());
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 0);
+
+        // ft_transfer to the organiser failed
+        resolve_context(PromiseResult::Failed);
+        contract.resolve_ft_withdraw("catchcon".to_string(), U128(100));
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+        assert_eq!(contract.get_ft_liabilities().0, 100);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.withdraw_ft_proceeds("catchcon".to_string());
+
+        resolve_context(PromiseResult::Successful(vec![]));
+        contract.resolve_ft_withdraw("catchcon".to_string(), U128(100));
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 0);
+        assert_eq!(contract.get_ft_liabilities().0, 0);
+    }
+
+    fn usdc() -> ValidAccountId {
+        ValidAccountId::try_from("usdc.near").unwrap()
+    }
+
+    #[test]
+    #[should_panic(expected = "100 of the payment token is still owed")]
+    fn set_payment_token_with_ft_owed_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(usdc());
+    }
+
+    #[test]
+    #[should_panic(expected = "100 of the payment token is still owed")]
+    fn set_payment_token_during_a_withdrawal_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+
+        // the proceeds are restored in the old token if the transfer fails
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.withdraw_ft_proceeds("catchcon".to_string());
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(usdc());
+    }
+
+    #[test]
+    fn set_payment_token_once_the_ft_owed_is_paid_out() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+        contract.ft_on_transfer(
+            carol(),
+            U128(50),
+            r#"{"action":"event_funding","event_id":"catchcon"}"#.to_string(),
+        );
+        assert_eq!(contract.get_ft_liabilities().0, 150);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.withdraw_ft_proceeds("catchcon".to_string());
+        contract.archive_event("catchcon".to_string());
+
+        resolve_context(PromiseResult::Successful(vec![]));
+        contract.resolve_ft_withdraw("catchcon".to_string(), U128(100));
+        contract.resolve_reward_pool_return("catchcon".to_string(), U128(50));
+        assert_eq!(contract.get_ft_liabilities().0, 0);
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(usdc());
+        assert_eq!(contract.get_payment_token(), Some(usdc().into()));
+    }
+
+    #[test]
+    fn migrate_ft_liabilities_totals_the_listed_entries() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+        contract.ft_on_transfer(
+            carol(),
+            U128(50),
+            r#"{"action":"event_funding","event_id":"catchcon"}"#.to_string(),
+        );
+        contract
+            .pending_rewards
+            .insert(&("catchcon".to_string(), bob().into()), &10);
+        contract.affiliate_rewards.insert(&bob().into(), &5);
+
+        // owed before the total was kept
+        contract.ft_liabilities = 0;
+
+        testing_env!(get_context(nft().to_string(), 0));
+        let total = contract.migrate_ft_liabilities(
+            vec!["catchcon".to_string()],
+            vec![("catchcon".to_string(), bob().into())],
+            vec![bob().into()],
+        );
+        assert_eq!(total.0, 165);
+        assert_eq!(contract.get_ft_liabilities().0, 165);
+    }
+
+    #[test]
+    fn cancellation_refunds_buyers() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+        contract.ft_on_transfer(bob(), U128(100), buy_pass_msg("gold"));
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event("catchcon".to_string(), "Venue unavailable".to_string());
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.claim_event_refund("catchcon".to_string());
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+
+        // alice isn't registered on the FT contract, the refund can be claimed again
+        resolve_context(PromiseResult::Failed);
+        contract.resolve_ft_refund("catchcon".to_string(), alice().into(), U128(100));
+        assert_eq!(
+            contract
+                .get_ft_paid("catchcon".to_string(), alice().into())
+                .0,
+            100
+        );
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 200);
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.claim_event_refund("catchcon".to_string());
+
+        resolve_context(PromiseResult::Successful(vec![]));
+        contract.resolve_ft_refund("catchcon".to_string(), bob().into(), U128(100));
+        assert_eq!(
+            contract.get_ft_paid("catchcon".to_string(), bob().into()).0,
+            0
+        );
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is cancelled")]
+    fn buy_pass_of_cancelled_event_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event("catchcon".to_string(), "Venue unavailable".to_string());
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Proceeds of a cancelled event are refunded to the buyers")]
+    fn withdraw_proceeds_of_cancelled_event_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(alice(), U128(100), buy_pass_msg("gold"));
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event("catchcon".to_string(), "Venue unavailable".to_string());
+        contract.withdraw_ft_proceeds("catchcon".to_string());
+    }
+}
+
+#[cfg(test)]
+mod ft_payments_simulation {
+    use super::*;
+    use crate::simulation::*;
+    use crate::utils::test_utils::*;
+    use ft::StorageManager as _;
+
+    fn treasury() -> ValidAccountId {
+        ValidAccountId::try_from("treasury.catchlabs.near").unwrap()
+    }
+
+    fn ft_metadata() -> ft::FungibleTokenMetadata {
+        ft::FungibleTokenMetadata {
+            spec: "ft-1.0.0".to_string(),
+            name: "Catch Token".to_string(),
+            symbol: "CATCH".to_string(),
+            icon: "C-A-T-C-H".to_string(),
+            reference: "https://catchlabs.io/ft".to_string(),
+            reference_hash: Base64VecU8::from([5_u8; 32].to_vec()),
+            decimals: 0,
+        }
+    }
+
+    /// alice holds 1000 CATCH and a storage balance on the NFT contract, catchcon.gold costs 100 CATCH
+    fn setup() -> Chain {
+        let mut chain = Chain::default();
+
+        chain.deploy(ft(), || {
+            ft::Contract::new(treasury(), U128(1_000_000), ft_metadata())
+        });
+        for account_id in [alice(), nft()] {
+            chain.call(
+                treasury(),
+                ft(),
+                STORAGE_DEPOSIT,
+                |ft: &mut ft::Contract| ft.storage_deposit(Some(account_id)),
+            );
+        }
+        chain.call(treasury(), ft(), 1, |ft: &mut ft::Contract| {
+            ft.ft_transfer(alice(), U128(1_000), None)
+        });
+
+        chain.deploy(nft(), create_contract);
+        chain.call(nft(), nft(), 1, |nft: &mut Contract| {
+            nft.set_payment_token(ft())
+        });
+        chain.call(carol(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            create_event(nft, "catchcon", &["gold"], 10)
+        });
+        chain.call(carol(), nft(), 0, |nft: &mut Contract| {
+            nft.set_token_ft_price("catchcon.gold".to_string(), Some(U128(100)))
+        });
+        chain.call(alice(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.storage_deposit(None)
+        });
+
+        chain
+    }
+
+    fn pay(chain: &mut Chain, amount: u128, msg: &str) {
+        chain.call(alice(), ft(), 1, |ft: &mut ft::Contract| {
+            ft.ft_transfer_call(nft(), U128(amount), None, msg.to_string());
+        });
+    }
+
+    fn ft_balance(chain: &mut Chain, account_id: ValidAccountId) -> u128 {
+        chain
+            .view(ft(), |ft: &ft::Contract| ft.ft_balance_of(account_id))
+            .0
+    }
+
+    fn supply_for_owner(chain: &mut Chain, account_id: ValidAccountId) -> u128 {
+        chain
+            .view(nft(), |nft: &Contract| {
+                nft.nft_supply_for_owner(account_id.into())
+            })
+            .0
+    }
+
+    fn proceeds(chain: &mut Chain) -> u128 {
+        chain
+            .view(nft(), |nft: &Contract| {
+                nft.get_ft_proceeds("catchcon".to_string())
+            })
+            .0
+    }
+
+    #[test]
+    fn ft_transfer_call_mints_the_token() {
+        let mut chain = setup();
+
+        pay(
+            &mut chain,
+            100,
+            r#"{"action":"mint","token_id":"catchcon.gold"}"#,
+        );
+
+        assert!(chain.receipt("ft_on_transfer").error.is_none());
+        assert!(chain.receipt("ft_resolve_transfer").error.is_none());
+        assert_eq!(ft_balance(&mut chain, alice()), 900);
+        assert_eq!(ft_balance(&mut chain, nft()), 100);
+        assert_eq!(supply_for_owner(&mut chain, alice()), 1);
+        assert_eq!(proceeds(&mut chain), 100);
+    }
+
+    #[test]
+    fn ft_transfer_call_refunds_the_excess() {
+        let mut chain = setup();
+
+        pay(
+            &mut chain,
+            150,
+            r#"{"action":"mint","token_id":"catchcon.gold","receiver_id":"bob.near"}"#,
+        );
+
+        assert_eq!(ft_balance(&mut chain, alice()), 900);
+        assert_eq!(ft_balance(&mut chain, nft()), 100);
+        assert_eq!(supply_for_owner(&mut chain, bob()), 1);
+        assert_eq!(supply_for_owner(&mut chain, alice()), 0);
+        assert!(chain
+            .logs
+            .iter()
+            .any(|log| log.contains(r#""amount":"50","memo":"refund""#)));
+    }
+
+    #[test]
+    fn failed_mint_refunds_everything() {
+        let mut chain = setup();
+
+        pay(
+            &mut chain,
+            99,
+            r#"{"action":"mint","token_id":"catchcon.gold"}"#,
+        );
+
+        assert!(chain
+            .receipt("ft_on_transfer")
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Attached amount is less than the price 100"));
+        assert_eq!(ft_balance(&mut chain, alice()), 1_000);
+        assert_eq!(ft_balance(&mut chain, nft()), 0);
+        assert_eq!(supply_for_owner(&mut chain, alice()), 0);
+        assert_eq!(proceeds(&mut chain), 0);
+    }
+
+    #[test]
+    fn mint_without_storage_balance_refunds_everything() {
+        let mut chain = setup();
+        chain.call(alice(), nft(), 1, |nft: &mut Contract| {
+            nft.storage_withdraw(None)
+        });
+
+        pay(
+            &mut chain,
+            100,
+            r#"{"action":"mint","token_id":"catchcon.gold"}"#,
+        );
+
+        assert!(chain
+            .receipt("ft_on_transfer")
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("alice.near must cover"));
+        assert_eq!(ft_balance(&mut chain, alice()), 1_000);
+        assert_eq!(supply_for_owner(&mut chain, alice()), 0);
+        // the copy reserved by the failed receipt isn't counted
+        let copies_minted = chain.view(nft(), |nft: &Contract| {
+            nft.tokens_by_id
+                .get(&"catchcon.gold".to_string())
+                .unwrap()
+                .copies_minted
+        });
+        assert_eq!(copies_minted, 0);
+    }
+
+    fn paid(chain: &mut Chain, account_id: ValidAccountId) -> u128 {
+        chain
+            .view(nft(), |nft: &Contract| {
+                nft.get_ft_paid("catchcon".to_string(), account_id.into())
+            })
+            .0
+    }
+
+    #[test]
+    fn buy_pass_escrows_the_price_and_refunds_the_excess() {
+        let mut chain = setup();
+
+        pay(
+            &mut chain,
+            120,
+            r#"{"action":"buy_pass","event_id":"catchcon","tier":"gold"}"#,
+        );
+
+        assert!(chain.receipt("ft_on_transfer").error.is_none());
+        assert_eq!(ft_balance(&mut chain, alice()), 900);
+        assert_eq!(ft_balance(&mut chain, nft()), 100);
+        assert_eq!(supply_for_owner(&mut chain, alice()), 1);
+        assert_eq!(proceeds(&mut chain), 100);
+        assert_eq!(paid(&mut chain, alice()), 100);
+    }
+
+    #[test]
+    fn sold_out_buy_pass_refunds_everything() {
+        let mut chain = setup();
+        chain.call(carol(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            create_event(nft, "finale", &["gold"], 1)
+        });
+        chain.call(carol(), nft(), 0, |nft: &mut Contract| {
+            nft.set_token_ft_price("finale.gold".to_string(), Some(U128(100)))
+        });
+
+        pay(
+            &mut chain,
+            100,
+            r#"{"action":"buy_pass","event_id":"finale","tier":"gold","receiver_id":"bob.near"}"#,
+        );
+        pay(
+            &mut chain,
+            100,
+            r#"{"action":"buy_pass","event_id":"finale","tier":"gold"}"#,
+        );
+
+        assert!(chain.receipt("ft_on_transfer").error.is_none());
+        assert!(chain
+            .logs
+            .iter()
+            .any(|log| log.contains("finale.gold is sold out, refunding 100")));
+        assert_eq!(ft_balance(&mut chain, alice()), 900);
+        assert_eq!(ft_balance(&mut chain, nft()), 100);
+        assert_eq!(supply_for_owner(&mut chain, bob()), 1);
+        assert_eq!(supply_for_owner(&mut chain, alice()), 0);
+    }
+
+    #[test]
+    fn cancellation_refunds_the_buyer() {
+        let mut chain = setup();
+        pay(
+            &mut chain,
+            100,
+            r#"{"action":"buy_pass","event_id":"catchcon","tier":"gold"}"#,
+        );
+
+        chain.call(carol(), nft(), 0, |nft: &mut Contract| {
+            nft.cancel_event("catchcon".to_string(), "Venue unavailable".to_string())
+        });
+        chain.call(alice(), nft(), 0, |nft: &mut Contract| {
+            nft.claim_event_refund("catchcon".to_string());
+        });
+
+        assert!(chain.receipt("ft_transfer").error.is_none());
+        assert!(chain.receipt("resolve_ft_refund").error.is_none());
+        assert_eq!(ft_balance(&mut chain, alice()), 1_000);
+        assert_eq!(ft_balance(&mut chain, nft()), 0);
+        assert_eq!(proceeds(&mut chain), 0);
+        assert_eq!(paid(&mut chain, alice()), 0);
+        assert_eq!(
+            chain
+                .view(nft(), |nft: &Contract| nft.get_ft_liabilities())
+                .0,
+            0
+        );
+    }
+}
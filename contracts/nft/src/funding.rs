@@ -0,0 +1,95 @@
+use crate::*;
+
+//bytes of an account record with one full access key, the least a new account takes
+const MIN_ACCOUNT_STORAGE: u64 = 182;
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, sets the balance the account created by a mint is funded with, it has to
+    /// cover at least the storage of the account and its key
+    #[payable]
+    pub fn set_account_funding_amount(&mut self, amount: U128) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let min_amount = env::storage_byte_cost() * Balance::from(MIN_ACCOUNT_STORAGE);
+        require!(
+            amount.0 >= min_amount,
+            format!("Funding amount can't be less than {} yoctoNEAR", min_amount)
+        );
+
+        let old_amount = std::mem::replace(&mut self.account_funding_amount, amount.0);
+
+        CatchEventLogVariant::AccountFundingUpdated(AccountFundingLog {
+            old_amount: old_amount.into(),
+            new_amount: amount,
+        })
+        .emit();
+    }
+
+    pub fn get_account_funding_amount(&self) -> U128 {
+        self.account_funding_amount.into()
+    }
+}
+
+#[cfg(test)]
+mod funding_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const AMOUNT: Balance = 2_000_000_000_000_000_000_000;
+
+    #[test]
+    fn mint_funds_account_with_the_set_amount() {
+        testing_env!(get_context(nft().to_string(), 1));
+        let mut contract = create_contract();
+        contract.set_account_funding_amount(U128(AMOUNT));
+
+        assert_eq!(contract.get_account_funding_amount().0, AMOUNT);
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"account_funding_updated","data":{"old_amount":"10000000000000000000000","new_amount":"2000000000000000000000"}}"#
+        );
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            "genesis.hero".to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+
+        let pending = contract
+            .pending_mints
+            .get(&"genesis.hero".to_string())
+            .unwrap();
+        assert!(pending.cost > AMOUNT && pending.cost < BASE_STORAGE_COST);
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains(&format!(r#""deposit":{}"#, AMOUNT)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Funding amount can't be less than 1820000000000000000000 yoctoNEAR")]
+    fn funding_below_account_storage_fails() {
+        testing_env!(get_context(nft().to_string(), 1));
+        let mut contract = create_contract();
+        contract.set_account_funding_amount(U128(1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn set_funding_by_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.set_account_funding_amount(U128(AMOUNT));
+    }
+}
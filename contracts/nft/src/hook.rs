@@ -0,0 +1,256 @@
+use crate::*;
+
+const GAS_FOR_HOOK: Gas = 5_000_000_000_000;
+const GAS_FOR_RESOLVE_HOOK: Gas = 3_000_000_000_000;
+// gas kept for the rest of the main operation when deciding if the hook can be called
+const GAS_RESERVED_FOR_OPERATION: Gas = 10_000_000_000_000;
+
+const DEFAULT_MAX_HOOK_FAILURES: u32 = 5;
+
+/// Same JSON as the NEP-171 event logged for the activity
+pub type ActivityPayload = EventLog;
+
+#[ext_contract(ext_activity_hook)]
+pub trait ActivityHook {
+    fn on_nft_activity(&mut self, activity: ActivityPayload);
+}
+
+#[ext_contract(ext_hook_self)]
+pub trait HookResolver {
+    fn resolve_hook(&mut self);
+}
+
+/// Circuit breaker of the transfer hook
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HookBreaker {
+    /// consecutive failures after which the hook is disabled
+    pub max_failures: u32,
+    pub consecutive_failures: u32,
+    /// set once max_failures is reached, only the owner can reset it
+    pub tripped: bool,
+}
+
+impl Default for HookBreaker {
+    fn default() -> Self {
+        Self {
+            max_failures: DEFAULT_MAX_HOOK_FAILURES,
+            consecutive_failures: 0,
+            tripped: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonTransferHook {
+    pub hook_id: Option<AccountId>,
+    pub breaker: HookBreaker,
+}
+
+impl Contract {
+    //best effort call of the hook, skipped if it is disabled or the operation can't spare the gas
+    pub(crate) fn internal_notify_hook(&self, event: EventLogVariant) {
+        let hook_id = match &self.transfer_hook {
+            Some(hook_id) if !self.hook_breaker.tripped => hook_id,
+            _ => return,
+        };
+
+        let remaining_gas = env::prepaid_gas().saturating_sub(env::used_gas());
+        if remaining_gas < GAS_FOR_HOOK + GAS_FOR_RESOLVE_HOOK + GAS_RESERVED_FOR_OPERATION {
+            env::log(b"Not enough gas to notify the transfer hook");
+            return;
+        }
+
+        let activity = ActivityPayload {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_STANDARD_VERSION.to_string(),
+            event,
+        };
+
+        ext_activity_hook::on_nft_activity(activity, hook_id, 0, GAS_FOR_HOOK).then(
+            ext_hook_self::resolve_hook(&env::current_account_id(), 0, GAS_FOR_RESOLVE_HOOK),
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, contract notified with on_nft_activity on every mint, transfer and burn,
+    /// None removes it. Setting it resets the circuit breaker
+    pub fn set_transfer_hook(
+        &mut self,
+        hook_id: Option<ValidAccountId>,
+        max_failures: Option<u32>,
+    ) {
+        self.assert_owner();
+
+        self.transfer_hook = hook_id.map(|id| id.into());
+        self.hook_breaker = HookBreaker {
+            max_failures: max_failures.unwrap_or(DEFAULT_MAX_HOOK_FAILURES),
+            ..Default::default()
+        };
+    }
+
+    /// Owner only, re-enables a hook disabled by the circuit breaker
+    pub fn reset_transfer_hook(&mut self) {
+        self.assert_owner();
+
+        self.hook_breaker.consecutive_failures = 0;
+        self.hook_breaker.tripped = false;
+    }
+
+    pub fn get_transfer_hook(&self) -> JsonTransferHook {
+        JsonTransferHook {
+            hook_id: self.transfer_hook.clone(),
+            breaker: self.hook_breaker.clone(),
+        }
+    }
+
+    //counts consecutive failures of the hook and disables it after max_failures
+    #[private]
+    pub fn resolve_hook(&mut self) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.hook_breaker.consecutive_failures = 0;
+            return;
+        }
+
+        self.hook_breaker.consecutive_failures += 1;
+
+        if !self.hook_breaker.tripped
+            && self.hook_breaker.consecutive_failures >= self.hook_breaker.max_failures
+        {
+            self.hook_breaker.tripped = true;
+            CatchEventLogVariant::TransferHookDisabled(HookDisabledLog {
+                hook_id: self.transfer_hook.clone(),
+                consecutive_failures: self.hook_breaker.consecutive_failures,
+            })
+            .emit();
+        }
+    }
+}
+
+#[cfg(test)]
+mod hook_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    fn hook() -> ValidAccountId {
+        ValidAccountId::try_from("indexer.catchlabs.near").unwrap()
+    }
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.set_transfer_hook(Some(hook()), Some(2));
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        contract
+    }
+
+    fn hook_calls() -> Vec<String> {
+        test_utils::get_created_receipts()
+            .iter()
+            .map(|receipt| near_sdk::serde_json::to_string(receipt).unwrap())
+            .filter(|receipt| receipt.contains("on_nft_activity"))
+            .collect()
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_hook();
+    }
+
+    #[test]
+    fn mint_and_transfer_notify_hook() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        let calls = hook_calls();
+        assert_eq!(calls.len(), 1);
+        assert!(calls[0].contains("indexer.catchlabs.near"));
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            bob().to_string(),
+            "catchcon.gold.alice.near".to_string(),
+            None,
+            None,
+        );
+        assert_eq!(hook_calls().len(), 1);
+    }
+
+    #[test]
+    fn breaker_disables_hook_after_consecutive_failures() {
+        let mut contract = setup();
+
+        resolve(&mut contract, PromiseResult::Failed);
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+        resolve(&mut contract, PromiseResult::Failed);
+        assert!(!contract.get_transfer_hook().breaker.tripped);
+
+        resolve(&mut contract, PromiseResult::Failed);
+        assert!(contract.get_transfer_hook().breaker.tripped);
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"transfer_hook_disabled","data":{"hook_id":"indexer.catchlabs.near","consecutive_failures":2}}"#
+            ]
+        );
+
+        // the mint goes through without calling the hook
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        assert!(hook_calls().is_empty());
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.reset_transfer_hook();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+        assert_eq!(hook_calls().len(), 1);
+    }
+
+    #[test]
+    fn hook_is_skipped_without_spare_gas() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        let mut context = get_context(alice().to_string(), 1);
+        context.prepaid_gas = GAS_FOR_HOOK + GAS_FOR_RESOLVE_HOOK;
+        testing_env!(context);
+        contract.nft_transfer(
+            bob().to_string(),
+            "catchcon.gold.alice.near".to_string(),
+            None,
+            None,
+        );
+
+        assert!(hook_calls().is_empty());
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn reset_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.reset_transfer_hook();
+    }
+}
@@ -4,10 +4,11 @@ use crate::*;
 
 pub const NFT_STANDARD_NAME: &str = "nep171";
 
-pub const NFT_METADATA_SPEC: &str = "1.0.0";
+//version of NEP-171 the nep171 events follow, their payloads are exactly the ones of the standard
+pub const NFT_STANDARD_VERSION: &str = "1.1.0";
 
 /// Enum that represents the data type of the EventLog.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(tag = "event", content = "data")]
 #[serde(rename_all = "snake_case")]
 #[serde(crate = "near_sdk::serde")]
@@ -15,9 +16,11 @@ pub const NFT_METADATA_SPEC: &str = "1.0.0";
 pub enum EventLogVariant {
     NftMint(Vec<NftMintLog>),
     NftTransfer(Vec<NftTransferLog>),
+    NftBurn(Vec<NftBurnLog>),
+    NftMetadataUpdate(Vec<NftMetadataUpdateLog>),
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub struct EventLog {
     pub standard: String,
@@ -37,7 +40,20 @@ impl fmt::Display for EventLog {
     }
 }
 
-#[derive(Serialize, Debug)]
+impl EventLogVariant {
+    /// Wraps the variant in the NEP-171 envelope and logs it
+    pub fn emit(self) {
+        let event_log = EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_STANDARD_VERSION.to_string(),
+            event: self,
+        };
+
+        env::log(event_log.to_string().as_bytes());
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub struct NftMintLog {
     pub owner_id: String,
@@ -47,19 +63,7 @@ pub struct NftMintLog {
     pub memo: Option<String>,
 }
 
-impl NftMintLog {
-    pub fn emit(mint_logs: Vec<NftMintLog>) {
-        let event_log = EventLog {
-            standard: NFT_STANDARD_NAME.to_string(),
-            version: NFT_METADATA_SPEC.to_string(),
-            event: EventLogVariant::NftMint(mint_logs),
-        };
-
-        env::log(event_log.to_string().as_bytes());
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub struct NftTransferLog {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -73,29 +77,478 @@ pub struct NftTransferLog {
     pub memo: Option<String>,
 }
 
-impl NftTransferLog {
-    pub fn emit(transfer_logs: Vec<NftTransferLog>) {
-        let event_log = EventLog {
-            standard: NFT_STANDARD_NAME.to_string(),
-            version: NFT_METADATA_SPEC.to_string(),
-            event: EventLogVariant::NftTransfer(transfer_logs),
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftBurnLog {
+    pub owner_id: String,
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorized_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftMetadataUpdateLog {
+    pub token_ids: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+/*********************************************/
+/*  CATCH SPECIFIC EVENTS (NON NEP-171)      */
+/*********************************************/
+
+pub const CATCH_STANDARD_NAME: &str = "catch";
+
+//bumped whenever the fields of a variant change or a variant is dropped, new variants don't
+//need a bump
+pub const CATCH_EVENT_SPEC: &str = "1.1.0";
+
+/// Enum that represents the Catch specific events emitted by this contract.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[serde(crate = "near_sdk::serde")]
+#[non_exhaustive]
+pub enum CatchEventLogVariant {
+    NftRoyaltyPayout(RoyaltyPayoutLog),
+    NftRoyaltyUpdated(RoyaltyConfigLog),
+    CatchAffiliate(AffiliateLog),
+    AffiliateProgramUpdated(AffiliateProgram),
+    AffiliatePoolFunded(AffiliatePoolLog),
+    SubscriptionPlanUpdated(SubscriptionPlanLog),
+    SubscriptionPaid(SubscriptionLog),
+    SubscriptionExpired(SubscriptionLog),
+    CatchUpgrade(UpgradeLog),
+    NftAttached(AttachLog),
+    NftDetached(AttachLog),
+    CatchRenewal(RenewalLog),
+    NftExpiryExtended(ExpiryExtendedLog),
+    NftRevoke(RevokeLog),
+    OwnershipProposed(OwnershipLog),
+    OwnershipAccepted(OwnershipLog),
+    MinterAdded(MinterLog),
+    MinterRemoved(MinterLog),
+    ContractMetadataUpdated(ContractMetadataLog),
+    SoulboundReleased(SoulboundLog),
+    NftFrozen(FreezeLog),
+    NftUnfrozen(FreezeLog),
+    AccountBlacklisted(BlacklistLog),
+    AccountUnblacklisted(BlacklistLog),
+    NftRecoveryTransfer(RecoveryTransferLog),
+    AccountKeyRotated(KeyRotationLog),
+    AccountKeyRotationFailed(KeyRotationLog),
+    AccountFundingUpdated(AccountFundingLog),
+    EventCreated(EventCreatedLog),
+    EventOrganiserTransferred(EventOrganiserLog),
+    EventCancelled(EventCancelledLog),
+    EventUpdated(EventUpdatedLog),
+    EventDeleted(EventDeletedLog),
+    ContractUpgraded(ContractUpgradedLog),
+    NftLeaseStarted(LeaseLog),
+    NftLeaseEnded(LeaseLog),
+    PaymentTokenUpdated(PaymentTokenLog),
+    TransferHookDisabled(HookDisabledLog),
+    MarketplaceSyncFailed(MarketplaceSyncLog),
+    CheckinReward(CheckinRewardLog),
+    NftLocked(LockLog),
+    NftUnlocked(LockLog),
+    NftApprove(NftApproveLog),
+    MarketplaceChange(MarketplaceChangeLog),
+    EventPassIssued(EventPassIssuedLog),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CatchEventLog {
+    pub standard: String,
+    pub version: String,
+
+    #[serde(flatten)]
+    pub event: CatchEventLogVariant,
+}
+
+impl fmt::Display for CatchEventLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "EVENT_JSON:{}",
+            &serde_json::to_string(self).map_err(|_| fmt::Error)?
+        ))
+    }
+}
+
+impl CatchEventLogVariant {
+    /// Wraps the variant in the Catch envelope and logs it
+    pub fn emit(self) {
+        let event_log = CatchEventLog {
+            standard: CATCH_STANDARD_NAME.to_string(),
+            version: CATCH_EVENT_SPEC.to_string(),
+            event: self,
         };
 
         env::log(event_log.to_string().as_bytes());
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltyPayoutLog {
+    pub token_id: TokenId,
+    pub marketplace_id: AccountId,
+    pub balance: U128,
+    // sorted for deterministic logs
+    pub payout: std::collections::BTreeMap<AccountId, U128>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoyaltyConfigLog {
+    pub token_id: TokenId,
+    pub royalty: std::collections::BTreeMap<AccountId, u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum AffiliateRewardStatus {
+    Paid,
+    //the transfer failed or the sale couldn't spare the gas, claimable with claim_affiliate_reward
+    Queued,
+    //the affiliate pool can't cover the reward
+    Unfunded,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AffiliateLog {
+    pub token_id: TokenId,
+    pub affiliate_id: AccountId,
+    pub amount: U128,
+    pub status: AffiliateRewardStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AffiliatePoolLog {
+    pub amount: U128,
+    pub pool: U128,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionPlanLog {
+    pub plan_id: PlanId,
+    //None once the plan is removed
+    pub plan: Option<SubscriptionPlan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionLog {
+    pub account_id: AccountId,
+    pub plan_id: PlanId,
+    pub paid_until: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeLog {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub level: u16,
+    pub cost: U128,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AttachLog {
+    pub owner_id: AccountId,
+    pub child_token_id: TokenId,
+    pub parent_token_id: TokenId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RenewalLog {
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    //Unix epoch in milliseconds, None if the token never expired
+    pub old_expires_at: Option<u64>,
+    pub new_expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExpiryExtendedLog {
+    //TokenId of form event_id.token_id
+    pub token_id: TokenId,
+    pub renewer_id: AccountId,
+    //Unix epoch in milliseconds
+    pub old_expires_at: u64,
+    pub new_expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RevokeLog {
+    //TokenId of form event_id.token_id.owner_id
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    //accounts which lost the approval, marketplaces delist the token
+    pub account_ids: Vec<AccountId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnershipLog {
+    //owner before the change
+    pub owner_id: AccountId,
+    pub proposed_owner_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MinterLog {
+    pub minter_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractMetadataLog {
+    //names of the contract metadata fields which changed
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SoulboundLog {
+    pub token_id: TokenId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FreezeLog {
+    pub token_id: TokenId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BlacklistLog {
+    pub account_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecoveryTransferLog {
+    pub token_id: TokenId,
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+    pub justification: String,
+    //recovery transfers of the copy so far, this one included
+    pub recoveries: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KeyRotationLog {
+    pub account_id: AccountId,
+    pub old_public_key: Base58PublicKey,
+    pub new_public_key: Base58PublicKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountFundingLog {
+    pub old_amount: U128,
+    pub new_amount: U128,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventCreatedLog {
+    pub event_id: EventId,
+    pub organiser: AccountId,
+    pub metadata: EventMetadata,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventOrganiserLog {
+    pub event_id: EventId,
+    pub old_organiser: AccountId,
+    pub new_organiser: AccountId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventCancelledLog {
+    pub event_id: EventId,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventUpdatedLog {
+    pub event_id: EventId,
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventDeletedLog {
+    pub event_id: EventId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ContractUpgradedLog {
+    //sha256 of the deployed wasm
+    pub code_hash: Base64VecU8,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaseLog {
+    //TokenId of form event_id.token_id.owner_id
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub borrower_id: AccountId,
+    //block timestamp in nanoseconds
+    pub expires_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockLog {
+    //TokenId of form event_id.token_id.owner_id
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub locker_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PaymentTokenLog {
+    //FT contract accepted for mints from now on
+    pub payment_token_id: AccountId,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HookDisabledLog {
+    pub hook_id: Option<AccountId>,
+    //the owner re-enables the hook with reset_hook_breaker
+    pub consecutive_failures: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketplaceSyncLog {
+    //can be sent again with retry_marketplace_sync
+    pub marketplace_id: AccountId,
+    pub action: SyncAction,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum CheckinRewardStatus {
+    Delivered,
+    //the transfer failed, claimable with claim_reward
+    Queued,
+    //the reward pool of the event can't cover the reward
+    Unfunded,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CheckinRewardLog {
+    pub event_id: EventId,
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub status: CheckinRewardStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftApproveLog {
+    //TokenId of form event_id.token_id.owner_id
+    pub token_id: TokenId,
+    pub owner_id: AccountId,
+    pub account_id: AccountId,
+    //listings made with an older approval ID of the account are stale
+    pub approval_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketplaceChangeLog {
+    pub marketplace_id: AccountId,
+    //false once removed, listings on the marketplace can't be settled anymore
+    pub approved: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventPassIssuedLog {
+    pub event_id: EventId,
+    pub owner_id: AccountId,
+    //full token ID of the pass, checked in at the door
+    pub token_id: TokenId,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::utils::test_utils::*;
     use near_sdk::MockedBlockchain;
     use near_sdk::{test_utils, testing_env};
+
+    //the envelope serializes to the expected JSON and deserializes back to the same variant
+    fn assert_round_trip(event: EventLogVariant, expected: &str) {
+        let event_log = EventLog {
+            standard: NFT_STANDARD_NAME.to_string(),
+            version: NFT_STANDARD_VERSION.to_string(),
+            event,
+        };
+
+        let json = serde_json::to_string(&event_log).unwrap();
+        assert_eq!(json, expected);
+
+        let parsed: EventLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, event_log);
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+
+    //same for the Catch envelope
+    fn assert_catch_round_trip(event: CatchEventLogVariant, expected: Option<&str>) {
+        let event_log = CatchEventLog {
+            standard: CATCH_STANDARD_NAME.to_string(),
+            version: CATCH_EVENT_SPEC.to_string(),
+            event,
+        };
+
+        let json = serde_json::to_string(&event_log).unwrap();
+        if let Some(expected) = expected {
+            assert_eq!(json, expected);
+        }
+
+        let parsed: CatchEventLog = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, event_log);
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+
     #[test]
     fn batch_mint_events() {
         testing_env!(get_context(carol().to_string(), 0));
 
-        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"foundation.near","token_ids":["aurora"]},{"owner_id":"user1.near","token_ids":["meme"]}]}"#;
+        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_mint","data":[{"owner_id":"foundation.near","token_ids":["aurora"]},{"owner_id":"user1.near","token_ids":["meme"]}]}"#;
 
         let mint_logs = vec![
             NftMintLog {
@@ -109,7 +562,7 @@ mod tests {
                 memo: None,
             },
         ];
-        NftMintLog::emit(mint_logs);
+        EventLogVariant::NftMint(mint_logs).emit();
         let logs = &test_utils::get_logs()[0];
         assert_eq!(expected, logs);
     }
@@ -118,7 +571,7 @@ mod tests {
     fn transfer_event() {
         testing_env!(get_context(carol().to_string(), 0));
 
-        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"authorized_id":"market.near","old_owner_id":"user1.near","new_owner_id":"user2.near","token_ids":["token"],"memo":"Go Team!"}]}"#;
+        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_transfer","data":[{"authorized_id":"market.near","old_owner_id":"user1.near","new_owner_id":"user2.near","token_ids":["token"],"memo":"Go Team!"}]}"#;
 
         let transfer_logs = vec![NftTransferLog {
             authorized_id: Some("market.near".to_string()),
@@ -128,8 +581,368 @@ mod tests {
             memo: Some("Go Team!".to_owned()),
         }];
 
-        NftTransferLog::emit(transfer_logs);
+        EventLogVariant::NftTransfer(transfer_logs).emit();
+        let log = &test_utils::get_logs()[0];
+        assert_eq!(expected, log);
+    }
+
+    #[test]
+    fn burn_event() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_burn","data":[{"owner_id":"user1.near","token_ids":["token"],"authorized_id":"market.near"}]}"#;
+
+        EventLogVariant::NftBurn(vec![NftBurnLog {
+            owner_id: "user1.near".to_string(),
+            token_ids: vec!["token".to_string()],
+            authorized_id: Some("market.near".to_string()),
+            memo: None,
+        }])
+        .emit();
+        let log = &test_utils::get_logs()[0];
+        assert_eq!(expected, log);
+    }
+
+    #[test]
+    fn metadata_update_event() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        let expected = r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["token"],"memo":"title,media"}]}"#;
+
+        EventLogVariant::NftMetadataUpdate(vec![NftMetadataUpdateLog {
+            token_ids: vec!["token".to_string()],
+            memo: Some("title,media".to_string()),
+        }])
+        .emit();
+        let log = &test_utils::get_logs()[0];
+        assert_eq!(expected, log);
+    }
+
+    #[test]
+    fn mint_round_trip() {
+        assert_round_trip(
+            EventLogVariant::NftMint(vec![NftMintLog {
+                owner_id: "user1.near".to_string(),
+                token_ids: vec!["catchcon.gold".to_string()],
+                memo: Some("Welcome".to_string()),
+            }]),
+            r#"{"standard":"nep171","version":"1.1.0","event":"nft_mint","data":[{"owner_id":"user1.near","token_ids":["catchcon.gold"],"memo":"Welcome"}]}"#,
+        );
+    }
+
+    #[test]
+    fn transfer_round_trip() {
+        assert_round_trip(
+            EventLogVariant::NftTransfer(vec![NftTransferLog {
+                authorized_id: None,
+                old_owner_id: "user1.near".to_string(),
+                new_owner_id: "user2.near".to_string(),
+                token_ids: vec!["catchcon.gold.user1.near".to_string()],
+                memo: None,
+            }]),
+            r#"{"standard":"nep171","version":"1.1.0","event":"nft_transfer","data":[{"old_owner_id":"user1.near","new_owner_id":"user2.near","token_ids":["catchcon.gold.user1.near"]}]}"#,
+        );
+    }
+
+    #[test]
+    fn burn_round_trip() {
+        assert_round_trip(
+            EventLogVariant::NftBurn(vec![NftBurnLog {
+                owner_id: "user1.near".to_string(),
+                token_ids: vec!["catchcon.gold.user1.near".to_string()],
+                authorized_id: Some("market.near".to_string()),
+                memo: Some("Refunded".to_string()),
+            }]),
+            r#"{"standard":"nep171","version":"1.1.0","event":"nft_burn","data":[{"owner_id":"user1.near","token_ids":["catchcon.gold.user1.near"],"authorized_id":"market.near","memo":"Refunded"}]}"#,
+        );
+    }
+
+    #[test]
+    fn metadata_update_round_trip() {
+        assert_round_trip(
+            EventLogVariant::NftMetadataUpdate(vec![NftMetadataUpdateLog {
+                token_ids: vec!["catchcon.gold".to_string()],
+                memo: None,
+            }]),
+            r#"{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["catchcon.gold"]}]}"#,
+        );
+    }
+
+    #[test]
+    fn catch_event() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        let expected = r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_frozen","data":{"token_id":"token","reason":"Disputed"}}"#;
+
+        CatchEventLogVariant::NftFrozen(FreezeLog {
+            token_id: "token".to_string(),
+            reason: Some("Disputed".to_string()),
+        })
+        .emit();
         let log = &test_utils::get_logs()[0];
         assert_eq!(expected, log);
     }
+
+    #[test]
+    fn approve_round_trip() {
+        assert_catch_round_trip(
+            CatchEventLogVariant::NftApprove(NftApproveLog {
+                token_id: "catchcon.gold.user1.near".to_string(),
+                owner_id: "user1.near".to_string(),
+                account_id: "market.near".to_string(),
+                approval_id: 3,
+            }),
+            Some(
+                r#"{"standard":"catch","version":"1.1.0","event":"nft_approve","data":{"token_id":"catchcon.gold.user1.near","owner_id":"user1.near","account_id":"market.near","approval_id":3}}"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn marketplace_change_round_trip() {
+        assert_catch_round_trip(
+            CatchEventLogVariant::MarketplaceChange(MarketplaceChangeLog {
+                marketplace_id: "market.near".to_string(),
+                approved: false,
+            }),
+            Some(
+                r#"{"standard":"catch","version":"1.1.0","event":"marketplace_change","data":{"marketplace_id":"market.near","approved":false}}"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn event_pass_issued_round_trip() {
+        assert_catch_round_trip(
+            CatchEventLogVariant::EventPassIssued(EventPassIssuedLog {
+                event_id: "catchcon".to_string(),
+                owner_id: "user1.near".to_string(),
+                token_id: "catchcon.pass.user1.near".to_string(),
+            }),
+            Some(
+                r#"{"standard":"catch","version":"1.1.0","event":"event_pass_issued","data":{"event_id":"catchcon","owner_id":"user1.near","token_id":"catchcon.pass.user1.near"}}"#,
+            ),
+        );
+    }
+
+    #[test]
+    fn catch_events_round_trip() {
+        let token_id = || "catchcon.gold.user1.near".to_string();
+        let account_id = || "user1.near".to_string();
+        let public_key = || {
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap()
+        };
+        let attach = || AttachLog {
+            owner_id: account_id(),
+            child_token_id: "catchcon.badge.user1.near".to_string(),
+            parent_token_id: token_id(),
+        };
+        let subscription = || SubscriptionLog {
+            account_id: account_id(),
+            plan_id: "season".to_string(),
+            paid_until: 2_000,
+        };
+        let ownership = || OwnershipLog {
+            owner_id: account_id(),
+            proposed_owner_id: "user2.near".to_string(),
+        };
+        let lease = || LeaseLog {
+            token_id: token_id(),
+            owner_id: account_id(),
+            borrower_id: "user2.near".to_string(),
+            expires_at: 3_600,
+        };
+        let lock = || LockLog {
+            token_id: token_id(),
+            owner_id: account_id(),
+            locker_id: "ft.near".to_string(),
+        };
+        let key_rotation = || KeyRotationLog {
+            account_id: account_id(),
+            old_public_key: public_key(),
+            new_public_key: public_key(),
+        };
+
+        let events = vec![
+            CatchEventLogVariant::NftRoyaltyPayout(RoyaltyPayoutLog {
+                token_id: token_id(),
+                marketplace_id: "market.near".to_string(),
+                balance: U128(100),
+                payout: [(account_id(), U128(90)), ("artist.near".to_string(), U128(10))]
+                    .into_iter()
+                    .collect(),
+            }),
+            CatchEventLogVariant::NftRoyaltyUpdated(RoyaltyConfigLog {
+                token_id: "catchcon.gold".to_string(),
+                royalty: [("artist.near".to_string(), 1_000)].into_iter().collect(),
+            }),
+            CatchEventLogVariant::CatchAffiliate(AffiliateLog {
+                token_id: token_id(),
+                affiliate_id: "referrer.near".to_string(),
+                amount: U128(5),
+                status: AffiliateRewardStatus::Queued,
+            }),
+            CatchEventLogVariant::AffiliateProgramUpdated(AffiliateProgram {
+                enabled: true,
+                bps: 100,
+                ft_per_near: U128(10),
+            }),
+            CatchEventLogVariant::AffiliatePoolFunded(AffiliatePoolLog {
+                amount: U128(10),
+                pool: U128(30),
+            }),
+            CatchEventLogVariant::SubscriptionPlanUpdated(SubscriptionPlanLog {
+                plan_id: "season".to_string(),
+                plan: Some(SubscriptionPlan {
+                    price: U128(10),
+                    period_sec: 86_400,
+                    pass_token_id: "catchcon.season".to_string(),
+                }),
+            }),
+            CatchEventLogVariant::SubscriptionPaid(subscription()),
+            CatchEventLogVariant::SubscriptionExpired(subscription()),
+            CatchEventLogVariant::CatchUpgrade(UpgradeLog {
+                token_id: token_id(),
+                owner_id: account_id(),
+                level: 2,
+                cost: U128(50),
+            }),
+            CatchEventLogVariant::NftAttached(attach()),
+            CatchEventLogVariant::NftDetached(attach()),
+            CatchEventLogVariant::CatchRenewal(RenewalLog {
+                token_id: token_id(),
+                owner_id: account_id(),
+                old_expires_at: None,
+                new_expires_at: 2_000,
+            }),
+            CatchEventLogVariant::NftExpiryExtended(ExpiryExtendedLog {
+                token_id: "catchcon.gold".to_string(),
+                renewer_id: account_id(),
+                old_expires_at: 1_000,
+                new_expires_at: 2_000,
+            }),
+            CatchEventLogVariant::NftRevoke(RevokeLog {
+                token_id: token_id(),
+                owner_id: account_id(),
+                account_ids: vec!["market.near".to_string()],
+            }),
+            CatchEventLogVariant::OwnershipProposed(ownership()),
+            CatchEventLogVariant::OwnershipAccepted(ownership()),
+            CatchEventLogVariant::MinterAdded(MinterLog {
+                minter_id: account_id(),
+            }),
+            CatchEventLogVariant::MinterRemoved(MinterLog {
+                minter_id: account_id(),
+            }),
+            CatchEventLogVariant::ContractMetadataUpdated(ContractMetadataLog {
+                fields: vec!["icon".to_string()],
+            }),
+            CatchEventLogVariant::SoulboundReleased(SoulboundLog {
+                token_id: token_id(),
+            }),
+            CatchEventLogVariant::NftFrozen(FreezeLog {
+                token_id: token_id(),
+                reason: Some("Disputed".to_string()),
+            }),
+            CatchEventLogVariant::NftUnfrozen(FreezeLog {
+                token_id: token_id(),
+                reason: None,
+            }),
+            CatchEventLogVariant::AccountBlacklisted(BlacklistLog {
+                account_id: account_id(),
+            }),
+            CatchEventLogVariant::AccountUnblacklisted(BlacklistLog {
+                account_id: account_id(),
+            }),
+            CatchEventLogVariant::NftRecoveryTransfer(RecoveryTransferLog {
+                token_id: token_id(),
+                old_owner_id: account_id(),
+                new_owner_id: "user2.near".to_string(),
+                justification: "Lost keys".to_string(),
+                recoveries: 1,
+            }),
+            CatchEventLogVariant::AccountKeyRotated(key_rotation()),
+            CatchEventLogVariant::AccountKeyRotationFailed(key_rotation()),
+            CatchEventLogVariant::AccountFundingUpdated(AccountFundingLog {
+                old_amount: U128(1),
+                new_amount: U128(2),
+            }),
+            CatchEventLogVariant::EventCreated(EventCreatedLog {
+                event_id: "catchcon".to_string(),
+                organiser: account_id(),
+                metadata: EventMetadata {
+                    title: "Catch Con".to_string(),
+                    description: None,
+                    venue: Some("Lisbon".to_string()),
+                    starts_at: 1_000,
+                    ends_at: 2_000,
+                    max_capacity: 100,
+                    media: None,
+                    media_hash: None,
+                    reference: None,
+                    reference_hash: None,
+                },
+            }),
+            CatchEventLogVariant::EventOrganiserTransferred(EventOrganiserLog {
+                event_id: "catchcon".to_string(),
+                old_organiser: account_id(),
+                new_organiser: "user2.near".to_string(),
+            }),
+            CatchEventLogVariant::EventCancelled(EventCancelledLog {
+                event_id: "catchcon".to_string(),
+                reason: "Venue closed".to_string(),
+            }),
+            CatchEventLogVariant::EventUpdated(EventUpdatedLog {
+                event_id: "catchcon".to_string(),
+                fields: vec!["venue".to_string()],
+            }),
+            CatchEventLogVariant::EventDeleted(EventDeletedLog {
+                event_id: "catchcon".to_string(),
+            }),
+            CatchEventLogVariant::ContractUpgraded(ContractUpgradedLog {
+                code_hash: Base64VecU8::from([1_u8; 32].to_vec()),
+            }),
+            CatchEventLogVariant::NftLeaseStarted(lease()),
+            CatchEventLogVariant::NftLeaseEnded(lease()),
+            CatchEventLogVariant::PaymentTokenUpdated(PaymentTokenLog {
+                payment_token_id: "ft.near".to_string(),
+            }),
+            CatchEventLogVariant::TransferHookDisabled(HookDisabledLog {
+                hook_id: Some("hook.near".to_string()),
+                consecutive_failures: 3,
+            }),
+            CatchEventLogVariant::MarketplaceSyncFailed(MarketplaceSyncLog {
+                marketplace_id: "market.near".to_string(),
+                action: SyncAction::Remove,
+            }),
+            CatchEventLogVariant::CheckinReward(CheckinRewardLog {
+                event_id: "catchcon".to_string(),
+                account_id: account_id(),
+                amount: U128(10),
+                status: CheckinRewardStatus::Unfunded,
+            }),
+            CatchEventLogVariant::NftLocked(lock()),
+            CatchEventLogVariant::NftUnlocked(lock()),
+            CatchEventLogVariant::NftApprove(NftApproveLog {
+                token_id: token_id(),
+                owner_id: account_id(),
+                account_id: "market.near".to_string(),
+                approval_id: 0,
+            }),
+            CatchEventLogVariant::MarketplaceChange(MarketplaceChangeLog {
+                marketplace_id: "market.near".to_string(),
+                approved: true,
+            }),
+            CatchEventLogVariant::EventPassIssued(EventPassIssuedLog {
+                event_id: "catchcon".to_string(),
+                owner_id: account_id(),
+                token_id: "catchcon.pass.user1.near".to_string(),
+            }),
+        ];
+
+        for event in events {
+            assert_catch_round_trip(event, None);
+        }
+    }
 }
@@ -10,28 +10,8 @@ pub(crate) fn bytes_for_token_or_event_or_account_id(id: &String) -> u64 {
     id.as_str().len() as u64 + 4
 }
 
-pub(crate) fn refund_approved_account_ids_iter<'a, I>(
-    account_id: AccountId,
-    approved_account_ids: I, //the approved account IDs must be passed in as an iterator
-) -> Promise
-where
-    I: Iterator<Item = &'a AccountId>,
-{
-    let storage_released: u64 = approved_account_ids
-        .map(bytes_for_approved_account_id)
-        .sum();
-    Promise::new(account_id).transfer(Balance::from(storage_released) * env::storage_byte_cost())
-}
-
-pub(crate) fn refund_approved_account_ids(
-    account_id: AccountId,
-    approved_account_ids: &HashMap<AccountId, u64>,
-) -> Promise {
-    //call the refund_approved_account_ids_iter with the approved account IDs as keys
-    refund_approved_account_ids_iter(account_id, approved_account_ids.keys())
-}
-
-//used to generate a unique prefix in our storage collections (this is to avoid data collisions)
+//used to generate a unique prefix in our storage collections (this is to avoid data collisions),
+//the whole sha256 of the id is used so ids sharing a long common start get different prefixes
 pub(crate) fn hash_id(id: &str) -> CryptoHash {
     let mut hash = CryptoHash::default();
     hash.copy_from_slice(&env::sha256(id.as_bytes()));
@@ -65,6 +45,29 @@ pub(crate) fn refund_deposit(storage_used: u64) {
 }
 
 impl Contract {
+    //the storage of the approvals goes back to the storage balance of the account or is refunded
+    pub(crate) fn refund_approved_account_ids_iter<'a, I>(
+        &mut self,
+        account_id: AccountId,
+        approved_account_ids: I, //the approved account IDs must be passed in as an iterator
+    ) where
+        I: Iterator<Item = &'a AccountId>,
+    {
+        let storage_released: u64 = approved_account_ids
+            .map(bytes_for_approved_account_id)
+            .sum();
+        self.internal_release_storage(account_id, storage_released);
+    }
+
+    pub(crate) fn refund_approved_account_ids(
+        &mut self,
+        account_id: AccountId,
+        approved_account_ids: &HashMap<AccountId, u64>,
+    ) {
+        //call the refund_approved_account_ids_iter with the approved account IDs as keys
+        self.refund_approved_account_ids_iter(account_id, approved_account_ids.keys())
+    }
+
     //add a token to the set of tokens an owner has
     pub(crate) fn internal_add_token_to_owner(
         &mut self,
@@ -90,6 +93,10 @@ impl Contract {
 
         //we insert that set for the given account ID.
         self.tokens_per_owner.insert(account_id, &tokens_set);
+        self.token_count_per_owner
+            .insert(account_id, &tokens_set.len());
+
+        self.internal_index_pass(account_id, token_id);
     }
 
     //remove a token from an owner .
@@ -108,109 +115,369 @@ impl Contract {
         //if the token set is now empty, we remove the owner from the tokens_per_owner collection
         if tokens_set.is_empty() {
             self.tokens_per_owner.remove(account_id);
+            self.token_count_per_owner.remove(account_id);
         } else {
             //if the token set is not empty, we simply insert it back for the account ID.
             self.tokens_per_owner.insert(account_id, &tokens_set);
+            self.token_count_per_owner
+                .insert(account_id, &tokens_set.len());
         }
+
+        self.internal_unindex_pass(account_id, token_id);
     }
 
-    //internal method that transfers the NFT to the receiver_id
-    pub(crate) fn internal_transfer(
+    //mints the next copy of the token to the account after checking expiry, availability and dependencies
+    pub(crate) fn internal_mint_copy(
         &mut self,
-        sender_id: &AccountId,
-        receiver_id: &AccountId,
+        account_id: &AccountId,
         token_id: &TokenId,
-        approval_id: Option<u64>,
-        memo: Option<String>,
-    ) -> (AccountId, ApprovalInfo) {
-        let (token_id, owner_id) = resolve_token_id(token_id.to_string());
+        memo: String,
+    ) {
+        self.internal_reserve_copy(account_id, token_id);
+        self.internal_finish_mint(account_id, token_id, memo);
+    }
+
+    //counts the next copy of the token as minted after checking expiry, availability and dependencies,
+    //the copy is given to the account by internal_finish_mint
+    pub(crate) fn internal_reserve_copy(&mut self, account_id: &AccountId, token_id: &TokenId) {
+        self.assert_not_blacklisted(account_id);
 
         let mut token = self
             .tokens_by_id
-            .get(&token_id)
-            .unwrap_or_else(|| env::panic(b"No token"));
+            .get(token_id)
+            .unwrap_or_else(|| env::panic(b"Token does not exist"));
 
+        require!(!internal_is_token_expired(&token), "Token has expired");
+
+        // standalone tokens of nft_mint have no event
+        let event_id: EventId = token_id.split_once(".").unwrap().0.to_string();
         require!(
-            !internal_is_token_expired(&token),
-            "Token Can't be transferred Since it has already expired"
+            !self
+                .events_by_id
+                .get(&event_id)
+                .is_some_and(|event| event.cancelled),
+            "Event is cancelled"
         );
 
-        let token_set = self
-            .tokens_per_owner
-            .get(&owner_id)
-            .unwrap_or_else(|| env::panic(b"You own no tokens"));
+        assert_token_availability(&token);
 
         require!(
-            token_set.contains(&token_id),
-            "You need to own the token to transfer it"
+            self.internal_is_eligible_to_mint_token(account_id, &token),
+            format!(
+                "{} doesn't satisfy all the dependencies for the token {}",
+                account_id, token_id
+            )
         );
 
         require!(
-            self.internal_is_eligible_to_mint_token(receiver_id, &token),
-            "receiver_id doesn't satisfy all dependencies for the token"
+            !self.internal_owns_token(account_id, token_id),
+            format!("{} account already has token {}", &account_id, &token_id)
         );
 
+        self.internal_count_mint();
+        token.copies_minted += 1;
+
+        self.tokens_by_id.insert(token_id, &token);
+        self.internal_count_event_pass(token_id, true);
+    }
+
+    //counts a mint against max_total_supply, reserved and pending tokens count until they are
+    //released or rolled back
+    pub(crate) fn internal_count_mint(&mut self) {
         require!(
-            &owner_id != receiver_id,
-            "The token owner and the receiver should be different"
+            self.max_total_supply
+                .is_none_or(|max_total_supply| self.minted_count < max_total_supply),
+            "Max total supply reached"
         );
 
-        let (mut old_approval_info, mut approval_info) = Default::default();
+        self.minted_count += 1;
+    }
+
+    //true once starts_at of the token class has passed, tokens without starts_at are always active
+    pub(crate) fn internal_is_active(&self, token_id: &TokenId) -> bool {
+        self.internal_token_metadata(token_id)
+            .and_then(|metadata| metadata.starts_at)
+            .is_none_or(|starts_at| starts_at.saturating_mul(1_000_000) <= env::block_timestamp())
+    }
+
+    //tokens held by the account, the set is only read for accounts which haven't gained or lost a
+    //token since the count was introduced
+    pub(crate) fn internal_token_count(&self, account_id: &AccountId) -> u64 {
+        self.token_count_per_owner
+            .get(account_id)
+            .unwrap_or_else(|| {
+                self.tokens_per_owner
+                    .get(account_id)
+                    .map_or(0, |tokens_set| tokens_set.len())
+            })
+    }
+
+    //true if the account holds a copy of the token
+    pub(crate) fn internal_owns_token(&self, account_id: &AccountId, token_id: &TokenId) -> bool {
+        self.tokens_per_owner
+            .get(account_id)
+            .is_some_and(|tokens_set| tokens_set.contains(token_id))
+    }
+
+    //releases a copy reserved by internal_reserve_copy which won't be minted
+    pub(crate) fn internal_release_copy(&mut self, token_id: &TokenId) {
+        let mut token = self.tokens_by_id.get(token_id).unwrap();
+
+        token.copies_minted -= 1;
+        self.minted_count -= 1;
+
+        self.tokens_by_id.insert(token_id, &token);
+        self.internal_count_event_pass(token_id, false);
+    }
+
+    //gives a reserved copy of the token to the account
+    pub(crate) fn internal_finish_mint(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &TokenId,
+        memo: String,
+    ) {
+        self.internal_add_token_to_owner(account_id, token_id);
+
+        let mint_event = EventLogVariant::NftMint(vec![NftMintLog {
+            owner_id: account_id.clone(),
+            token_ids: vec![token_id.clone()],
+            memo: Some(memo),
+        }]);
+
+        self.internal_notify_hook(mint_event.clone());
+
+        mint_event.emit();
+
+        //passes of an event are also announced with the event ID so indexers needn't parse token IDs
+        let event_id = token_id
+            .split_once('.')
+            .map_or("", |(event_id, _)| event_id);
+        if *token_id == format!("{}.{}", event_id, EVENT_PASS_TOKEN)
+            && self.events_by_id.get(&event_id.to_string()).is_some()
+        {
+            CatchEventLogVariant::EventPassIssued(EventPassIssuedLog {
+                event_id: event_id.to_string(),
+                owner_id: account_id.clone(),
+                token_id: build_full_token_id(token_id.clone(), account_id.clone()),
+            })
+            .emit();
+        }
+    }
+
+    //checks everything a transfer requires which can be verified synchronously,
+    //the error is the panic message of the transfer
+    pub(crate) fn internal_check_transfer(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+    ) -> Result<(), String> {
+        if self.internal_active_lease(token_id).is_some() {
+            return Err("Token is leased".to_string());
+        }
+
+        if let Some(parent_token_id) = self.parent_by_token_id.get(token_id) {
+            return Err(format!("Token is attached to {}", parent_token_id));
+        }
+
+        let full_token_id = token_id;
+        let (token_id, owner_id) = resolve_token_id(token_id.to_string());
+
+        let token = self.tokens_by_id.get(&token_id).ok_or("No token")?;
+
+        if token.soulbound {
+            return Err("Token is soulbound".to_string());
+        }
+
+        // passes of a cancelled event are void
+        let event_id: EventId = token_id.split_once('.').unwrap().0.to_string();
+        if let Some(event) = self.events_by_id.get(&event_id) {
+            if event.cancelled {
+                return Err("Event is cancelled".to_string());
+            }
+            if !event.transferable {
+                return Err("Passes of the event aren't transferable".to_string());
+            }
+        }
+
+        if self.frozen_tokens.get(full_token_id).is_some() {
+            return Err("Token is frozen".to_string());
+        }
+
+        if self.locks_by_token_id.get(full_token_id).is_some() {
+            return Err("Token is locked".to_string());
+        }
+
+        if let Some(account_id) = [sender_id, &owner_id, receiver_id]
+            .into_iter()
+            .find(|account_id| self.blacklist.contains(account_id))
+        {
+            return Err(format!("{} is blacklisted", account_id));
+        }
+
+        if self.internal_is_pending(&token_id) {
+            return Err("Token is pending".to_string());
+        }
+
+        if self.internal_is_copy_expired(full_token_id, &token) {
+            return Err("Token Can't be transferred Since it has already expired".to_string());
+        }
+
+        // the owner can still correct the distribution before the start
+        if !self.internal_is_active(&token_id) && sender_id != &self.owner_id {
+            return Err("Token not yet active".to_string());
+        }
+
+        let token_set = self
+            .tokens_per_owner
+            .get(&owner_id)
+            .ok_or("You own no tokens")?;
+
+        if !token_set.contains(&token_id) {
+            return Err("You need to own the token to transfer it".to_string());
+        }
+
+        let (missing_token_ids, missing_event_ids) =
+            self.internal_missing_dependencies(receiver_id, &token);
+        if !missing_token_ids.is_empty() || !missing_event_ids.is_empty() {
+            let missing: Vec<String> = missing_token_ids
+                .into_iter()
+                .chain(
+                    missing_event_ids
+                        .into_iter()
+                        .map(|id| format!("a pass of {}", id)),
+                )
+                .collect();
+            return Err(format!(
+                "receiver_id doesn't satisfy all dependencies for the token, missing {}",
+                missing.join(", ")
+            ));
+        }
+
+        if &owner_id == receiver_id {
+            return Err("The token owner and the receiver should be different".to_string());
+        }
 
         //if the sender doesn't equal the owner, we check if the sender is in the approval list
 
         if sender_id != &owner_id {
-            approval_info = token
-                .account_approval_info_per_owner
-                .get(&owner_id)
-                .unwrap_or_else(|| env::panic(b"Token Owner hasn't approved any account"));
-
-            require!(
-                approval_info.approved_account_ids.contains_key(sender_id),
-                "Unauthorised"
-            );
+            let approval_info = self
+                .approvals_by_token_id
+                .get(full_token_id)
+                .ok_or("Token Owner hasn't approved any account")?;
 
-            old_approval_info = approval_info.clone();
+            let actual_approval_id = approval_info
+                .approved_account_ids
+                .get(sender_id)
+                .ok_or("Unauthorised")?;
 
             // If they included an approval_id, check if the sender's actual approval_id is the same as the one included
 
             if let Some(enforced_approval_id) = approval_id {
-                //get the actual approval ID
-
-                let actual_approval_id = approval_info
-                    .approved_account_ids
-                    .get(sender_id)
-                    .unwrap_or_else(|| env::panic(b"Sender is not approved account"));
-
-                require!(
-                    actual_approval_id == &enforced_approval_id,
-                    format!(
+                if actual_approval_id != &enforced_approval_id {
+                    return Err(format!(
                         "The actual approval_id {} is different from the given approval_id {}",
                         actual_approval_id, enforced_approval_id
-                    )
-                );
+                    ));
+                }
             }
         }
 
-        // update token struct
-        token.account_approval_info_per_owner.remove(&owner_id);
+        Ok(())
+    }
+
+    //internal method that transfers the NFT to the receiver_id
+    pub(crate) fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> (AccountId, ApprovalInfo) {
+        if let Err(message) =
+            self.internal_check_transfer(sender_id, receiver_id, token_id, approval_id)
+        {
+            env::panic(message.as_bytes());
+        }
+
+        self.internal_move_token(sender_id, receiver_id, token_id, approval_id, memo)
+    }
 
-        approval_info.approved_account_ids = Default::default();
-        token
-            .account_approval_info_per_owner
-            .insert(receiver_id, &approval_info);
+    //moves a token which passed the transfer checks along with its attached children
+    pub(crate) fn internal_move_token(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) -> (AccountId, ApprovalInfo) {
+        //an expired lease or an unused lease approval doesn't follow the token, their storage was
+        //drawn from the storage balance of the owner and goes back to it
+        let initial_storage = env::storage_usage();
+        self.internal_remove_lease(token_id);
+        self.lease_approvals.remove(token_id);
+        self.internal_release_storage(
+            resolve_token_id(token_id.to_string()).1,
+            initial_storage.saturating_sub(env::storage_usage()),
+        );
 
-        //insert that new token into the tokens_by_id, replacing the old entry
-        self.tokens_by_id.insert(&token_id, &token);
+        //the paid period of a season pass does
+        if let Some(paid_until) = self.pass_expiry_by_token_id.remove(token_id) {
+            let (class_token_id, _) = resolve_token_id(token_id.to_string());
+            self.pass_expiry_by_token_id.insert(
+                &build_full_token_id(class_token_id, receiver_id.clone()),
+                &paid_until,
+            );
+        }
 
-        self.internal_remove_token_from_owner(&owner_id, &token_id);
-        self.internal_add_token_to_owner(receiver_id, &token_id);
+        //so do the level, the recovery count and the metadata history
+        let old_token_id = token_id.clone();
+        let (class_token_id, _) = resolve_token_id(token_id.to_string());
+        let new_token_id = build_full_token_id(class_token_id, receiver_id.clone());
+        if let Some(level) = self.level_by_token_id.remove(token_id) {
+            self.level_by_token_id.insert(&new_token_id, &level);
+        }
+        if let Some(recoveries) = self.recoveries_by_token_id.remove(token_id) {
+            self.recoveries_by_token_id
+                .insert(&new_token_id, &recoveries);
+        }
+        if let Some(history) = self.metadata_history_by_token_id.remove(token_id) {
+            self.metadata_history_by_token_id
+                .insert(&new_token_id, &history);
+        }
+        // a redeemed pass stays redeemed for the next holder
+        if let Some(checked_in_at) = self.checked_in_at_by_token_id.remove(token_id) {
+            self.checked_in_at_by_token_id
+                .insert(&new_token_id, &checked_in_at);
+        }
+
+        let (token_id, owner_id) = resolve_token_id(token_id.to_string());
 
-        //if there was some memo attached, we log it.
-        if let Some(memo) = memo.as_ref() {
-            env::log(format!("Memo: {}", memo).as_bytes());
+        //the approvals of the outgoing owner end with the transfer, the caller refunds their storage.
+        //next_approval_id follows the copy so ids granted before never become valid again, even if
+        //the token comes back to the same owner. A copy that was never approved has no record to carry
+        let old_approval_info = self
+            .approvals_by_token_id
+            .remove(&old_token_id)
+            .unwrap_or_default();
+
+        if old_approval_info.next_approval_id > 0 {
+            self.approvals_by_token_id.insert(
+                &new_token_id,
+                &ApprovalInfo {
+                    approved_account_ids: Default::default(),
+                    next_approval_id: old_approval_info.next_approval_id,
+                },
+            );
         }
 
+        self.internal_remove_token_from_owner(&owner_id, &token_id);
+        self.internal_add_token_to_owner(receiver_id, &token_id);
+
         // Default the authorized ID to be None for the logs.
         let mut authorized_id = None;
         //if the approval ID was provided, set the authorized ID equal to the sender
@@ -218,16 +485,48 @@ impl Contract {
             authorized_id = Some(sender_id.to_string());
         }
 
-        let transfer_logs = vec![NftTransferLog {
+        let transfer_event = EventLogVariant::NftTransfer(vec![NftTransferLog {
             authorized_id,
             old_owner_id: owner_id.clone(),
             new_owner_id: receiver_id.to_string(),
             token_ids: vec![token_id],
             memo,
-        }];
+        }]);
 
-        NftTransferLog::emit(transfer_logs);
+        self.internal_notify_hook(transfer_event.clone());
+
+        transfer_event.emit();
+
+        self.internal_move_children(&old_token_id, &new_token_id, receiver_id);
 
         (owner_id, old_approval_info)
     }
 }
+
+#[cfg(test)]
+mod internal_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    #[test]
+    fn ids_differing_at_the_end_get_different_prefixes() {
+        testing_env!(get_context(nft().to_string(), 0));
+
+        let long_id = "a".repeat(63);
+        let prefix = |id: String| {
+            StorageKey::TokenPerOwnerInner {
+                account_id_hash: hash_id(&id),
+            }
+            .try_to_vec()
+            .unwrap()
+        };
+
+        assert_ne!(
+            prefix(format!("{}b", long_id)),
+            prefix(format!("{}c", long_id))
+        );
+        assert_eq!(hash_id(&long_id).to_vec(), env::sha256(long_id.as_bytes()));
+    }
+}
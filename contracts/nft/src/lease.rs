@@ -0,0 +1,1105 @@
+use crate::*;
+
+const GAS_FOR_ON_LEASE_APPROVE: Gas = 10_000_000_000_000;
+
+// a borrowed token counts for the dependencies of the borrower, short leases would let one
+// token satisfy the dependencies of many accounts in turn
+const MIN_LEASE_DURATION_NS: u64 = 3_600 * 1_000_000_000;
+
+/// Lease of a token of form event_id.token_id.owner_id, the owner keeps the token but can't
+/// transfer or approve it until the lease expires
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Lease {
+    pub borrower_id: AccountId,
+    pub expires_at: u64,
+    //rental marketplace which created the lease, None for nft_lease
+    pub marketplace_id: Option<AccountId>,
+}
+
+/// Capability given to a rental marketplace to create one lease of the token
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LeaseApproval {
+    pub marketplace_id: AccountId,
+    pub approval_id: u64,
+}
+
+#[ext_contract(ext_rental_marketplace)]
+pub trait RentalMarketplace {
+    fn on_lease_approve(
+        &mut self,
+        token_id: TokenId,
+        owner_id: AccountId,
+        approval_id: u64,
+        terms_msg: String,
+    );
+}
+
+impl Contract {
+    //lease of the token which hasn't expired yet
+    pub(crate) fn internal_active_lease(&self, token_id: &TokenId) -> Option<Lease> {
+        self.leases
+            .get(token_id)
+            .filter(|lease| env::block_timestamp() < lease.expires_at)
+    }
+
+    //copy of the token class leased to the account which hasn't expired yet
+    pub(crate) fn internal_borrowed_copy(
+        &self,
+        account_id: &AccountId,
+        class_token_id: &TokenId,
+    ) -> Option<TokenId> {
+        self.leased_copies_by_borrower
+            .get(&(account_id.clone(), class_token_id.clone()))?
+            .into_iter()
+            .find(|token_id| self.internal_active_lease(token_id).is_some())
+    }
+
+    //copy of the token class the account owns, or else borrows
+    pub(crate) fn internal_held_copy(
+        &self,
+        account_id: &AccountId,
+        class_token_id: &TokenId,
+    ) -> Option<TokenId> {
+        if self.internal_owns_token(account_id, class_token_id) {
+            return Some(build_full_token_id(
+                class_token_id.clone(),
+                account_id.clone(),
+            ));
+        }
+
+        self.internal_borrowed_copy(account_id, class_token_id)
+    }
+
+    //nft_lease and marketplace_create_lease go through here so both keep the same invariants,
+    //a lease hands the token over like a transfer so the token has to be transferable
+    fn internal_create_lease(
+        &mut self,
+        token_id: &TokenId,
+        borrower_id: AccountId,
+        duration_ns: u64,
+        marketplace_id: Option<AccountId>,
+    ) -> Lease {
+        self.assert_not_paused(PausableFeature::Transfer);
+
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        let token = self
+            .tokens_by_id
+            .get(&class_token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+
+        require!(
+            self.internal_owns_token(&owner_id, &class_token_id),
+            "Only owned tokens can be leased"
+        );
+        require!(!internal_is_token_expired(&token), "Token has expired");
+        require!(!token.soulbound, "Token is soulbound");
+        require!(
+            self.internal_is_transferable_pass(&class_token_id),
+            "Passes of the event aren't transferable"
+        );
+        require!(
+            self.frozen_tokens.get(token_id).is_none(),
+            "Token is frozen"
+        );
+        require!(
+            self.locks_by_token_id.get(token_id).is_none(),
+            "Token is locked"
+        );
+        self.assert_not_blacklisted(&owner_id);
+        self.assert_not_blacklisted(&borrower_id);
+        require!(
+            self.internal_active_lease(token_id).is_none(),
+            "Token is already leased"
+        );
+        require!(
+            self.parent_by_token_id.get(token_id).is_none(),
+            "Token is attached"
+        );
+        require!(
+            borrower_id != owner_id,
+            "The token owner can't borrow the token"
+        );
+        require!(
+            duration_ns >= MIN_LEASE_DURATION_NS,
+            format!(
+                "Lease must last at least {} nanoseconds",
+                MIN_LEASE_DURATION_NS
+            )
+        );
+
+        let lease = Lease {
+            borrower_id,
+            expires_at: env::block_timestamp() + duration_ns,
+            marketplace_id,
+        };
+
+        self.leases.insert(token_id, &lease);
+
+        let key = (lease.borrower_id.clone(), class_token_id);
+        let mut leased_copies = self.leased_copies_by_borrower.get(&key).unwrap_or_default();
+        leased_copies.push(token_id.clone());
+        self.leased_copies_by_borrower.insert(&key, &leased_copies);
+
+        CatchEventLogVariant::NftLeaseStarted(LeaseLog {
+            token_id: token_id.clone(),
+            owner_id,
+            borrower_id: lease.borrower_id.clone(),
+            expires_at: lease.expires_at,
+        })
+        .emit();
+
+        lease
+    }
+
+    //removes the lease of the copy, expired or not, and its entry in the copies of the borrower
+    pub(crate) fn internal_remove_lease(&mut self, token_id: &TokenId) -> Option<Lease> {
+        let lease = self.leases.remove(token_id)?;
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        let key = (lease.borrower_id.clone(), class_token_id);
+        if let Some(mut leased_copies) = self.leased_copies_by_borrower.get(&key) {
+            leased_copies.retain(|leased_token_id| leased_token_id != token_id);
+            if leased_copies.is_empty() {
+                self.leased_copies_by_borrower.remove(&key);
+            } else {
+                self.leased_copies_by_borrower.insert(&key, &leased_copies);
+            }
+        }
+
+        CatchEventLogVariant::NftLeaseEnded(LeaseLog {
+            token_id: token_id.clone(),
+            owner_id,
+            borrower_id: lease.borrower_id.clone(),
+            expires_at: lease.expires_at,
+        })
+        .emit();
+
+        Some(lease)
+    }
+
+    //removes the lease and gives its storage back to the owner, who paid for it
+    fn internal_end_lease(&mut self, token_id: &TokenId, owner_id: AccountId) {
+        let initial_storage = env::storage_usage();
+
+        self.internal_remove_lease(token_id);
+
+        self.internal_release_storage(
+            owner_id,
+            initial_storage.saturating_sub(env::storage_usage()),
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Token owner only, lends the token to the borrower for duration_ns nanoseconds, the storage
+    /// of the lease is drawn from the storage balance of the owner, or from the deposit if the
+    /// owner has none. The lease ends by itself once it expires, until then the borrower is the
+    /// user of the token
+    #[payable]
+    pub fn nft_lease(&mut self, token_id: TokenId, borrower_id: ValidAccountId, duration_ns: u64) {
+        assert_at_least_one_yocto();
+        let initial_storage = env::storage_usage();
+
+        let (_, owner_id) = resolve_token_id(token_id.clone());
+        require!(
+            env::predecessor_account_id() == owner_id,
+            "Only the token owner can lease the token"
+        );
+
+        self.internal_create_lease(&token_id, borrower_id.into(), duration_ns, None);
+
+        self.internal_pay_storage(env::storage_usage().saturating_sub(initial_storage));
+    }
+
+    /// Deprecated, use nft_lease. Kept for existing callers, leases the token for duration_sec
+    #[payable]
+    pub fn nft_lend(&mut self, token_id: TokenId, borrower_id: ValidAccountId, duration_sec: u64) {
+        self.nft_lease(token_id, borrower_id, duration_sec * 1_000_000_000);
+    }
+
+    /// Token owner only, lets a Catch approved marketplace create one lease of the token,
+    /// terms_msg is passed to on_lease_approve of the marketplace. The deposit pays the storage
+    /// of the approval, the lease is paid from the storage balance of the owner once created
+    #[payable]
+    pub fn nft_approve_lease(
+        &mut self,
+        token_id: TokenId,
+        marketplace_id: ValidAccountId,
+        terms_msg: String,
+    ) -> Promise {
+        assert_at_least_one_yocto();
+        let initial_storage = env::storage_usage();
+
+        let marketplace_id: AccountId = marketplace_id.into();
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        require!(
+            self.internal_is_approved_marketplace(&marketplace_id),
+            "Only Catch approved marketplaces can create leases"
+        );
+        require!(
+            env::predecessor_account_id() == owner_id,
+            "Only the token owner can approve leases"
+        );
+        require!(
+            self.internal_owns_token(&owner_id, &class_token_id),
+            "Only owned tokens can be leased"
+        );
+        require!(
+            self.internal_active_lease(&token_id).is_none(),
+            "Token is already leased"
+        );
+
+        // lease approvals share the approval ids of the token
+        let mut approval_info = self
+            .approvals_by_token_id
+            .get(&token_id)
+            .unwrap_or_default();
+
+        let approval_id = approval_info.next_approval_id;
+        approval_info.next_approval_id += 1;
+
+        self.approvals_by_token_id.insert(&token_id, &approval_info);
+
+        self.lease_approvals.insert(
+            &token_id,
+            &LeaseApproval {
+                marketplace_id: marketplace_id.clone(),
+                approval_id,
+            },
+        );
+
+        self.internal_pay_storage(env::storage_usage().saturating_sub(initial_storage));
+
+        ext_rental_marketplace::on_lease_approve(
+            token_id,
+            owner_id,
+            approval_id,
+            terms_msg,
+            &marketplace_id,
+            0,
+            GAS_FOR_ON_LEASE_APPROVE,
+        )
+    }
+
+    /// Lease approved marketplace only, leases the token to the borrower for duration_sec. The
+    /// lease replaces the approval, the storage it takes beyond the approval is paid from the
+    /// storage balance of the owner
+    pub fn marketplace_create_lease(
+        &mut self,
+        token_id: TokenId,
+        borrower_id: ValidAccountId,
+        duration_sec: u64,
+        approval_id: u64,
+    ) -> Lease {
+        let approval = self
+            .lease_approvals
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token isn't approved for leasing"));
+
+        require!(
+            env::predecessor_account_id() == approval.marketplace_id,
+            "Only the lease approved marketplace can create the lease"
+        );
+        require!(
+            approval.approval_id == approval_id,
+            format!(
+                "The actual approval_id {} is different from the given approval_id {}",
+                approval.approval_id, approval_id
+            )
+        );
+
+        let initial_storage = env::storage_usage();
+
+        let lease = self.internal_create_lease(
+            &token_id,
+            borrower_id.into(),
+            duration_sec * 1_000_000_000,
+            Some(approval.marketplace_id),
+        );
+
+        // the approval is used up by the lease
+        self.lease_approvals.remove(&token_id);
+
+        let (_, owner_id) = resolve_token_id(token_id);
+        let storage_usage = env::storage_usage();
+        if storage_usage > initial_storage {
+            self.internal_use_storage_balance(&owner_id, storage_usage - initial_storage);
+        } else {
+            self.internal_release_storage(owner_id, initial_storage - storage_usage);
+        }
+
+        lease
+    }
+
+    /// Token owner only, takes back the lease approval of the token before the marketplace used
+    /// it, the storage of the approval goes back to the owner
+    #[payable]
+    pub fn nft_revoke_lease(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+
+        let (_, owner_id) = resolve_token_id(token_id.clone());
+        require!(
+            env::predecessor_account_id() == owner_id,
+            "Only the token owner can revoke the lease approval"
+        );
+
+        let initial_storage = env::storage_usage();
+        require!(
+            self.lease_approvals.remove(&token_id).is_some(),
+            "Token isn't approved for leasing"
+        );
+
+        self.internal_release_storage(
+            owner_id,
+            initial_storage.saturating_sub(env::storage_usage()),
+        );
+    }
+
+    /// Token owner only, removes an expired lease
+    pub fn nft_reclaim(&mut self, token_id: TokenId) {
+        let (_, owner_id) = resolve_token_id(token_id.clone());
+        require!(
+            env::predecessor_account_id() == owner_id,
+            "Only the token owner can reclaim the token"
+        );
+
+        require!(self.leases.get(&token_id).is_some(), "Token isn't leased");
+        require!(
+            self.internal_active_lease(&token_id).is_none(),
+            "Lease hasn't expired"
+        );
+
+        self.internal_end_lease(&token_id, owner_id);
+    }
+
+    /// Token owner or borrower only, the borrower can give the token back any time and the owner
+    /// can remove the lease once it has expired. The storage of the lease goes back to the owner
+    pub fn nft_end_lease(&mut self, token_id: TokenId) {
+        let lease = self
+            .leases
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token isn't leased"));
+        let (_, owner_id) = resolve_token_id(token_id.clone());
+
+        let predecessor_id = env::predecessor_account_id();
+        require!(
+            predecessor_id == owner_id || predecessor_id == lease.borrower_id,
+            "Only the token owner or the borrower can end the lease"
+        );
+        require!(
+            predecessor_id == lease.borrower_id || self.internal_active_lease(&token_id).is_none(),
+            "Lease hasn't expired"
+        );
+
+        self.internal_end_lease(&token_id, owner_id);
+    }
+
+    pub fn nft_lease_of(&self, token_id: TokenId) -> Option<Lease> {
+        self.leases.get(&token_id)
+    }
+
+    /// Account using the token, the borrower while the token is leased and the owner otherwise
+    pub fn nft_user_of(&self, token_id: TokenId) -> Option<AccountId> {
+        if let Some(lease) = self.internal_active_lease(&token_id) {
+            return Some(lease.borrower_id);
+        }
+
+        let (class_token_id, owner_id) = try_resolve_token_id(&token_id)?;
+        self.internal_owns_token(&owner_id, &class_token_id)
+            .then_some(owner_id)
+    }
+}
+
+#[cfg(test)]
+mod lease_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+    const DAY: u64 = 86_400;
+    const DAY_NS: u64 = DAY * 1_000_000_000;
+
+    /// alice holds a gold pass and approved the rental marketplace to lease it, with a storage
+    /// balance to pay the lease with or else paying the storage of the approval with the deposit
+    fn setup_with(storage_balance: bool) -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.approve_marketplaces(vec![marketplace()]);
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        if storage_balance {
+            contract.storage_deposit(None);
+        }
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve_lease(
+            PASS.to_string(),
+            marketplace(),
+            r#"{"price_per_day":"1"}"#.to_string(),
+        );
+
+        contract
+    }
+
+    fn setup() -> Contract {
+        setup_with(true)
+    }
+
+    fn storage_used(contract: &Contract) -> u64 {
+        contract
+            .storage_deposits
+            .get(alice().as_ref())
+            .unwrap()
+            .used
+    }
+
+    fn at(predecessor: ValidAccountId, timestamp_sec: u64, deposit: Balance) {
+        let mut context = get_context(predecessor.to_string(), deposit);
+        context.block_timestamp = timestamp_sec * 1_000_000_000;
+        testing_env!(context);
+    }
+
+    #[test]
+    fn marketplace_creates_lease() {
+        let mut contract = setup();
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("on_lease_approve"));
+
+        at(marketplace(), 0, 0);
+        let lease = contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+
+        assert_eq!(lease.borrower_id, "bob.near");
+        assert_eq!(contract.nft_lease_of(PASS.to_string()), Some(lease));
+        assert!(contract.internal_owns_token(&alice().into(), &"catchcon.gold".to_string()));
+    }
+
+    #[test]
+    fn marketplace_lease_is_paid_from_the_storage_balance_of_the_owner() {
+        let mut contract = setup();
+        let approval_used = storage_used(&contract);
+
+        at(marketplace(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+        assert!(storage_used(&contract) > approval_used);
+
+        // the storage of the lease goes back to alice
+        at(bob(), 0, 0);
+        contract.nft_end_lease(PASS.to_string());
+        assert!(storage_used(&contract) < approval_used);
+
+        at(alice(), 0, 1);
+        contract.nft_approve_lease(PASS.to_string(), marketplace(), String::new());
+        assert_eq!(storage_used(&contract), approval_used);
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near must register with storage_deposit")]
+    fn marketplace_lease_without_storage_balance_fails() {
+        let mut contract = setup_with(false);
+
+        at(marketplace(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+    }
+
+    #[test]
+    fn owner_revokes_lease_approval() {
+        let mut contract = setup();
+        let approval_used = storage_used(&contract);
+
+        at(alice(), 0, 1);
+        contract.nft_revoke_lease(PASS.to_string());
+        assert!(contract.lease_approvals.get(&PASS.to_string()).is_none());
+        assert!(storage_used(&contract) < approval_used);
+
+        contract.nft_approve_lease(PASS.to_string(), marketplace(), String::new());
+        assert_eq!(storage_used(&contract), approval_used);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token isn't approved for leasing")]
+    fn revoked_lease_approval_cannot_be_used() {
+        let mut contract = setup();
+
+        at(alice(), 0, 1);
+        contract.nft_revoke_lease(PASS.to_string());
+
+        at(marketplace(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can revoke the lease approval")]
+    fn other_account_cannot_revoke_lease_approval() {
+        let mut contract = setup();
+
+        at(bob(), 0, 1);
+        contract.nft_revoke_lease(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Lease hasn't expired")]
+    fn early_reclaim_fails() {
+        let mut contract = setup();
+
+        at(marketplace(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+
+        at(alice(), DAY - 1, 0);
+        contract.nft_reclaim(PASS.to_string());
+    }
+
+    #[test]
+    fn reclaim_after_expiry() {
+        let mut contract = setup();
+
+        at(marketplace(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+
+        at(alice(), DAY, 1);
+        contract.nft_reclaim(PASS.to_string());
+        assert!(contract.nft_lease_of(PASS.to_string()).is_none());
+
+        contract.nft_transfer(carol().to_string(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is leased")]
+    fn leased_token_cannot_be_transferred() {
+        let mut contract = setup();
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.nft_lend(PASS.to_string(), bob(), DAY);
+
+        at(alice(), 0, 1);
+        contract.nft_transfer(carol().to_string(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is already leased")]
+    fn lend_and_marketplace_lease_share_invariants() {
+        let mut contract = setup();
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.nft_lend(PASS.to_string(), bob(), DAY);
+
+        at(marketplace(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), carol(), DAY, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the lease approved marketplace can create the lease")]
+    fn other_account_cannot_create_lease() {
+        let mut contract = setup();
+
+        at(bob(), 0, 0);
+        contract.marketplace_create_lease(PASS.to_string(), bob(), DAY, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is leased")]
+    fn leased_token_cannot_be_approved() {
+        let mut contract = setup();
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.nft_lend(PASS.to_string(), bob(), DAY);
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    fn lend_leases_the_token_for_duration_sec() {
+        let mut contract = setup();
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.nft_lend(PASS.to_string(), bob(), DAY);
+
+        assert_eq!(
+            contract.nft_lease_of(PASS.to_string()),
+            Some(Lease {
+                borrower_id: bob().into(),
+                expires_at: DAY_NS,
+                marketplace_id: None,
+            })
+        );
+    }
+
+    //alice leases her gold pass to bob, paying the storage from her storage balance
+    fn lease_to_bob(contract: &mut Contract) {
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.storage_deposit(None);
+
+        at(alice(), 0, 1);
+        contract.nft_lease(PASS.to_string(), bob(), DAY_NS);
+    }
+
+    #[test]
+    fn borrower_is_the_user_until_the_lease_expires() {
+        let mut contract = setup();
+        lease_to_bob(&mut contract);
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            &format!(
+                r#"EVENT_JSON:{{"standard":"catch","version":"1.1.0","event":"nft_lease_started","data":{{"token_id":"{}","owner_id":"alice.near","borrower_id":"bob.near","expires_at":{}}}}}"#,
+                PASS, DAY_NS
+            )
+        );
+        assert_eq!(
+            contract.nft_user_of(PASS.to_string()),
+            Some("bob.near".to_string())
+        );
+        assert_eq!(
+            contract.nft_token(PASS.to_string()).unwrap().owner_id,
+            "alice.near"
+        );
+
+        at(alice(), DAY, 0);
+        assert_eq!(
+            contract.nft_user_of(PASS.to_string()),
+            Some("alice.near".to_string())
+        );
+
+        contract.nft_end_lease(PASS.to_string());
+        assert!(contract.nft_lease_of(PASS.to_string()).is_none());
+        assert!(test_utils::get_logs()
+            .last()
+            .unwrap()
+            .contains(r#""event":"nft_lease_ended""#));
+    }
+
+    #[test]
+    fn borrower_ends_lease_early() {
+        let mut contract = setup();
+        lease_to_bob(&mut contract);
+
+        at(bob(), 1, 0);
+        contract.nft_end_lease(PASS.to_string());
+        assert_eq!(
+            contract.nft_user_of(PASS.to_string()),
+            Some("alice.near".to_string())
+        );
+
+        at(alice(), 1, 1);
+        contract.nft_transfer(carol().to_string(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lease hasn't expired")]
+    fn owner_cannot_end_active_lease() {
+        let mut contract = setup();
+        lease_to_bob(&mut contract);
+
+        at(alice(), DAY - 1, 0);
+        contract.nft_end_lease(PASS.to_string());
+    }
+
+    #[test]
+    fn borrowed_token_counts_for_dependencies() {
+        let mut contract = setup();
+        lease_to_bob(&mut contract);
+
+        at(carol(), 0, STORAGE_DEPOSIT);
+        contract.organise_event(
+            "partnercon".to_string(),
+            vec![TokenInfo {
+                token_id: "vip".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec!["catchcon.gold".to_string()],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        at(bob(), 0, STORAGE_DEPOSIT);
+        contract.nft_event_register(bob().to_string(), "partnercon.vip".to_string());
+        assert!(contract
+            .nft_token("partnercon.vip.bob.near".to_string())
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Lease must last at least 3600000000000 nanoseconds")]
+    fn short_lease_fails() {
+        let mut contract = setup();
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.storage_deposit(None);
+
+        at(alice(), 0, 1);
+        contract.nft_lease(PASS.to_string(), bob(), MIN_LEASE_DURATION_NS - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is frozen")]
+    fn frozen_token_cannot_be_leased() {
+        let mut contract = setup();
+
+        at(nft(), 0, 1);
+        contract.freeze_token(PASS.to_string(), "dispute".to_string());
+
+        lease_to_bob(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near is blacklisted")]
+    fn token_cannot_be_leased_to_blacklisted_account() {
+        let mut contract = setup();
+
+        at(nft(), 0, 1);
+        contract.add_to_blacklist(bob());
+
+        lease_to_bob(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn lease_while_transfers_are_paused_fails() {
+        let mut contract = setup();
+
+        at(nft(), 0, 0);
+        contract.pause(vec![PausableFeature::Transfer]);
+
+        lease_to_bob(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is soulbound")]
+    fn soulbound_token_cannot_be_leased() {
+        let mut contract = setup();
+
+        at(carol(), 0, STORAGE_DEPOSIT);
+        contract.organise_event(
+            "kyc".to_string(),
+            vec![TokenInfo {
+                token_id: "badge".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: true,
+            }],
+            None,
+        );
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.nft_event_register(alice().to_string(), "kyc.badge".to_string());
+        contract.storage_deposit(None);
+
+        at(alice(), 0, 1);
+        contract.nft_lease("kyc.badge.alice.near".to_string(), bob(), DAY_NS);
+    }
+
+    #[test]
+    #[should_panic(expected = "Passes of the event aren't transferable")]
+    fn non_transferable_pass_cannot_be_leased() {
+        let mut contract = setup();
+
+        at(carol(), 0, STORAGE_DEPOSIT);
+        create_event(&mut contract, "privatecon", &["seat"], 10);
+        contract.set_event_transferable("privatecon".to_string(), false);
+
+        at(alice(), 0, STORAGE_DEPOSIT);
+        contract.nft_event_register(alice().to_string(), "privatecon.seat".to_string());
+        contract.storage_deposit(None);
+
+        at(alice(), 0, 1);
+        contract.nft_lease("privatecon.seat.alice.near".to_string(), bob(), DAY_NS);
+    }
+}
+
+#[cfg(test)]
+mod lease_simulation {
+    use super::*;
+    use crate::simulation::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::serde_json::Value;
+
+    const PASS: &str = "catchcon.gold.alice.near";
+    const DAY: u64 = 86_400;
+    const GAS_FOR_RENT: Gas = 10_000_000_000_000;
+
+    #[ext_contract(ext_nft_lease)]
+    trait NftLease {
+        fn marketplace_create_lease(
+            &mut self,
+            token_id: TokenId,
+            borrower_id: AccountId,
+            duration_sec: u64,
+            approval_id: u64,
+        ) -> Lease;
+    }
+
+    #[ext_contract(ext_rental_self)]
+    trait RentalSelf {
+        fn resolve_rent(&mut self, token_id: TokenId, borrower_id: AccountId) -> bool;
+    }
+
+    /// Rental marketplace which keeps the lease approvals it is told about and leases the token
+    /// to whoever rents it first
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct RentalMarketplace {
+        nft_id: AccountId,
+        //approval id and terms of the tokens which can be rented
+        offers: HashMap<TokenId, (u64, String)>,
+        //borrowers of the tokens rented through the marketplace
+        rentals: HashMap<TokenId, AccountId>,
+    }
+
+    impl RentalMarketplace {
+        fn new(nft_id: ValidAccountId) -> Self {
+            Self {
+                nft_id: nft_id.into(),
+                offers: HashMap::new(),
+                rentals: HashMap::new(),
+            }
+        }
+
+        fn on_lease_approve(&mut self, token_id: TokenId, approval_id: u64, terms_msg: String) {
+            require!(
+                env::predecessor_account_id() == self.nft_id,
+                "Only the NFT contract approves leases"
+            );
+            self.offers.insert(token_id, (approval_id, terms_msg));
+        }
+
+        fn rent(&mut self, token_id: TokenId, duration_sec: u64) -> Promise {
+            let (approval_id, _) = self
+                .offers
+                .get(&token_id)
+                .cloned()
+                .unwrap_or_else(|| env::panic(b"Token isn't offered"));
+
+            ext_nft_lease::marketplace_create_lease(
+                token_id.clone(),
+                env::predecessor_account_id(),
+                duration_sec,
+                approval_id,
+                &self.nft_id,
+                0,
+                GAS_FOR_RENT,
+            )
+            .then(ext_rental_self::resolve_rent(
+                token_id,
+                env::predecessor_account_id(),
+                &env::current_account_id(),
+                0,
+                GAS_FOR_RENT,
+            ))
+        }
+
+        //the offer stays up if the NFT contract refused the lease
+        fn resolve_rent(&mut self, token_id: TokenId, borrower_id: AccountId) -> bool {
+            if !matches!(env::promise_result(0), PromiseResult::Successful(_)) {
+                return false;
+            }
+
+            self.offers.remove(&token_id);
+            self.rentals.insert(token_id, borrower_id);
+            true
+        }
+    }
+
+    impl Runnable for RentalMarketplace {
+        fn run(&mut self, method: &str, args: &Value) -> Outcome {
+            match method {
+                "on_lease_approve" => {
+                    self.on_lease_approve(
+                        arg(args, "token_id"),
+                        arg(args, "approval_id"),
+                        arg(args, "terms_msg"),
+                    );
+                    Outcome::Value(vec![])
+                }
+                "resolve_rent" => Outcome::json(
+                    &self.resolve_rent(arg(args, "token_id"), arg(args, "borrower_id")),
+                ),
+                _ => {
+                    env::panic(format!("{} is not a method of the marketplace", method).as_bytes())
+                }
+            }
+        }
+    }
+
+    /// alice holds a gold pass and a storage balance to pay leases with, the rental marketplace
+    /// is Catch approved
+    fn setup() -> Chain {
+        let mut chain = Chain::default();
+
+        chain.deploy(nft(), create_contract);
+        chain.deploy(marketplace(), || RentalMarketplace::new(nft()));
+        chain.call(nft(), nft(), 0, |nft: &mut Contract| {
+            nft.approve_marketplaces(vec![marketplace()])
+        });
+        chain.call(carol(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            create_event(nft, "catchcon", &["gold"], 10)
+        });
+        chain.call(alice(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.nft_event_register(alice().to_string(), "catchcon.gold".to_string())
+        });
+        chain.call(alice(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.storage_deposit(None);
+        });
+
+        chain
+    }
+
+    fn approve_lease(chain: &mut Chain) {
+        chain.call(alice(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.nft_approve_lease(
+                PASS.to_string(),
+                marketplace(),
+                r#"{"price_per_day":"1"}"#.to_string(),
+            );
+        });
+    }
+
+    fn rent(chain: &mut Chain, borrower_id: ValidAccountId, duration_sec: u64) {
+        chain.call(
+            borrower_id,
+            marketplace(),
+            0,
+            |marketplace: &mut RentalMarketplace| {
+                marketplace.rent(PASS.to_string(), duration_sec);
+            },
+        );
+    }
+
+    fn lease(chain: &mut Chain) -> Option<Lease> {
+        chain.view(nft(), |nft: &Contract| nft.nft_lease_of(PASS.to_string()))
+    }
+
+    #[test]
+    fn approval_is_forwarded_to_the_marketplace() {
+        let mut chain = setup();
+
+        approve_lease(&mut chain);
+
+        assert!(chain.receipt("on_lease_approve").error.is_none());
+        assert_eq!(
+            chain.view(marketplace(), |marketplace: &RentalMarketplace| {
+                marketplace.offers.get(PASS).cloned()
+            }),
+            Some((0, r#"{"price_per_day":"1"}"#.to_string()))
+        );
+    }
+
+    #[test]
+    fn marketplace_leases_the_token_to_the_renter() {
+        let mut chain = setup();
+        approve_lease(&mut chain);
+
+        rent(&mut chain, bob(), DAY);
+
+        assert!(chain.receipt("marketplace_create_lease").error.is_none());
+        assert_eq!(
+            lease(&mut chain),
+            Some(Lease {
+                borrower_id: bob().into(),
+                expires_at: chain.block_timestamp + DAY * 1_000_000_000,
+                marketplace_id: Some(marketplace().into()),
+            })
+        );
+        assert_eq!(
+            chain.view(nft(), |nft: &Contract| nft.nft_user_of(PASS.to_string())),
+            Some(bob().into())
+        );
+        assert_eq!(
+            chain.view(marketplace(), |marketplace: &RentalMarketplace| {
+                marketplace.rentals.get(PASS).cloned()
+            }),
+            Some(bob().into())
+        );
+    }
+
+    #[test]
+    fn approval_is_used_up_by_the_lease() {
+        let mut chain = setup();
+        approve_lease(&mut chain);
+        rent(&mut chain, bob(), DAY);
+
+        // the marketplace can't lease the token again with the same approval
+        chain.call(
+            marketplace(),
+            marketplace(),
+            0,
+            |marketplace: &mut RentalMarketplace| {
+                marketplace
+                    .offers
+                    .insert(PASS.to_string(), (0, String::new()));
+            },
+        );
+        rent(&mut chain, carol(), DAY);
+
+        assert!(chain
+            .receipt("marketplace_create_lease")
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Token isn't approved for leasing"));
+        assert_eq!(lease(&mut chain).unwrap().borrower_id, "bob.near");
+    }
+
+    #[test]
+    fn refused_lease_keeps_the_offer() {
+        let mut chain = setup();
+        approve_lease(&mut chain);
+
+        // shorter than the minimum duration
+        rent(&mut chain, bob(), 60);
+
+        assert!(chain
+            .receipt("marketplace_create_lease")
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Lease must last at least"));
+        assert_eq!(lease(&mut chain), None);
+
+        rent(&mut chain, bob(), DAY);
+        assert_eq!(lease(&mut chain).unwrap().borrower_id, "bob.near");
+    }
+
+    #[test]
+    fn other_marketplace_cannot_use_the_approval() {
+        let mut chain = setup();
+        approve_lease(&mut chain);
+
+        let other = ValidAccountId::try_from("othermarket.near").unwrap();
+        chain.deploy(other.clone(), || RentalMarketplace::new(nft()));
+        chain.call(
+            other.clone(),
+            other.clone(),
+            0,
+            |marketplace: &mut RentalMarketplace| {
+                marketplace
+                    .offers
+                    .insert(PASS.to_string(), (0, String::new()));
+            },
+        );
+        chain.call(bob(), other, 0, |marketplace: &mut RentalMarketplace| {
+            marketplace.rent(PASS.to_string(), DAY);
+        });
+
+        assert!(chain
+            .receipt("marketplace_create_lease")
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Only the lease approved marketplace can create the lease"));
+        assert_eq!(lease(&mut chain), None);
+    }
+}
@@ -4,50 +4,126 @@
 * Enumeration NEP-181
 * Metadata NEP-177
 * Royalties and Payout NEP-199
+* Storage Management NEP-145
 *
 * The aim of the contract is to provide a basic implementation of the improved function NFT standard.
 *
 * lib.rs is the main entry point.
 * nft_core.rs implements NEP-171 standard handles core function regarding nft transfers [Transfers only among users who satisfy dependencies]
+* dependencies.rs verifies dependencies on tokens of partner NFT contracts when transferring
 * approval.rs implements Approval Management NEP-178 for management of approvals of transfer of NFT and   also implements Marketplace Approval System.
+* burn.rs destroys tokens and refunds their storage
+* storage.rs implements Storage Management NEP-145 so that approvals and leases are paid from a storage deposit
+* composable.rs attaches child tokens to a parent token so that they are transferred together
+* lease.rs lends tokens directly or through approved rental marketplaces
+* locks.rs locks tokens in place for staking contracts, which stake them without taking custody
+* marketplace_sync.rs keeps the approved marketplaces in sync with the marketplace contracts
 * enumeration.rs implements NEP-181 standard for getter functions to retrieve data off-chain
 * mint.rs implements nft_minting functionality
+* series.rs defines drops whose tokens share one metadata
 * metadata.rs implements NEP-177 standard for both Contract and NFT-specific metadata.
+* royalty.rs implements royalty payouts NEP-199
+* affiliate.rs pays CATCH FT to the referrers of sales
+* upgrades.rs levels up tokens for CATCH FT routed to the treasury to be burnt
+* renewals.rs extends the expiry of tokens for NEAR or CATCH FT
+* subscriptions.rs renews season passes paid with the CATCH FT
+* rewards.rs pays CATCH FT rewards to attendees on check-in
+* registry.rs validates receivers against the Catch accounts registry
+* hook.rs notifies a configurable hook contract of mints and transfers
 * indexing.rs extends NEP-297 for better indexing
 * events.rs implements the functionality related to events such as issuing NFT passes for an event
+* registration.rs sells passes of events to Catch players for NEAR
+* waitlist.rs queues Catch players for passes of sold out events
+* ft_payments.rs implements ft_on_transfer so that tokens can be bought with the CATCH FT
+* migrate.rs maps the state written by older versions of the contract to the current layout
+* simulation.rs runs the contract together with the CATCH FT contract and mock contracts in tests
 * internal.rs contains internal methods.
 **/
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::collections::{
+    LazyOption, LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector,
+};
 use near_sdk::json_types::{Base58PublicKey, Base64VecU8, ValidAccountId, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    assert_one_yocto, env, ext_contract, near_bindgen, AccountId, Balance, CryptoHash, Gas,
-    PanicOnDefault, Promise,
+    assert_one_yocto, env, ext_contract, near_bindgen, AccountId, Balance, BlockHeight,
+    CryptoHash, Gas, PanicOnDefault, Promise, PromiseOrValue, PromiseResult,
 };
 
 use std::collections::HashMap;
 use std::mem::size_of;
 
+pub use crate::affiliate::*;
 pub use crate::approval::*;
+pub use crate::dependencies::*;
 pub use crate::events::*;
+pub use crate::freeze::*;
+pub use crate::ft_payments::*;
+pub use crate::hook::*;
 pub use crate::indexing::*;
 use crate::internal::*;
+pub use crate::lease::*;
+pub use crate::locks::*;
+pub use crate::marketplace_sync::*;
 pub use crate::metadata::*;
-pub use crate::nft_core::NonFungibleTokenCore;
+pub use crate::migrate::*;
+pub use crate::mint::*;
+pub use crate::nft_core::{NonFungibleTokenCore, NonFungibleTokenResolver};
+pub use crate::pause::*;
+pub use crate::registration::*;
+pub use crate::registry::*;
+pub use crate::renewals::*;
+pub use crate::rewards::*;
+pub use crate::royalty::*;
+pub use crate::series::*;
+pub use crate::storage::*;
+pub use crate::subscriptions::*;
+pub use crate::upgrades::*;
 use crate::utils::*;
 pub use crate::view::*;
 pub use view::*;
 
+mod account_keys;
+mod affiliate;
 mod approval;
+mod blacklist;
+mod burn;
+mod composable;
+mod dependencies;
 mod enumeration;
 mod events;
+mod freeze;
+mod ft_payments;
+mod funding;
+mod hook;
 mod indexing;
 mod internal;
+mod lease;
+mod locks;
+mod marketplace_sync;
 mod metadata;
+mod migrate;
+mod mint;
+mod minters;
 mod nft_core;
+mod ownership;
+mod pause;
+mod recovery;
+mod registration;
+mod registry;
+mod renewals;
+mod rewards;
+mod royalty;
+mod series;
+#[cfg(test)]
+mod simulation;
+mod soulbound;
+mod storage;
+mod subscriptions;
+mod upgrades;
 mod utils;
 mod view;
+mod waitlist;
 
 const CATCH_MARKETPLACE_CONTRACT: &str = "marketplace.catchlabs.near";
 
@@ -57,16 +133,70 @@ const CATCH_MARKETPLACE_CONTRACT_TESTNET: &str = "marketplace.catchlabs.testnet"
 
 const BASE_STORAGE_COST: Balance = 10_000_000_000_000_000_000_000; // this is equal to 0.01 NEAR
 
+// reference document of new_default_meta, inlined as a data url so its hash can be checked
+const DEFAULT_REFERENCE_DOCUMENT: &str = r#"{"name":"Catch","description":"Event passes and collectibles of Catch Labs","external_url":"https://catchlabs.io"}"#;
+
+const DEFAULT_REFERENCE: &str = "data:application/json;base64,eyJuYW1lIjoiQ2F0Y2giLCJkZXNjcmlwdGlvbiI6IkV2ZW50IHBhc3NlcyBhbmQgY29sbGVjdGlibGVzIG9mIENhdGNoIExhYnMiLCJleHRlcm5hbF91cmwiOiJodHRwczovL2NhdGNobGFicy5pbyJ9";
+
 #[derive(BorshSerialize)]
 pub enum StorageKey {
     TokensPerOwner,
     TokenPerOwnerInner { account_id_hash: CryptoHash },
     TokensById,
+    //approvals kept inside the token classes before approvals_by_token_id, see
+    //migrate_token_approvals
     ApprovedAccountsPerToken { token_id_hash: CryptoHash },
     TokenMetadataById,
     EventsById,
+    //whitelist kept before marketplace approvals had an expiry, see migrate_approved_marketplaces
     ApprovedMarketplaces,
     NFTContractMetadata,
+    FtPriceByTokenId,
+    FtProceedsByEventId,
+    FtPaidByEventAccount,
+    CheckedInAtByTokenId,
+    RewardPoolByEventId,
+    PendingRewards,
+    RegistryPlayers,
+    PendingMints,
+    MarketplaceSync,
+    AffiliateRewards,
+    Leases,
+    LeaseApprovals,
+    SubscriptionPlans,
+    Subscriptions,
+    PassExpiryByTokenId,
+    StorageDeposits,
+    UpgradeConfigByTokenId,
+    LevelByTokenId,
+    MetadataHistoryByTokenId,
+    ParentByTokenId,
+    ChildrenByTokenId,
+    RenewalConfigByTokenId,
+    SeriesById,
+    TokensPerSeries,
+    TokensPerSeriesInner { series_id_hash: CryptoHash },
+    PassesPerEvent,
+    PassesPerEventInner { event_id_hash: CryptoHash },
+    MarketplaceApprovals,
+    Minters,
+    FrozenTokens,
+    Blacklist,
+    RecoveriesByTokenId,
+    EventsPerOrganiser,
+    EventsPerOrganiserInner { account_id_hash: CryptoHash },
+    TokensPerEvent,
+    TokensPerEventInner { event_id_hash: CryptoHash },
+    Waitlists,
+    WaitlistsInner { event_id_hash: CryptoHash },
+    WaitlistEscrow,
+    ApprovalsByTokenId,
+    VersionedTokensById,
+    TokenCountPerOwner,
+    LeasedCopiesByBorrower,
+    LocksByTokenId,
+    NearProceedsByEventId,
+    NearPaidByEventAccount,
 }
 
 #[near_bindgen]
@@ -79,7 +209,7 @@ pub struct Contract {
     pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
 
     //keeps track of the token struct for a given token ID
-    pub tokens_by_id: LookupMap<TokenId, Token>,
+    pub tokens_by_id: VersionedTokens,
 
     //keeps track of the token metadata for a given token ID
     pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
@@ -87,25 +217,202 @@ pub struct Contract {
     //keeps track of events for a given event ID
     pub events_by_id: UnorderedMap<EventId, Event>,
 
-    //keeps track of the approved marketplace contracts
-    pub approved_marketplaces: UnorderedSet<AccountId>,
+    //keeps track of the approved marketplace contracts and until when they are approved
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
 
     //keeps track of the metadata for the contract
     pub metadata: LazyOption<NFTContractMetadata>,
+
+    //FT contract accepted as payment for mints
+    pub payment_token_id: Option<AccountId>,
+
+    //keeps track of the price in the payment token for a given token ID
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+
+    //keeps track of the FT collected for a given event ID, owed to the organiser
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+
+    //keeps track of the FT paid by a given account for passes of a given event, refundable on cancellation
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+
+    //keeps track of when a given pass (event_id.token_id.owner_id) was checked in, Unix epoch in milliseconds
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+
+    //keeps track of the FT funded by the organiser for check-in rewards of a given event ID
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+
+    //keeps track of check-in rewards which couldn't be delivered, claimable by the attendee
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+
+    //royalty every new token starts with, events and tokens can override it
+    pub default_royalty: Royalty,
+
+    //Catch accounts contract, source of truth for who is a Catch player
+    pub accounts_registry: Option<AccountId>,
+
+    //if true mints ask the registry with a cross contract call before giving the token
+    pub registry_async_validation: bool,
+
+    //Catch players pushed by the registry, used to validate receivers synchronously
+    pub registry_players: LookupSet<AccountId>,
+
+    //contract notified on every mint and transfer, best effort
+    pub transfer_hook: Option<AccountId>,
+
+    //disables the transfer hook after consecutive failures
+    pub hook_breaker: HookBreaker,
+
+    //tokens of nft_mint waiting for the account creation, excluded from enumeration and transfers
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+
+    //last whitelist notification sent to each marketplace
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+
+    //referrers of sales earn the payment token out of the affiliate pool
+    pub affiliate_program: Option<AffiliateProgram>,
+
+    pub affiliate_pool: Balance,
+
+    //affiliate rewards which couldn't be delivered, claimable by the affiliate
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+
+    //leases by TokenId of form event_id.token_id.owner_id
+    pub leases: LookupMap<TokenId, Lease>,
+
+    //rental marketplaces approved to create a lease of the token
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+
+    //season pass plans paid in the payment token
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+
+    //expiry of copies renewed by subscriptions or nft_renew by TokenId of form event_id.token_id.owner_id,
+    //overrides the expiry of the token
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+
+    //NEP-145 storage deposits which pay for approvals and leases
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+
+    //level-up costs of token classes paid in the payment token
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+
+    //receives the upgrade payments and burns them
+    pub upgrade_treasury_id: Option<AccountId>,
+
+    //upgrade payments which couldn't be sent to the treasury
+    pub unrouted_upgrade_payments: Balance,
+
+    //levels by TokenId of form event_id.token_id.owner_id
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+
+    //attached tokens by TokenId of form event_id.token_id.owner_id, children travel with their parent
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+
+    //prices of renewing the copies of token classes
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+
+    //drops whose tokens of form series_id.index share the metadata of the series
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+
+    //accounts holding at least one pass of a given event ID
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+
+    //besides the owner, extends the expiry of token classes
+    pub renewer_id: Option<AccountId>,
+
+    //live approvals an owner can have on a token at once
+    pub max_approvals_per_token: u32,
+
+    //features whose entry points are paused
+    pub pause_status: PauseStatus,
+
+    //account proposed as the new owner, it becomes the owner once it accepts
+    pub pending_owner_id: Option<AccountId>,
+
+    //accounts which can mint besides the owner
+    pub minters: UnorderedSet<AccountId>,
+
+    //index of the next token of nft_mint_auto
+    pub next_token_id: u64,
+
+    //cap of the tokens in circulation set at init, it can't be changed afterwards
+    pub max_total_supply: Option<u64>,
+
+    //tokens minted and not burnt, reserved and pending tokens included
+    pub minted_count: u64,
+
+    //copies (event_id.token_id.owner_id) held while a dispute is investigated
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+
+    //accounts which can't send, receive or approve tokens, tokens they hold stay with them
+    pub blacklist: UnorderedSet<AccountId>,
+
+    //recovery transfers per copy (event_id.token_id.owner_id), the count follows the copy
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+
+    //balance the new account of a mint is funded with, BASE_STORAGE_COST until changed
+    pub account_funding_amount: Balance,
+
+    //events organised by a given account, follows transfer_event_organizer
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+
+    //passes (event_id.token_id.owner_id) held of a given event ID
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+
+    //share of the price of passes sold with register_for_event kept for the owner, in basis points
+    pub platform_fee_bps: u16,
+
+    //accounts queued in order for a pass of a sold out event
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+
+    //deposit held for a queued account until it is promoted or leaves the waitlist
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+
+    //approvals of a copy (event_id.token_id.owner_id), next_approval_id follows the copy
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+
+    //tokens held by a given account, kept so the count doesn't need the set of the account
+    pub token_count_per_owner: LookupMap<AccountId, u64>,
+
+    //copies (event_id.token_id.owner_id) of a token class leased to a given account
+    pub leased_copies_by_borrower: LookupMap<(AccountId, TokenId), Vec<TokenId>>,
+
+    //copies (event_id.token_id.owner_id) locked in place by a staking contract, only it unlocks them
+    pub locks_by_token_id: LookupMap<TokenId, AccountId>,
+
+    //keeps track of the NEAR paid for passes of a given event with register_for_event, held until the event ends
+    pub near_proceeds_by_event_id: LookupMap<EventId, Balance>,
+
+    //keeps track of the NEAR paid by a given account for passes of a given event, refundable on cancellation
+    pub near_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+
+    //FT of the payment token owed as proceeds, reward pools, rewards and upgrade payments, transfers in flight included
+    pub ft_liabilities: Balance,
 }
 
 #[near_bindgen]
 impl Contract {
-    /// Initialize The Contract
+    /// Initialize The Contract, max_total_supply caps the tokens in circulation for good
     #[init]
-    pub fn new(owner_id: ValidAccountId, metadata: NFTContractMetadata) -> Self {
+    pub fn new(
+        owner_id: ValidAccountId,
+        metadata: NFTContractMetadata,
+        max_total_supply: Option<u64>,
+    ) -> Self {
         metadata.assert_valid_metadata();
         let mut this = Self {
             owner_id: owner_id.into(),
 
             tokens_per_owner: LookupMap::new(StorageKey::TokensPerOwner.try_to_vec().unwrap()),
 
-            tokens_by_id: LookupMap::new(StorageKey::TokensById.try_to_vec().unwrap()),
+            tokens_by_id: VersionedTokens::new(),
 
             token_metadata_by_id: UnorderedMap::new(
                 StorageKey::TokenMetadataById.try_to_vec().unwrap(),
@@ -113,25 +420,177 @@ impl Contract {
 
             events_by_id: UnorderedMap::new(StorageKey::EventsById.try_to_vec().unwrap()),
 
-            approved_marketplaces: UnorderedSet::new(
-                StorageKey::ApprovedMarketplaces.try_to_vec().unwrap(),
+            approved_marketplaces: UnorderedMap::new(
+                StorageKey::MarketplaceApprovals.try_to_vec().unwrap(),
             ),
 
             metadata: LazyOption::new(
                 StorageKey::NFTContractMetadata.try_to_vec().unwrap(),
                 Some(&metadata),
             ),
-        };
 
-        let catch_marketplace = AccountId::from(CATCH_MARKETPLACE_CONTRACT_TESTNET);
+            payment_token_id: None,
+
+            ft_price_by_token_id: UnorderedMap::new(
+                StorageKey::FtPriceByTokenId.try_to_vec().unwrap(),
+            ),
+
+            ft_proceeds_by_event_id: LookupMap::new(
+                StorageKey::FtProceedsByEventId.try_to_vec().unwrap(),
+            ),
+
+            ft_paid_by_event_account: LookupMap::new(
+                StorageKey::FtPaidByEventAccount.try_to_vec().unwrap(),
+            ),
+
+            checked_in_at_by_token_id: LookupMap::new(
+                StorageKey::CheckedInAtByTokenId.try_to_vec().unwrap(),
+            ),
+
+            reward_pool_by_event_id: LookupMap::new(
+                StorageKey::RewardPoolByEventId.try_to_vec().unwrap(),
+            ),
+
+            pending_rewards: LookupMap::new(StorageKey::PendingRewards.try_to_vec().unwrap()),
+
+            default_royalty: HashMap::new(),
+
+            accounts_registry: None,
+
+            registry_async_validation: false,
+
+            registry_players: LookupSet::new(StorageKey::RegistryPlayers.try_to_vec().unwrap()),
+
+            transfer_hook: None,
+
+            hook_breaker: HookBreaker::default(),
+
+            pending_mints: UnorderedMap::new(StorageKey::PendingMints.try_to_vec().unwrap()),
+
+            marketplace_sync: LookupMap::new(StorageKey::MarketplaceSync.try_to_vec().unwrap()),
+
+            affiliate_program: None,
+
+            affiliate_pool: 0,
+
+            affiliate_rewards: LookupMap::new(StorageKey::AffiliateRewards.try_to_vec().unwrap()),
+
+            leases: LookupMap::new(StorageKey::Leases.try_to_vec().unwrap()),
+
+            lease_approvals: LookupMap::new(StorageKey::LeaseApprovals.try_to_vec().unwrap()),
+
+            subscription_plans: UnorderedMap::new(
+                StorageKey::SubscriptionPlans.try_to_vec().unwrap(),
+            ),
+
+            subscriptions: LookupMap::new(StorageKey::Subscriptions.try_to_vec().unwrap()),
+
+            pass_expiry_by_token_id: LookupMap::new(
+                StorageKey::PassExpiryByTokenId.try_to_vec().unwrap(),
+            ),
+
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits.try_to_vec().unwrap()),
+
+            upgrade_config_by_token_id: LookupMap::new(
+                StorageKey::UpgradeConfigByTokenId.try_to_vec().unwrap(),
+            ),
+
+            upgrade_treasury_id: None,
+
+            unrouted_upgrade_payments: 0,
+
+            level_by_token_id: LookupMap::new(StorageKey::LevelByTokenId.try_to_vec().unwrap()),
+
+            metadata_history_by_token_id: LookupMap::new(
+                StorageKey::MetadataHistoryByTokenId.try_to_vec().unwrap(),
+            ),
+
+            parent_by_token_id: LookupMap::new(StorageKey::ParentByTokenId.try_to_vec().unwrap()),
+
+            children_by_token_id: LookupMap::new(
+                StorageKey::ChildrenByTokenId.try_to_vec().unwrap(),
+            ),
+
+            renewal_config_by_token_id: LookupMap::new(
+                StorageKey::RenewalConfigByTokenId.try_to_vec().unwrap(),
+            ),
 
-        this.approved_marketplaces.insert(&catch_marketplace);
+            series_by_id: UnorderedMap::new(StorageKey::SeriesById.try_to_vec().unwrap()),
+
+            tokens_per_series: LookupMap::new(StorageKey::TokensPerSeries.try_to_vec().unwrap()),
+
+            passes_per_event: LookupMap::new(StorageKey::PassesPerEvent.try_to_vec().unwrap()),
+
+            renewer_id: None,
+
+            max_approvals_per_token: DEFAULT_MAX_APPROVALS_PER_TOKEN,
+
+            pause_status: PauseStatus::default(),
+
+            pending_owner_id: None,
+
+            minters: UnorderedSet::new(StorageKey::Minters.try_to_vec().unwrap()),
+
+            next_token_id: 1,
+
+            max_total_supply,
+
+            minted_count: 0,
+
+            frozen_tokens: LookupMap::new(StorageKey::FrozenTokens.try_to_vec().unwrap()),
+
+            blacklist: UnorderedSet::new(StorageKey::Blacklist.try_to_vec().unwrap()),
+
+            recoveries_by_token_id: LookupMap::new(
+                StorageKey::RecoveriesByTokenId.try_to_vec().unwrap(),
+            ),
+
+            account_funding_amount: BASE_STORAGE_COST,
+
+            events_per_organiser: LookupMap::new(
+                StorageKey::EventsPerOrganiser.try_to_vec().unwrap(),
+            ),
+
+            tokens_per_event: LookupMap::new(StorageKey::TokensPerEvent.try_to_vec().unwrap()),
+
+            platform_fee_bps: 0,
+
+            waitlists: LookupMap::new(StorageKey::Waitlists.try_to_vec().unwrap()),
+
+            waitlist_escrow: LookupMap::new(StorageKey::WaitlistEscrow.try_to_vec().unwrap()),
+
+            approvals_by_token_id: LookupMap::new(
+                StorageKey::ApprovalsByTokenId.try_to_vec().unwrap(),
+            ),
+
+            token_count_per_owner: LookupMap::new(
+                StorageKey::TokenCountPerOwner.try_to_vec().unwrap(),
+            ),
+
+            leased_copies_by_borrower: LookupMap::new(
+                StorageKey::LeasedCopiesByBorrower.try_to_vec().unwrap(),
+            ),
+
+            locks_by_token_id: LookupMap::new(StorageKey::LocksByTokenId.try_to_vec().unwrap()),
+
+            near_proceeds_by_event_id: LookupMap::new(
+                StorageKey::NearProceedsByEventId.try_to_vec().unwrap(),
+            ),
+
+            near_paid_by_event_account: LookupMap::new(
+                StorageKey::NearPaidByEventAccount.try_to_vec().unwrap(),
+            ),
+            ft_liabilities: 0,
+        };
+
+        this.internal_approve_marketplace(catch_marketplace_of_network(), None);
+        Self::internal_write_state_version();
 
         this
     }
 
     #[init]
-    pub fn new_default_meta(owner_id: ValidAccountId) -> Self {
+    pub fn new_default_meta(owner_id: ValidAccountId, max_total_supply: Option<u64>) -> Self {
         //calls the other function "new: with some default metadata and the owner_id passed in
         Self::new(
             owner_id,
@@ -141,60 +600,11 @@ impl Contract {
                 symbol: "CATCH".to_string(),
                 icon: Some("data:image/svg+xml;base64,PHN2ZyBpZD0iQ2FwYV8xIiBkYXRhLW5hbWU9IkNhcGEgMSIgeG1sbnM9Imh0dHA6Ly93d3cudzMub3JnLzIwMDAvc3ZnIiB2aWV3Qm94PSIwIDAgMTA3OC41NSAxMDgwIj48ZGVmcz48c3R5bGU+LmNscy0xe2ZpbGw6Izc4NzFmZjt9PC9zdHlsZT48L2RlZnM+PHBhdGggZD0iTTczMSwzNDcuNzJINDI2LjU3YTc4Ljg4LDc4Ljg4LDAsMCwwLTc5LDc5LjA3VjY1My4yNGE3OC44Niw3OC44NiwwLDAsMCw3OSw3OUg3MzFWNjQ5SDQzMC4zMlY0MzEuMDVINzMxWiIvPjxwYXRoIGNsYXNzPSJjbHMtMSIgZD0iTTY2Miw0ODFhNTksNTksMCwwLDAtNTksNTloMGE1OSw1OSwwLDAsMCw1OSw1OWg1LjYzYTU5LDU5LDAsMCwwLDU5LTU5aDBhNTksNTksMCwwLDAtNTktNTlaIi8+PC9zdmc+".to_string()),
                 base_uri: "ipfs".to_string(),
-                reference: "ipfs://example.com/hash".to_string(),
-                reference_hash: Base64VecU8::from([5_u8; 32].to_vec()),
+                reference: DEFAULT_REFERENCE.to_string(),
+                reference_hash: Base64VecU8::from(env::sha256(DEFAULT_REFERENCE_DOCUMENT.as_bytes())),
+                paused: None,
             },
+            max_total_supply,
         )
     }
-
-    // #[payable]
-    // pub fn nft_mint(
-    //     &mut self,
-    //     receiver_id: AccountId,
-    //     token_id: TokenId,
-    //     token_metadata: TokenMetadata,
-    //     public_key: Base58PublicKey,
-    // ) {
-    //     self.assert_owner();
-
-    //     let initial_storage = env::storage_usage();
-
-    //     assert_valid_catch_user_account_pattern(&receiver_id);
-
-    //     Promise::new(receiver_id.clone())
-    //         .create_account()
-    //         .transfer(BASE_STORAGE_COST)
-    //         .add_full_access_key(public_key.into());
-
-    //     let token = Token {
-    //         token_id: token_id.clone(),
-    //         copies_minted: 1,
-    //         max_copies: 1,
-    //         expires_at: token_metadata.expires_at,
-    //         token_dependency_by_id: vec![],
-    //         event_dependency_by_id: vec![],
-    //         account_approval_info_per_owner: LookupMap::new(
-    //             StorageKey::ApprovedAccountsPerToken {
-    //                 token_id_hash: hash_id(&token_id),
-    //             }
-    //             .try_to_vec()
-    //             .unwrap(),
-    //         ),
-    //     };
-
-    //     require!(
-    //         self.tokens_by_id.insert(&token_id, &token).is_none(),
-    //         "Token Already exists"
-    //     );
-
-    //     self.token_metadata_by_id.insert(&token_id, &token_metadata);
-
-    //     self.internal_add_token_to_owner(&receiver_id, &token_id);
-
-    //     // refunding deposit
-    //     let storage_used = env::storage_usage() - initial_storage;
-    //     let storage_cost = env::storage_byte_cost() * storage_used as u128;
-    //     let cost = storage_cost + BASE_STORAGE_COST;
-    //     Promise::new(receiver_id).transfer(env::attached_deposit() - cost);
-    // }
 }
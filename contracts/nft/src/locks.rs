@@ -0,0 +1,429 @@
+use crate::*;
+
+const GAS_FOR_RESOLVE_LOCK: Gas = 10_000_000_000_000;
+const GAS_FOR_NFT_LOCK_CALL: Gas = 25_000_000_000_000 + GAS_FOR_RESOLVE_LOCK;
+
+#[ext_contract(ext_nft_locker)]
+pub trait NftLocker {
+    //returns true if the token should be unlocked right away
+    fn nft_on_lock(
+        &mut self,
+        owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+}
+
+#[ext_contract(ext_locks_self)]
+trait LocksResolver {
+    fn resolve_lock(&mut self, token_id: TokenId, locker_id: AccountId) -> bool;
+}
+
+impl Contract {
+    //removes the lock of the copy and gives its storage back to the owner, who paid for it
+    fn internal_remove_lock(&mut self, token_id: &TokenId) {
+        let initial_storage = env::storage_usage();
+
+        let locker_id = match self.locks_by_token_id.remove(token_id) {
+            Some(locker_id) => locker_id,
+            None => return,
+        };
+        let (_, owner_id) = resolve_token_id(token_id.clone());
+
+        self.internal_release_storage(
+            owner_id.clone(),
+            initial_storage.saturating_sub(env::storage_usage()),
+        );
+
+        CatchEventLogVariant::NftUnlocked(LockLog {
+            token_id: token_id.clone(),
+            owner_id,
+            locker_id,
+        })
+        .emit();
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Token owner only, locks the copy of form event_id.token_id.owner_id in place and calls
+    /// nft_on_lock on the locker. The owner keeps the copy but can't transfer, approve, lease or
+    /// burn it until the locker unlocks it, so that staking contracts don't need custody of the
+    /// copies. The copy is unlocked again if the locker returns true or the call fails
+    #[payable]
+    pub fn nft_lock_call(
+        &mut self,
+        token_id: TokenId,
+        locker_id: ValidAccountId,
+        msg: String,
+    ) -> Promise {
+        assert_at_least_one_yocto();
+        let initial_storage = env::storage_usage();
+
+        let locker_id: AccountId = locker_id.into();
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        require!(
+            env::predecessor_account_id() == owner_id,
+            "Only the token owner can lock the token"
+        );
+        require!(
+            self.internal_owns_token(&owner_id, &class_token_id),
+            "No token"
+        );
+        require!(
+            self.locks_by_token_id.get(&token_id).is_none(),
+            "Token is already locked"
+        );
+        require!(
+            self.frozen_tokens.get(&token_id).is_none(),
+            "Token is frozen"
+        );
+        require!(
+            self.internal_active_lease(&token_id).is_none(),
+            "Token is leased"
+        );
+        self.assert_not_blacklisted(&owner_id);
+
+        self.locks_by_token_id.insert(&token_id, &locker_id);
+
+        CatchEventLogVariant::NftLocked(LockLog {
+            token_id: token_id.clone(),
+            owner_id: owner_id.clone(),
+            locker_id: locker_id.clone(),
+        })
+        .emit();
+
+        self.internal_pay_storage(env::storage_usage().saturating_sub(initial_storage));
+
+        ext_nft_locker::nft_on_lock(
+            owner_id,
+            token_id.clone(),
+            msg,
+            &locker_id,
+            0,
+            env::prepaid_gas() - GAS_FOR_NFT_LOCK_CALL,
+        )
+        .then(ext_locks_self::resolve_lock(
+            token_id,
+            locker_id,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_LOCK,
+        ))
+    }
+
+    /// Returns true if the token stayed locked
+    #[private]
+    pub fn resolve_lock(&mut self, token_id: TokenId, locker_id: AccountId) -> bool {
+        let unlock = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            _ => true,
+        };
+
+        // the locker may have unlocked the token meanwhile
+        if self.locks_by_token_id.get(&token_id) != Some(locker_id) {
+            return false;
+        }
+
+        if unlock {
+            self.internal_remove_lock(&token_id);
+        }
+
+        !unlock
+    }
+
+    /// Locker only, unlocks the copy, its storage goes back to the owner
+    pub fn nft_unlock(&mut self, token_id: TokenId) {
+        require!(
+            self.locks_by_token_id.get(&token_id) == Some(env::predecessor_account_id()),
+            "Only the locker can unlock the token"
+        );
+
+        self.internal_remove_lock(&token_id);
+    }
+
+    /// The A/c which locked the copy, if it is locked
+    pub fn nft_lock_of(&self, token_id: TokenId) -> Option<AccountId> {
+        self.locks_by_token_id.get(&token_id)
+    }
+}
+
+#[cfg(test)]
+mod locks_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    /// alice's gold pass is locked by the FT contract
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_lock_call(PASS.to_string(), ft(), "".to_string());
+
+        contract
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) -> bool {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_lock(PASS.to_string(), ft().into())
+    }
+
+    #[test]
+    fn lock_is_kept_when_the_locker_accepts() {
+        let mut contract = setup();
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_locked","data":{"token_id":"catchcon.gold.alice.near","owner_id":"alice.near","locker_id":"ft.catchlabs.near"}}"#
+        );
+
+        assert!(resolve(
+            &mut contract,
+            PromiseResult::Successful(b"false".to_vec())
+        ));
+        assert_eq!(contract.nft_lock_of(PASS.to_string()), Some(ft().into()));
+    }
+
+    fn assert_unlocked(contract: &Contract) {
+        assert!(contract.nft_lock_of(PASS.to_string()).is_none());
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_unlocked","data":{"token_id":"catchcon.gold.alice.near","owner_id":"alice.near","locker_id":"ft.catchlabs.near"}}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn lock_is_removed_when_the_locker_refuses() {
+        let mut contract = setup();
+
+        assert!(!resolve(
+            &mut contract,
+            PromiseResult::Successful(b"true".to_vec())
+        ));
+        assert_unlocked(&contract);
+    }
+
+    #[test]
+    fn lock_is_removed_when_the_locker_fails() {
+        let mut contract = setup();
+
+        assert!(!resolve(&mut contract, PromiseResult::Failed));
+        assert_unlocked(&contract);
+    }
+
+    #[test]
+    fn unlocked_token_can_be_transferred() {
+        let mut contract = setup();
+        resolve(&mut contract, PromiseResult::Successful(b"false".to_vec()));
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.nft_unlock(PASS.to_string());
+        assert!(contract.nft_lock_of(PASS.to_string()).is_none());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is locked")]
+    fn transfer_of_locked_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is locked")]
+    fn approve_of_locked_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is locked")]
+    fn burn_of_locked_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the locker can unlock the token")]
+    fn unlock_by_the_owner_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.nft_unlock(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is already locked")]
+    fn locked_token_cannot_be_locked_again() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_lock_call(PASS.to_string(), bob(), "".to_string());
+    }
+}
+
+#[cfg(test)]
+mod locks_simulation {
+    use super::*;
+    use crate::simulation::*;
+    use crate::utils::test_utils::*;
+    use ft::StorageManager as _;
+
+    const ALICE_PASS: &str = "catchcon.gold.alice.near";
+    const BOB_PASS: &str = "catchcon.gold.bob.near";
+    const DAY: u64 = 86_400 * 1_000_000_000;
+
+    fn ft_metadata() -> ft::FungibleTokenMetadata {
+        ft::FungibleTokenMetadata {
+            spec: "ft-1.0.0".to_string(),
+            name: "Catch Token".to_string(),
+            symbol: "CATCH".to_string(),
+            icon: "C-A-T-C-H".to_string(),
+            reference: "https://catchlabs.io/ft".to_string(),
+            reference_hash: Base64VecU8::from([5_u8; 32].to_vec()),
+            decimals: 0,
+        }
+    }
+
+    /// alice and bob hold a gold pass each, the FT contract stakes catchcon.gold for 100 CATCH a
+    /// day and both have staked their pass
+    fn setup() -> Chain {
+        let mut chain = Chain::default();
+
+        chain.deploy(nft(), create_contract);
+        chain.deploy(ft(), || {
+            ft::Contract::new(carol(), U128(1_000_000), ft_metadata())
+        });
+        chain.call(carol(), ft(), 1, |ft: &mut ft::Contract| {
+            ft.set_staking_nft_contract(nft());
+            ft.set_nft_reward_rate("catchcon.gold".to_string(), Some(U128(100)));
+            ft.fund_staking_pool(U128(1_000));
+        });
+        chain.call(carol(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            create_event(nft, "catchcon", &["gold"], 10)
+        });
+
+        for (account_id, token_id) in [(alice(), ALICE_PASS), (bob(), BOB_PASS)] {
+            chain.call(
+                account_id.clone(),
+                ft(),
+                STORAGE_DEPOSIT,
+                |ft: &mut ft::Contract| ft.storage_deposit(Some(account_id.clone())),
+            );
+            chain.call(
+                account_id.clone(),
+                nft(),
+                STORAGE_DEPOSIT,
+                |nft: &mut Contract| {
+                    nft.nft_event_register(account_id.to_string(), "catchcon.gold".to_string())
+                },
+            );
+            chain.call(account_id, nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+                nft.nft_lock_call(token_id.to_string(), ft(), "".to_string());
+            });
+        }
+
+        chain
+    }
+
+    fn staked_nfts(chain: &mut Chain, account_id: ValidAccountId) -> Vec<String> {
+        chain
+            .view(ft(), |ft: &ft::Contract| {
+                ft.get_staked_nfts(account_id, None, None)
+            })
+            .into_iter()
+            .map(|stake| stake.token_id)
+            .collect()
+    }
+
+    fn lock_of(chain: &mut Chain, token_id: &str) -> Option<AccountId> {
+        chain.view(nft(), |nft: &Contract| {
+            nft.nft_lock_of(token_id.to_string())
+        })
+    }
+
+    #[test]
+    fn copies_of_one_class_are_staked_by_their_owners() {
+        let mut chain = setup();
+
+        assert_eq!(staked_nfts(&mut chain, alice()), vec![ALICE_PASS]);
+        assert_eq!(staked_nfts(&mut chain, bob()), vec![BOB_PASS]);
+        assert_eq!(lock_of(&mut chain, ALICE_PASS), Some(ft().into()));
+        assert_eq!(lock_of(&mut chain, BOB_PASS), Some(ft().into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is locked")]
+    fn staked_copy_cannot_be_transferred() {
+        let mut chain = setup();
+
+        chain.call(alice(), nft(), 1, |nft: &mut Contract| {
+            nft.nft_transfer(carol().into(), ALICE_PASS.to_string(), None, None);
+        });
+    }
+
+    #[test]
+    fn unstaking_unlocks_the_copy_and_pays_rewards() {
+        let mut chain = setup();
+        chain.block_timestamp = 2 * DAY;
+
+        chain.call(bob(), ft(), 1, |ft: &mut ft::Contract| {
+            ft.unstake_nft(BOB_PASS.to_string());
+        });
+
+        assert!(chain.receipt("nft_unlock").error.is_none());
+        assert_eq!(lock_of(&mut chain, BOB_PASS), None);
+        assert_eq!(lock_of(&mut chain, ALICE_PASS), Some(ft().into()));
+        assert_eq!(
+            chain.view(ft(), |ft: &ft::Contract| ft.ft_balance_of(bob())),
+            U128(200)
+        );
+        assert!(staked_nfts(&mut chain, bob()).is_empty());
+        assert_eq!(staked_nfts(&mut chain, alice()), vec![ALICE_PASS]);
+    }
+
+    #[test]
+    fn unknown_class_is_unlocked_right_away() {
+        let mut chain = setup();
+        chain.call(carol(), ft(), 1, |ft: &mut ft::Contract| {
+            ft.set_nft_reward_rate("catchcon.gold".to_string(), None);
+        });
+        chain.call(bob(), ft(), 1, |ft: &mut ft::Contract| {
+            ft.unstake_nft(BOB_PASS.to_string());
+        });
+
+        chain.call(bob(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.nft_lock_call(BOB_PASS.to_string(), ft(), "".to_string());
+        });
+
+        assert!(chain.receipt("resolve_lock").error.is_none());
+        assert_eq!(lock_of(&mut chain, BOB_PASS), None);
+        assert!(staked_nfts(&mut chain, bob()).is_empty());
+    }
+}
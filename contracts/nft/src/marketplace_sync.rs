@@ -0,0 +1,250 @@
+use crate::*;
+
+const GAS_FOR_MARKETPLACE_SYNC: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_MARKETPLACE_SYNC: Gas = 10_000_000_000_000;
+
+#[ext_contract(ext_marketplace_sync)]
+pub trait MarketplaceSyncReceiver {
+    fn register_nft_contract(&mut self, collection_metadata: NFTContractMetadata);
+    fn on_marketplace_removed(&mut self);
+}
+
+#[ext_contract(ext_marketplace_sync_self)]
+pub trait MarketplaceSyncResolver {
+    fn resolve_marketplace_sync(&mut self, marketplace_id: AccountId);
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SyncAction {
+    Register,
+    Remove,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum SyncStatus {
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// Last notification sent to the marketplace and whether it went through
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MarketplaceSync {
+    pub action: SyncAction,
+    pub status: SyncStatus,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonMarketplace {
+    pub marketplace_id: AccountId,
+    //None for marketplaces approved without syncing
+    pub sync: Option<MarketplaceSync>,
+}
+
+impl Contract {
+    //notifies the marketplace of the action, the status is set by resolve_marketplace_sync
+    fn internal_sync_marketplace(&mut self, marketplace_id: AccountId, action: SyncAction) {
+        let notification = match action {
+            SyncAction::Register => ext_marketplace_sync::register_nft_contract(
+                self.metadata.get().unwrap(),
+                &marketplace_id,
+                0,
+                GAS_FOR_MARKETPLACE_SYNC,
+            ),
+            SyncAction::Remove => ext_marketplace_sync::on_marketplace_removed(
+                &marketplace_id,
+                0,
+                GAS_FOR_MARKETPLACE_SYNC,
+            ),
+        };
+
+        self.marketplace_sync.insert(
+            &marketplace_id,
+            &MarketplaceSync {
+                action,
+                status: SyncStatus::Pending,
+            },
+        );
+
+        notification.then(ext_marketplace_sync_self::resolve_marketplace_sync(
+            marketplace_id,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_MARKETPLACE_SYNC,
+        ));
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, approves the marketplace and registers this contract on it
+    pub fn sync_marketplace(&mut self, marketplace_id: ValidAccountId) {
+        self.assert_owner();
+
+        let marketplace_id: AccountId = marketplace_id.into();
+        self.internal_approve_marketplace(marketplace_id.clone(), None);
+
+        self.internal_sync_marketplace(marketplace_id, SyncAction::Register);
+    }
+
+    /// Owner only, removes the marketplace from the approved marketplaces and notifies it
+    pub fn remove_marketplace(&mut self, marketplace_id: ValidAccountId) {
+        self.assert_owner();
+
+        let marketplace_id: AccountId = marketplace_id.into();
+        require!(
+            self.approved_marketplaces.remove(&marketplace_id).is_some(),
+            "Marketplace is not approved"
+        );
+
+        CatchEventLogVariant::MarketplaceChange(MarketplaceChangeLog {
+            marketplace_id: marketplace_id.clone(),
+            approved: false,
+        })
+        .emit();
+
+        self.internal_sync_marketplace(marketplace_id, SyncAction::Remove);
+    }
+
+    /// Owner only, sends the failed notification to the marketplace again
+    pub fn retry_marketplace_sync(&mut self, marketplace_id: ValidAccountId) {
+        self.assert_owner();
+
+        let marketplace_id: AccountId = marketplace_id.into();
+        let sync = self
+            .marketplace_sync
+            .get(&marketplace_id)
+            .unwrap_or_else(|| env::panic(b"Marketplace was never synced"));
+
+        require!(
+            sync.status == SyncStatus::Failed,
+            "Only failed syncs can be retried"
+        );
+
+        self.internal_sync_marketplace(marketplace_id, sync.action);
+    }
+
+    pub fn get_marketplace_sync(&self, marketplace_id: AccountId) -> Option<MarketplaceSync> {
+        self.marketplace_sync.get(&marketplace_id)
+    }
+
+    #[private]
+    pub fn resolve_marketplace_sync(&mut self, marketplace_id: AccountId) {
+        let mut sync = self.marketplace_sync.get(&marketplace_id).unwrap();
+
+        sync.status = match env::promise_result(0) {
+            PromiseResult::Successful(_) => SyncStatus::Confirmed,
+            _ => {
+                CatchEventLogVariant::MarketplaceSyncFailed(MarketplaceSyncLog {
+                    marketplace_id: marketplace_id.clone(),
+                    action: sync.action.clone(),
+                })
+                .emit();
+                SyncStatus::Failed
+            }
+        };
+
+        self.marketplace_sync.insert(&marketplace_id, &sync);
+    }
+}
+
+#[cfg(test)]
+mod marketplace_sync_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_marketplace_sync(marketplace().into());
+    }
+
+    fn sync_status(contract: &Contract) -> Option<SyncStatus> {
+        contract
+            .get_marketplace_sync(marketplace().into())
+            .map(|sync| sync.status)
+    }
+
+    #[test]
+    fn sync_registers_contract_on_marketplace() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.sync_marketplace(marketplace());
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("register_nft_contract"));
+        assert_eq!(sync_status(&contract), Some(SyncStatus::Pending));
+
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+        assert_eq!(sync_status(&contract), Some(SyncStatus::Confirmed));
+
+        let whitelist = contract.get_approved_marketplace(None, None);
+        let synced = whitelist
+            .iter()
+            .find(|marketplace_info| marketplace_info.marketplace_id == "marketplace.near")
+            .unwrap();
+        assert_eq!(
+            synced.sync.as_ref().map(|sync| sync.action.clone()),
+            Some(SyncAction::Register)
+        );
+    }
+
+    #[test]
+    fn failed_removal_can_be_retried() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.sync_marketplace(marketplace());
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.remove_marketplace(marketplace());
+        resolve(&mut contract, PromiseResult::Failed);
+        assert_eq!(sync_status(&contract), Some(SyncStatus::Failed));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"marketplace_sync_failed","data":{"marketplace_id":"marketplace.near","action":"Remove"}}"#
+            ]
+        );
+        assert!(!contract.internal_is_approved_marketplace(&marketplace().into()));
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.retry_marketplace_sync(marketplace());
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("on_marketplace_removed"));
+
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+        assert_eq!(
+            contract.get_marketplace_sync(marketplace().into()),
+            Some(MarketplaceSync {
+                action: SyncAction::Remove,
+                status: SyncStatus::Confirmed
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only failed syncs can be retried")]
+    fn confirmed_sync_cannot_be_retried() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.sync_marketplace(marketplace());
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.retry_marketplace_sync(marketplace());
+    }
+}
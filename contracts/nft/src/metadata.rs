@@ -19,6 +19,10 @@ pub struct NFTContractMetadata {
     pub base_uri: String, // Decentralized storage gateway
     pub reference: String,
     pub reference_hash: Base64VecU8,
+    //filled in by nft_metadata, not stored
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paused: Option<PauseStatus>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
@@ -40,7 +44,7 @@ pub struct TokenMetadata {
     pub reference_hash: Option<Base64VecU8>,
 }
 
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
 pub struct Token {
     pub token_id: TokenId,
     pub copies_minted: u64,
@@ -48,7 +52,11 @@ pub struct Token {
     pub expires_at: Option<u64>,
     pub token_dependency_by_id: Vec<TokenId>,
     pub event_dependency_by_id: Vec<EventId>,
-    pub account_approval_info_per_owner: LookupMap<AccountId, ApprovalInfo>,
+    pub external_dependencies: Vec<ExternalDependency>,
+    pub royalty: Royalty,
+    pub metadata_frozen: bool,
+    //bound to the owner, it can be burnt but not transferred or approved
+    pub soulbound: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -58,8 +66,24 @@ pub struct JsonToken {
     pub owner_id: AccountId,
     pub metadata: TokenMetadata,
     pub approved_account_ids: HashMap<AccountId, u64>,
+    //royalty of the token class in basis points, the default royalty merged in
+    pub royalty: Royalty,
+    pub copies_minted: u64,
+    pub max_copies: u64,
+    //Unix epoch in milliseconds, the renewed expiry of the copy if it was renewed
+    pub expires_at: Option<u64>,
     pub token_dependency_by_id: Vec<TokenId>,
     pub event_dependency_by_id: Vec<EventId>,
+    pub external_dependencies: Vec<ExternalDependency>,
+    pub level: u16,
+    //expired tokens can't be transferred or approved
+    pub expired: bool,
+    //soulbound tokens can't be transferred or listed
+    pub soulbound: bool,
+    //Unix epoch in milliseconds, when the pass was checked in at the door
+    pub checked_in_at: Option<u64>,
+    //false for soulbound tokens and passes of events which aren't transferable
+    pub transferable: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -68,6 +92,60 @@ pub struct JsonEvent {
     pub event_id: EventId,
     pub organiser: AccountId,
     pub event_tokens_metadata: Vec<JsonTokenGeneral>,
+    pub metadata: Option<EventMetadata>,
+    pub status: EventStatus,
+}
+
+/// Counters of an event for the dashboards of organisers
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventStats {
+    pub event_id: EventId,
+    pub passes_issued: u64,
+    //None for events organised with organise_event
+    pub max_capacity: Option<u64>,
+    pub checked_in_count: u64,
+    //accounts holding at least one pass of the event
+    pub unique_holders: u64,
+    pub revenue_collected: U128,
+    pub waitlist_length: u64,
+    pub status: EventStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpcomingEventView {
+    pub event_id: EventId,
+    //title of the pass
+    pub title: String,
+    //Unix epoch in milliseconds, starts_at of the pass
+    pub starts_at: u64,
+    //TokenId of form event_id.token_id.owner_id
+    pub token_id: TokenId,
+    pub tier: String,
+    pub checked_in: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CopiesView {
+    pub token_id: TokenId,
+    pub copies_minted: u64,
+    pub max_copies: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpcomingEvents {
+    pub events: Vec<UpcomingEventView>,
+    pub has_more: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SupplyInfo {
+    pub minted: u64,
+    pub max: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -79,6 +157,80 @@ pub struct JsonTokenGeneral {
     pub metadata: TokenMetadata,
     pub token_dependency_by_id: Vec<TokenId>,
     pub event_dependency_by_id: Vec<EventId>,
+    pub external_dependencies: Vec<ExternalDependency>,
+    pub royalty: Royalty,
+    pub soulbound: bool,
+}
+
+/// Fields of TokenMetadata nft_update_metadata can replace, None keeps the field as it is.
+/// copies and expires_at have their own rules and can't be updated
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadataUpdate {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+//replaces the field if a different value is given and records its name
+pub(crate) fn update_field<T: PartialEq>(
+    field: &mut T,
+    value: Option<T>,
+    name: &str,
+    changed: &mut Vec<String>,
+) {
+    if let Some(value) = value {
+        if *field != value {
+            *field = value;
+            changed.push(name.to_string());
+        }
+    }
+}
+
+impl TokenMetadataUpdate {
+    //writes the update to the metadata, returns the names of the fields which changed
+    fn apply(self, metadata: &mut TokenMetadata) -> Vec<String> {
+        let mut changed = vec![];
+
+        update_field(&mut metadata.title, self.title, "title", &mut changed);
+        update_field(
+            &mut metadata.description,
+            self.description.map(Some),
+            "description",
+            &mut changed,
+        );
+        update_field(&mut metadata.media, self.media, "media", &mut changed);
+        update_field(
+            &mut metadata.media_hash,
+            self.media_hash,
+            "media_hash",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.extra,
+            self.extra.map(Some),
+            "extra",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.reference,
+            self.reference.map(Some),
+            "reference",
+            &mut changed,
+        );
+        update_field(
+            &mut metadata.reference_hash,
+            self.reference_hash.map(Some),
+            "reference_hash",
+            &mut changed,
+        );
+
+        changed
+    }
 }
 
 pub trait NonFungibleTokenMetadata {
@@ -88,12 +240,336 @@ pub trait NonFungibleTokenMetadata {
 #[near_bindgen]
 impl NonFungibleTokenMetadata for Contract {
     fn nft_metadata(&self) -> NFTContractMetadata {
-        self.metadata.get().unwrap()
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.paused = Some(self.pause_status.clone());
+        metadata
     }
 }
 
+#[near_bindgen]
+impl Contract {
+    /// Owner only, fixes the metadata of the token class (event_id.token_id) as long as it isn't
+    /// frozen. The storage the update adds is paid from the deposit, storage it frees is refunded
+    #[payable]
+    pub fn nft_update_metadata(&mut self, token_id: TokenId, updates: TokenMetadataUpdate) {
+        assert_at_least_one_yocto();
+        self.assert_owner();
+
+        let token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+        require!(!token.metadata_frozen, "Token metadata is frozen");
+
+        let mut metadata = self
+            .token_metadata_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token shares the metadata of its series"));
+
+        let initial_storage = env::storage_usage();
+
+        let fields = updates.apply(&mut metadata);
+        require!(!fields.is_empty(), "Nothing to update");
+
+        metadata.assert_valid();
+        metadata.updated_at = Some(now_ms());
+        self.token_metadata_by_id.insert(&token_id, &metadata);
+
+        let final_storage = env::storage_usage();
+        if final_storage > initial_storage {
+            refund_deposit(final_storage - initial_storage);
+        } else {
+            let storage_released = initial_storage - final_storage;
+            Promise::new(env::predecessor_account_id()).transfer(
+                env::attached_deposit()
+                    + Balance::from(storage_released) * env::storage_byte_cost(),
+            );
+        }
+
+        // the standard payload has no field names, the memo lists them for the indexers
+        EventLogVariant::NftMetadataUpdate(vec![NftMetadataUpdateLog {
+            token_ids: vec![token_id],
+            memo: Some(fields.join(",")),
+        }])
+        .emit();
+    }
+
+    /// Owner only, spec, name and symbol can't be changed since wallets key off them
+    #[payable]
+    pub fn update_contract_metadata(
+        &mut self,
+        base_uri: Option<String>,
+        icon: Option<String>,
+        reference: Option<String>,
+        reference_hash: Option<Base64VecU8>,
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let mut metadata = self.metadata.get().unwrap();
+        let mut fields = vec![];
+
+        update_field(&mut metadata.base_uri, base_uri, "base_uri", &mut fields);
+        update_field(&mut metadata.icon, icon.map(Some), "icon", &mut fields);
+        update_field(&mut metadata.reference, reference, "reference", &mut fields);
+        update_field(
+            &mut metadata.reference_hash,
+            reference_hash,
+            "reference_hash",
+            &mut fields,
+        );
+        require!(!fields.is_empty(), "Nothing to update");
+
+        metadata.assert_valid_metadata();
+        self.metadata.set(&metadata);
+
+        CatchEventLogVariant::ContractMetadataUpdated(ContractMetadataLog { fields }).emit();
+    }
+}
+
+impl TokenMetadata {
+    /// media and reference have to come with the sha256 of what they point to, so neither can be
+    /// swapped after a sale
+    pub fn assert_valid(&self) {
+        if self.media.is_empty() {
+            require!(
+                self.media_hash.0.is_empty(),
+                "media_hash is given without media"
+            );
+        } else {
+            assert_valid_hash(&self.media_hash, "media_hash");
+        }
+
+        match (&self.reference, &self.reference_hash) {
+            (Some(_), Some(reference_hash)) => assert_valid_hash(reference_hash, "reference_hash"),
+            (Some(_), None) => env::panic(b"reference requires reference_hash"),
+            (None, Some(_)) => env::panic(b"reference_hash is given without reference"),
+            (None, None) => {}
+        }
+    }
+}
+
+//hashes of media and reference documents are sha256
+pub(crate) fn assert_valid_hash(hash: &Base64VecU8, name: &str) {
+    require!(
+        hash.0.len() == 32,
+        format!("{} has to be 32 bytes, got {} bytes", name, hash.0.len())
+    );
+}
+
 impl NFTContractMetadata {
     pub fn assert_valid_metadata(&self) {
-        require!(self.reference_hash.0.len() == 32, "Hash has to be 32 bytes");
+        match (self.reference.is_empty(), self.reference_hash.0.is_empty()) {
+            (false, false) => assert_valid_hash(&self.reference_hash, "reference_hash"),
+            (false, true) => env::panic(b"reference requires reference_hash"),
+            (true, false) => env::panic(b"reference_hash is given without reference"),
+            (true, true) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod metadata_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const TOKEN: &str = "catchcon.gold";
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        contract
+    }
+
+    #[test]
+    fn update_keeps_the_other_fields() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_update_metadata(
+            TOKEN.to_string(),
+            TokenMetadataUpdate {
+                title: Some("Catch Pass".to_string()),
+                media: Some("ipfs://fixed".to_string()),
+                description: Some("Gold pass of catchcon".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let metadata = contract
+            .token_metadata_by_id
+            .get(&TOKEN.to_string())
+            .unwrap();
+        assert_eq!(metadata.title, "Catch Pass");
+        assert_eq!(metadata.media, "ipfs://fixed");
+        assert_eq!(
+            metadata.description,
+            Some("Gold pass of catchcon".to_string())
+        );
+        assert_eq!(metadata.copies, Some(10));
+        assert!(metadata.updated_at.is_some());
+
+        // the title didn't change
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_metadata_update","data":[{"token_ids":["catchcon.gold"],"memo":"description,media"}]}"#
+            ]
+        );
+        // the unused deposit
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+    }
+
+    #[test]
+    fn contract_metadata_update_is_visible() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.update_contract_metadata(
+            Some("https://gateway.catchlabs.io/ipfs".to_string()),
+            None,
+            None,
+            None,
+        );
+
+        let metadata = contract.nft_metadata();
+        assert_eq!(metadata.base_uri, "https://gateway.catchlabs.io/ipfs");
+        assert_eq!(metadata.symbol, "CATCH");
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"contract_metadata_updated","data":{"fields":["base_uri"]}}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reference_hash has to be 32 bytes, got 16 bytes")]
+    fn contract_metadata_with_invalid_hash_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.update_contract_metadata(
+            None,
+            None,
+            Some("ipfs://metadata".to_string()),
+            Some(Base64VecU8::from(vec![5_u8; 16])),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "media_hash has to be 32 bytes")]
+    fn media_without_hash_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+
+        let mut metadata = token_metadata(1);
+        metadata.media_hash = Base64VecU8::from(vec![]);
+        metadata.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "media_hash has to be 32 bytes")]
+    fn media_hash_of_wrong_length_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+
+        let mut metadata = token_metadata(1);
+        metadata.media_hash = Base64VecU8::from(vec![5_u8; 31]);
+        metadata.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "media_hash is given without media")]
+    fn media_hash_without_media_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+
+        let mut metadata = token_metadata(1);
+        metadata.media = String::new();
+        metadata.assert_valid();
+    }
+
+    #[test]
+    fn default_reference_hash_matches_the_document() {
+        let contract = setup();
+        let metadata = contract.nft_metadata();
+
+        let document: Base64VecU8 = serde_json::from_str(&format!(
+            "\"{}\"",
+            metadata.reference.split_once(',').unwrap().1
+        ))
+        .unwrap();
+        assert_eq!(document.0, DEFAULT_REFERENCE_DOCUMENT.as_bytes());
+        assert_eq!(
+            metadata.reference_hash.0,
+            env::sha256(DEFAULT_REFERENCE_DOCUMENT.as_bytes())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "reference_hash is given without reference")]
+    fn contract_reference_hash_without_reference_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.update_contract_metadata(None, None, Some(String::new()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "reference requires reference_hash")]
+    fn reference_without_hash_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+
+        let mut metadata = token_metadata(1);
+        metadata.reference = Some("ipfs://reference".to_string());
+        metadata.assert_valid();
+    }
+
+    #[test]
+    #[should_panic(expected = "media_hash has to be 32 bytes")]
+    fn update_to_invalid_media_hash_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.nft_update_metadata(
+            TOKEN.to_string(),
+            TokenMetadataUpdate {
+                media_hash: Some(Base64VecU8::from(vec![5_u8; 16])),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Token metadata is frozen")]
+    fn update_of_frozen_metadata_fails() {
+        let mut contract = setup();
+        contract.freeze_token_metadata(TOKEN.to_string());
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.nft_update_metadata(
+            TOKEN.to_string(),
+            TokenMetadataUpdate {
+                title: Some("Gold".to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn update_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.nft_update_metadata(
+            TOKEN.to_string(),
+            TokenMetadataUpdate {
+                title: Some("Gold".to_string()),
+                ..Default::default()
+            },
+        );
     }
 }
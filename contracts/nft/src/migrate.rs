@@ -0,0 +1,1314 @@
+use crate::*;
+
+// gas kept by upgrade for itself and the deploy, the rest goes to migrate
+const GAS_RESERVED_FOR_UPGRADE: Gas = 30_000_000_000_000;
+const MIN_GAS_FOR_MIGRATE: Gas = 100_000_000_000_000;
+
+//marks the layout the contract state was written with, missing for states older than V2
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+pub const STATE_VERSION: u8 = 7;
+
+/// Layouts the contract state was written with. A layout change freezes the current Contract
+/// as the struct of its version here, migrate maps every older layout to the new Contract
+pub enum VersionedContract {
+    V1(ContractV1),
+    V2(ContractV2),
+    V3(ContractV3),
+    V4(ContractV4),
+    V5(ContractV5),
+    V6(ContractV6),
+    V7(Contract),
+}
+
+impl VersionedContract {
+    fn read() -> Self {
+        let state =
+            env::storage_read(b"STATE").unwrap_or_else(|| env::panic(b"No state to migrate"));
+        let version = env::storage_read(STATE_VERSION_KEY).map_or(1, |version| version[0]);
+
+        match version {
+            1 => Self::V1(ContractV1::try_from_slice(&state).unwrap()),
+            2 => Self::V2(ContractV2::try_from_slice(&state).unwrap()),
+            3 => Self::V3(ContractV3::try_from_slice(&state).unwrap()),
+            4 => Self::V4(ContractV4::try_from_slice(&state).unwrap()),
+            5 => Self::V5(ContractV5::try_from_slice(&state).unwrap()),
+            6 => Self::V6(ContractV6::try_from_slice(&state).unwrap()),
+            STATE_VERSION => Self::V7(Contract::try_from_slice(&state).unwrap()),
+            _ => env::panic(format!("Unknown state version {}", version).as_bytes()),
+        }
+    }
+
+    fn owner_id(&self) -> &AccountId {
+        match self {
+            Self::V1(contract) => &contract.owner_id,
+            Self::V2(contract) => &contract.owner_id,
+            Self::V3(contract) => &contract.owner_id,
+            Self::V4(contract) => &contract.owner_id,
+            Self::V5(contract) => &contract.owner_id,
+            Self::V6(contract) => &contract.owner_id,
+            Self::V7(contract) => &contract.owner_id,
+        }
+    }
+}
+
+impl From<VersionedContract> for Contract {
+    fn from(versioned: VersionedContract) -> Self {
+        match versioned {
+            VersionedContract::V1(old) => ContractV6::from(ContractV5::from(ContractV4::from(
+                ContractV3::from(ContractV2::from(old)),
+            )))
+            .into(),
+            VersionedContract::V2(old) => {
+                ContractV6::from(ContractV5::from(ContractV4::from(ContractV3::from(old)))).into()
+            }
+            VersionedContract::V3(old) => {
+                ContractV6::from(ContractV5::from(ContractV4::from(old))).into()
+            }
+            VersionedContract::V4(old) => ContractV6::from(ContractV5::from(old)).into(),
+            VersionedContract::V5(old) => ContractV6::from(old).into(),
+            VersionedContract::V6(old) => old.into(),
+            VersionedContract::V7(contract) => contract,
+        }
+    }
+}
+
+/// Layout of the contract state before tokens were versioned
+#[derive(BorshDeserialize)]
+pub struct ContractV1 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: LookupMap<TokenId, UnversionedToken>,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub events_by_id: UnorderedMap<EventId, Event>,
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub payment_token_id: Option<AccountId>,
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+    pub default_royalty: Royalty,
+    pub accounts_registry: Option<AccountId>,
+    pub registry_async_validation: bool,
+    pub registry_players: LookupSet<AccountId>,
+    pub transfer_hook: Option<AccountId>,
+    pub hook_breaker: HookBreaker,
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+    pub affiliate_program: Option<AffiliateProgram>,
+    pub affiliate_pool: Balance,
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+    pub leases: LookupMap<TokenId, Lease>,
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+    pub upgrade_treasury_id: Option<AccountId>,
+    pub unrouted_upgrade_payments: Balance,
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+    pub renewer_id: Option<AccountId>,
+    pub max_approvals_per_token: u32,
+    pub pause_status: PauseStatus,
+    pub pending_owner_id: Option<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub next_token_id: u64,
+    pub max_total_supply: Option<u64>,
+    pub minted_count: u64,
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+    pub account_funding_amount: Balance,
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+    pub platform_fee_bps: u16,
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+}
+
+impl From<ContractV1> for ContractV2 {
+    fn from(old: ContractV1) -> Self {
+        Self {
+            owner_id: old.owner_id,
+            tokens_per_owner: old.tokens_per_owner,
+            tokens_by_id: VersionedTokens::from_legacy(old.tokens_by_id),
+            token_metadata_by_id: old.token_metadata_by_id,
+            events_by_id: old.events_by_id,
+            approved_marketplaces: old.approved_marketplaces,
+            metadata: old.metadata,
+            payment_token_id: old.payment_token_id,
+            ft_price_by_token_id: old.ft_price_by_token_id,
+            ft_proceeds_by_event_id: old.ft_proceeds_by_event_id,
+            ft_paid_by_event_account: old.ft_paid_by_event_account,
+            checked_in_at_by_token_id: old.checked_in_at_by_token_id,
+            reward_pool_by_event_id: old.reward_pool_by_event_id,
+            pending_rewards: old.pending_rewards,
+            default_royalty: old.default_royalty,
+            accounts_registry: old.accounts_registry,
+            registry_async_validation: old.registry_async_validation,
+            registry_players: old.registry_players,
+            transfer_hook: old.transfer_hook,
+            hook_breaker: old.hook_breaker,
+            pending_mints: old.pending_mints,
+            marketplace_sync: old.marketplace_sync,
+            affiliate_program: old.affiliate_program,
+            affiliate_pool: old.affiliate_pool,
+            affiliate_rewards: old.affiliate_rewards,
+            leases: old.leases,
+            lease_approvals: old.lease_approvals,
+            subscription_plans: old.subscription_plans,
+            subscriptions: old.subscriptions,
+            pass_expiry_by_token_id: old.pass_expiry_by_token_id,
+            storage_deposits: old.storage_deposits,
+            upgrade_config_by_token_id: old.upgrade_config_by_token_id,
+            upgrade_treasury_id: old.upgrade_treasury_id,
+            unrouted_upgrade_payments: old.unrouted_upgrade_payments,
+            level_by_token_id: old.level_by_token_id,
+            metadata_history_by_token_id: old.metadata_history_by_token_id,
+            parent_by_token_id: old.parent_by_token_id,
+            children_by_token_id: old.children_by_token_id,
+            renewal_config_by_token_id: old.renewal_config_by_token_id,
+            series_by_id: old.series_by_id,
+            tokens_per_series: old.tokens_per_series,
+            passes_per_event: old.passes_per_event,
+            renewer_id: old.renewer_id,
+            max_approvals_per_token: old.max_approvals_per_token,
+            pause_status: old.pause_status,
+            pending_owner_id: old.pending_owner_id,
+            minters: old.minters,
+            next_token_id: old.next_token_id,
+            max_total_supply: old.max_total_supply,
+            minted_count: old.minted_count,
+            frozen_tokens: old.frozen_tokens,
+            blacklist: old.blacklist,
+            recoveries_by_token_id: old.recoveries_by_token_id,
+            account_funding_amount: old.account_funding_amount,
+            events_per_organiser: old.events_per_organiser,
+            tokens_per_event: old.tokens_per_event,
+            platform_fee_bps: old.platform_fee_bps,
+            waitlists: old.waitlists,
+            waitlist_escrow: old.waitlist_escrow,
+            approvals_by_token_id: old.approvals_by_token_id,
+        }
+    }
+}
+
+/// Layout of the contract state before the tokens of each owner were counted
+#[derive(BorshDeserialize)]
+pub struct ContractV2 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: VersionedTokens,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub events_by_id: UnorderedMap<EventId, Event>,
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub payment_token_id: Option<AccountId>,
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+    pub default_royalty: Royalty,
+    pub accounts_registry: Option<AccountId>,
+    pub registry_async_validation: bool,
+    pub registry_players: LookupSet<AccountId>,
+    pub transfer_hook: Option<AccountId>,
+    pub hook_breaker: HookBreaker,
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+    pub affiliate_program: Option<AffiliateProgram>,
+    pub affiliate_pool: Balance,
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+    pub leases: LookupMap<TokenId, Lease>,
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+    pub upgrade_treasury_id: Option<AccountId>,
+    pub unrouted_upgrade_payments: Balance,
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+    pub renewer_id: Option<AccountId>,
+    pub max_approvals_per_token: u32,
+    pub pause_status: PauseStatus,
+    pub pending_owner_id: Option<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub next_token_id: u64,
+    pub max_total_supply: Option<u64>,
+    pub minted_count: u64,
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+    pub account_funding_amount: Balance,
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+    pub platform_fee_bps: u16,
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+}
+
+impl From<ContractV2> for ContractV3 {
+    fn from(old: ContractV2) -> Self {
+        Self {
+            owner_id: old.owner_id,
+            tokens_per_owner: old.tokens_per_owner,
+            tokens_by_id: old.tokens_by_id,
+            token_metadata_by_id: old.token_metadata_by_id,
+            events_by_id: old.events_by_id,
+            approved_marketplaces: old.approved_marketplaces,
+            metadata: old.metadata,
+            payment_token_id: old.payment_token_id,
+            ft_price_by_token_id: old.ft_price_by_token_id,
+            ft_proceeds_by_event_id: old.ft_proceeds_by_event_id,
+            ft_paid_by_event_account: old.ft_paid_by_event_account,
+            checked_in_at_by_token_id: old.checked_in_at_by_token_id,
+            reward_pool_by_event_id: old.reward_pool_by_event_id,
+            pending_rewards: old.pending_rewards,
+            default_royalty: old.default_royalty,
+            accounts_registry: old.accounts_registry,
+            registry_async_validation: old.registry_async_validation,
+            registry_players: old.registry_players,
+            transfer_hook: old.transfer_hook,
+            hook_breaker: old.hook_breaker,
+            pending_mints: old.pending_mints,
+            marketplace_sync: old.marketplace_sync,
+            affiliate_program: old.affiliate_program,
+            affiliate_pool: old.affiliate_pool,
+            affiliate_rewards: old.affiliate_rewards,
+            leases: old.leases,
+            lease_approvals: old.lease_approvals,
+            subscription_plans: old.subscription_plans,
+            subscriptions: old.subscriptions,
+            pass_expiry_by_token_id: old.pass_expiry_by_token_id,
+            storage_deposits: old.storage_deposits,
+            upgrade_config_by_token_id: old.upgrade_config_by_token_id,
+            upgrade_treasury_id: old.upgrade_treasury_id,
+            unrouted_upgrade_payments: old.unrouted_upgrade_payments,
+            level_by_token_id: old.level_by_token_id,
+            metadata_history_by_token_id: old.metadata_history_by_token_id,
+            parent_by_token_id: old.parent_by_token_id,
+            children_by_token_id: old.children_by_token_id,
+            renewal_config_by_token_id: old.renewal_config_by_token_id,
+            series_by_id: old.series_by_id,
+            tokens_per_series: old.tokens_per_series,
+            passes_per_event: old.passes_per_event,
+            renewer_id: old.renewer_id,
+            max_approvals_per_token: old.max_approvals_per_token,
+            pause_status: old.pause_status,
+            pending_owner_id: old.pending_owner_id,
+            minters: old.minters,
+            next_token_id: old.next_token_id,
+            max_total_supply: old.max_total_supply,
+            minted_count: old.minted_count,
+            frozen_tokens: old.frozen_tokens,
+            blacklist: old.blacklist,
+            recoveries_by_token_id: old.recoveries_by_token_id,
+            account_funding_amount: old.account_funding_amount,
+            events_per_organiser: old.events_per_organiser,
+            tokens_per_event: old.tokens_per_event,
+            platform_fee_bps: old.platform_fee_bps,
+            waitlists: old.waitlists,
+            waitlist_escrow: old.waitlist_escrow,
+            approvals_by_token_id: old.approvals_by_token_id,
+            token_count_per_owner: LookupMap::new(
+                StorageKey::TokenCountPerOwner.try_to_vec().unwrap(),
+            ),
+        }
+    }
+}
+
+/// Layout of the contract state before leased copies were indexed by borrower
+#[derive(BorshDeserialize)]
+pub struct ContractV3 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: VersionedTokens,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub events_by_id: UnorderedMap<EventId, Event>,
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub payment_token_id: Option<AccountId>,
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+    pub default_royalty: Royalty,
+    pub accounts_registry: Option<AccountId>,
+    pub registry_async_validation: bool,
+    pub registry_players: LookupSet<AccountId>,
+    pub transfer_hook: Option<AccountId>,
+    pub hook_breaker: HookBreaker,
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+    pub affiliate_program: Option<AffiliateProgram>,
+    pub affiliate_pool: Balance,
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+    pub leases: LookupMap<TokenId, Lease>,
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+    pub upgrade_treasury_id: Option<AccountId>,
+    pub unrouted_upgrade_payments: Balance,
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+    pub renewer_id: Option<AccountId>,
+    pub max_approvals_per_token: u32,
+    pub pause_status: PauseStatus,
+    pub pending_owner_id: Option<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub next_token_id: u64,
+    pub max_total_supply: Option<u64>,
+    pub minted_count: u64,
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+    pub account_funding_amount: Balance,
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+    pub platform_fee_bps: u16,
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+    pub token_count_per_owner: LookupMap<AccountId, u64>,
+}
+
+impl From<ContractV3> for ContractV4 {
+    fn from(old: ContractV3) -> Self {
+        Self {
+            owner_id: old.owner_id,
+            tokens_per_owner: old.tokens_per_owner,
+            tokens_by_id: old.tokens_by_id,
+            token_metadata_by_id: old.token_metadata_by_id,
+            events_by_id: old.events_by_id,
+            approved_marketplaces: old.approved_marketplaces,
+            metadata: old.metadata,
+            payment_token_id: old.payment_token_id,
+            ft_price_by_token_id: old.ft_price_by_token_id,
+            ft_proceeds_by_event_id: old.ft_proceeds_by_event_id,
+            ft_paid_by_event_account: old.ft_paid_by_event_account,
+            checked_in_at_by_token_id: old.checked_in_at_by_token_id,
+            reward_pool_by_event_id: old.reward_pool_by_event_id,
+            pending_rewards: old.pending_rewards,
+            default_royalty: old.default_royalty,
+            accounts_registry: old.accounts_registry,
+            registry_async_validation: old.registry_async_validation,
+            registry_players: old.registry_players,
+            transfer_hook: old.transfer_hook,
+            hook_breaker: old.hook_breaker,
+            pending_mints: old.pending_mints,
+            marketplace_sync: old.marketplace_sync,
+            affiliate_program: old.affiliate_program,
+            affiliate_pool: old.affiliate_pool,
+            affiliate_rewards: old.affiliate_rewards,
+            leases: old.leases,
+            lease_approvals: old.lease_approvals,
+            subscription_plans: old.subscription_plans,
+            subscriptions: old.subscriptions,
+            pass_expiry_by_token_id: old.pass_expiry_by_token_id,
+            storage_deposits: old.storage_deposits,
+            upgrade_config_by_token_id: old.upgrade_config_by_token_id,
+            upgrade_treasury_id: old.upgrade_treasury_id,
+            unrouted_upgrade_payments: old.unrouted_upgrade_payments,
+            level_by_token_id: old.level_by_token_id,
+            metadata_history_by_token_id: old.metadata_history_by_token_id,
+            parent_by_token_id: old.parent_by_token_id,
+            children_by_token_id: old.children_by_token_id,
+            renewal_config_by_token_id: old.renewal_config_by_token_id,
+            series_by_id: old.series_by_id,
+            tokens_per_series: old.tokens_per_series,
+            passes_per_event: old.passes_per_event,
+            renewer_id: old.renewer_id,
+            max_approvals_per_token: old.max_approvals_per_token,
+            pause_status: old.pause_status,
+            pending_owner_id: old.pending_owner_id,
+            minters: old.minters,
+            next_token_id: old.next_token_id,
+            max_total_supply: old.max_total_supply,
+            minted_count: old.minted_count,
+            frozen_tokens: old.frozen_tokens,
+            blacklist: old.blacklist,
+            recoveries_by_token_id: old.recoveries_by_token_id,
+            account_funding_amount: old.account_funding_amount,
+            events_per_organiser: old.events_per_organiser,
+            tokens_per_event: old.tokens_per_event,
+            platform_fee_bps: old.platform_fee_bps,
+            waitlists: old.waitlists,
+            waitlist_escrow: old.waitlist_escrow,
+            approvals_by_token_id: old.approvals_by_token_id,
+            token_count_per_owner: old.token_count_per_owner,
+            leased_copies_by_borrower: LookupMap::new(
+                StorageKey::LeasedCopiesByBorrower.try_to_vec().unwrap(),
+            ),
+        }
+    }
+}
+
+/// Layout of the contract state before copies could be locked in place
+#[derive(BorshDeserialize)]
+pub struct ContractV4 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: VersionedTokens,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub events_by_id: UnorderedMap<EventId, Event>,
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub payment_token_id: Option<AccountId>,
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+    pub default_royalty: Royalty,
+    pub accounts_registry: Option<AccountId>,
+    pub registry_async_validation: bool,
+    pub registry_players: LookupSet<AccountId>,
+    pub transfer_hook: Option<AccountId>,
+    pub hook_breaker: HookBreaker,
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+    pub affiliate_program: Option<AffiliateProgram>,
+    pub affiliate_pool: Balance,
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+    pub leases: LookupMap<TokenId, Lease>,
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+    pub upgrade_treasury_id: Option<AccountId>,
+    pub unrouted_upgrade_payments: Balance,
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+    pub renewer_id: Option<AccountId>,
+    pub max_approvals_per_token: u32,
+    pub pause_status: PauseStatus,
+    pub pending_owner_id: Option<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub next_token_id: u64,
+    pub max_total_supply: Option<u64>,
+    pub minted_count: u64,
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+    pub account_funding_amount: Balance,
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+    pub platform_fee_bps: u16,
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+    pub token_count_per_owner: LookupMap<AccountId, u64>,
+    pub leased_copies_by_borrower: LookupMap<(AccountId, TokenId), Vec<TokenId>>,
+}
+
+impl From<ContractV4> for ContractV5 {
+    fn from(old: ContractV4) -> Self {
+        Self {
+            owner_id: old.owner_id,
+            tokens_per_owner: old.tokens_per_owner,
+            tokens_by_id: old.tokens_by_id,
+            token_metadata_by_id: old.token_metadata_by_id,
+            events_by_id: old.events_by_id,
+            approved_marketplaces: old.approved_marketplaces,
+            metadata: old.metadata,
+            payment_token_id: old.payment_token_id,
+            ft_price_by_token_id: old.ft_price_by_token_id,
+            ft_proceeds_by_event_id: old.ft_proceeds_by_event_id,
+            ft_paid_by_event_account: old.ft_paid_by_event_account,
+            checked_in_at_by_token_id: old.checked_in_at_by_token_id,
+            reward_pool_by_event_id: old.reward_pool_by_event_id,
+            pending_rewards: old.pending_rewards,
+            default_royalty: old.default_royalty,
+            accounts_registry: old.accounts_registry,
+            registry_async_validation: old.registry_async_validation,
+            registry_players: old.registry_players,
+            transfer_hook: old.transfer_hook,
+            hook_breaker: old.hook_breaker,
+            pending_mints: old.pending_mints,
+            marketplace_sync: old.marketplace_sync,
+            affiliate_program: old.affiliate_program,
+            affiliate_pool: old.affiliate_pool,
+            affiliate_rewards: old.affiliate_rewards,
+            leases: old.leases,
+            lease_approvals: old.lease_approvals,
+            subscription_plans: old.subscription_plans,
+            subscriptions: old.subscriptions,
+            pass_expiry_by_token_id: old.pass_expiry_by_token_id,
+            storage_deposits: old.storage_deposits,
+            upgrade_config_by_token_id: old.upgrade_config_by_token_id,
+            upgrade_treasury_id: old.upgrade_treasury_id,
+            unrouted_upgrade_payments: old.unrouted_upgrade_payments,
+            level_by_token_id: old.level_by_token_id,
+            metadata_history_by_token_id: old.metadata_history_by_token_id,
+            parent_by_token_id: old.parent_by_token_id,
+            children_by_token_id: old.children_by_token_id,
+            renewal_config_by_token_id: old.renewal_config_by_token_id,
+            series_by_id: old.series_by_id,
+            tokens_per_series: old.tokens_per_series,
+            passes_per_event: old.passes_per_event,
+            renewer_id: old.renewer_id,
+            max_approvals_per_token: old.max_approvals_per_token,
+            pause_status: old.pause_status,
+            pending_owner_id: old.pending_owner_id,
+            minters: old.minters,
+            next_token_id: old.next_token_id,
+            max_total_supply: old.max_total_supply,
+            minted_count: old.minted_count,
+            frozen_tokens: old.frozen_tokens,
+            blacklist: old.blacklist,
+            recoveries_by_token_id: old.recoveries_by_token_id,
+            account_funding_amount: old.account_funding_amount,
+            events_per_organiser: old.events_per_organiser,
+            tokens_per_event: old.tokens_per_event,
+            platform_fee_bps: old.platform_fee_bps,
+            waitlists: old.waitlists,
+            waitlist_escrow: old.waitlist_escrow,
+            approvals_by_token_id: old.approvals_by_token_id,
+            token_count_per_owner: old.token_count_per_owner,
+            leased_copies_by_borrower: old.leased_copies_by_borrower,
+            locks_by_token_id: LookupMap::new(StorageKey::LocksByTokenId.try_to_vec().unwrap()),
+        }
+    }
+}
+
+/// Layout of the contract state before the NEAR price of passes was held in escrow
+#[derive(BorshDeserialize)]
+pub struct ContractV5 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: VersionedTokens,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub events_by_id: UnorderedMap<EventId, Event>,
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub payment_token_id: Option<AccountId>,
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+    pub default_royalty: Royalty,
+    pub accounts_registry: Option<AccountId>,
+    pub registry_async_validation: bool,
+    pub registry_players: LookupSet<AccountId>,
+    pub transfer_hook: Option<AccountId>,
+    pub hook_breaker: HookBreaker,
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+    pub affiliate_program: Option<AffiliateProgram>,
+    pub affiliate_pool: Balance,
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+    pub leases: LookupMap<TokenId, Lease>,
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+    pub upgrade_treasury_id: Option<AccountId>,
+    pub unrouted_upgrade_payments: Balance,
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+    pub renewer_id: Option<AccountId>,
+    pub max_approvals_per_token: u32,
+    pub pause_status: PauseStatus,
+    pub pending_owner_id: Option<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub next_token_id: u64,
+    pub max_total_supply: Option<u64>,
+    pub minted_count: u64,
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+    pub account_funding_amount: Balance,
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+    pub platform_fee_bps: u16,
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+    pub token_count_per_owner: LookupMap<AccountId, u64>,
+    pub leased_copies_by_borrower: LookupMap<(AccountId, TokenId), Vec<TokenId>>,
+    pub locks_by_token_id: LookupMap<TokenId, AccountId>,
+}
+
+impl From<ContractV5> for ContractV6 {
+    fn from(old: ContractV5) -> Self {
+        Self {
+            owner_id: old.owner_id,
+            tokens_per_owner: old.tokens_per_owner,
+            tokens_by_id: old.tokens_by_id,
+            token_metadata_by_id: old.token_metadata_by_id,
+            events_by_id: old.events_by_id,
+            approved_marketplaces: old.approved_marketplaces,
+            metadata: old.metadata,
+            payment_token_id: old.payment_token_id,
+            ft_price_by_token_id: old.ft_price_by_token_id,
+            ft_proceeds_by_event_id: old.ft_proceeds_by_event_id,
+            ft_paid_by_event_account: old.ft_paid_by_event_account,
+            checked_in_at_by_token_id: old.checked_in_at_by_token_id,
+            reward_pool_by_event_id: old.reward_pool_by_event_id,
+            pending_rewards: old.pending_rewards,
+            default_royalty: old.default_royalty,
+            accounts_registry: old.accounts_registry,
+            registry_async_validation: old.registry_async_validation,
+            registry_players: old.registry_players,
+            transfer_hook: old.transfer_hook,
+            hook_breaker: old.hook_breaker,
+            pending_mints: old.pending_mints,
+            marketplace_sync: old.marketplace_sync,
+            affiliate_program: old.affiliate_program,
+            affiliate_pool: old.affiliate_pool,
+            affiliate_rewards: old.affiliate_rewards,
+            leases: old.leases,
+            lease_approvals: old.lease_approvals,
+            subscription_plans: old.subscription_plans,
+            subscriptions: old.subscriptions,
+            pass_expiry_by_token_id: old.pass_expiry_by_token_id,
+            storage_deposits: old.storage_deposits,
+            upgrade_config_by_token_id: old.upgrade_config_by_token_id,
+            upgrade_treasury_id: old.upgrade_treasury_id,
+            unrouted_upgrade_payments: old.unrouted_upgrade_payments,
+            level_by_token_id: old.level_by_token_id,
+            metadata_history_by_token_id: old.metadata_history_by_token_id,
+            parent_by_token_id: old.parent_by_token_id,
+            children_by_token_id: old.children_by_token_id,
+            renewal_config_by_token_id: old.renewal_config_by_token_id,
+            series_by_id: old.series_by_id,
+            tokens_per_series: old.tokens_per_series,
+            passes_per_event: old.passes_per_event,
+            renewer_id: old.renewer_id,
+            max_approvals_per_token: old.max_approvals_per_token,
+            pause_status: old.pause_status,
+            pending_owner_id: old.pending_owner_id,
+            minters: old.minters,
+            next_token_id: old.next_token_id,
+            max_total_supply: old.max_total_supply,
+            minted_count: old.minted_count,
+            frozen_tokens: old.frozen_tokens,
+            blacklist: old.blacklist,
+            recoveries_by_token_id: old.recoveries_by_token_id,
+            account_funding_amount: old.account_funding_amount,
+            events_per_organiser: old.events_per_organiser,
+            tokens_per_event: old.tokens_per_event,
+            platform_fee_bps: old.platform_fee_bps,
+            waitlists: old.waitlists,
+            waitlist_escrow: old.waitlist_escrow,
+            approvals_by_token_id: old.approvals_by_token_id,
+            token_count_per_owner: old.token_count_per_owner,
+            leased_copies_by_borrower: old.leased_copies_by_borrower,
+            locks_by_token_id: old.locks_by_token_id,
+            near_proceeds_by_event_id: LookupMap::new(
+                StorageKey::NearProceedsByEventId.try_to_vec().unwrap(),
+            ),
+            near_paid_by_event_account: LookupMap::new(
+                StorageKey::NearPaidByEventAccount.try_to_vec().unwrap(),
+            ),
+        }
+    }
+}
+
+/// Layout of the contract state before the FT owed in the payment token was totalled
+#[derive(BorshDeserialize)]
+pub struct ContractV6 {
+    pub owner_id: AccountId,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>,
+    pub tokens_by_id: VersionedTokens,
+    pub token_metadata_by_id: UnorderedMap<TokenId, TokenMetadata>,
+    pub events_by_id: UnorderedMap<EventId, Event>,
+    pub approved_marketplaces: UnorderedMap<AccountId, MarketplaceApproval>,
+    pub metadata: LazyOption<NFTContractMetadata>,
+    pub payment_token_id: Option<AccountId>,
+    pub ft_price_by_token_id: UnorderedMap<TokenId, Balance>,
+    pub ft_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub ft_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+    pub checked_in_at_by_token_id: LookupMap<TokenId, u64>,
+    pub reward_pool_by_event_id: LookupMap<EventId, Balance>,
+    pub pending_rewards: LookupMap<(EventId, AccountId), Balance>,
+    pub default_royalty: Royalty,
+    pub accounts_registry: Option<AccountId>,
+    pub registry_async_validation: bool,
+    pub registry_players: LookupSet<AccountId>,
+    pub transfer_hook: Option<AccountId>,
+    pub hook_breaker: HookBreaker,
+    pub pending_mints: UnorderedMap<TokenId, PendingMint>,
+    pub marketplace_sync: LookupMap<AccountId, MarketplaceSync>,
+    pub affiliate_program: Option<AffiliateProgram>,
+    pub affiliate_pool: Balance,
+    pub affiliate_rewards: LookupMap<AccountId, Balance>,
+    pub leases: LookupMap<TokenId, Lease>,
+    pub lease_approvals: LookupMap<TokenId, LeaseApproval>,
+    pub subscription_plans: UnorderedMap<PlanId, SubscriptionPlan>,
+    pub subscriptions: LookupMap<(AccountId, PlanId), Subscription>,
+    pub pass_expiry_by_token_id: LookupMap<TokenId, u64>,
+    pub storage_deposits: LookupMap<AccountId, AccountStorage>,
+    pub upgrade_config_by_token_id: LookupMap<TokenId, UpgradeConfig>,
+    pub upgrade_treasury_id: Option<AccountId>,
+    pub unrouted_upgrade_payments: Balance,
+    pub level_by_token_id: LookupMap<TokenId, u16>,
+    pub metadata_history_by_token_id: LookupMap<TokenId, Vec<MetadataChange>>,
+    pub parent_by_token_id: LookupMap<TokenId, TokenId>,
+    pub children_by_token_id: LookupMap<TokenId, Vec<TokenId>>,
+    pub renewal_config_by_token_id: LookupMap<TokenId, RenewalConfig>,
+    pub series_by_id: UnorderedMap<SeriesId, Series>,
+    pub tokens_per_series: LookupMap<SeriesId, UnorderedSet<TokenId>>,
+    pub passes_per_event: LookupMap<EventId, UnorderedSet<AccountId>>,
+    pub renewer_id: Option<AccountId>,
+    pub max_approvals_per_token: u32,
+    pub pause_status: PauseStatus,
+    pub pending_owner_id: Option<AccountId>,
+    pub minters: UnorderedSet<AccountId>,
+    pub next_token_id: u64,
+    pub max_total_supply: Option<u64>,
+    pub minted_count: u64,
+    pub frozen_tokens: LookupMap<TokenId, FreezeInfo>,
+    pub blacklist: UnorderedSet<AccountId>,
+    pub recoveries_by_token_id: LookupMap<TokenId, u32>,
+    pub account_funding_amount: Balance,
+    pub events_per_organiser: LookupMap<AccountId, UnorderedSet<EventId>>,
+    pub tokens_per_event: LookupMap<EventId, UnorderedSet<TokenId>>,
+    pub platform_fee_bps: u16,
+    pub waitlists: LookupMap<EventId, Vector<AccountId>>,
+    pub waitlist_escrow: LookupMap<(EventId, AccountId), Balance>,
+    pub approvals_by_token_id: LookupMap<TokenId, ApprovalInfo>,
+    pub token_count_per_owner: LookupMap<AccountId, u64>,
+    pub leased_copies_by_borrower: LookupMap<(AccountId, TokenId), Vec<TokenId>>,
+    pub locks_by_token_id: LookupMap<TokenId, AccountId>,
+    pub near_proceeds_by_event_id: LookupMap<EventId, Balance>,
+    pub near_paid_by_event_account: LookupMap<(EventId, AccountId), Balance>,
+}
+
+impl From<ContractV6> for Contract {
+    fn from(old: ContractV6) -> Self {
+        Self {
+            owner_id: old.owner_id,
+            tokens_per_owner: old.tokens_per_owner,
+            tokens_by_id: old.tokens_by_id,
+            token_metadata_by_id: old.token_metadata_by_id,
+            events_by_id: old.events_by_id,
+            approved_marketplaces: old.approved_marketplaces,
+            metadata: old.metadata,
+            payment_token_id: old.payment_token_id,
+            ft_price_by_token_id: old.ft_price_by_token_id,
+            ft_proceeds_by_event_id: old.ft_proceeds_by_event_id,
+            ft_paid_by_event_account: old.ft_paid_by_event_account,
+            checked_in_at_by_token_id: old.checked_in_at_by_token_id,
+            reward_pool_by_event_id: old.reward_pool_by_event_id,
+            pending_rewards: old.pending_rewards,
+            default_royalty: old.default_royalty,
+            accounts_registry: old.accounts_registry,
+            registry_async_validation: old.registry_async_validation,
+            registry_players: old.registry_players,
+            transfer_hook: old.transfer_hook,
+            hook_breaker: old.hook_breaker,
+            pending_mints: old.pending_mints,
+            marketplace_sync: old.marketplace_sync,
+            affiliate_program: old.affiliate_program,
+            affiliate_pool: old.affiliate_pool,
+            affiliate_rewards: old.affiliate_rewards,
+            leases: old.leases,
+            lease_approvals: old.lease_approvals,
+            subscription_plans: old.subscription_plans,
+            subscriptions: old.subscriptions,
+            pass_expiry_by_token_id: old.pass_expiry_by_token_id,
+            storage_deposits: old.storage_deposits,
+            upgrade_config_by_token_id: old.upgrade_config_by_token_id,
+            upgrade_treasury_id: old.upgrade_treasury_id,
+            unrouted_upgrade_payments: old.unrouted_upgrade_payments,
+            level_by_token_id: old.level_by_token_id,
+            metadata_history_by_token_id: old.metadata_history_by_token_id,
+            parent_by_token_id: old.parent_by_token_id,
+            children_by_token_id: old.children_by_token_id,
+            renewal_config_by_token_id: old.renewal_config_by_token_id,
+            series_by_id: old.series_by_id,
+            tokens_per_series: old.tokens_per_series,
+            passes_per_event: old.passes_per_event,
+            renewer_id: old.renewer_id,
+            max_approvals_per_token: old.max_approvals_per_token,
+            pause_status: old.pause_status,
+            pending_owner_id: old.pending_owner_id,
+            minters: old.minters,
+            next_token_id: old.next_token_id,
+            max_total_supply: old.max_total_supply,
+            minted_count: old.minted_count,
+            frozen_tokens: old.frozen_tokens,
+            blacklist: old.blacklist,
+            recoveries_by_token_id: old.recoveries_by_token_id,
+            account_funding_amount: old.account_funding_amount,
+            events_per_organiser: old.events_per_organiser,
+            tokens_per_event: old.tokens_per_event,
+            platform_fee_bps: old.platform_fee_bps,
+            waitlists: old.waitlists,
+            waitlist_escrow: old.waitlist_escrow,
+            approvals_by_token_id: old.approvals_by_token_id,
+            token_count_per_owner: old.token_count_per_owner,
+            leased_copies_by_borrower: old.leased_copies_by_borrower,
+            locks_by_token_id: old.locks_by_token_id,
+            near_proceeds_by_event_id: old.near_proceeds_by_event_id,
+            near_paid_by_event_account: old.near_paid_by_event_account,
+            // the FT owed in the maps is totalled by migrate_ft_liabilities
+            ft_liabilities: old.affiliate_pool + old.unrouted_upgrade_payments,
+        }
+    }
+}
+
+/// Layout of the token classes while their approvals were a LookupMap per owner inside them
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenV1 {
+    pub token_id: TokenId,
+    pub copies_minted: u64,
+    pub max_copies: u64,
+    pub expires_at: Option<u64>,
+    pub token_dependency_by_id: Vec<TokenId>,
+    pub event_dependency_by_id: Vec<EventId>,
+    pub external_dependencies: Vec<ExternalDependency>,
+    //prefix of the LookupMap<AccountId, ApprovalInfo> of the class, the approvals of its copies
+    //are moved by migrate_token_approvals
+    pub approvals_prefix: Vec<u8>,
+    pub royalty: Royalty,
+    pub metadata_frozen: bool,
+    pub soulbound: bool,
+}
+
+impl From<TokenV1> for Token {
+    fn from(old: TokenV1) -> Self {
+        Self {
+            token_id: old.token_id,
+            copies_minted: old.copies_minted,
+            max_copies: old.max_copies,
+            expires_at: old.expires_at,
+            token_dependency_by_id: old.token_dependency_by_id,
+            event_dependency_by_id: old.event_dependency_by_id,
+            external_dependencies: old.external_dependencies,
+            royalty: old.royalty,
+            metadata_frozen: old.metadata_frozen,
+            soulbound: old.soulbound,
+        }
+    }
+}
+
+/// Layouts of the token classes. A layout change freezes the current Token as the struct of its
+/// version here and adds a variant, older variants are converted to the latest Token when read
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum VersionedToken {
+    V1(TokenV1),
+    V2(Token),
+}
+
+impl From<VersionedToken> for Token {
+    fn from(versioned: VersionedToken) -> Self {
+        match versioned {
+            VersionedToken::V1(old) => old.into(),
+            VersionedToken::V2(token) => token,
+        }
+    }
+}
+
+/// Token class written without a version tag, either in the V1 layout or, once approvals moved
+/// out of the classes, in the V2 layout. Only V1 classes hold the approvals prefix of their id
+pub struct UnversionedToken(VersionedToken);
+
+impl BorshDeserialize for UnversionedToken {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let raw_token = std::mem::take(buf);
+
+        if let Ok(old) = TokenV1::try_from_slice(raw_token) {
+            if old.approvals_prefix == legacy_approvals_prefix(&old.token_id) {
+                return Ok(Self(VersionedToken::V1(old)));
+            }
+        }
+
+        Token::try_from_slice(raw_token).map(|token| Self(VersionedToken::V2(token)))
+    }
+}
+
+impl BorshSerialize for UnversionedToken {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match &self.0 {
+            VersionedToken::V1(old) => old.serialize(writer),
+            VersionedToken::V2(token) => token.serialize(writer),
+        }
+    }
+}
+
+/// Token classes by id. Tokens written before versioning stay unversioned under the original
+/// prefix until they are written again, so no migration has to touch every token at once
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VersionedTokens {
+    legacy: LookupMap<TokenId, UnversionedToken>,
+    versioned: LookupMap<TokenId, VersionedToken>,
+}
+
+impl VersionedTokens {
+    pub(crate) fn new() -> Self {
+        Self::from_legacy(LookupMap::new(StorageKey::TokensById.try_to_vec().unwrap()))
+    }
+
+    fn from_legacy(legacy: LookupMap<TokenId, UnversionedToken>) -> Self {
+        Self {
+            legacy,
+            versioned: LookupMap::new(StorageKey::VersionedTokensById.try_to_vec().unwrap()),
+        }
+    }
+
+    pub fn get(&self, token_id: &TokenId) -> Option<Token> {
+        self.versioned
+            .get(token_id)
+            .map(Token::from)
+            .or_else(|| self.legacy.get(token_id).map(|legacy| legacy.0.into()))
+    }
+
+    //the token is stored in the latest layout, an unversioned copy is dropped
+    pub fn insert(&mut self, token_id: &TokenId, token: &Token) -> Option<Token> {
+        let legacy = self.legacy.remove(token_id).map(|legacy| legacy.0.into());
+        self.versioned
+            .insert(token_id, &VersionedToken::V2(token.clone()))
+            .map(Token::from)
+            .or(legacy)
+    }
+
+    pub fn remove(&mut self, token_id: &TokenId) -> Option<Token> {
+        let legacy = self.legacy.remove(token_id).map(|legacy| legacy.0.into());
+        self.versioned.remove(token_id).map(Token::from).or(legacy)
+    }
+
+    //rewrites a class still stored in the V1 layout in the latest one, false if there is none
+    pub(crate) fn migrate_v1(&mut self, token_id: &TokenId) -> bool {
+        match self.legacy.get(token_id) {
+            Some(UnversionedToken(VersionedToken::V1(old))) => {
+                self.insert(token_id, &old.into());
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Contract {
+    //marks the state of a new contract as written in the current layout
+    pub(crate) fn internal_write_state_version() {
+        env::storage_write(STATE_VERSION_KEY, &[STATE_VERSION]);
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, called right after deploying new code. Reads the state in the layout it was
+    /// written with and maps it to the current one, the state is returned as is when its layout
+    /// is current. The contract itself can call it too, to migrate in the deploy transaction
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let versioned = VersionedContract::read();
+
+        let predecessor_id = env::predecessor_account_id();
+        require!(
+            &predecessor_id == versioned.owner_id() || predecessor_id == env::current_account_id(),
+            "It is a owner only method"
+        );
+
+        Self::internal_write_state_version();
+
+        versioned.into()
+    }
+
+    /// Owner only, deploys the raw wasm passed as the input of the call to the contract and calls
+    /// migrate in the same transaction. The gas left after the deploy goes to migrate
+    pub fn upgrade(&self) -> Promise {
+        self.assert_owner();
+
+        let code = env::input().unwrap_or_default();
+        require!(
+            !code.is_empty(),
+            "Input must be the wasm of the new contract"
+        );
+
+        let migrate_gas = env::prepaid_gas()
+            .saturating_sub(env::used_gas())
+            .saturating_sub(GAS_RESERVED_FOR_UPGRADE);
+        require!(
+            migrate_gas >= MIN_GAS_FOR_MIGRATE,
+            format!(
+                "Attach at least {} gas",
+                MIN_GAS_FOR_MIGRATE + GAS_RESERVED_FOR_UPGRADE
+            )
+        );
+
+        CatchEventLogVariant::ContractUpgraded(ContractUpgradedLog {
+            code_hash: Base64VecU8::from(env::sha256(&code)),
+        })
+        .emit();
+
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(b"migrate".to_vec(), vec![], 0, migrate_gas)
+    }
+}
+
+#[cfg(test)]
+mod migrate_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::VMContext;
+    use near_sdk::{test_utils, testing_env};
+
+    const CLASS: &str = "catchcon.gold";
+
+    fn token_v1(token: Token) -> TokenV1 {
+        TokenV1 {
+            approvals_prefix: legacy_approvals_prefix(&token.token_id),
+            token_id: token.token_id,
+            copies_minted: token.copies_minted,
+            max_copies: token.max_copies,
+            expires_at: token.expires_at,
+            token_dependency_by_id: token.token_dependency_by_id,
+            event_dependency_by_id: token.event_dependency_by_id,
+            external_dependencies: token.external_dependencies,
+            royalty: token.royalty,
+            metadata_frozen: token.metadata_frozen,
+            soulbound: token.soulbound,
+        }
+    }
+
+    //state of the contract as V1 wrote it, the class is kept unversioned in the layout written
+    //before approvals_by_token_id
+    fn write_v1_state() {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), CLASS.to_string());
+
+        let token = contract.tokens_by_id.remove(&CLASS.to_string()).unwrap();
+        contract.tokens_by_id.legacy.insert(
+            &CLASS.to_string(),
+            &UnversionedToken(VersionedToken::V1(token_v1(token))),
+        );
+
+        //V1 kept the legacy map alone in place of VersionedTokens
+        let tokens_at = contract.owner_id.try_to_vec().unwrap().len()
+            + contract.tokens_per_owner.try_to_vec().unwrap().len()
+            + contract.tokens_by_id.legacy.try_to_vec().unwrap().len();
+        let versioned_len = contract.tokens_by_id.versioned.try_to_vec().unwrap().len();
+
+        let mut state = contract.try_to_vec().unwrap();
+        state.drain(tokens_at..tokens_at + versioned_len);
+        //nor the fields added since
+        let added_len = contract.token_count_per_owner.try_to_vec().unwrap().len()
+            + contract
+                .leased_copies_by_borrower
+                .try_to_vec()
+                .unwrap()
+                .len()
+            + contract.locks_by_token_id.try_to_vec().unwrap().len()
+            + contract
+                .near_proceeds_by_event_id
+                .try_to_vec()
+                .unwrap()
+                .len()
+            + contract
+                .near_paid_by_event_account
+                .try_to_vec()
+                .unwrap()
+                .len()
+            + contract.ft_liabilities.try_to_vec().unwrap().len();
+        state.truncate(state.len() - added_len);
+        assert!(ContractV1::try_from_slice(&state).is_ok());
+
+        env::storage_write(b"STATE", &state);
+        env::storage_remove(STATE_VERSION_KEY);
+    }
+
+    #[test]
+    fn v1_state_is_migrated() {
+        write_v1_state();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = Contract::migrate();
+
+        assert_eq!(contract.owner_id, nft().to_string());
+        assert_eq!(contract.nft_total_supply().0, 1);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+        assert_eq!(
+            env::storage_read(STATE_VERSION_KEY),
+            Some(vec![STATE_VERSION])
+        );
+
+        //the token is read from the legacy map until it is written again
+        let token = contract.tokens_by_id.get(&CLASS.to_string()).unwrap();
+        assert_eq!(token.copies_minted, 1);
+        assert!(contract
+            .nft_token("catchcon.gold.alice.near".to_string())
+            .is_some());
+
+        contract.tokens_by_id.insert(&CLASS.to_string(), &token);
+        assert!(contract
+            .tokens_by_id
+            .legacy
+            .get(&CLASS.to_string())
+            .is_none());
+        assert!(contract
+            .tokens_by_id
+            .versioned
+            .get(&CLASS.to_string())
+            .is_some());
+
+        //migrating the current layout keeps the state
+        env::state_write(&contract);
+        let contract = Contract::migrate();
+        assert!(contract.tokens_by_id.get(&CLASS.to_string()).is_some());
+    }
+
+    #[test]
+    fn unversioned_tokens_of_both_layouts_are_read() {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold", "silver"], 10);
+
+        //gold was written before approvals_by_token_id, silver after it
+        let gold = contract.tokens_by_id.remove(&CLASS.to_string()).unwrap();
+        contract.tokens_by_id.legacy.insert(
+            &CLASS.to_string(),
+            &UnversionedToken(VersionedToken::V1(token_v1(gold))),
+        );
+        let silver_id = "catchcon.silver".to_string();
+        let silver = contract.tokens_by_id.remove(&silver_id).unwrap();
+        contract
+            .tokens_by_id
+            .legacy
+            .insert(&silver_id, &UnversionedToken(VersionedToken::V2(silver)));
+
+        assert_eq!(
+            contract
+                .tokens_by_id
+                .get(&CLASS.to_string())
+                .unwrap()
+                .max_copies,
+            10
+        );
+        assert_eq!(
+            contract.tokens_by_id.get(&silver_id).unwrap().max_copies,
+            10
+        );
+
+        assert!(contract.tokens_by_id.migrate_v1(&CLASS.to_string()));
+        assert!(!contract.tokens_by_id.migrate_v1(&CLASS.to_string()));
+        assert!(!contract.tokens_by_id.migrate_v1(&silver_id));
+        assert!(contract
+            .tokens_by_id
+            .versioned
+            .get(&CLASS.to_string())
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn migrate_by_other_account_fails() {
+        write_v1_state();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        Contract::migrate();
+    }
+
+    fn upgrade_context(predecessor_account_id: AccountId, code: Vec<u8>) -> VMContext {
+        let mut context = get_context(predecessor_account_id, 0);
+        context.input = code;
+        context
+    }
+
+    #[test]
+    fn upgrade_deploys_and_migrates() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let contract = create_contract();
+
+        let code = b"\0asm new contract".to_vec();
+        testing_env!(upgrade_context(nft().to_string(), code.clone()));
+        contract.upgrade();
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains(r#""receiver_id":"nft.catchlabs.near""#));
+        assert!(receipts.contains("DeployContract"));
+        assert!(receipts.contains(r#""method_name":"migrate""#));
+
+        let code_hash =
+            near_sdk::serde_json::to_string(&Base64VecU8::from(env::sha256(&code))).unwrap();
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            &format!(
+                r#"EVENT_JSON:{{"standard":"catch","version":"1.1.0","event":"contract_upgraded","data":{{"code_hash":{}}}}}"#,
+                code_hash
+            )
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Input must be the wasm of the new contract")]
+    fn upgrade_without_code_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let contract = create_contract();
+
+        contract.upgrade();
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn upgrade_by_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let contract = create_contract();
+
+        testing_env!(upgrade_context(carol().to_string(), b"\0asm".to_vec()));
+        contract.upgrade();
+    }
+}
@@ -0,0 +1,1039 @@
+use crate::*;
+use std::collections::HashSet;
+
+const GAS_FOR_RESOLVE_MINT: Gas = 20_000_000_000_000;
+pub const MAX_MINT_BATCH: usize = 25;
+pub const MAX_TOKEN_ID_LEN: usize = 64;
+//event part of the token ids of nft_mint_auto, tokens are minted as auto.next_token_id
+const AUTO_TOKEN_PREFIX: &str = "auto";
+
+/// Token of nft_mint written before the account of the receiver is created
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct PendingMint {
+    pub minter_id: AccountId,
+    pub receiver_id: AccountId,
+    pub deposit: Balance,
+    //storage of the token and the balance of the new account
+    pub cost: Balance,
+}
+
+/// Token of nft_mint_batch, the arguments of nft_mint
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintArgs {
+    pub receiver_id: ValidAccountId,
+    pub token_id: TokenId,
+    pub token_metadata: TokenMetadata,
+    pub public_key: Base58PublicKey,
+    pub royalty: Option<Royalty>,
+    #[serde(default)]
+    pub soulbound: bool,
+}
+
+#[ext_contract(ext_mint_self)]
+pub trait MintResolver {
+    fn resolve_mint(&mut self, token_id: TokenId);
+    fn resolve_mint_copy(
+        &mut self,
+        token_id: TokenId,
+        minter_id: AccountId,
+        deposit: U128,
+        cost: U128,
+    );
+}
+
+//panics with the shortfall unless the attached deposit covers the cost
+pub(crate) fn assert_deposit_covers(cost: Balance) {
+    let deposit = env::attached_deposit();
+    if deposit < cost {
+        env::panic(
+            format!(
+                "Must attach {} yoctoNEAR to cover storage, {} yoctoNEAR short",
+                cost,
+                cost - deposit
+            )
+            .as_bytes(),
+        );
+    }
+}
+
+impl Contract {
+    //true if the token waits for nft_mint to be finalized
+    pub(crate) fn internal_is_pending(&self, token_id: &TokenId) -> bool {
+        self.pending_mints.get(token_id).is_some()
+    }
+
+    //writes the standalone token and its metadata, the token stays pending until
+    //internal_start_mint is called for it
+    fn internal_write_mint(
+        &mut self,
+        token_id: &TokenId,
+        token_metadata: &TokenMetadata,
+        royalty: Option<Royalty>,
+        soulbound: bool,
+    ) {
+        assert_valid_token_id(token_id);
+        token_metadata.assert_valid();
+        self.internal_count_mint();
+
+        let token = Token {
+            token_id: token_id.clone(),
+            copies_minted: 1,
+            max_copies: token_metadata.copies.unwrap_or(1),
+            expires_at: token_metadata.expires_at,
+            token_dependency_by_id: vec![],
+            event_dependency_by_id: vec![],
+            external_dependencies: vec![],
+            royalty: self.internal_merge_royalty(&Default::default(), royalty.unwrap_or_default()),
+            metadata_frozen: false,
+            soulbound,
+        };
+
+        require!(
+            self.tokens_by_id.insert(token_id, &token).is_none(),
+            "Token Already exists"
+        );
+
+        self.token_metadata_by_id.insert(token_id, token_metadata);
+    }
+
+    //writes the standalone token and starts creating the account of the receiver
+    fn internal_mint(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        token_metadata: TokenMetadata,
+        public_key: Base58PublicKey,
+        royalty: Option<Royalty>,
+        soulbound: bool,
+    ) -> Promise {
+        // nothing is written for a deposit that can't cover the account and the token id
+        assert_deposit_covers(
+            env::storage_byte_cost()
+                * Balance::from(bytes_for_token_or_event_or_account_id(&token_id))
+                + self.account_funding_amount,
+        );
+
+        let initial_storage = env::storage_usage();
+
+        self.internal_write_mint(&token_id, &token_metadata, royalty, soulbound);
+
+        // the token is given to the receiver once the account is created
+        let storage_used = env::storage_usage() - initial_storage
+            + bytes_for_token_or_event_or_account_id(&token_id);
+        let cost =
+            env::storage_byte_cost() * Balance::from(storage_used) + self.account_funding_amount;
+
+        assert_deposit_covers(cost);
+
+        self.internal_start_mint(
+            receiver_id,
+            token_id,
+            public_key,
+            env::attached_deposit(),
+            cost,
+        )
+    }
+
+    //takes the next free auto.n token id, ids minted explicitly in the auto range are skipped
+    fn internal_next_token_id(&mut self) -> TokenId {
+        loop {
+            let token_id = format!("{}.{}", AUTO_TOKEN_PREFIX, self.next_token_id);
+            self.next_token_id += 1;
+
+            if self.tokens_by_id.get(&token_id).is_none() {
+                return token_id;
+            }
+        }
+    }
+
+    //marks the token pending and creates the account of the receiver, resolve_mint refunds
+    //deposit - cost on success and the whole deposit on failure
+    pub(crate) fn internal_start_mint(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        public_key: Base58PublicKey,
+        deposit: Balance,
+        cost: Balance,
+    ) -> Promise {
+        self.assert_not_blacklisted(&receiver_id);
+
+        self.pending_mints.insert(
+            &token_id,
+            &PendingMint {
+                minter_id: env::predecessor_account_id(),
+                receiver_id: receiver_id.clone(),
+                deposit,
+                cost,
+            },
+        );
+
+        Promise::new(receiver_id)
+            .create_account()
+            .transfer(self.account_funding_amount)
+            .add_full_access_key(public_key.into())
+            .then(ext_mint_self::resolve_mint(
+                token_id,
+                &env::current_account_id(),
+                0,
+                GAS_FOR_RESOLVE_MINT,
+            ))
+    }
+
+    //creates the account of the receiver for a copy reserved with internal_reserve_copy, the
+    //deposit has to cover storage_used plus the storage of the copy and the account funding,
+    //resolve_mint_copy refunds the rest
+    pub(crate) fn internal_start_mint_copy(
+        &mut self,
+        receiver_id: AccountId,
+        original_token_id: TokenId,
+        public_key: Base58PublicKey,
+        storage_used: u64,
+    ) -> Promise {
+        // the receiver's set of tokens is written once the account is created
+        let storage_used = storage_used
+            + bytes_for_token_or_event_or_account_id(&receiver_id)
+            + bytes_for_token_or_event_or_account_id(&original_token_id);
+        let cost =
+            env::storage_byte_cost() * Balance::from(storage_used) + self.account_funding_amount;
+
+        assert_deposit_covers(cost);
+
+        Promise::new(receiver_id.clone())
+            .create_account()
+            .transfer(self.account_funding_amount)
+            .add_full_access_key(public_key.into())
+            .then(ext_mint_self::resolve_mint_copy(
+                build_full_token_id(original_token_id, receiver_id),
+                env::predecessor_account_id(),
+                env::attached_deposit().into(),
+                cost.into(),
+                &env::current_account_id(),
+                0,
+                GAS_FOR_RESOLVE_MINT,
+            ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner or minter only, creates the account of the receiver and mints a standalone token
+    /// to it. The token is pending until the account is created, if creating it fails the token
+    /// is removed and the whole deposit is refunded. royalty is merged over the default royalty
+    /// and paid on every sale. copies of the metadata sets the size of the edition, the other
+    /// copies are minted with nft_mint_copy
+    #[payable]
+    pub fn nft_mint(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        token_metadata: TokenMetadata,
+        public_key: Base58PublicKey,
+        royalty: Option<Royalty>,
+        soulbound: Option<bool>,
+    ) -> Promise {
+        self.assert_minter();
+        self.assert_not_paused(PausableFeature::Mint);
+
+        self.internal_mint(
+            receiver_id.into(),
+            token_id,
+            token_metadata,
+            public_key,
+            royalty,
+            soulbound.unwrap_or(false),
+        )
+    }
+
+    /// Owner or minter only, nft_mint for a receiver whose account already exists, no account is
+    /// created so the token is minted right away and only its storage is charged, the excess
+    /// is refunded
+    #[payable]
+    pub fn nft_mint_existing(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: TokenId,
+        token_metadata: TokenMetadata,
+        royalty: Option<Royalty>,
+        soulbound: Option<bool>,
+    ) {
+        self.assert_minter();
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let receiver_id: AccountId = receiver_id.into();
+        self.assert_not_blacklisted(&receiver_id);
+
+        let initial_storage = env::storage_usage();
+
+        self.internal_write_mint(
+            &token_id,
+            &token_metadata,
+            royalty,
+            soulbound.unwrap_or(false),
+        );
+        self.internal_finish_mint(
+            &receiver_id,
+            &token_id,
+            format!("{} minted to {}", &token_id, &receiver_id),
+        );
+
+        let storage_used = env::storage_usage() - initial_storage;
+        let cost = env::storage_byte_cost() * Balance::from(storage_used);
+
+        assert_deposit_covers(cost);
+
+        if env::attached_deposit() > cost {
+            Promise::new(env::predecessor_account_id()).transfer(env::attached_deposit() - cost);
+        }
+    }
+
+    /// Owner or minter only, nft_mint with a token id assigned by the contract, tokens are
+    /// minted as auto.1, auto.2 .. and the assigned token id is returned. Like nft_mint the
+    /// token is pending until the account of the receiver is created
+    #[payable]
+    pub fn nft_mint_auto(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_metadata: TokenMetadata,
+        public_key: Base58PublicKey,
+        royalty: Option<Royalty>,
+        soulbound: Option<bool>,
+    ) -> TokenId {
+        self.assert_minter();
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let token_id = self.internal_next_token_id();
+
+        self.internal_mint(
+            receiver_id.into(),
+            token_id.clone(),
+            token_metadata,
+            public_key,
+            royalty,
+            soulbound.unwrap_or(false),
+        );
+
+        token_id
+    }
+
+    /// Owner or minter only, nft_mint for up to MAX_MINT_BATCH tokens at once. The deposit has
+    /// to cover the storage of the whole batch and the excess is refunded once, nothing is minted if
+    /// any token id already exists. A token whose account can't be created is rolled back on
+    /// its own and its share of the cost refunded
+    #[payable]
+    pub fn nft_mint_batch(&mut self, mints: Vec<MintArgs>) {
+        self.assert_minter();
+        self.assert_not_paused(PausableFeature::Mint);
+
+        require!(!mints.is_empty(), "Mint batch is empty");
+        require!(
+            mints.len() <= MAX_MINT_BATCH,
+            format!("Can't mint more than {} tokens in a batch", MAX_MINT_BATCH)
+        );
+
+        let mut token_ids = HashSet::new();
+        for mint in mints.iter() {
+            require!(
+                token_ids.insert(&mint.token_id),
+                format!("Token {} is given twice in the batch", &mint.token_id)
+            );
+            require!(
+                self.tokens_by_id.get(&mint.token_id).is_none(),
+                format!("Token {} already exists", &mint.token_id)
+            );
+        }
+
+        let mut costs = Vec::with_capacity(mints.len());
+        for mint in mints.iter() {
+            let initial_storage = env::storage_usage();
+
+            self.internal_write_mint(
+                &mint.token_id,
+                &mint.token_metadata,
+                mint.royalty.clone(),
+                mint.soulbound,
+            );
+
+            let storage_used = env::storage_usage() - initial_storage
+                + bytes_for_token_or_event_or_account_id(&mint.token_id);
+            costs.push(
+                env::storage_byte_cost() * Balance::from(storage_used)
+                    + self.account_funding_amount,
+            );
+        }
+
+        let total_cost: Balance = costs.iter().sum();
+        assert_deposit_covers(total_cost);
+
+        for (mint, cost) in mints.into_iter().zip(costs) {
+            self.internal_start_mint(
+                mint.receiver_id.into(),
+                mint.token_id,
+                mint.public_key,
+                cost,
+                cost,
+            );
+        }
+
+        if env::attached_deposit() > total_cost {
+            Promise::new(env::predecessor_account_id())
+                .transfer(env::attached_deposit() - total_cost);
+        }
+    }
+
+    /// Owner or minter only, creates the account of the receiver and mints the next copy of a
+    /// standalone token to it, the copy shares the metadata of the token. The copy is reserved until the
+    /// account is created, if creating it fails the copy is released and the deposit refunded
+    #[payable]
+    pub fn nft_mint_copy(
+        &mut self,
+        original_token_id: TokenId,
+        receiver_id: ValidAccountId,
+        public_key: Base58PublicKey,
+    ) -> Promise {
+        self.assert_minter();
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let receiver_id: AccountId = receiver_id.into();
+
+        require!(
+            !self.internal_is_pending(&original_token_id),
+            "Token is pending"
+        );
+
+        self.internal_reserve_copy(&receiver_id, &original_token_id);
+
+        self.internal_start_mint_copy(receiver_id, original_token_id, public_key, 0)
+    }
+
+    //gives the pending token to the receiver and refunds the unused deposit if the account was
+    //created, otherwise removes the token and refunds the whole deposit
+    #[private]
+    pub fn resolve_mint(&mut self, token_id: TokenId) -> bool {
+        let pending = self
+            .pending_mints
+            .remove(&token_id)
+            .unwrap_or_else(|| env::panic(b"No pending mint"));
+
+        let created = (0..env::promise_results_count())
+            .all(|index| matches!(env::promise_result(index), PromiseResult::Successful(_)));
+
+        if created {
+            self.internal_finish_mint(
+                &pending.receiver_id,
+                &token_id,
+                format!("{} minted to {}", &token_id, &pending.receiver_id),
+            );
+
+            if pending.deposit > pending.cost {
+                Promise::new(pending.minter_id).transfer(pending.deposit - pending.cost);
+            }
+        } else {
+            env::log(
+                format!(
+                    "Creating {} failed, the mint of {} is rolled back",
+                    &pending.receiver_id, &token_id
+                )
+                .as_bytes(),
+            );
+
+            self.tokens_by_id.remove(&token_id);
+            self.token_metadata_by_id.remove(&token_id);
+            self.internal_remove_from_series(&token_id);
+            self.minted_count -= 1;
+
+            Promise::new(pending.minter_id).transfer(pending.deposit);
+        }
+
+        created
+    }
+
+    //gives the reserved copy (original_token_id.receiver_id) to the receiver if the account was
+    //created, otherwise releases the copy and refunds the whole deposit
+    #[private]
+    pub fn resolve_mint_copy(
+        &mut self,
+        token_id: TokenId,
+        minter_id: AccountId,
+        deposit: U128,
+        cost: U128,
+    ) -> bool {
+        let (original_token_id, receiver_id) = resolve_token_id(token_id);
+
+        let created = (0..env::promise_results_count())
+            .all(|index| matches!(env::promise_result(index), PromiseResult::Successful(_)));
+
+        if created {
+            self.internal_finish_mint(
+                &receiver_id,
+                &original_token_id,
+                format!("Copy of {} minted to {}", &original_token_id, &receiver_id),
+            );
+
+            if deposit.0 > cost.0 {
+                Promise::new(minter_id).transfer(deposit.0 - cost.0);
+            }
+        } else {
+            env::log(
+                format!(
+                    "Creating {} failed, the copy of {} is released",
+                    &receiver_id, &original_token_id
+                )
+                .as_bytes(),
+            );
+
+            self.internal_release_copy(&original_token_id);
+
+            Promise::new(minter_id).transfer(deposit.0);
+        }
+
+        created
+    }
+}
+
+#[cfg(test)]
+mod mint_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const TOKEN: &str = "genesis.hero";
+
+    fn mint(contract: &mut Contract) {
+        let public_key =
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            TOKEN.to_string(),
+            token_metadata(1),
+            public_key,
+            None,
+            None,
+        );
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) -> bool {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_mint(TOKEN.to_string())
+    }
+
+    #[test]
+    fn pending_token_is_hidden() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        // only the account creation batch and the callback, the refund waits for the callback
+        assert_eq!(test_utils::get_created_receipts().len(), 2);
+
+        assert!(contract.nft_token_by_id(TOKEN.to_string()).is_none());
+        assert!(contract
+            .nft_token(format!("{}.{}", TOKEN, alice()))
+            .is_none());
+        assert!(contract.nft_tokens(None, None).is_empty());
+        assert_eq!(contract.nft_total_supply().0, 0);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+    }
+
+    #[test]
+    fn mint_is_finalized_once_account_is_created() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        assert!(resolve(&mut contract, PromiseResult::Successful(vec![])));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_mint","data":[{"owner_id":"alice.near","token_ids":["genesis.hero"],"memo":"genesis.hero minted to alice.near"}]}"#
+            ]
+        );
+
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+        assert!(contract.nft_token_by_id(TOKEN.to_string()).is_some());
+        assert_eq!(contract.nft_total_supply().0, 1);
+    }
+
+    #[test]
+    fn unused_deposit_is_refunded_to_minter() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        let cost = contract.pending_mints.get(&TOKEN.to_string()).unwrap().cost;
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+
+        let receipts = test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        let refund = near_sdk::serde_json::to_string(&receipts[0]).unwrap();
+        assert!(refund.contains(&format!(r#""receiver_id":"{}""#, nft())));
+        assert!(refund.contains(&format!(r#""deposit":{}"#, STORAGE_DEPOSIT - cost)));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Must attach 10160000000000000000000 yoctoNEAR to cover storage, 160000000000000000000 yoctoNEAR short"
+    )]
+    fn mint_with_too_small_deposit_fails() {
+        testing_env!(get_context(nft().to_string(), BASE_STORAGE_COST));
+        let mut contract = create_contract();
+
+        contract.nft_mint(
+            alice(),
+            TOKEN.to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn mint_to_existing_account_skips_account_creation() {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+
+        contract.nft_mint_existing(alice(), TOKEN.to_string(), token_metadata(1), None, None);
+
+        // only the refund, no account is created
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(!receipts.contains("CreateAccount"));
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+
+        assert!(!contract.internal_is_pending(&TOKEN.to_string()));
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+        assert!(contract
+            .nft_token(format!("{}.{}", TOKEN, alice()))
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "yoctoNEAR short")]
+    fn mint_to_existing_account_without_deposit_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        contract.nft_mint_existing(alice(), TOKEN.to_string(), token_metadata(1), None, None);
+    }
+
+    #[test]
+    fn auto_mint_assigns_sequential_ids() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        // an explicit id in the auto range is skipped
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            "auto.2".to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+
+        let token_ids: Vec<TokenId> = (0..2)
+            .map(|_| {
+                contract.nft_mint_auto(
+                    bob(),
+                    token_metadata(1),
+                    Base58PublicKey::try_from(
+                        "ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN",
+                    )
+                    .unwrap(),
+                    None,
+                    None,
+                )
+            })
+            .collect();
+
+        assert_eq!(token_ids, vec!["auto.1".to_string(), "auto.3".to_string()]);
+        assert_eq!(contract.next_token_id, 4);
+        assert!(contract.internal_is_pending(&"auto.3".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "TokenId can only contain Ascii alphanumerics and - . _ :")]
+    fn mint_with_invalid_token_id_fails() {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+
+        contract.nft_mint(
+            alice(),
+            "genesis.hero/1".to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn failed_account_creation_rolls_back_mint() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        assert!(!resolve(&mut contract, PromiseResult::Failed));
+
+        assert_eq!(
+            test_utils::get_logs(),
+            vec!["Creating alice.near failed, the mint of genesis.hero is rolled back"]
+        );
+        assert!(contract.tokens_by_id.get(&TOKEN.to_string()).is_none());
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+
+        // the token id can be minted again
+        mint(&mut contract);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is pending")]
+    fn pending_token_cannot_be_transferred() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            bob().to_string(),
+            format!("{}.{}", TOKEN, alice()),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or a minter can mint")]
+    fn mint_by_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            TOKEN.to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+    }
+
+    fn mint_with_royalty(contract: &mut Contract, royalty: Royalty) {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            TOKEN.to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            Some(royalty),
+            None,
+        );
+    }
+
+    #[test]
+    fn royalty_of_mint_is_paid_out() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        mint_with_royalty(
+            &mut contract,
+            HashMap::from([(carol().to_string(), 1_000), (alice().to_string(), 500)]),
+        );
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+
+        // alice's own royalty is merged into her share
+        let payout = contract
+            .nft_payout(format!("{}.{}", TOKEN, alice()), U128(1_000), 2)
+            .payout;
+        assert_eq!(payout.len(), 2);
+        assert_eq!(payout[&carol().to_string()].0, 100);
+        assert_eq!(payout[&alice().to_string()].0, 900);
+    }
+
+    #[test]
+    fn royalty_of_exactly_100_percent() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        mint_with_royalty(
+            &mut contract,
+            HashMap::from([(carol().to_string(), 6_000), (bob().to_string(), 4_000)]),
+        );
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+
+        let payout = contract
+            .nft_payout(format!("{}.{}", TOKEN, alice()), U128(1_000), 3)
+            .payout;
+        assert_eq!(payout[&alice().to_string()].0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't exceed 100%, they add up to 10001 basis points")]
+    fn royalty_over_100_percent_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        mint_with_royalty(
+            &mut contract,
+            HashMap::from([(carol().to_string(), 6_000), (bob().to_string(), 4_001)]),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't exceed 100%, they add up to 10001 basis points")]
+    fn duplicate_recipient_counts_once() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        // the last share of a recipient given twice wins, it can't dodge the total
+        let royalty: Royalty = near_sdk::serde_json::from_str(
+            r#"{"carol.near": 1, "carol.near": 6001, "bob.near": 4000}"#,
+        )
+        .unwrap();
+        assert_eq!(royalty.len(), 2);
+
+        mint_with_royalty(&mut contract, royalty);
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalty of bob.near can't be zero")]
+    fn zero_royalty_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        mint_with_royalty(
+            &mut contract,
+            HashMap::from([(carol().to_string(), 1_000), (bob().to_string(), 0)]),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't have more than 6 recipients")]
+    fn seven_royalty_recipients_fail() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        mint_with_royalty(
+            &mut contract,
+            (0..7).map(|i| (format!("artist{}.near", i), 100)).collect(),
+        );
+    }
+
+    fn mint_args(receiver_id: ValidAccountId, token_id: &str) -> MintArgs {
+        MintArgs {
+            receiver_id,
+            token_id: token_id.to_string(),
+            token_metadata: token_metadata(1),
+            public_key: Base58PublicKey::try_from(
+                "ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN",
+            )
+            .unwrap(),
+            royalty: None,
+            soulbound: false,
+        }
+    }
+
+    #[test]
+    fn batch_mint_refunds_excess_once() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint_batch(vec![
+            mint_args(alice(), "genesis.hero"),
+            mint_args(bob(), "genesis.villain"),
+        ]);
+
+        // account creation and callback per token plus the single refund
+        assert_eq!(test_utils::get_created_receipts().len(), 5);
+        assert!(contract.internal_is_pending(&"genesis.hero".to_string()));
+        assert!(contract.internal_is_pending(&"genesis.villain".to_string()));
+
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+        assert!(contract.resolve_mint("genesis.villain".to_string()));
+
+        // the excess was refunded with the batch
+        assert!(test_utils::get_created_receipts().is_empty());
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token genesis.hero already exists")]
+    fn batch_with_existing_token_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint_batch(vec![
+            mint_args(bob(), "genesis.villain"),
+            mint_args(bob(), TOKEN),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token genesis.hero is given twice in the batch")]
+    fn batch_with_duplicate_token_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint_batch(vec![mint_args(alice(), TOKEN), mint_args(bob(), TOKEN)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't mint more than 25 tokens in a batch")]
+    fn oversized_batch_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint_batch(
+            (0..26)
+                .map(|i| mint_args(alice(), &format!("genesis.hero{}", i)))
+                .collect(),
+        );
+    }
+
+    fn mint_copy(contract: &mut Contract, receiver_id: ValidAccountId) {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint_copy(
+            TOKEN.to_string(),
+            receiver_id,
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+        );
+    }
+
+    fn resolve_copy(contract: &mut Contract, receiver_id: ValidAccountId, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_mint_copy(
+            format!("{}.{}", TOKEN, receiver_id),
+            nft().into(),
+            U128(STORAGE_DEPOSIT),
+            U128(0),
+        );
+    }
+
+    /// alice holds the first copy of an edition of 2
+    fn setup_edition() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint(
+            alice(),
+            TOKEN.to_string(),
+            token_metadata(2),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+        resolve(&mut contract, PromiseResult::Successful(vec![]));
+
+        contract
+    }
+
+    #[test]
+    fn copy_shares_metadata_of_the_original() {
+        let mut contract = setup_edition();
+
+        mint_copy(&mut contract, bob());
+        resolve_copy(&mut contract, bob(), PromiseResult::Successful(vec![]));
+
+        let copy = contract.nft_token(format!("{}.{}", TOKEN, bob())).unwrap();
+        assert_eq!(copy.metadata.title, "Catch Pass");
+        assert_eq!(
+            contract.nft_copies_of(TOKEN.to_string()).unwrap(),
+            CopiesView {
+                token_id: TOKEN.to_string(),
+                copies_minted: 2,
+                max_copies: 2,
+            }
+        );
+        assert_eq!(contract.nft_total_supply().0, 1);
+    }
+
+    #[test]
+    fn failed_account_creation_releases_copy() {
+        let mut contract = setup_edition();
+
+        mint_copy(&mut contract, bob());
+        assert_eq!(
+            contract
+                .nft_copies_of(TOKEN.to_string())
+                .unwrap()
+                .copies_minted,
+            2
+        );
+        assert!(contract.nft_token(format!("{}.{}", TOKEN, bob())).is_none());
+
+        resolve_copy(&mut contract, bob(), PromiseResult::Failed);
+
+        assert_eq!(
+            test_utils::get_logs(),
+            vec!["Creating bob.near failed, the copy of genesis.hero is released"]
+        );
+        assert_eq!(
+            contract
+                .nft_copies_of(TOKEN.to_string())
+                .unwrap()
+                .copies_minted,
+            1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "All the copies of this token have been minted")]
+    fn copy_beyond_edition_fails() {
+        let mut contract = setup_edition();
+
+        mint_copy(&mut contract, bob());
+        mint_copy(&mut contract, carol());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is pending")]
+    fn copy_of_pending_token_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        mint(&mut contract);
+
+        mint_copy(&mut contract, bob());
+    }
+}
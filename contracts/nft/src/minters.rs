@@ -0,0 +1,144 @@
+use crate::*;
+
+impl Contract {
+    /// Assert that Predecessor A/c is the Owner of the Contract or a minter
+    pub(crate) fn assert_minter(&self) {
+        let predecessor_id = env::predecessor_account_id();
+        require!(
+            predecessor_id == self.owner_id || self.minters.contains(&predecessor_id),
+            "Only the owner or a minter can mint"
+        );
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, the minter can mint like the owner but can't manage minters or marketplaces
+    #[payable]
+    pub fn add_minter(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let minter_id: AccountId = account_id.into();
+        if !self.minters.insert(&minter_id) {
+            return false;
+        }
+
+        CatchEventLogVariant::MinterAdded(MinterLog { minter_id }).emit();
+
+        true
+    }
+
+    /// Owner only
+    #[payable]
+    pub fn remove_minter(&mut self, account_id: ValidAccountId) -> bool {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let minter_id: AccountId = account_id.into();
+        if !self.minters.remove(&minter_id) {
+            return false;
+        }
+
+        CatchEventLogVariant::MinterRemoved(MinterLog { minter_id }).emit();
+
+        true
+    }
+
+    pub fn get_minters(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<AccountId> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.minters
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    pub fn is_minter(&self, account_id: AccountId) -> bool {
+        self.minters.contains(&account_id)
+    }
+}
+
+#[cfg(test)]
+mod minters_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 1));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.add_minter(alice());
+
+        contract
+    }
+
+    fn mint(contract: &mut Contract, token_id: &str) {
+        contract.nft_mint(
+            bob(),
+            token_id.to_string(),
+            token_metadata(1),
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn minter_mints() {
+        let mut contract = setup();
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"minter_added","data":{"minter_id":"alice.near"}}"#
+            ]
+        );
+        assert!(contract.is_minter(alice().into()));
+        assert_eq!(contract.get_minters(None, None), vec![alice().to_string()]);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        mint(&mut contract, "genesis.hero");
+        assert!(contract
+            .pending_mints
+            .get(&"genesis.hero".to_string())
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or a minter can mint")]
+    fn removed_minter_cannot_mint() {
+        let mut contract = setup();
+
+        assert!(contract.remove_minter(alice()));
+        assert!(!contract.remove_minter(alice()));
+        assert!(test_utils::get_logs()
+            .last()
+            .unwrap()
+            .contains(r#""event":"minter_removed""#));
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        mint(&mut contract, "genesis.hero");
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn minter_cannot_add_minters() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.add_minter(bob());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn minter_cannot_approve_marketplaces() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.add_approved_marketplace(marketplace(), None);
+    }
+}
@@ -1,5 +1,31 @@
 use crate::*;
 
+const GAS_FOR_RESOLVE_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_NFT_TRANSFER_CALL: Gas = 25_000_000_000_000 + GAS_FOR_RESOLVE_TRANSFER;
+
+#[ext_contract(ext_non_fungible_token_receiver)]
+trait NonFungibleTokenReceiver {
+    //returns true if the token should be returned to the previous owner
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: TokenId,
+        msg: String,
+    ) -> Promise;
+}
+
+#[ext_contract(ext_self)]
+trait ExtSelf {
+    fn nft_resolve_transfer(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: HashMap<AccountId, u64>,
+    ) -> bool;
+}
+
 pub trait NonFungibleTokenCore {
     //transfers an NFT to a receiver ID (if eligible) and returns Payout Object
     fn nft_transfer(
@@ -8,10 +34,11 @@ pub trait NonFungibleTokenCore {
         token_id: TokenId,
         approval_id: Option<u64>,
         memo: Option<String>,
-    );
+    ) -> PromiseOrValue<()>;
 
     //transfers an NFT to a receiver ID (if eligible) and returns Payout Object
     //need to take max_len_payout as argument for compatibility purpose
+    #[allow(clippy::too_many_arguments)]
     fn nft_transfer_payout(
         &mut self,
         receiver_id: AccountId,
@@ -20,12 +47,35 @@ pub trait NonFungibleTokenCore {
         balance: U128,
         memo: Option<String>,
         max_len_payout: u32,
+        affiliate_id: Option<AccountId>,
     ) -> Payout;
 
+    //transfers an NFT to a receiver contract and calls nft_on_transfer on it,
+    //the token is returned if the receiver asks for it or the call fails
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+
     //get information about the NFT token passed in
     fn nft_token(&self, token_id: TokenId) -> Option<JsonToken>;
 }
 
+pub trait NonFungibleTokenResolver {
+    //returns true if the token stayed with the receiver
+    fn nft_resolve_transfer(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: HashMap<AccountId, u64>,
+    ) -> bool;
+}
+
 #[near_bindgen]
 impl NonFungibleTokenCore for Contract {
     //This transfers the NFT from the current owner to the receiver.
@@ -36,18 +86,49 @@ impl NonFungibleTokenCore for Contract {
         token_id: TokenId,
         approval_id: Option<u64>,
         memo: Option<String>,
-    ) {
+    ) -> PromiseOrValue<()> {
         assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Transfer);
+        assert_valid_memo(&memo);
         let sender_id = env::predecessor_account_id();
 
+        self.internal_assert_catch_player(&receiver_id);
+
+        //tokens depending on partner NFT contracts are transferred once the partners confirm the receiver
+        let external_dependencies = self.internal_external_dependencies(&token_id);
+        if !external_dependencies.is_empty() {
+            if let Err(message) =
+                self.internal_check_transfer(&sender_id, &receiver_id, &token_id, approval_id)
+            {
+                env::panic(message.as_bytes());
+            }
+
+            let transfer = PendingTransfer {
+                sender_id,
+                receiver_id,
+                token_id,
+                approval_id,
+                memo,
+            };
+
+            return PromiseOrValue::Promise(
+                self.internal_verify_external_dependencies(transfer, external_dependencies),
+            );
+        }
+
         //call the internal transfer method and get back the previous token owner and approved_ids
         let (old_owner_id, old_approval_info) =
             self.internal_transfer(&sender_id, &receiver_id, &token_id, approval_id, memo);
 
-        refund_approved_account_ids(old_owner_id, &old_approval_info.approved_account_ids);
+        self.refund_approved_account_ids(old_owner_id, &old_approval_info.approved_account_ids);
+
+        PromiseOrValue::Value(())
     }
 
+    //only Catch approved marketplaces can settle sales, the marketplace has to be approved for the token as well,
+    //affiliate_id is the referrer of the sale
     #[payable]
+    #[allow(clippy::too_many_arguments)]
     fn nft_transfer_payout(
         &mut self,
         receiver_id: AccountId,
@@ -56,32 +137,173 @@ impl NonFungibleTokenCore for Contract {
         balance: U128,
         memo: Option<String>,
         max_len_payout: u32,
+        affiliate_id: Option<AccountId>,
     ) -> Payout {
-        self.nft_transfer(receiver_id, token_id.clone(), approval_id, memo);
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Transfer);
+        assert_valid_memo(&memo);
+        let sender_id = env::predecessor_account_id();
+
+        require!(
+            self.internal_is_approved_marketplace(&sender_id),
+            "Only Catch approved marketplaces can settle sales"
+        );
+
+        self.internal_assert_catch_player(&receiver_id);
+
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        //neither side of the sale can refer it to itself
+        if let Some(affiliate_id) = &affiliate_id {
+            require!(
+                affiliate_id != &receiver_id,
+                "The buyer can't be the affiliate of the sale"
+            );
+            require!(
+                affiliate_id != &owner_id,
+                "The token owner can't be the affiliate of the sale"
+            );
+        }
+
+        let token = self
+            .tokens_by_id
+            .get(&class_token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+
+        require!(
+            token.external_dependencies.is_empty(),
+            "Tokens with external dependencies can't be settled synchronously"
+        );
 
-        let (_, owner_id) = resolve_token_id(token_id);
+        let payout = self.internal_payout(&token, &owner_id, balance.into(), max_len_payout);
+
+        //transferring clears all the approvals of the token
+        let (old_owner_id, old_approval_info) =
+            self.internal_transfer(&sender_id, &receiver_id, &token_id, approval_id, memo);
 
-        Payout {
-            payout: HashMap::from([(owner_id, balance)]),
+        self.refund_approved_account_ids(old_owner_id, &old_approval_info.approved_account_ids);
+
+        //the referrer of the sale earns the payment token if the affiliate program is enabled
+        if let Some(affiliate_id) = affiliate_id {
+            self.internal_reward_affiliate(&token_id, affiliate_id, balance.into());
         }
+
+        CatchEventLogVariant::NftRoyaltyPayout(RoyaltyPayoutLog {
+            token_id,
+            marketplace_id: sender_id,
+            balance,
+            payout: payout
+                .payout
+                .iter()
+                .map(|(account_id, amount)| (account_id.clone(), *amount))
+                .collect(),
+        })
+        .emit();
+
+        payout
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Transfer);
+        assert_valid_memo(&memo);
+        let sender_id = env::predecessor_account_id();
+
+        self.internal_assert_catch_player(&receiver_id);
+
+        require!(
+            self.internal_external_dependencies(&token_id).is_empty(),
+            "Tokens with external dependencies can't be transferred with a call"
+        );
+
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        require!(self.tokens_by_id.get(&class_token_id).is_some(), "No token");
+        let approval_info = self
+            .approvals_by_token_id
+            .get(&token_id)
+            .unwrap_or_default();
+
+        self.internal_transfer(&sender_id, &receiver_id, &token_id, approval_id, memo);
+
+        //the approvals are kept until the receiver decided, their storage is refunded once it keeps the token,
+        //next_approval_id is kept so that approval ids aren't reused after a rollback
+        if approval_info.next_approval_id > 0 {
+            self.approvals_by_token_id.insert(
+                &token_id,
+                &ApprovalInfo {
+                    approved_account_ids: Default::default(),
+                    next_approval_id: approval_info.next_approval_id,
+                },
+            );
+        }
+
+        let token_id = build_full_token_id(class_token_id, receiver_id.clone());
+
+        ext_non_fungible_token_receiver::nft_on_transfer(
+            sender_id,
+            owner_id.clone(),
+            token_id.clone(),
+            msg,
+            &receiver_id,
+            0,
+            env::prepaid_gas() - GAS_FOR_NFT_TRANSFER_CALL,
+        )
+        .then(ext_self::nft_resolve_transfer(
+            owner_id,
+            receiver_id,
+            token_id,
+            approval_info.approved_account_ids,
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_TRANSFER,
+        ))
+        .into()
     }
 
     //get the information for a specific token ID
     fn nft_token(&self, token_id: TokenId) -> Option<JsonToken> {
-        let (token_id, owner_id) = resolve_token_id(token_id);
+        //malformed ids are probed by the frontend too, they aren't worth a panic
+        let (token_id, owner_id) = try_resolve_token_id(&token_id)?;
+        //pending, burnt or never minted copies
+        if self.internal_is_pending(&token_id) || !self.internal_owns_token(&owner_id, &token_id) {
+            return None;
+        }
+
         if let Some(token) = self.tokens_by_id.get(&token_id) {
-            let metadata = self.token_metadata_by_id.get(&token_id).unwrap();
+            let metadata = self.internal_token_metadata(&token_id).unwrap();
+            let full_token_id = build_full_token_id(token_id, owner_id.clone());
+            let expired = self.internal_is_copy_expired(&full_token_id, &token);
             Some(JsonToken {
-                token_id: build_full_token_id(token_id, owner_id.clone()),
+                token_id: full_token_id.clone(),
                 owner_id: owner_id.clone(),
                 metadata,
-                approved_account_ids: token
-                    .account_approval_info_per_owner
-                    .get(&owner_id)
+                approved_account_ids: self
+                    .approvals_by_token_id
+                    .get(&full_token_id)
                     .unwrap_or_default()
                     .approved_account_ids,
+                expires_at: self.internal_copy_expires_at(&full_token_id, &token),
+                royalty: token.royalty,
+                copies_minted: token.copies_minted,
+                max_copies: token.max_copies,
                 token_dependency_by_id: token.token_dependency_by_id,
                 event_dependency_by_id: token.event_dependency_by_id,
+                external_dependencies: token.external_dependencies,
+                level: self.level_by_token_id.get(&full_token_id).unwrap_or(0),
+                expired,
+                soulbound: token.soulbound,
+                checked_in_at: self.checked_in_at_by_token_id.get(&full_token_id),
+                transferable: !token.soulbound
+                    && self.internal_is_transferable_pass(&full_token_id),
             })
         } else {
             //if there wasn't a token ID in the tokens_by_id collection, we return None
@@ -89,3 +311,475 @@ impl NonFungibleTokenCore for Contract {
         }
     }
 }
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Contract {
+    //token_id is of form event_id.token_id.receiver_id, the rollback goes through the same checks as a transfer
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        approved_account_ids: HashMap<AccountId, u64>,
+    ) -> bool {
+        let return_token = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            _ => true,
+        };
+
+        let (class_token_id, _) = resolve_token_id(token_id.clone());
+        let owner_token_id = build_full_token_id(class_token_id.clone(), owner_id.clone());
+
+        let rollback = if return_token {
+            self.internal_check_transfer(&receiver_id, &owner_id, &token_id, None)
+        } else {
+            Err(String::new())
+        };
+
+        if let Err(message) = rollback {
+            if !message.is_empty() {
+                env::log(
+                    format!(
+                        "{} can't be returned to {}: {}",
+                        &token_id, &owner_id, message
+                    )
+                    .as_bytes(),
+                );
+            }
+
+            //the previous owner doesn't hold the token anymore
+            if !self.internal_owns_token(&owner_id, &class_token_id) {
+                self.approvals_by_token_id.remove(&owner_token_id);
+            }
+
+            self.refund_approved_account_ids(owner_id, &approved_account_ids);
+            return true;
+        }
+
+        let next_approval_id = self
+            .approvals_by_token_id
+            .get(&owner_token_id)
+            .map_or(0, |approval_info| approval_info.next_approval_id);
+
+        //approvals the receiver granted meanwhile end with the rollback
+        let (_, receiver_approval_info) =
+            self.internal_move_token(&receiver_id, &owner_id, &token_id, None, None);
+        self.refund_approved_account_ids(receiver_id, &receiver_approval_info.approved_account_ids);
+
+        let next_approval_id =
+            std::cmp::max(next_approval_id, receiver_approval_info.next_approval_id);
+        if next_approval_id > 0 {
+            self.approvals_by_token_id.insert(
+                &owner_token_id,
+                &ApprovalInfo {
+                    approved_account_ids,
+                    next_approval_id,
+                },
+            );
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod nft_core_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+    const BOB_PASS: &str = "catchcon.gold.bob.near";
+
+    /// alice holds a gold pass approved for the marketplace
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer_call(
+            bob().into(),
+            PASS.to_string(),
+            None,
+            None,
+            "stake".to_string(),
+        );
+
+        contract
+    }
+
+    //gas of a transfer of a pass by alice while she holds the given number of other tokens
+    fn transfer_gas(other_tokens: u64) -> Gas {
+        //each measurement starts from an empty storage
+        env::take_blockchain_interface();
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        for index in 0..other_tokens {
+            testing_env!(get_context(alice().to_string(), 0));
+            contract.internal_add_token_to_owner(&alice().into(), &format!("genesis.{:04}", index));
+        }
+
+        testing_env!(get_context(alice().to_string(), 1));
+        let initial_gas = env::used_gas();
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+        env::used_gas() - initial_gas
+    }
+
+    fn resolve(contract: &mut Contract, result: PromiseResult) -> bool {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.nft_resolve_transfer(
+            alice().into(),
+            bob().into(),
+            BOB_PASS.to_string(),
+            HashMap::from([(marketplace().into(), 0)]),
+        )
+    }
+
+    fn gold() -> TokenId {
+        "catchcon.gold".to_string()
+    }
+
+    #[test]
+    fn transfer_gas_doesnt_grow_with_the_tokens_of_the_sender() {
+        assert_eq!(transfer_gas(5), transfer_gas(5_000));
+    }
+
+    #[test]
+    fn approved_transfer_logs_nep171_event() {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(marketplace().to_string(), 1));
+        contract.nft_transfer(
+            bob().into(),
+            PASS.to_string(),
+            Some(0),
+            Some("sale 7".to_string()),
+        );
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_transfer","data":[{"authorized_id":"marketplace.near","old_owner_id":"alice.near","new_owner_id":"bob.near","token_ids":["catchcon.gold"],"memo":"sale 7"}]}"#
+        );
+    }
+
+    #[test]
+    fn transfer_without_memo_leaves_it_out_of_the_log() {
+        let mut contract = round_trip();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(carol().into(), PASS.to_string(), None, None);
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.1.0","event":"nft_transfer","data":[{"old_owner_id":"alice.near","new_owner_id":"carol.near","token_ids":["catchcon.gold"]}]}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Memo can't be longer than 256 bytes")]
+    fn transfer_with_long_memo_fails() {
+        let mut contract = round_trip();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            carol().into(),
+            PASS.to_string(),
+            None,
+            Some("a".repeat(257)),
+        );
+    }
+
+    #[test]
+    fn transfer_call_calls_receiver() {
+        let contract = setup();
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("nft_on_transfer"));
+        assert!(receipts.contains("nft_resolve_transfer"));
+        assert!(contract.internal_owns_token(&bob().into(), &gold()));
+    }
+
+    #[test]
+    fn kept_token_stays_with_receiver() {
+        let mut contract = setup();
+
+        assert!(resolve(
+            &mut contract,
+            PromiseResult::Successful(b"false".to_vec())
+        ));
+
+        assert!(contract.internal_owns_token(&bob().into(), &gold()));
+        assert!(!contract.nft_is_approved(PASS.to_string(), marketplace().into(), None));
+        // the approval storage is refunded to alice
+        assert!(!test_utils::get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn returned_token_gets_its_approvals_back() {
+        let mut contract = setup();
+
+        assert!(!resolve(
+            &mut contract,
+            PromiseResult::Successful(b"true".to_vec())
+        ));
+
+        assert!(contract.internal_owns_token(&alice().into(), &gold()));
+        assert!(!contract.internal_owns_token(&bob().into(), &gold()));
+        assert!(contract.nft_is_approved(PASS.to_string(), marketplace().into(), Some(0)));
+
+        // approval ids aren't reused
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_revoke_all(PASS.to_string());
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+        assert!(contract.nft_is_approved(PASS.to_string(), marketplace().into(), Some(1)));
+    }
+
+    #[test]
+    fn failed_receiver_returns_token() {
+        let mut contract = setup();
+
+        assert!(!resolve(&mut contract, PromiseResult::Failed));
+
+        assert!(contract.internal_owns_token(&alice().into(), &gold()));
+    }
+
+    #[test]
+    fn rollback_checks_dependencies() {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        // the vip pass requires a gold pass
+        contract.organise_event(
+            "vipcon".to_string(),
+            vec![TokenInfo {
+                token_id: "vip".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![gold()],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        for account_id in [alice(), bob()] {
+            testing_env!(get_context(account_id.to_string(), STORAGE_DEPOSIT));
+            contract.nft_event_register(account_id.to_string(), gold());
+        }
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "vipcon.vip".to_string());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer_call(
+            bob().into(),
+            "vipcon.vip.alice.near".to_string(),
+            None,
+            None,
+            "stake".to_string(),
+        );
+        // alice gives her gold pass away meanwhile
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(carol().into(), PASS.to_string(), None, None);
+
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        let kept = contract.nft_resolve_transfer(
+            alice().into(),
+            bob().into(),
+            "vipcon.vip.bob.near".to_string(),
+            Default::default(),
+        );
+
+        assert!(kept);
+        assert!(contract.internal_owns_token(&bob().into(), &"vipcon.vip".to_string()));
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            "vipcon.vip.bob.near can't be returned to alice.near: receiver_id doesn't satisfy all dependencies for the token, missing catchcon.gold"
+        );
+    }
+
+    /// alice holds a gold pass expiring at expires_at (ms)
+    fn setup_expiring(expires_at: Option<u64>) -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        contract.organise_event(
+            "catchcon".to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata: TokenMetadata {
+                    expires_at,
+                    ..token_metadata(10)
+                },
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), gold());
+
+        contract
+    }
+
+    fn context_at(account_id: ValidAccountId, timestamp_ms: u64, deposit: Balance) {
+        let mut context = get_context(account_id.to_string(), deposit);
+        context.block_timestamp = timestamp_ms * 1_000_000;
+        testing_env!(context);
+    }
+
+    #[test]
+    fn token_without_expiry_never_expires() {
+        let mut contract = setup_expiring(None);
+
+        context_at(alice(), u64::MAX / 1_000_000, 1);
+        assert!(!contract.nft_is_expired(PASS.to_string()));
+        assert!(!contract.nft_token(PASS.to_string()).unwrap().expired);
+
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+        assert!(contract.internal_owns_token(&bob().into(), &gold()));
+    }
+
+    #[test]
+    fn token_expires_at_expires_at() {
+        let contract = setup_expiring(Some(1_000));
+
+        context_at(alice(), 999, 0);
+        assert!(!contract.nft_is_expired(PASS.to_string()));
+
+        context_at(alice(), 1_000, 0);
+        assert!(contract.nft_is_expired(PASS.to_string()));
+        assert!(contract.nft_token(PASS.to_string()).unwrap().expired);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token Can't be transferred Since it has already expired")]
+    fn transfer_of_expired_token_fails() {
+        let mut contract = setup_expiring(Some(1_000));
+
+        context_at(alice(), 1_000, 1);
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't approve other account since Token has already expired")]
+    fn approval_of_expired_token_fails() {
+        let mut contract = setup_expiring(Some(1_000));
+
+        context_at(alice(), 1_000, STORAGE_DEPOSIT);
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    /// alice approves the marketplace with id 0, sends her pass to bob who sends it back
+    fn round_trip() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), marketplace().into(), None),
+            0
+        );
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+        // the storage of the approval goes back to alice
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.nft_transfer(alice().into(), BOB_PASS.to_string(), None, None);
+
+        contract
+    }
+
+    #[test]
+    fn approvals_end_with_the_transfer() {
+        let mut contract = round_trip();
+
+        assert!(!contract.nft_is_approved(PASS.to_string(), marketplace().into(), None));
+        assert!(contract
+            .approvals_by_token_id
+            .get(&"catchcon.gold.bob.near".to_string())
+            .is_none());
+
+        // ids granted before the transfers aren't handed out again
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        assert_eq!(
+            contract.nft_approve(PASS.to_string(), marketplace().into(), None),
+            1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorised")]
+    fn stale_approval_cannot_move_returned_token() {
+        let mut contract = round_trip();
+
+        testing_env!(get_context(marketplace().to_string(), 1));
+        contract.nft_transfer(carol().into(), PASS.to_string(), Some(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The actual approval_id 1 is different from the given approval_id 0")]
+    fn stale_approval_id_cannot_settle_sale() {
+        let mut contract = round_trip();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(marketplace().to_string(), 1));
+        contract.nft_transfer_payout(
+            carol().into(),
+            PASS.to_string(),
+            Some(0),
+            U128(100),
+            None,
+            10,
+            None,
+        );
+    }
+}
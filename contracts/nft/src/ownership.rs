@@ -0,0 +1,134 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, the proposed account becomes the owner once it accepts, proposing again
+    /// replaces the pending proposal
+    #[payable]
+    pub fn propose_new_owner(&mut self, proposed: ValidAccountId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let proposed_owner_id: AccountId = proposed.into();
+        require!(
+            proposed_owner_id != self.owner_id,
+            "The account is already the owner"
+        );
+
+        self.pending_owner_id = Some(proposed_owner_id.clone());
+
+        CatchEventLogVariant::OwnershipProposed(OwnershipLog {
+            owner_id: self.owner_id.clone(),
+            proposed_owner_id,
+        })
+        .emit();
+    }
+
+    /// Proposed account only
+    #[payable]
+    pub fn accept_ownership(&mut self) {
+        assert_one_yocto();
+
+        let proposed_owner_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&proposed_owner_id),
+            "Only the proposed owner can accept the ownership"
+        );
+
+        let previous_owner_id = std::mem::replace(&mut self.owner_id, proposed_owner_id.clone());
+        self.pending_owner_id = None;
+
+        CatchEventLogVariant::OwnershipAccepted(OwnershipLog {
+            owner_id: previous_owner_id,
+            proposed_owner_id,
+        })
+        .emit();
+    }
+
+    /// Owner only
+    #[payable]
+    pub fn cancel_ownership_proposal(&mut self) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            self.pending_owner_id.take().is_some(),
+            "No ownership proposal to cancel"
+        );
+    }
+
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+}
+
+#[cfg(test)]
+mod ownership_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.propose_new_owner(alice());
+
+        contract
+    }
+
+    #[test]
+    fn proposed_owner_accepts() {
+        let mut contract = setup();
+        assert_eq!(contract.get_pending_owner(), Some(alice().to_string()));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"ownership_proposed","data":{"owner_id":"nft.catchlabs.near","proposed_owner_id":"alice.near"}}"#
+            ]
+        );
+
+        // the owner keeps its rights until the proposal is accepted
+        contract.assert_owner();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.accept_ownership();
+
+        assert_eq!(contract.owner_id, alice().to_string());
+        assert!(contract.get_pending_owner().is_none());
+        assert!(test_utils::get_logs()[0].contains(r#""event":"ownership_accepted""#));
+        contract.assert_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept the ownership")]
+    fn cancelled_proposal_cannot_be_accepted() {
+        let mut contract = setup();
+
+        contract.cancel_ownership_proposal();
+        assert!(contract.get_pending_owner().is_none());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept the ownership")]
+    fn other_account_cannot_accept() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn proposal_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.propose_new_owner(bob());
+    }
+}
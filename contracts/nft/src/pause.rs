@@ -0,0 +1,165 @@
+use crate::*;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PausableFeature {
+    Mint,
+    Transfer,
+    Approve,
+    Events,
+}
+
+/// Circuit breaker of the contract, views keep working and so do the callbacks of calls
+/// which were made before pausing
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseStatus {
+    pub mint: bool,
+    pub transfer: bool,
+    pub approve: bool,
+    pub events: bool,
+}
+
+impl PauseStatus {
+    fn flag(&mut self, feature: PausableFeature) -> &mut bool {
+        match feature {
+            PausableFeature::Mint => &mut self.mint,
+            PausableFeature::Transfer => &mut self.transfer,
+            PausableFeature::Approve => &mut self.approve,
+            PausableFeature::Events => &mut self.events,
+        }
+    }
+
+    fn is_paused(&self, feature: PausableFeature) -> bool {
+        match feature {
+            PausableFeature::Mint => self.mint,
+            PausableFeature::Transfer => self.transfer,
+            PausableFeature::Approve => self.approve,
+            PausableFeature::Events => self.events,
+        }
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(&self, feature: PausableFeature) {
+        require!(!self.pause_status.is_paused(feature), "Contract is paused");
+    }
+
+    fn internal_set_paused(&mut self, features: Vec<PausableFeature>, paused: bool) {
+        self.assert_owner();
+
+        for feature in features {
+            *self.pause_status.flag(feature) = paused;
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, the entry points of the features panic until they are unpaused
+    pub fn pause(&mut self, features: Vec<PausableFeature>) {
+        self.internal_set_paused(features, true);
+    }
+
+    /// Owner only
+    pub fn unpause(&mut self, features: Vec<PausableFeature>) {
+        self.internal_set_paused(features, false);
+    }
+
+    pub fn get_pause_status(&self) -> PauseStatus {
+        self.pause_status.clone()
+    }
+}
+
+#[cfg(test)]
+mod pause_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    fn setup(features: Vec<PausableFeature>) -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.pause(features);
+
+        contract
+    }
+
+    #[test]
+    fn views_work_while_paused() {
+        let mut contract = setup(vec![PausableFeature::Transfer, PausableFeature::Mint]);
+
+        assert_eq!(
+            contract.get_pause_status(),
+            PauseStatus {
+                mint: true,
+                transfer: true,
+                approve: false,
+                events: false,
+            }
+        );
+        assert!(contract.nft_token(PASS.to_string()).is_some());
+        assert!(contract.nft_metadata().paused.unwrap().transfer);
+
+        // other features keep working
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.unpause(vec![PausableFeature::Transfer]);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn transfer_while_paused_fails() {
+        let mut contract = setup(vec![PausableFeature::Transfer]);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn approve_while_paused_fails() {
+        let mut contract = setup(vec![PausableFeature::Approve]);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn event_registration_while_paused_fails() {
+        let mut contract = setup(vec![PausableFeature::Events]);
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn pause_by_other_account_fails() {
+        let mut contract = setup(vec![]);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.pause(vec![PausableFeature::Mint]);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, moves the copy of the token class (event_id.token_id) from an A/c the player
+    /// lost access to. The approvals of the copy are not needed and end with the move, the other
+    /// transfer checks apply. The justification is logged and recoveries are counted per copy
+    #[payable]
+    pub fn recovery_transfer(
+        &mut self,
+        token_id: TokenId,
+        from: AccountId,
+        to: ValidAccountId,
+        justification: String,
+    ) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let to: AccountId = to.into();
+        require!(!justification.is_empty(), "Justification is required");
+        self.internal_assert_catch_player(&to);
+
+        let full_token_id = build_full_token_id(token_id.clone(), from.clone());
+        require!(
+            self.internal_external_dependencies(&full_token_id).is_empty(),
+            "Tokens with external dependencies can't be recovered"
+        );
+
+        // the holder as the sender, so no approval is needed
+        if let Err(message) = self.internal_check_transfer(&from, &to, &full_token_id, None) {
+            env::panic(message.as_bytes());
+        }
+
+        let recoveries = self.recoveries_by_token_id.get(&full_token_id).unwrap_or(0) + 1;
+        self.recoveries_by_token_id.insert(&full_token_id, &recoveries);
+
+        let (old_owner_id, old_approval_info) = self.internal_move_token(
+            &from,
+            &to,
+            &full_token_id,
+            None,
+            Some(justification.clone()),
+        );
+        self.refund_approved_account_ids(
+            old_owner_id.clone(),
+            &old_approval_info.approved_account_ids,
+        );
+
+        CatchEventLogVariant::NftRecoveryTransfer(RecoveryTransferLog {
+            token_id,
+            old_owner_id,
+            new_owner_id: to,
+            justification,
+            recoveries,
+        })
+        .emit();
+    }
+
+    //recovery transfers of the copy of form event_id.token_id.owner_id
+    pub fn get_recovery_count(&self, token_id: TokenId) -> u32 {
+        self.recoveries_by_token_id.get(&token_id).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod recovery_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    /// alice holds a gold pass approved for the marketplace
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        contract
+    }
+
+    fn recover(contract: &mut Contract, from: ValidAccountId, to: ValidAccountId) {
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.recovery_transfer(
+            "catchcon.gold".to_string(),
+            from.into(),
+            to,
+            "Lost the key, ticket 42".to_string(),
+        );
+    }
+
+    #[test]
+    fn recovery_moves_the_copy_without_its_approvals() {
+        let mut contract = setup();
+        recover(&mut contract, alice(), bob());
+
+        assert!(contract.nft_token(PASS.to_string()).is_none());
+        let token = contract
+            .nft_token("catchcon.gold.bob.near".to_string())
+            .unwrap();
+        assert!(token.approved_account_ids.is_empty());
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_recovery_transfer","data":{"token_id":"catchcon.gold","old_owner_id":"alice.near","new_owner_id":"bob.near","justification":"Lost the key, ticket 42","recoveries":1}}"#
+        );
+
+        // the count follows the copy
+        recover(&mut contract, bob(), carol());
+        assert_eq!(
+            contract.get_recovery_count("catchcon.gold.carol.near".to_string()),
+            2
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "carol.near account already has token catchcon.gold")]
+    fn recovery_keeps_the_transfer_checks() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(carol().to_string(), "catchcon.gold".to_string());
+
+        recover(&mut contract, alice(), carol());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn recovery_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.recovery_transfer(
+            "catchcon.gold".to_string(),
+            alice().into(),
+            bob(),
+            "Mine now".to_string(),
+        );
+    }
+}
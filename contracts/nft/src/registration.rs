@@ -0,0 +1,468 @@
+use crate::*;
+
+/// Whether an account holds a pass of an event and whether it can still buy one
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RegistrationStatus {
+    pub registered: bool,
+    //TokenId of form event_id.token_id.owner_id of the pass the account holds
+    pub token_id: Option<TokenId>,
+    pub price: Option<U128>,
+    pub sold_out: bool,
+}
+
+impl Contract {
+    //true if the account holds a pass of the event
+    pub(crate) fn internal_is_registered(
+        &self,
+        event_id: &EventId,
+        account_id: &AccountId,
+    ) -> bool {
+        self.passes_per_event
+            .get(event_id)
+            .is_some_and(|holders| holders.contains(account_id))
+    }
+
+    //escrows the price of a pass until the event ends, records it as paid by the holder so that it
+    //is refunded if the event is cancelled and counts it as revenue of the event
+    pub(crate) fn internal_escrow_pass_price(
+        &mut self,
+        event_id: &EventId,
+        account_id: &AccountId,
+        price: Balance,
+    ) {
+        let mut event = self.events_by_id.get(event_id).unwrap();
+        event.revenue_collected = U128(event.revenue_collected.0 + price);
+        self.events_by_id.insert(event_id, &event);
+
+        let proceeds = self.near_proceeds_by_event_id.get(event_id).unwrap_or(0);
+        self.near_proceeds_by_event_id
+            .insert(event_id, &(proceeds + price));
+
+        let key = (event_id.clone(), account_id.clone());
+        let paid = self.near_paid_by_event_account.get(&key).unwrap_or(0);
+        self.near_paid_by_event_account
+            .insert(&key, &(paid + price));
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, share of the price of passes sold with register_for_event kept for the
+    /// owner, in basis points
+    #[payable]
+    pub fn set_platform_fee_bps(&mut self, fee_bps: u16) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        require!(
+            fee_bps <= 10_000,
+            "Fee can't be more than 10000 basis points"
+        );
+
+        self.platform_fee_bps = fee_bps;
+    }
+
+    pub fn get_platform_fee_bps(&self) -> u16 {
+        self.platform_fee_bps
+    }
+
+    /// Owner or event organiser only, passes of an event of create_event are sold with
+    /// register_for_event for the price in yoctoNEAR, None stops the sale
+    #[payable]
+    pub fn set_event_price(&mut self, event_id: EventId, price: Option<U128>) {
+        assert_one_yocto();
+        self.assert_not_paused(PausableFeature::Events);
+
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        self.assert_event_manager(&event, "change the event");
+        require!(
+            event.metadata.is_some(),
+            "Passes are issued for events of create_event"
+        );
+
+        event.price = price;
+        self.events_by_id.insert(&event_id, &event);
+    }
+
+    /// Catch players only, buys a pass of the event for the caller. The deposit has to cover
+    /// the price and the storage of the pass, the price is held until the event ends and the rest
+    /// of the deposit is refunded. With join_waitlist a caller of a sold out event is queued on
+    /// its waitlist instead
+    #[payable]
+    pub fn register_for_event(&mut self, event_id: EventId, join_waitlist: Option<bool>) {
+        self.assert_not_paused(PausableFeature::Events);
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        let price = event
+            .price
+            .unwrap_or_else(|| env::panic(b"Passes of the event aren't for sale"))
+            .0;
+        if join_waitlist.unwrap_or(false) && event.is_sold_out() {
+            self.internal_join_waitlist(event_id, event, price);
+            return;
+        }
+        let metadata = event.assert_open();
+
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_catch_player(&account_id);
+        require!(
+            !self.internal_is_registered(&event_id, &account_id),
+            "Already registered for the event"
+        );
+
+        let initial_storage = env::storage_usage();
+
+        let token_id = self.internal_event_pass_class(&event_id, event, &metadata);
+        self.internal_mint_copy(
+            &account_id,
+            &token_id,
+            format!("{} registered for {}", &account_id, &event_id),
+        );
+        self.internal_escrow_pass_price(&event_id, &account_id, price);
+
+        let storage_cost =
+            env::storage_byte_cost() * Balance::from(env::storage_usage() - initial_storage);
+        let cost = price + storage_cost;
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= cost,
+            format!(
+                "Must attach {} yoctoNEAR to cover the price and storage",
+                cost
+            )
+        );
+
+        if deposit > cost {
+            Promise::new(account_id).transfer(deposit - cost);
+        }
+    }
+
+    /// Event organiser only, once the event has ended transfers the NEAR paid for its passes to
+    /// the organiser less the platform fee which goes to the owner
+    pub fn withdraw_near_proceeds(&mut self, event_id: EventId) {
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(
+            env::predecessor_account_id() == event.organiser,
+            "Only the event organiser can withdraw the proceeds"
+        );
+        require!(
+            !event.cancelled,
+            "Proceeds of a cancelled event are refunded to the buyers"
+        );
+        require!(
+            self.internal_event_status(&event) == EventStatus::Ended,
+            "Proceeds are held until the event ends"
+        );
+
+        let amount = self.near_proceeds_by_event_id.get(&event_id).unwrap_or(0);
+        require!(amount > 0, "No proceeds to withdraw");
+
+        self.near_proceeds_by_event_id.insert(&event_id, &0);
+
+        let fee = amount * Balance::from(self.platform_fee_bps) / 10_000;
+        if amount > fee {
+            Promise::new(event.organiser).transfer(amount - fee);
+        }
+        if fee > 0 {
+            Promise::new(self.owner_id.clone()).transfer(fee);
+        }
+    }
+
+    //NEAR paid for passes of an event which is held until the event ends
+    pub fn get_near_proceeds(&self, event_id: EventId) -> U128 {
+        self.near_proceeds_by_event_id
+            .get(&event_id)
+            .unwrap_or(0)
+            .into()
+    }
+
+    //NEAR paid by the account for passes of the event
+    pub fn get_near_paid(&self, event_id: EventId, account_id: AccountId) -> U128 {
+        self.near_paid_by_event_account
+            .get(&(event_id, account_id))
+            .unwrap_or(0)
+            .into()
+    }
+
+    pub fn get_registration_status(
+        &self,
+        event_id: EventId,
+        account_id: AccountId,
+    ) -> RegistrationStatus {
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        let token_id = event
+            .event_passes
+            .iter()
+            .find(|class_token_id| self.internal_owns_token(&account_id, class_token_id))
+            .map(|class_token_id| build_full_token_id(class_token_id.clone(), account_id.clone()));
+
+        RegistrationStatus {
+            registered: token_id.is_some(),
+            token_id,
+            price: event.price,
+            sold_out: event.is_sold_out(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod registration_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const EVENT: &str = "catchcon";
+    const PRICE: Balance = 10u128.pow(24);
+
+    fn setup(max_capacity: u64) -> Contract {
+        testing_env!(get_context(nft().to_string(), 1));
+        let mut contract = create_contract();
+        contract.set_platform_fee_bps(500);
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.create_event(
+            EVENT.to_string(),
+            EventMetadata {
+                title: "Catch Con".to_string(),
+                description: None,
+                venue: None,
+                starts_at: 1_000,
+                ends_at: 2_000,
+                max_capacity,
+                media: None,
+                media_hash: None,
+                reference: None,
+                reference_hash: None,
+            },
+            None,
+        );
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.set_event_price(EVENT.to_string(), Some(U128(PRICE)));
+
+        contract
+    }
+
+    fn register(contract: &mut Contract, account_id: ValidAccountId) {
+        testing_env!(get_context(account_id.to_string(), PRICE + STORAGE_DEPOSIT));
+        contract.register_for_event(EVENT.to_string(), None);
+    }
+
+    fn receipts() -> String {
+        near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap()
+    }
+
+    #[test]
+    fn registration_escrows_the_price() {
+        let mut contract = setup(100);
+
+        register(&mut contract, alice());
+
+        // only the rest of the deposit is refunded
+        let receipts = receipts();
+        assert!(!receipts.contains(r#""receiver_id":"carol.near""#));
+        assert!(receipts.contains(r#""receiver_id":"alice.near""#));
+        assert_eq!(contract.get_near_proceeds(EVENT.to_string()).0, PRICE);
+        assert_eq!(
+            contract.get_near_paid(EVENT.to_string(), alice().into()).0,
+            PRICE
+        );
+
+        assert_eq!(
+            contract.get_registration_status(EVENT.to_string(), alice().into()),
+            RegistrationStatus {
+                registered: true,
+                token_id: Some("catchcon.pass.alice.near".to_string()),
+                price: Some(U128(PRICE)),
+                sold_out: false,
+            }
+        );
+        assert!(
+            !contract
+                .get_registration_status(EVENT.to_string(), bob().into())
+                .registered
+        );
+    }
+
+    #[test]
+    fn registration_counts_in_the_event_stats() {
+        let mut contract = setup(100);
+
+        register(&mut contract, alice());
+        register(&mut contract, bob());
+
+        let stats = EventStats {
+            event_id: EVENT.to_string(),
+            passes_issued: 2,
+            max_capacity: Some(100),
+            checked_in_count: 0,
+            unique_holders: 2,
+            revenue_collected: U128(2 * PRICE),
+            waitlist_length: 0,
+            status: EventStatus::Upcoming,
+        };
+        assert_eq!(
+            contract.get_events_summary(None, None),
+            vec![contract.get_event_stats(EVENT.to_string()).unwrap()]
+        );
+        assert_eq!(contract.get_event_stats(EVENT.to_string()), Some(stats));
+    }
+
+    #[test]
+    fn ended_event_pays_the_organiser_and_the_fee() {
+        let mut contract = setup(100);
+        register(&mut contract, alice());
+        register(&mut contract, bob());
+
+        let mut context = get_context(carol().to_string(), 0);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context);
+        contract.withdraw_near_proceeds(EVENT.to_string());
+
+        let receipts = receipts();
+        assert!(receipts.contains(
+            r#""receiver_id":"carol.near","actions":[{"Transfer":{"deposit":1900000000000000000000000}}]"#
+        ));
+        assert!(receipts.contains(
+            r#""receiver_id":"nft.catchlabs.near","actions":[{"Transfer":{"deposit":100000000000000000000000}}]"#
+        ));
+        assert_eq!(contract.get_near_proceeds(EVENT.to_string()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proceeds are held until the event ends")]
+    fn withdraw_before_the_event_ends_fails() {
+        let mut contract = setup(100);
+        register(&mut contract, alice());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.withdraw_near_proceeds(EVENT.to_string());
+    }
+
+    #[test]
+    fn cancelled_event_refunds_the_price() {
+        let mut contract = setup(100);
+        register(&mut contract, alice());
+        register(&mut contract, bob());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event(EVENT.to_string(), "Venue closed".to_string());
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.claim_event_refund(EVENT.to_string());
+
+        let receipts = test_utils::get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert!(near_sdk::serde_json::to_string(&receipts)
+            .unwrap()
+            .contains(
+                r#""receiver_id":"alice.near","actions":[{"Transfer":{"deposit":1000000000000000000000000}}]"#
+            ));
+        assert_eq!(contract.get_near_proceeds(EVENT.to_string()).0, PRICE);
+        assert_eq!(
+            contract.get_near_paid(EVENT.to_string(), alice().into()).0,
+            0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to refund")]
+    fn second_refund_claim_fails() {
+        let mut contract = setup(100);
+        register(&mut contract, alice());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event(EVENT.to_string(), "Venue closed".to_string());
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.claim_event_refund(EVENT.to_string());
+        contract.claim_event_refund(EVENT.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Proceeds of a cancelled event are refunded to the buyers")]
+    fn withdraw_of_cancelled_event_fails() {
+        let mut contract = setup(100);
+        register(&mut contract, alice());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event(EVENT.to_string(), "Venue closed".to_string());
+
+        let mut context = get_context(carol().to_string(), 0);
+        context.block_timestamp = 2_000 * 1_000_000;
+        testing_env!(context);
+        contract.withdraw_near_proceeds(EVENT.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Already registered for the event")]
+    fn second_registration_fails() {
+        let mut contract = setup(100);
+
+        register(&mut contract, alice());
+        register(&mut contract, alice());
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is at capacity")]
+    fn registration_of_sold_out_event_fails() {
+        let mut contract = setup(1);
+
+        register(&mut contract, alice());
+        assert!(
+            contract
+                .get_registration_status(EVENT.to_string(), bob().into())
+                .sold_out
+        );
+
+        register(&mut contract, bob());
+    }
+
+    #[test]
+    #[should_panic(expected = "to cover the price and storage")]
+    fn registration_below_price_fails() {
+        let mut contract = setup(100);
+
+        testing_env!(get_context(alice().to_string(), PRICE));
+        contract.register_for_event(EVENT.to_string(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Passes of the event aren't for sale")]
+    fn registration_without_price_fails() {
+        let mut contract = setup(100);
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.set_event_price(EVENT.to_string(), None);
+
+        register(&mut contract, alice());
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn set_event_price_without_yocto_fails() {
+        let mut contract = setup(100);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_event_price(EVENT.to_string(), None);
+    }
+}
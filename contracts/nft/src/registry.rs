@@ -0,0 +1,364 @@
+use crate::*;
+
+pub(crate) const GAS_FOR_PLAYER_CHECK: Gas = 5_000_000_000_000;
+pub(crate) const GAS_FOR_RESOLVE_PLAYER: Gas = 10_000_000_000_000;
+
+#[ext_contract(ext_accounts_registry)]
+pub trait AccountsRegistry {
+    fn is_catch_player(&self, account_id: AccountId) -> bool;
+}
+
+/// FT mint waiting for the registry to confirm the receiver
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingFtMint {
+    pub payer_id: AccountId,
+    pub receiver_id: AccountId,
+    pub token_id: TokenId,
+    pub price: U128,
+    pub amount: U128,
+    //spent from the storage balance of the payer, given back if the mint is rolled back
+    pub storage_cost: U128,
+}
+
+#[ext_contract(ext_registry_self)]
+pub trait RegistryResolver {
+    fn resolve_player_mint(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        payer_id: AccountId,
+        storage_cost: U128,
+    );
+    fn resolve_player_ft_mint(&mut self, mint: PendingFtMint) -> U128;
+}
+
+impl Contract {
+    //true if mints are validated with a cross contract call to the accounts registry
+    pub(crate) fn internal_is_async_validation(&self) -> bool {
+        self.accounts_registry.is_some() && self.registry_async_validation
+    }
+
+    //panics if a registry is set and the account isn't in the allow set pushed by the registry
+    pub(crate) fn internal_assert_catch_player(&self, account_id: &AccountId) {
+        if self.accounts_registry.is_some() {
+            require!(
+                self.registry_players.contains(account_id),
+                format!("{} is not a Catch player", account_id)
+            );
+        }
+    }
+
+    pub(crate) fn internal_check_player(&self, account_id: &AccountId) -> Promise {
+        ext_accounts_registry::is_catch_player(
+            account_id.clone(),
+            self.accounts_registry.as_ref().unwrap(),
+            0,
+            GAS_FOR_PLAYER_CHECK,
+        )
+    }
+
+    //true if the registry confirmed the player and the receiver didn't get the token in the meantime
+    fn internal_is_confirmed_player(&self, receiver_id: &AccountId, token_id: &TokenId) -> bool {
+        let is_player = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(false)
+            }
+            _ => false,
+        };
+
+        if !is_player {
+            env::log(
+                format!(
+                    "{} is not a Catch player, the mint is rolled back",
+                    receiver_id
+                )
+                .as_bytes(),
+            );
+        }
+
+        is_player && !self.internal_owns_token(receiver_id, token_id)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, receivers have to be Catch players of the registry, None removes the validation.
+    /// With async_validation mints are confirmed by calling is_catch_player on the registry,
+    /// transfers always use the allow set pushed with registry_sync
+    pub fn set_accounts_registry(
+        &mut self,
+        accounts_registry: Option<ValidAccountId>,
+        async_validation: bool,
+    ) {
+        self.assert_owner();
+
+        self.accounts_registry = accounts_registry.map(|id| id.into());
+        self.registry_async_validation = async_validation;
+    }
+
+    pub fn get_accounts_registry(&self) -> Option<AccountId> {
+        self.accounts_registry.clone()
+    }
+
+    /// Registry only, adds the accounts to the allow set used by synchronous transfers
+    pub fn registry_sync(&mut self, accounts: Vec<AccountId>) {
+        require!(
+            Some(env::predecessor_account_id()) == self.accounts_registry,
+            "Only the accounts registry can sync players"
+        );
+
+        for account_id in accounts.iter() {
+            self.registry_players.insert(account_id);
+        }
+    }
+
+    pub fn is_synced_player(&self, account_id: AccountId) -> bool {
+        self.registry_players.contains(&account_id)
+    }
+
+    //gives the reserved copy if the registry confirmed the receiver, otherwise releases it
+    //and refunds the storage paid by the payer
+    #[private]
+    pub fn resolve_player_mint(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: TokenId,
+        payer_id: AccountId,
+        storage_cost: U128,
+    ) {
+        if self.internal_is_confirmed_player(&receiver_id, &token_id) {
+            let event_id: EventId = token_id.split_once(".").unwrap().0.to_string();
+            let memo = format!(
+                "{} has successfully registered for the event {}",
+                &receiver_id, &event_id
+            );
+
+            self.internal_finish_mint(&receiver_id, &token_id, memo);
+            return;
+        }
+
+        self.internal_release_copy(&token_id);
+
+        if storage_cost.0 > 0 {
+            Promise::new(payer_id).transfer(storage_cost.0);
+        }
+    }
+
+    //gives the reserved copy and keeps the price if the registry confirmed the receiver,
+    //returns the unused amount which the FT contract refunds to the payer
+    #[private]
+    pub fn resolve_player_ft_mint(&mut self, mint: PendingFtMint) -> U128 {
+        if !self.internal_is_confirmed_player(&mint.receiver_id, &mint.token_id) {
+            self.internal_release_copy(&mint.token_id);
+            self.internal_return_storage_balance(mint.payer_id, mint.storage_cost.0);
+            return mint.amount;
+        }
+
+        self.internal_finish_mint(
+            &mint.receiver_id,
+            &mint.token_id,
+            format!("{} bought with the payment token", &mint.token_id),
+        );
+        self.internal_record_ft_payment(&mint.token_id, mint.payer_id, mint.price.0);
+
+        U128(mint.amount.0 - mint.price.0)
+    }
+}
+
+#[cfg(test)]
+mod registry_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    fn registry() -> ValidAccountId {
+        ValidAccountId::try_from("accounts.catchlabs.near").unwrap()
+    }
+
+    fn setup(async_validation: bool) -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.set_accounts_registry(Some(registry()), async_validation);
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_token_ft_price("catchcon.gold".to_string(), Some(U128(100)));
+
+        testing_env!(get_context(registry().to_string(), 0));
+        contract.registry_sync(vec![alice().to_string()]);
+
+        contract
+    }
+
+    fn resolve_context(is_player: bool) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(
+                near_sdk::serde_json::to_vec(&is_player).unwrap()
+            )]
+        );
+    }
+
+    fn pending_mint(storage_cost: Balance) -> PendingFtMint {
+        PendingFtMint {
+            payer_id: bob().to_string(),
+            receiver_id: bob().to_string(),
+            token_id: "catchcon.gold".to_string(),
+            price: U128(100),
+            amount: U128(150),
+            storage_cost: storage_cost.into(),
+        }
+    }
+
+    fn storage_total(contract: &Contract, account_id: ValidAccountId) -> Balance {
+        contract
+            .storage_deposits
+            .get(account_id.as_ref())
+            .unwrap()
+            .total
+    }
+
+    fn copies_minted(contract: &Contract) -> u64 {
+        contract
+            .tokens_by_id
+            .get(&"catchcon.gold".to_string())
+            .unwrap()
+            .copies_minted
+    }
+
+    #[test]
+    fn synced_player_can_register_and_receive() {
+        let mut contract = setup(false);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+
+        testing_env!(get_context(registry().to_string(), 0));
+        contract.registry_sync(vec![bob().to_string()]);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            bob().to_string(),
+            "catchcon.gold.alice.near".to_string(),
+            None,
+            None,
+        );
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "bob.near is not a Catch player")]
+    fn transfer_to_unsynced_account_fails() {
+        let mut contract = setup(false);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(
+            bob().to_string(),
+            "catchcon.gold.alice.near".to_string(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the accounts registry can sync players")]
+    fn sync_by_other_account_fails() {
+        let mut contract = setup(false);
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.registry_sync(vec![bob().to_string()]);
+    }
+
+    #[test]
+    fn async_mint_is_given_once_confirmed() {
+        let mut contract = setup(true);
+
+        // bob isn't synced, the registry is asked instead
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+        assert_eq!(copies_minted(&contract), 1);
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 0);
+
+        resolve_context(true);
+        contract.resolve_player_mint(
+            bob().to_string(),
+            "catchcon.gold".to_string(),
+            bob().to_string(),
+            U128(0),
+        );
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+    }
+
+    #[test]
+    fn async_mint_is_rolled_back_on_rejection() {
+        let mut contract = setup(true);
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+
+        resolve_context(false);
+        contract.resolve_player_mint(
+            bob().to_string(),
+            "catchcon.gold".to_string(),
+            bob().to_string(),
+            U128(1_000),
+        );
+
+        assert_eq!(copies_minted(&contract), 0);
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 0);
+        assert_eq!(
+            test_utils::get_logs(),
+            vec!["bob.near is not a Catch player, the mint is rolled back"]
+        );
+    }
+
+    #[test]
+    fn async_ft_mint_refunds_everything_on_rejection() {
+        let mut contract = setup(true);
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.storage_deposit(None);
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = r#"{"action":"mint","token_id":"catchcon.gold"}"#;
+        let result = contract.ft_on_transfer(bob(), U128(150), msg.to_string());
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+
+        // the storage is paid upfront from the storage balance of the payer
+        let storage_cost = STORAGE_DEPOSIT - storage_total(&contract, bob());
+        assert!(storage_cost > 0);
+
+        resolve_context(false);
+        let unused = contract.resolve_player_ft_mint(pending_mint(storage_cost));
+        assert_eq!(unused.0, 150);
+        assert_eq!(copies_minted(&contract), 0);
+        assert_eq!(storage_total(&contract, bob()), STORAGE_DEPOSIT);
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 0);
+
+        testing_env!(get_context(ft().to_string(), 0));
+        contract.ft_on_transfer(bob(), U128(150), msg.to_string());
+
+        resolve_context(true);
+        let unused = contract.resolve_player_ft_mint(pending_mint(storage_cost));
+        assert_eq!(unused.0, 50);
+        assert_eq!(
+            storage_total(&contract, bob()),
+            STORAGE_DEPOSIT - storage_cost
+        );
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+        assert_eq!(contract.get_ft_proceeds("catchcon".to_string()).0, 100);
+    }
+}
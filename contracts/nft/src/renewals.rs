@@ -0,0 +1,495 @@
+use crate::*;
+
+/// Paid renewals of the copies of a token class, prices are per second of extension
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RenewalConfig {
+    //yoctoNEAR per second
+    pub price_per_sec: U128,
+    //payment token per second, None if renewals can't be paid with it
+    pub ft_price_per_sec: Option<U128>,
+    //copies can't be renewed beyond issued_at of the token + max_lifetime_sec
+    pub max_lifetime_sec: u64,
+}
+
+impl Contract {
+    //expiry of the copy of form event_id.token_id.owner_id, a renewed copy doesn't follow the token
+    pub(crate) fn internal_copy_expires_at(
+        &self,
+        token_id: &TokenId,
+        token: &Token,
+    ) -> Option<u64> {
+        self.pass_expiry_by_token_id
+            .get(token_id)
+            .or(token.expires_at)
+    }
+
+    pub(crate) fn internal_is_copy_expired(&self, token_id: &TokenId, token: &Token) -> bool {
+        match self.pass_expiry_by_token_id.get(token_id) {
+            Some(expires_at) => expires_at <= now_ms(),
+            None => internal_is_token_expired(token),
+        }
+    }
+
+    //extends the copy owned by the payer by extend_sec from its expiry or from now if it already expired,
+    //returns the cost of the renewal in NEAR or in the payment token
+    fn internal_renew(
+        &mut self,
+        payer_id: AccountId,
+        token_id: TokenId,
+        extend_sec: u64,
+        with_ft: bool,
+    ) -> Balance {
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        require!(
+            payer_id == owner_id && self.internal_owns_token(&owner_id, &class_token_id),
+            "Only the token owner can renew the token"
+        );
+        require!(extend_sec > 0, "Nothing to renew");
+
+        let config = self
+            .renewal_config_by_token_id
+            .get(&class_token_id)
+            .unwrap_or_else(|| env::panic(b"Token can't be renewed"));
+
+        let token = self.tokens_by_id.get(&class_token_id).unwrap();
+        require!(!token.metadata_frozen, "Token metadata is frozen");
+
+        let event_id: EventId = class_token_id.split_once('.').unwrap().0.to_string();
+        require!(
+            !self
+                .events_by_id
+                .get(&event_id)
+                .is_some_and(|event| event.cancelled),
+            "Event is cancelled"
+        );
+
+        let price_per_sec = if with_ft {
+            config
+                .ft_price_per_sec
+                .unwrap_or_else(|| env::panic(b"Token can't be renewed with the payment token"))
+                .0
+        } else {
+            config.price_per_sec.0
+        };
+
+        let old_expires_at = self.internal_copy_expires_at(&token_id, &token);
+        let new_expires_at =
+            std::cmp::max(old_expires_at.unwrap_or(0), now_ms()) + extend_sec * 1_000;
+
+        let issued_at = self
+            .internal_token_metadata(&class_token_id)
+            .unwrap()
+            .issued_at
+            .unwrap_or(0);
+        require!(
+            new_expires_at <= issued_at + config.max_lifetime_sec * 1_000,
+            "Renewal exceeds the max lifetime of the token"
+        );
+
+        self.pass_expiry_by_token_id
+            .insert(&token_id, &new_expires_at);
+
+        CatchEventLogVariant::CatchRenewal(RenewalLog {
+            token_id,
+            owner_id,
+            old_expires_at,
+            new_expires_at,
+        })
+        .emit();
+
+        price_per_sec * Balance::from(extend_sec)
+    }
+
+    //renewal paid with a renew msg of ft_transfer_call, the cost is escrowed as proceeds of the event
+    pub(crate) fn internal_renew_with_ft(
+        &mut self,
+        payer_id: AccountId,
+        token_id: TokenId,
+        extend_sec: u64,
+        amount: Balance,
+    ) -> PromiseOrValue<U128> {
+        let (class_token_id, _) = resolve_token_id(token_id.clone());
+
+        let cost = self.internal_renew(payer_id.clone(), token_id, extend_sec, true);
+        require!(
+            amount >= cost,
+            format!("Attached amount is less than the cost {}", cost)
+        );
+
+        self.internal_record_ft_payment(&class_token_id, payer_id, cost);
+
+        PromiseOrValue::Value(U128(amount - cost))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Token owner only, extends the copy (event_id.token_id.owner_id) by extend_sec, the price goes
+    /// to the event organiser and the excess deposit is refunded
+    #[payable]
+    pub fn nft_renew(&mut self, token_id: TokenId, extend_sec: u64) {
+        let payer_id = env::predecessor_account_id();
+        let (class_token_id, _) = resolve_token_id(token_id.clone());
+
+        let cost = self.internal_renew(payer_id.clone(), token_id, extend_sec, false);
+
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= cost,
+            format!("Must attach {} yoctoNEAR to renew", cost)
+        );
+
+        let event_id: EventId = class_token_id.split_once('.').unwrap().0.to_string();
+        let organiser = self.events_by_id.get(&event_id).unwrap().organiser;
+
+        if cost > 0 {
+            Promise::new(organiser).transfer(cost);
+        }
+        if deposit > cost {
+            Promise::new(payer_id).transfer(deposit - cost);
+        }
+    }
+
+    /// Owner or renewer only, pushes expires_at of the token class (event_id.token_id) and of its
+    /// metadata by extension_nanoseconds from its expiry or from now if it already lapsed.
+    /// Copies renewed with nft_renew keep their own expiry
+    pub fn nft_extend_expiry(&mut self, token_id: TokenId, extension_nanoseconds: u64) {
+        let predecessor_id = env::predecessor_account_id();
+        require!(
+            predecessor_id == self.owner_id || Some(&predecessor_id) == self.renewer_id.as_ref(),
+            "Only the owner or the renewer can extend the expiry"
+        );
+
+        let extension_ms = extension_nanoseconds / 1_000_000;
+        require!(extension_ms > 0, "Nothing to renew");
+
+        let mut token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token does not exist"));
+        require!(!token.metadata_frozen, "Token metadata is frozen");
+
+        let mut metadata = self
+            .token_metadata_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"Token shares the metadata of its series"));
+
+        let old_expires_at = token
+            .expires_at
+            .unwrap_or_else(|| env::panic(b"Token never expires"));
+        let new_expires_at = std::cmp::max(old_expires_at, now_ms()) + extension_ms;
+
+        token.expires_at = Some(new_expires_at);
+        self.tokens_by_id.insert(&token_id, &token);
+
+        metadata.expires_at = Some(new_expires_at);
+        self.token_metadata_by_id.insert(&token_id, &metadata);
+
+        CatchEventLogVariant::NftExpiryExtended(ExpiryExtendedLog {
+            token_id,
+            renewer_id: predecessor_id,
+            old_expires_at,
+            new_expires_at,
+        })
+        .emit();
+    }
+
+    /// Owner only, account allowed to call nft_extend_expiry, None removes it
+    pub fn set_renewer(&mut self, renewer_id: Option<ValidAccountId>) {
+        self.assert_owner();
+
+        self.renewer_id = renewer_id.map(|renewer_id| renewer_id.into());
+    }
+
+    pub fn get_renewer(&self) -> Option<AccountId> {
+        self.renewer_id.clone()
+    }
+
+    /// Token classes whose expires_at is before timestamp (ms), expired ones included,
+    /// copies renewed with nft_renew aren't listed
+    pub fn nft_tokens_expiring_before(
+        &self,
+        timestamp: u64,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonTokenGeneral> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.token_metadata_by_id
+            .iter()
+            .filter(|(token_id, metadata)| {
+                metadata
+                    .expires_at
+                    .is_some_and(|expires_at| expires_at < timestamp)
+                    && !self.internal_is_pending(token_id)
+            })
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|(token_id, _)| self.nft_token_by_id(token_id).unwrap())
+            .collect()
+    }
+
+    /// Owner only, None stops renewals of the token class (event_id.token_id)
+    pub fn set_renewal_config(&mut self, token_id: TokenId, config: Option<RenewalConfig>) {
+        self.assert_owner();
+
+        require!(
+            self.tokens_by_id.get(&token_id).is_some(),
+            "Token does not exist"
+        );
+
+        match config {
+            Some(config) => {
+                require!(
+                    self.internal_token_metadata(&token_id)
+                        .unwrap()
+                        .issued_at
+                        .is_some(),
+                    "Token has no issued_at to bound its lifetime"
+                );
+
+                self.renewal_config_by_token_id.insert(&token_id, &config);
+            }
+            None => {
+                self.renewal_config_by_token_id.remove(&token_id);
+            }
+        }
+    }
+
+    pub fn get_renewal_config(&self, token_id: TokenId) -> Option<RenewalConfig> {
+        self.renewal_config_by_token_id.get(&token_id)
+    }
+
+    //expiry in milliseconds of the copy of form event_id.token_id.owner_id
+    pub fn get_copy_expires_at(&self, token_id: TokenId) -> Option<u64> {
+        let (class_token_id, _) = resolve_token_id(token_id.clone());
+
+        let token = self
+            .tokens_by_id
+            .get(&class_token_id)
+            .unwrap_or_else(|| env::panic(b"No Token"));
+
+        self.internal_copy_expires_at(&token_id, &token)
+    }
+}
+
+#[cfg(test)]
+mod renewals_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const DAY_SEC: u64 = 86_400;
+    const DAY_MS: u64 = DAY_SEC * 1_000;
+    const PASS: &str = "season.member.alice.near";
+
+    /// carol runs a membership issued at 0 which expires after 30 days, renewals cost 1 yocto
+    /// or 2 of the payment token per second for up to a year, alice is a member
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        let mut token_metadata = token_metadata(10);
+        token_metadata.issued_at = Some(0);
+        token_metadata.expires_at = Some(30 * DAY_MS);
+        contract.organise_event(
+            "season".to_string(),
+            vec![TokenInfo {
+                token_id: "member".to_string(),
+                token_metadata,
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_renewal_config(
+            "season.member".to_string(),
+            Some(RenewalConfig {
+                price_per_sec: U128(1),
+                ft_price_per_sec: Some(U128(2)),
+                max_lifetime_sec: 365 * DAY_SEC,
+            }),
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "season.member".to_string());
+
+        contract
+    }
+
+    fn renew_at(contract: &mut Contract, timestamp_ms: u64, extend_sec: u64, deposit: Balance) {
+        let mut context = get_context(alice().to_string(), deposit);
+        context.block_timestamp = timestamp_ms * 1_000_000;
+        testing_env!(context);
+
+        contract.nft_renew(PASS.to_string(), extend_sec);
+    }
+
+    #[test]
+    fn renewal_before_expiry_extends_from_expiry() {
+        let mut contract = setup();
+
+        renew_at(&mut contract, 10 * DAY_MS, DAY_SEC, DAY_SEC as u128 + 5);
+
+        assert_eq!(
+            contract.get_copy_expires_at(PASS.to_string()),
+            Some(31 * DAY_MS)
+        );
+        assert!(test_utils::get_logs()[0].contains(r#""event":"catch_renewal""#));
+        assert!(test_utils::get_logs()[0].contains(r#""old_expires_at":2592000000"#));
+
+        // other members keep the expiry of the token
+        assert_eq!(
+            contract.get_copy_expires_at("season.member.bob.near".to_string()),
+            Some(30 * DAY_MS)
+        );
+    }
+
+    #[test]
+    fn renewal_after_expiry_extends_from_now() {
+        let mut contract = setup();
+
+        renew_at(&mut contract, 40 * DAY_MS, DAY_SEC, DAY_SEC as u128);
+
+        assert_eq!(
+            contract.get_copy_expires_at(PASS.to_string()),
+            Some(41 * DAY_MS)
+        );
+
+        // the renewed copy can be transferred again
+        let token = contract
+            .tokens_by_id
+            .get(&"season.member".to_string())
+            .unwrap();
+        assert!(internal_is_token_expired(&token));
+        assert!(!contract.internal_is_copy_expired(&PASS.to_string(), &token));
+    }
+
+    #[test]
+    fn renewal_with_ft_returns_excess() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = format!(
+            r#"{{"action":"renew","token_id":"{}","extend_sec":{}}}"#,
+            PASS, DAY_SEC
+        );
+        let unused = contract.ft_on_transfer(alice(), U128(2 * DAY_SEC as u128 + 7), msg);
+
+        assert!(matches!(unused, PromiseOrValue::Value(U128(7))));
+        assert_eq!(
+            contract.get_copy_expires_at(PASS.to_string()),
+            Some(31 * DAY_MS)
+        );
+        assert_eq!(
+            contract.get_ft_proceeds("season".to_string()).0,
+            2 * DAY_SEC as u128
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Renewal exceeds the max lifetime of the token")]
+    fn renewal_beyond_lifetime_fails() {
+        let mut contract = setup();
+
+        renew_at(&mut contract, DAY_MS, 336 * DAY_SEC, 336 * DAY_SEC as u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach 86400 yoctoNEAR to renew")]
+    fn underpaid_renewal_fails() {
+        let mut contract = setup();
+
+        renew_at(&mut contract, DAY_MS, DAY_SEC, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is cancelled")]
+    fn renewal_of_cancelled_event_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.cancel_event("season".to_string(), "Venue unavailable".to_string());
+
+        renew_at(&mut contract, DAY_MS, DAY_SEC, DAY_SEC as u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token metadata is frozen")]
+    fn renewal_of_frozen_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.freeze_token_metadata("season.member".to_string());
+
+        renew_at(&mut contract, DAY_MS, DAY_SEC, DAY_SEC as u128);
+    }
+
+    #[test]
+    fn renewer_extends_lapsed_token() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.set_renewer(Some(bob()));
+
+        let mut context = get_context(bob().to_string(), 0);
+        context.block_timestamp = 40 * DAY_MS * 1_000_000;
+        testing_env!(context);
+        assert!(contract.nft_is_expired(PASS.to_string()));
+
+        contract.nft_extend_expiry("season.member".to_string(), DAY_MS * 1_000_000);
+
+        let token = contract
+            .nft_token_by_id("season.member".to_string())
+            .unwrap();
+        assert_eq!(token.metadata.expires_at, Some(41 * DAY_MS));
+        assert!(!contract.nft_is_expired(PASS.to_string()));
+        assert!(test_utils::get_logs()[0].contains(r#""event":"nft_expiry_extended""#));
+        assert!(test_utils::get_logs()[0].contains(r#""old_expires_at":2592000000"#));
+    }
+
+    #[test]
+    fn tokens_expiring_before_timestamp() {
+        let contract = setup();
+
+        assert!(contract
+            .nft_tokens_expiring_before(30 * DAY_MS, None, None)
+            .is_empty());
+
+        let tokens = contract.nft_tokens_expiring_before(30 * DAY_MS + 1, None, None);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_id, "season.member");
+    }
+
+    #[test]
+    #[should_panic(expected = "Token never expires")]
+    fn extending_token_without_expiry_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.nft_extend_expiry("catchcon.gold".to_string(), DAY_MS * 1_000_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or the renewer can extend the expiry")]
+    fn extending_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.nft_extend_expiry("season.member".to_string(), DAY_MS * 1_000_000);
+    }
+}
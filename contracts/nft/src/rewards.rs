@@ -0,0 +1,382 @@
+use crate::*;
+
+const GAS_FOR_REWARD_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_REWARD: Gas = 10_000_000_000_000;
+
+#[ext_contract(ext_rewards_self)]
+pub trait RewardsResolver {
+    fn resolve_reward(&mut self, event_id: EventId, account_id: AccountId, amount: U128);
+    fn resolve_reward_pool_return(&mut self, event_id: EventId, amount: U128);
+}
+
+impl Contract {
+    //adds FT sent by the organiser with an event_funding msg to the reward pool of the event
+    pub(crate) fn internal_fund_reward_pool(
+        &mut self,
+        sender_id: AccountId,
+        event_id: EventId,
+        amount: Balance,
+    ) {
+        let event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(
+            sender_id == event.organiser,
+            "Only the event organiser can fund the reward pool"
+        );
+        require!(!event.archived, "Event is archived");
+
+        let pool = self.reward_pool_by_event_id.get(&event_id).unwrap_or(0);
+        self.reward_pool_by_event_id
+            .insert(&event_id, &(pool + amount));
+
+        self.ft_liabilities += amount;
+    }
+
+    //takes the reward out of the pool and transfers it to the attendee, nothing is paid if the pool ran dry
+    pub(crate) fn internal_issue_checkin_reward(
+        &mut self,
+        event_id: &EventId,
+        account_id: &AccountId,
+        reward: Balance,
+    ) {
+        let pool = self.reward_pool_by_event_id.get(event_id).unwrap_or(0);
+
+        if pool < reward {
+            CatchEventLogVariant::CheckinReward(CheckinRewardLog {
+                event_id: event_id.clone(),
+                account_id: account_id.clone(),
+                amount: reward.into(),
+                status: CheckinRewardStatus::Unfunded,
+            })
+            .emit();
+            return;
+        }
+
+        self.reward_pool_by_event_id
+            .insert(event_id, &(pool - reward));
+
+        self.internal_transfer_reward(event_id.clone(), account_id.clone(), reward);
+    }
+
+    fn internal_transfer_reward(&self, event_id: EventId, account_id: AccountId, amount: Balance) {
+        ext_ft::ft_transfer(
+            account_id.clone(),
+            amount.into(),
+            Some(format!("Check-in reward of the event {}", &event_id)),
+            self.payment_token_id.as_ref().unwrap(),
+            1,
+            GAS_FOR_REWARD_TRANSFER,
+        )
+        .then(ext_rewards_self::resolve_reward(
+            event_id,
+            account_id,
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_REWARD,
+        ));
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Event organiser only, FT paid to each attendee on check-in, None stops the rewards
+    pub fn set_checkin_reward(&mut self, event_id: EventId, reward: Option<U128>) {
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(
+            env::predecessor_account_id() == event.organiser,
+            "Only the event organiser can set the check-in reward"
+        );
+        require!(self.payment_token_id.is_some(), "Payment token is not set");
+
+        event.checkin_reward = reward;
+        self.events_by_id.insert(&event_id, &event);
+    }
+
+    pub fn get_reward_pool(&self, event_id: EventId) -> U128 {
+        self.reward_pool_by_event_id
+            .get(&event_id)
+            .unwrap_or(0)
+            .into()
+    }
+
+    //check-in rewards of the event which couldn't be delivered to the account
+    pub fn get_pending_reward(&self, event_id: EventId, account_id: AccountId) -> U128 {
+        self.pending_rewards
+            .get(&(event_id, account_id))
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Transfers the check-in rewards which couldn't be delivered, e.g. the attendee
+    /// wasn't registered on the FT contract at check-in
+    pub fn claim_reward(&mut self, event_id: EventId) {
+        let account_id = env::predecessor_account_id();
+
+        let amount = self
+            .pending_rewards
+            .remove(&(event_id.clone(), account_id.clone()))
+            .unwrap_or(0);
+        require!(amount > 0, "No reward to claim");
+
+        self.internal_transfer_reward(event_id, account_id, amount);
+    }
+
+    /// Event organiser only, stops check-ins and returns the unspent reward pool to the organiser,
+    /// pending rewards stay claimable
+    pub fn archive_event(&mut self, event_id: EventId) {
+        let mut event = self
+            .events_by_id
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic(b"Invalid event id"));
+
+        require!(
+            env::predecessor_account_id() == event.organiser,
+            "Only the event organiser can archive the event"
+        );
+
+        let pool = self.reward_pool_by_event_id.remove(&event_id).unwrap_or(0);
+
+        // calling it again retries returning a pool which couldn't be returned
+        require!(!event.archived || pool > 0, "Event is already archived");
+
+        event.archived = true;
+        self.events_by_id.insert(&event_id, &event);
+
+        if pool > 0 {
+            ext_ft::ft_transfer(
+                event.organiser,
+                pool.into(),
+                Some(format!("Unspent reward pool of the event {}", &event_id)),
+                self.payment_token_id.as_ref().unwrap(),
+                1,
+                GAS_FOR_REWARD_TRANSFER,
+            )
+            .then(ext_rewards_self::resolve_reward_pool_return(
+                event_id,
+                pool.into(),
+                &env::current_account_id(),
+                0,
+                GAS_FOR_RESOLVE_REWARD,
+            ));
+        }
+    }
+
+    //marks the reward delivered or queues it for claim_reward
+    #[private]
+    pub fn resolve_reward(&mut self, event_id: EventId, account_id: AccountId, amount: U128) {
+        let delivered = matches!(env::promise_result(0), PromiseResult::Successful(_));
+
+        CatchEventLogVariant::CheckinReward(CheckinRewardLog {
+            event_id: event_id.clone(),
+            account_id: account_id.clone(),
+            amount,
+            status: if delivered {
+                CheckinRewardStatus::Delivered
+            } else {
+                CheckinRewardStatus::Queued
+            },
+        })
+        .emit();
+
+        if delivered {
+            self.internal_settle_ft_liability(amount.0);
+            return;
+        }
+
+        let key = (event_id, account_id);
+        let pending = self.pending_rewards.get(&key).unwrap_or(0);
+        self.pending_rewards.insert(&key, &(pending + amount.0));
+    }
+
+    //puts the pool back if the return to the organiser failed, archive_event returns it again
+    #[private]
+    pub fn resolve_reward_pool_return(&mut self, event_id: EventId, amount: U128) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.internal_settle_ft_liability(amount.0);
+            return;
+        }
+
+        let pool = self.reward_pool_by_event_id.get(&event_id).unwrap_or(0);
+        self.reward_pool_by_event_id
+            .insert(&event_id, &(pool + amount.0));
+    }
+}
+
+#[cfg(test)]
+mod rewards_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    /// carol organises catchcon with a reward of 10 per check-in, funds the pool with 15
+    /// and alice holds a gold pass
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.set_checkin_reward("catchcon".to_string(), Some(U128(10)));
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = r#"{"action":"event_funding","event_id":"catchcon"}"#;
+        contract.ft_on_transfer(carol(), U128(15), msg.to_string());
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        contract
+    }
+
+    fn resolve_context(result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+    }
+
+    #[test]
+    fn check_in_pays_reward() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in(PASS.to_string());
+        assert_eq!(contract.get_reward_pool("catchcon".to_string()).0, 5);
+
+        resolve_context(PromiseResult::Successful(vec![]));
+        contract.resolve_reward("catchcon".to_string(), alice().into(), U128(10));
+
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"checkin_reward","data":{"event_id":"catchcon","account_id":"alice.near","amount":"10","status":"delivered"}}"#
+            ]
+        );
+        assert_eq!(
+            contract
+                .get_pending_reward("catchcon".to_string(), alice().into())
+                .0,
+            0
+        );
+    }
+
+    #[test]
+    fn failed_reward_is_queued_for_claim() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in(PASS.to_string());
+
+        // alice isn't registered on the FT contract
+        resolve_context(PromiseResult::Failed);
+        contract.resolve_reward("catchcon".to_string(), alice().into(), U128(10));
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"checkin_reward","data":{"event_id":"catchcon","account_id":"alice.near","amount":"10","status":"queued"}}"#
+            ]
+        );
+        assert_eq!(
+            contract
+                .get_pending_reward("catchcon".to_string(), alice().into())
+                .0,
+            10
+        );
+
+        testing_env!(get_context(alice().to_string(), 0));
+        contract.claim_reward("catchcon".to_string());
+        assert_eq!(
+            contract
+                .get_pending_reward("catchcon".to_string(), alice().into())
+                .0,
+            0
+        );
+    }
+
+    #[test]
+    fn empty_pool_pays_no_reward() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(bob().to_string(), "catchcon.gold".to_string());
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in(PASS.to_string());
+        contract.check_in("catchcon.gold.bob.near".to_string());
+
+        assert_eq!(contract.get_reward_pool("catchcon".to_string()).0, 5);
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"checkin_reward","data":{"event_id":"catchcon","account_id":"bob.near","amount":"10","status":"unfunded"}}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Pass already redeemed")]
+    fn second_check_in_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in(PASS.to_string());
+        contract.check_in(PASS.to_string());
+    }
+
+    #[test]
+    fn archive_returns_unspent_pool() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.check_in(PASS.to_string());
+        contract.archive_event("catchcon".to_string());
+        assert_eq!(contract.get_reward_pool("catchcon".to_string()).0, 0);
+
+        // carol isn't registered on the FT contract, the pool is kept
+        resolve_context(PromiseResult::Failed);
+        contract.resolve_reward_pool_return("catchcon".to_string(), U128(5));
+        assert_eq!(contract.get_reward_pool("catchcon".to_string()).0, 5);
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.archive_event("catchcon".to_string());
+        assert_eq!(contract.get_reward_pool("catchcon".to_string()).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Event is archived")]
+    fn check_in_after_archive_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.archive_event("catchcon".to_string());
+        contract.check_in(PASS.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the event organiser can fund the reward pool")]
+    fn funding_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = r#"{"action":"event_funding","event_id":"catchcon"}"#;
+        contract.ft_on_transfer(bob(), U128(15), msg.to_string());
+    }
+}
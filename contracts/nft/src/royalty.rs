@@ -0,0 +1,806 @@
+use crate::*;
+
+/// Royalty per A/c in basis points, 10_000 = 100%
+pub type Royalty = HashMap<AccountId, u32>;
+
+pub const ROYALTY_BASIS_POINTS: u32 = 10_000;
+
+pub const MAX_ROYALTY_RECIPIENTS: usize = 6;
+
+/// panics if the royalties add up to more than 100%, have too many recipients or a zero share
+pub(crate) fn assert_valid_royalty(royalty: &Royalty) {
+    require!(
+        royalty.len() <= MAX_ROYALTY_RECIPIENTS,
+        format!(
+            "Royalties can't have more than {} recipients",
+            MAX_ROYALTY_RECIPIENTS
+        )
+    );
+
+    if let Some((account_id, _)) = royalty.iter().find(|(_, bps)| **bps == 0) {
+        env::panic(format!("Royalty of {} can't be zero", account_id).as_bytes());
+    }
+
+    let total: u64 = royalty.values().map(|bps| *bps as u64).sum();
+
+    require!(
+        total <= ROYALTY_BASIS_POINTS as u64,
+        format!(
+            "Royalties can't exceed 100%, they add up to {} basis points",
+            total
+        )
+    );
+}
+
+//converts the royalty basis points into the amount to pay out of the balance
+pub(crate) fn royalty_to_payout(bps: u32, balance: Balance) -> U128 {
+    U128(balance * bps as u128 / ROYALTY_BASIS_POINTS as u128)
+}
+
+impl Contract {
+    /// Merges the default royalty, the event override and the royalty of the token,
+    /// later ones win for the same A/c
+    pub(crate) fn internal_merge_royalty(
+        &self,
+        event_royalty: &Royalty,
+        token_royalty: Royalty,
+    ) -> Royalty {
+        let mut royalty = self.default_royalty.clone();
+        royalty.extend(event_royalty.clone());
+        royalty.extend(token_royalty);
+
+        assert_valid_royalty(&royalty);
+
+        royalty
+    }
+
+    /// Royalty receivers get their share of the balance and the owner gets the rest,
+    /// panics if the payout has more entries than max_len_payout
+    pub(crate) fn internal_payout(
+        &self,
+        token: &Token,
+        owner_id: &AccountId,
+        balance: Balance,
+        max_len_payout: u32,
+    ) -> Payout {
+        let mut payout = HashMap::with_capacity(token.royalty.len() + 1);
+        let mut total_royalty: Balance = 0;
+
+        for (account_id, bps) in token.royalty.iter() {
+            // the owner's own royalty is included in the remainder
+            if account_id != owner_id {
+                let amount = royalty_to_payout(*bps, balance);
+                total_royalty += amount.0;
+                payout.insert(account_id.clone(), amount);
+            }
+        }
+
+        payout.insert(owner_id.clone(), U128(balance - total_royalty));
+
+        require!(
+            payout.len() <= max_len_payout as usize,
+            format!(
+                "The payout has {} receivers which is more than max_len_payout {}",
+                payout.len(),
+                max_len_payout
+            )
+        );
+
+        Payout { payout }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    //calculates the payout for a token given the passed in balance. This is a view method
+    pub fn nft_payout(&self, token_id: TokenId, balance: U128, max_len_payout: u32) -> Payout {
+        let (token_id, owner_id) = resolve_token_id(token_id);
+
+        let token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+        require!(!token.soulbound, "Token is soulbound");
+
+        self.internal_payout(&token, &owner_id, balance.into(), max_len_payout)
+    }
+
+    /// Owner only, royalty applied to tokens of events organised from now on
+    pub fn set_default_royalty(&mut self, royalty: Royalty) {
+        self.assert_owner();
+        assert_valid_royalty(&royalty);
+
+        self.default_royalty = royalty;
+    }
+
+    pub fn get_default_royalty(&self) -> Royalty {
+        self.default_royalty.clone()
+    }
+
+    /// Owner only, replaces the royalty of the token as long as its metadata isn't frozen
+    pub fn update_token_royalties(&mut self, token_id: TokenId, royalty: Royalty) {
+        self.assert_owner();
+        assert_valid_royalty(&royalty);
+
+        let mut token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+
+        require!(!token.metadata_frozen, "Token metadata is frozen");
+
+        token.royalty = royalty;
+        self.tokens_by_id.insert(&token_id, &token);
+
+        CatchEventLogVariant::NftRoyaltyUpdated(RoyaltyConfigLog {
+            token_id,
+            royalty: token.royalty.into_iter().collect(),
+        })
+        .emit();
+    }
+
+    /// Event organiser only, freezes the metadata and royalties of the token for good
+    pub fn freeze_token_metadata(&mut self, token_id: TokenId) {
+        let mut token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+
+        let event_id = token_id.split_once('.').unwrap().0.to_string();
+        let event = self.events_by_id.get(&event_id).unwrap();
+
+        require!(
+            env::predecessor_account_id() == event.organiser,
+            "Only the event organiser can freeze the token"
+        );
+
+        token.metadata_frozen = true;
+        self.tokens_by_id.insert(&token_id, &token);
+    }
+}
+
+#[cfg(test)]
+mod royalty_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const TOKEN_ID: &str = "catchcon.gold.alice.near";
+
+    /// carol organises catchcon.gold with 10% royalty for herself and 5% for charity,
+    /// alice owns a copy and lists it on the marketplace
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.approve_marketplaces(vec![marketplace()]);
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.organise_event(
+            "catchcon".to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: HashMap::from([
+                    (carol().to_string(), 1_000),
+                    ("charity.near".to_string(), 500),
+                ]),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+        contract.nft_approve(TOKEN_ID.to_string(), marketplace().to_string(), None);
+
+        contract
+    }
+
+    fn buy(contract: &mut Contract, balance: u128, max_len_payout: u32) -> Payout {
+        testing_env!(get_context(marketplace().to_string(), 1));
+        contract.nft_transfer_payout(
+            bob().to_string(),
+            TOKEN_ID.to_string(),
+            Some(0),
+            U128(balance),
+            None,
+            max_len_payout,
+            None,
+        )
+    }
+
+    #[test]
+    fn sale_pays_royalties_and_owner() {
+        let mut contract = setup();
+
+        let payout = buy(&mut contract, 1_000_000, 7).payout;
+
+        assert_eq!(payout.len(), 3);
+        assert_eq!(payout[&carol().to_string()].0, 100_000);
+        assert_eq!(payout["charity.near"].0, 50_000);
+        assert_eq!(payout[&alice().to_string()].0, 850_000);
+        assert_eq!(
+            payout.values().map(|amount| amount.0).sum::<u128>(),
+            1_000_000
+        );
+
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 0);
+        assert_eq!(contract.nft_supply_for_owner(bob().into()).0, 1);
+        // approvals don't carry over to the buyer
+        assert!(contract
+            .nft_token("catchcon.gold.bob.near".to_string())
+            .unwrap()
+            .approved_account_ids
+            .is_empty());
+
+        assert_eq!(
+            test_utils::get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_royalty_payout","data":{"token_id":"catchcon.gold.alice.near","marketplace_id":"marketplace.near","balance":"1000000","payout":{"alice.near":"850000","carol.near":"100000","charity.near":"50000"}}}"#
+        );
+    }
+
+    #[test]
+    fn organiser_selling_own_token_gets_the_remainder() {
+        let mut contract = setup();
+        buy(&mut contract, 1_000_000, 7);
+
+        let payout = contract
+            .nft_payout("catchcon.gold.bob.near".to_string(), U128(1_000), 7)
+            .payout;
+        assert_eq!(payout[&bob().to_string()].0, 850);
+
+        let payout = contract
+            .nft_payout("catchcon.gold.carol.near".to_string(), U128(1_000), 2)
+            .payout;
+        assert_eq!(payout.len(), 2);
+        assert_eq!(payout[&carol().to_string()].0, 950);
+    }
+
+    #[test]
+    #[should_panic(expected = "The payout has 3 receivers which is more than max_len_payout 2")]
+    fn payout_above_max_len_fails() {
+        let mut contract = setup();
+
+        buy(&mut contract, 1_000_000, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only Catch approved marketplaces can settle sales")]
+    fn payout_from_unapproved_marketplace_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.nft_transfer_payout(
+            bob().to_string(),
+            TOKEN_ID.to_string(),
+            Some(0),
+            U128(1_000),
+            None,
+            7,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The actual approval_id 0 is different from the given approval_id 3")]
+    fn payout_with_stale_approval_id_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(marketplace().to_string(), 1));
+        contract.nft_transfer_payout(
+            bob().to_string(),
+            TOKEN_ID.to_string(),
+            Some(3),
+            U128(1_000),
+            None,
+            7,
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unauthorised")]
+    fn payout_after_revoke_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_revoke(TOKEN_ID.to_string(), marketplace().to_string());
+
+        buy(&mut contract, 1_000_000, 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't exceed 100%")]
+    fn royalty_above_100_percent_fails() {
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+
+        contract.organise_event(
+            "catchcon".to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: HashMap::from([
+                    (carol().to_string(), 6_000),
+                    ("charity.near".to_string(), 5_000),
+                ]),
+                soulbound: false,
+            }],
+            None,
+        );
+    }
+
+    fn six_recipients() -> Royalty {
+        (0..6).map(|i| (format!("artist{}.near", i), 500)).collect()
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't exceed 100%")]
+    fn merge_above_100_percent_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.set_default_royalty(HashMap::from([("treasury.near".to_string(), 2_000)]));
+
+        // each map is valid on its own, the merge isn't
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.organise_event(
+            "catchcon".to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: HashMap::from([(carol().to_string(), 5_000)]),
+                soulbound: false,
+            }],
+            Some(HashMap::from([("charity.near".to_string(), 4_000)])),
+        );
+    }
+
+    #[test]
+    fn merge_overrides_default_and_event() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.set_default_royalty(HashMap::from([
+            ("treasury.near".to_string(), 200),
+            (carol().to_string(), 100),
+        ]));
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.organise_event(
+            "catchcon".to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: HashMap::from([(carol().to_string(), 1_000)]),
+                soulbound: false,
+            }],
+            Some(HashMap::from([("treasury.near".to_string(), 300)])),
+        );
+
+        let payout = contract
+            .nft_payout("catchcon.gold.alice.near".to_string(), U128(10_000), 3)
+            .payout;
+        assert_eq!(payout[&carol().to_string()].0, 1_000);
+        assert_eq!(payout["treasury.near"].0, 300);
+        assert_eq!(payout[&alice().to_string()].0, 8_700);
+    }
+
+    #[test]
+    #[should_panic(expected = "Royalties can't have more than 6 recipients")]
+    fn seven_recipients_fail() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        let mut royalty = six_recipients();
+        royalty.insert("artist6.near".to_string(), 1);
+        contract.set_default_royalty(royalty);
+    }
+
+    #[test]
+    fn six_recipients_and_owner_fit_max_len_payout_7() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.update_token_royalties("catchcon.gold".to_string(), six_recipients());
+
+        assert_eq!(
+            test_utils::get_logs()[0],
+            r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"nft_royalty_updated","data":{"token_id":"catchcon.gold","royalty":{"artist0.near":500,"artist1.near":500,"artist2.near":500,"artist3.near":500,"artist4.near":500,"artist5.near":500}}}"#
+        );
+
+        let payout = buy(&mut contract, 1_000_000, 7).payout;
+        assert_eq!(payout.len(), 7);
+        assert_eq!(payout[&alice().to_string()].0, 700_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "The payout has 7 receivers which is more than max_len_payout 6")]
+    fn six_recipients_and_owner_fail_max_len_payout_6() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.update_token_royalties("catchcon.gold".to_string(), six_recipients());
+
+        buy(&mut contract, 1_000_000, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token metadata is frozen")]
+    fn update_frozen_token_royalties_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.freeze_token_metadata("catchcon.gold".to_string());
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.update_token_royalties("catchcon.gold".to_string(), HashMap::new());
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::too_many_arguments)]
+mod royalty_simulation {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::simulation::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::serde_json::{self, Value};
+
+    const TOKEN_ID: &str = "catchcon.gold.alice.near";
+    const PRICE: Balance = 1_000_000_000_000_000_000_000_000;
+    const GAS_FOR_NFT_TRANSFER_PAYOUT: Gas = 50_000_000_000_000;
+    const GAS_FOR_RESOLVE_PURCHASE: Gas = 50_000_000_000_000;
+    const GAS_FOR_PAYOUT_TRANSFER: Gas = 5_000_000_000_000;
+
+    #[ext_contract(ext_nft_payout)]
+    trait NftPayout {
+        fn nft_transfer_payout(
+            &mut self,
+            receiver_id: AccountId,
+            token_id: TokenId,
+            approval_id: Option<u64>,
+            balance: U128,
+            memo: Option<String>,
+            max_len_payout: u32,
+            affiliate_id: Option<AccountId>,
+        ) -> Payout;
+    }
+
+    #[ext_contract(ext_sale_self)]
+    trait SaleSelf {
+        fn resolve_purchase(&mut self, token_id: TokenId, buyer_id: AccountId, price: U128);
+    }
+
+    #[derive(Deserialize)]
+    #[serde(crate = "near_sdk::serde")]
+    struct SaleTerms {
+        price: U128,
+    }
+
+    /// Marketplace which lists the tokens it is approved for at the price in the approval msg and
+    /// settles sales with nft_transfer_payout. Payees it can't pay with the gas left are owed
+    /// their share, which they withdraw later
+    #[derive(BorshDeserialize, BorshSerialize)]
+    struct SaleMarketplace {
+        nft_id: AccountId,
+        //approval id and price of the listed tokens
+        listings: HashMap<TokenId, (u64, Balance)>,
+        owed: HashMap<AccountId, Balance>,
+        gas_for_resolve_purchase: Gas,
+    }
+
+    impl SaleMarketplace {
+        fn new(nft_id: ValidAccountId) -> Self {
+            Self {
+                nft_id: nft_id.into(),
+                listings: HashMap::new(),
+                owed: HashMap::new(),
+                gas_for_resolve_purchase: GAS_FOR_RESOLVE_PURCHASE,
+            }
+        }
+
+        fn nft_on_approve(&mut self, token_id: TokenId, approval_id: u64, msg: String) {
+            require!(
+                env::predecessor_account_id() == self.nft_id,
+                "Only the NFT contract lists tokens"
+            );
+            let terms: SaleTerms =
+                serde_json::from_str(&msg).unwrap_or_else(|_| env::panic(b"Invalid sale terms"));
+            self.listings.insert(token_id, (approval_id, terms.price.0));
+        }
+
+        fn buy(&mut self, token_id: TokenId) -> Promise {
+            let (approval_id, price) = self
+                .listings
+                .remove(&token_id)
+                .unwrap_or_else(|| env::panic(b"Token isn't listed"));
+            require!(
+                env::attached_deposit() == price,
+                "Attached deposit must be the price"
+            );
+
+            ext_nft_payout::nft_transfer_payout(
+                env::predecessor_account_id(),
+                token_id.clone(),
+                Some(approval_id),
+                price.into(),
+                None,
+                7,
+                None,
+                &self.nft_id,
+                1,
+                GAS_FOR_NFT_TRANSFER_PAYOUT,
+            )
+            .then(ext_sale_self::resolve_purchase(
+                token_id,
+                env::predecessor_account_id(),
+                price.into(),
+                &env::current_account_id(),
+                0,
+                self.gas_for_resolve_purchase,
+            ))
+        }
+
+        //pays the payees in the order of their ids while the gas lasts, the rest is owed to them.
+        //The buyer gets the price back if the NFT contract refused the sale
+        fn resolve_purchase(&mut self, token_id: TokenId, buyer_id: AccountId, price: U128) {
+            let payout = match env::promise_result(0) {
+                PromiseResult::Successful(value) => {
+                    serde_json::from_slice::<Payout>(&value).unwrap().payout
+                }
+                _ => {
+                    env::log(
+                        format!("Sale of {} failed, refunding the buyer", token_id).as_bytes(),
+                    );
+                    Promise::new(buyer_id).transfer(price.0);
+                    return;
+                }
+            };
+
+            let mut payees: Vec<(AccountId, U128)> = payout.into_iter().collect();
+            payees.sort_by(|a, b| a.0.cmp(&b.0));
+            // every transfer is budgeted GAS_FOR_PAYOUT_TRANSFER of the gas left
+            let mut remaining_gas = env::prepaid_gas().saturating_sub(env::used_gas());
+            for (account_id, amount) in payees {
+                if remaining_gas < GAS_FOR_PAYOUT_TRANSFER {
+                    *self.owed.entry(account_id).or_default() += amount.0;
+                    continue;
+                }
+                remaining_gas -= GAS_FOR_PAYOUT_TRANSFER;
+                Promise::new(account_id).transfer(amount.0);
+            }
+        }
+
+        fn withdraw_owed(&mut self) -> Promise {
+            let account_id = env::predecessor_account_id();
+            let amount = self
+                .owed
+                .remove(&account_id)
+                .unwrap_or_else(|| env::panic(b"Nothing is owed"));
+
+            Promise::new(account_id).transfer(amount)
+        }
+    }
+
+    impl Runnable for SaleMarketplace {
+        fn run(&mut self, method: &str, args: &Value) -> Outcome {
+            match method {
+                "nft_on_approve" => {
+                    self.nft_on_approve(
+                        arg(args, "token_id"),
+                        arg(args, "approval_id"),
+                        arg(args, "msg"),
+                    );
+                    Outcome::Value(vec![])
+                }
+                "resolve_purchase" => {
+                    self.resolve_purchase(
+                        arg(args, "token_id"),
+                        arg(args, "buyer_id"),
+                        arg(args, "price"),
+                    );
+                    Outcome::Value(vec![])
+                }
+                _ => {
+                    env::panic(format!("{} is not a method of the marketplace", method).as_bytes())
+                }
+            }
+        }
+    }
+
+    fn charity() -> ValidAccountId {
+        ValidAccountId::try_from("charity.near").unwrap()
+    }
+
+    /// carol organises catchcon.gold with 10% royalty for herself and 5% for charity, alice
+    /// holds a copy and a storage balance, the marketplace is Catch approved
+    fn setup() -> Chain {
+        let mut chain = Chain::default();
+
+        chain.deploy(nft(), create_contract);
+        chain.deploy(marketplace(), || SaleMarketplace::new(nft()));
+        chain.call(nft(), nft(), 0, |nft: &mut Contract| {
+            nft.approve_marketplaces(vec![marketplace()])
+        });
+        chain.call(carol(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.organise_event(
+                "catchcon".to_string(),
+                vec![TokenInfo {
+                    token_id: "gold".to_string(),
+                    token_metadata: token_metadata(10),
+                    token_dependency_by_id: vec![],
+                    event_dependency_by_id: vec![],
+                    external_dependencies: vec![],
+                    royalty: HashMap::from([
+                        (carol().to_string(), 1_000),
+                        (charity().to_string(), 500),
+                    ]),
+                    soulbound: false,
+                }],
+                None,
+            );
+        });
+        // the storage of the approval goes back to her storage balance on the sale
+        chain.call(alice(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.storage_deposit(None);
+            nft.nft_event_register(alice().to_string(), "catchcon.gold".to_string())
+        });
+
+        chain
+    }
+
+    fn list(chain: &mut Chain) {
+        chain.call(alice(), nft(), STORAGE_DEPOSIT, |nft: &mut Contract| {
+            nft.nft_approve(
+                TOKEN_ID.to_string(),
+                marketplace().to_string(),
+                Some(format!(r#"{{"price":"{}"}}"#, PRICE)),
+            );
+        });
+    }
+
+    fn buy(chain: &mut Chain) {
+        chain.call(
+            bob(),
+            marketplace(),
+            PRICE,
+            |marketplace: &mut SaleMarketplace| {
+                marketplace.buy(TOKEN_ID.to_string());
+            },
+        );
+    }
+
+    //NEAR received by the A/c since the snapshot of chain.received
+    fn received(
+        chain: &Chain,
+        before: &HashMap<AccountId, Balance>,
+        account_id: ValidAccountId,
+    ) -> Balance {
+        let received = |received: &HashMap<AccountId, Balance>| {
+            received.get(account_id.as_ref()).copied().unwrap_or(0)
+        };
+        received(&chain.received) - received(before)
+    }
+
+    fn owed(chain: &mut Chain, account_id: ValidAccountId) -> Option<Balance> {
+        chain.view(marketplace(), |marketplace: &SaleMarketplace| {
+            marketplace.owed.get(account_id.as_ref()).copied()
+        })
+    }
+
+    #[test]
+    fn approval_lists_the_token() {
+        let mut chain = setup();
+
+        list(&mut chain);
+
+        assert!(chain.receipt("nft_on_approve").error.is_none());
+        assert_eq!(
+            chain.view(marketplace(), |marketplace: &SaleMarketplace| {
+                marketplace.listings.get(TOKEN_ID).copied()
+            }),
+            Some((0, PRICE))
+        );
+    }
+
+    #[test]
+    fn sale_pays_royalties_and_the_seller() {
+        let mut chain = setup();
+        list(&mut chain);
+
+        let before = chain.received.clone();
+        buy(&mut chain);
+
+        assert!(chain.receipt("nft_transfer_payout").error.is_none());
+        assert!(chain.receipt("resolve_purchase").error.is_none());
+        assert_eq!(received(&chain, &before, carol()), PRICE / 10);
+        assert_eq!(received(&chain, &before, charity()), PRICE / 20);
+        assert_eq!(received(&chain, &before, alice()), PRICE * 85 / 100);
+        assert_eq!(received(&chain, &before, bob()), 0);
+        assert_eq!(
+            chain.view(nft(), |nft: &Contract| {
+                nft.nft_token("catchcon.gold.bob.near".to_string())
+                    .map(|token| token.owner_id)
+            }),
+            Some(bob().into())
+        );
+        assert!(chain
+            .logs
+            .iter()
+            .any(|log| log.contains(r#""event":"nft_royalty_payout""#)));
+    }
+
+    #[test]
+    fn refused_sale_refunds_the_buyer() {
+        let mut chain = setup();
+        list(&mut chain);
+
+        chain.call(alice(), nft(), 1, |nft: &mut Contract| {
+            nft.nft_revoke(TOKEN_ID.to_string(), marketplace().to_string())
+        });
+        let before = chain.received.clone();
+        buy(&mut chain);
+
+        assert!(chain
+            .receipt("nft_transfer_payout")
+            .error
+            .as_ref()
+            .unwrap()
+            .contains("Unauthorised"));
+        assert_eq!(received(&chain, &before, bob()), PRICE);
+        assert_eq!(received(&chain, &before, alice()), 0);
+        assert_eq!(
+            chain.view(nft(), |nft: &Contract| nft
+                .nft_supply_for_owner(alice().into())),
+            U128(1)
+        );
+    }
+
+    #[test]
+    fn payees_left_without_gas_are_paid_on_withdrawal() {
+        let mut chain = setup();
+        list(&mut chain);
+
+        // enough gas for two of the three payees
+        chain.call(
+            marketplace(),
+            marketplace(),
+            0,
+            |marketplace: &mut SaleMarketplace| {
+                marketplace.gas_for_resolve_purchase = GAS_FOR_PAYOUT_TRANSFER * 5 / 2;
+            },
+        );
+        let before = chain.received.clone();
+        buy(&mut chain);
+
+        assert_eq!(received(&chain, &before, alice()), PRICE * 85 / 100);
+        assert_eq!(received(&chain, &before, carol()), PRICE / 10);
+        assert_eq!(received(&chain, &before, charity()), 0);
+        assert_eq!(owed(&mut chain, charity()), Some(PRICE / 20));
+
+        chain.call(
+            charity(),
+            marketplace(),
+            0,
+            |marketplace: &mut SaleMarketplace| {
+                marketplace.withdraw_owed();
+            },
+        );
+
+        assert_eq!(received(&chain, &before, charity()), PRICE / 20);
+        assert_eq!(owed(&mut chain, charity()), None);
+    }
+}
@@ -0,0 +1,304 @@
+use crate::*;
+
+pub type SeriesId = String;
+
+/// Drop whose tokens share one metadata, tokens are minted as series_id.index
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Series {
+    pub metadata: TokenMetadata,
+    pub max_supply: u64,
+    //tokens minted so far, burnt ones included, the next token gets index minted + 1
+    pub minted: u64,
+    //royalty override of the series, merged over the default royalty of the contract
+    pub royalty: Royalty,
+}
+
+impl Contract {
+    //series of a token class of form series_id.index
+    pub(crate) fn internal_series_of(&self, token_id: &TokenId) -> Option<(SeriesId, Series)> {
+        let (series_id, index) = token_id.split_once('.')?;
+        index.parse::<u64>().ok()?;
+
+        self.series_by_id
+            .get(&series_id.to_string())
+            .map(|series| (series_id.to_string(), series))
+    }
+
+    //metadata of the token class, tokens of a series read the metadata of the series
+    pub(crate) fn internal_token_metadata(&self, token_id: &TokenId) -> Option<TokenMetadata> {
+        self.token_metadata_by_id.get(token_id).or_else(|| {
+            self.internal_series_of(token_id)
+                .map(|(_, series)| series.metadata)
+        })
+    }
+
+    //forgets a token of a series which was burnt or whose mint was rolled back
+    pub(crate) fn internal_remove_from_series(&mut self, token_id: &TokenId) {
+        let series_id = match self.internal_series_of(token_id) {
+            Some((series_id, _)) => series_id,
+            None => return,
+        };
+
+        if let Some(mut tokens_set) = self.tokens_per_series.get(&series_id) {
+            tokens_set.remove(token_id);
+            self.tokens_per_series.insert(&series_id, &tokens_set);
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, defines the metadata of a drop once, tokens are minted against it with
+    /// nft_mint_from_series
+    pub fn create_series(
+        &mut self,
+        series_id: SeriesId,
+        metadata: TokenMetadata,
+        max_supply: u64,
+        royalty: Option<Royalty>,
+    ) {
+        self.assert_owner();
+
+        assert_valid_id(&series_id);
+        metadata.assert_valid();
+        require!(max_supply > 0, "max_supply must be positive");
+        require!(
+            self.series_by_id.get(&series_id).is_none(),
+            "Series already exists"
+        );
+        require!(
+            self.events_by_id.get(&series_id).is_none(),
+            "An event with the same id exists"
+        );
+
+        let royalty = royalty.unwrap_or_default();
+        assert_valid_royalty(&royalty);
+
+        self.series_by_id.insert(
+            &series_id,
+            &Series {
+                metadata,
+                max_supply,
+                minted: 0,
+                royalty,
+            },
+        );
+
+        self.tokens_per_series.insert(
+            &series_id,
+            &UnorderedSet::new(
+                StorageKey::TokensPerSeriesInner {
+                    series_id_hash: hash_id(&series_id),
+                }
+                .try_to_vec()
+                .unwrap(),
+            ),
+        );
+    }
+
+    /// Owner only, possible once no token of the series exists
+    pub fn delete_series(&mut self, series_id: SeriesId) {
+        self.assert_owner();
+
+        let tokens_set = self
+            .tokens_per_series
+            .get(&series_id)
+            .unwrap_or_else(|| env::panic(b"Invalid series id"));
+        require!(
+            tokens_set.is_empty(),
+            format!("{} tokens of the series exist", tokens_set.len())
+        );
+
+        self.tokens_per_series.remove(&series_id);
+        self.series_by_id.remove(&series_id);
+    }
+
+    /// Owner or minter only, creates the account of the receiver and mints the next token of the
+    /// series to it as series_id.index, like nft_mint the token is pending until the account is
+    /// created
+    #[payable]
+    pub fn nft_mint_from_series(
+        &mut self,
+        series_id: SeriesId,
+        receiver_id: ValidAccountId,
+        public_key: Base58PublicKey,
+    ) -> Promise {
+        self.assert_minter();
+        self.assert_not_paused(PausableFeature::Mint);
+
+        let initial_storage = env::storage_usage();
+
+        let mut series = self
+            .series_by_id
+            .get(&series_id)
+            .unwrap_or_else(|| env::panic(b"Invalid series id"));
+        require!(
+            series.minted < series.max_supply,
+            "All the tokens of the series have been minted"
+        );
+
+        series.minted += 1;
+        let token_id = format!("{}.{}", &series_id, series.minted);
+        assert_valid_token_id(&token_id);
+        self.internal_count_mint();
+
+        let token = Token {
+            token_id: token_id.clone(),
+            copies_minted: 1,
+            max_copies: 1,
+            expires_at: series.metadata.expires_at,
+            token_dependency_by_id: vec![],
+            event_dependency_by_id: vec![],
+            external_dependencies: vec![],
+            royalty: self.internal_merge_royalty(&Default::default(), series.royalty.clone()),
+            metadata_frozen: false,
+            soulbound: false,
+        };
+
+        require!(
+            self.tokens_by_id.insert(&token_id, &token).is_none(),
+            "Token Already exists"
+        );
+
+        self.series_by_id.insert(&series_id, &series);
+
+        let mut tokens_set = self.tokens_per_series.get(&series_id).unwrap();
+        tokens_set.insert(&token_id);
+        self.tokens_per_series.insert(&series_id, &tokens_set);
+
+        let storage_used = env::storage_usage() - initial_storage
+            + bytes_for_token_or_event_or_account_id(&token_id);
+        let cost =
+            env::storage_byte_cost() * Balance::from(storage_used) + self.account_funding_amount;
+
+        assert_deposit_covers(cost);
+
+        self.internal_start_mint(
+            receiver_id.into(),
+            token_id,
+            public_key,
+            env::attached_deposit(),
+            cost,
+        )
+    }
+}
+
+#[cfg(test)]
+mod series_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    const SERIES: &str = "season1";
+
+    fn setup(max_supply: u64) -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+        contract.create_series(SERIES.to_string(), token_metadata(1), max_supply, None);
+        contract
+    }
+
+    fn mint(contract: &mut Contract, receiver_id: ValidAccountId) {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.nft_mint_from_series(
+            SERIES.to_string(),
+            receiver_id,
+            Base58PublicKey::try_from("ed25519:DVFW2W92JjTKTB4isyCTtGSSB1jNZ9Pq2LNbkNzRxMFN")
+                .unwrap(),
+        );
+    }
+
+    fn resolve(contract: &mut Contract, token_id: &str, result: PromiseResult) {
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![result]
+        );
+        contract.resolve_mint(token_id.to_string());
+    }
+
+    #[test]
+    fn tokens_share_the_series_metadata() {
+        let mut contract = setup(10);
+
+        mint(&mut contract, alice());
+        resolve(
+            &mut contract,
+            "season1.1",
+            PromiseResult::Successful(vec![]),
+        );
+        mint(&mut contract, bob());
+        resolve(
+            &mut contract,
+            "season1.2",
+            PromiseResult::Successful(vec![]),
+        );
+
+        let token = contract
+            .nft_token("season1.2.bob.near".to_string())
+            .unwrap();
+        assert_eq!(token.metadata.title, "Catch Pass");
+        assert!(contract
+            .token_metadata_by_id
+            .get(&"season1.2".to_string())
+            .is_none());
+
+        assert_eq!(contract.series_supply(SERIES.to_string()).0, 2);
+        assert_eq!(contract.nft_total_supply().0, 2);
+        let tokens: Vec<TokenId> = contract
+            .nft_tokens_for_series(SERIES.to_string(), Some(U128(1)), None)
+            .into_iter()
+            .map(|token| token.token_id)
+            .collect();
+        assert_eq!(tokens, vec!["season1.2".to_string()]);
+    }
+
+    #[test]
+    fn failed_mint_leaves_the_series() {
+        let mut contract = setup(10);
+
+        mint(&mut contract, alice());
+        resolve(&mut contract, "season1.1", PromiseResult::Failed);
+
+        assert_eq!(contract.series_supply(SERIES.to_string()).0, 0);
+        assert_eq!(contract.get_series(SERIES.to_string()).unwrap().minted, 1);
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.delete_series(SERIES.to_string());
+        assert!(contract.get_series(SERIES.to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "All the tokens of the series have been minted")]
+    fn mint_beyond_max_supply_fails() {
+        let mut contract = setup(1);
+
+        mint(&mut contract, alice());
+        mint(&mut contract, bob());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 tokens of the series exist")]
+    fn delete_series_with_tokens_fails() {
+        let mut contract = setup(10);
+
+        mint(&mut contract, alice());
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.delete_series(SERIES.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn create_series_by_other_account_fails() {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), 0));
+        contract.create_series(SERIES.to_string(), token_metadata(1), 10, None);
+    }
+}
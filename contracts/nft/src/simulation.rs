@@ -0,0 +1,474 @@
+//! Runs the NFT contract together with other contracts and user A/c's on the mocked blockchain.
+//!
+//! Every A/c has its own storage, the state of a contract is kept under STATE like on chain and
+//! the receipts created by a call are executed on their receivers with the results of the receipts
+//! they depend on. A receipt which panics leaves the storage of its receiver untouched
+use crate::utils::test_utils::*;
+use crate::*;
+use near_sdk::serde::de::DeserializeOwned;
+use near_sdk::serde_json::{self, Value};
+use near_sdk::{MockedBlockchain, VMContext};
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+
+type Storage = HashMap<Vec<u8>, Vec<u8>>;
+
+const TX_GAS: Gas = 300_000_000_000_000;
+
+/// What a method leaves as the result of its receipt
+pub enum Outcome {
+    Value(Vec<u8>),
+    //the result is the one of the last receipt created by the method
+    Promise,
+}
+
+impl Outcome {
+    pub fn json<T: Serialize>(value: &T) -> Self {
+        Outcome::Value(serde_json::to_vec(value).unwrap())
+    }
+
+    pub fn from_promise_or_value<T: Serialize>(result: PromiseOrValue<T>) -> Self {
+        match result {
+            PromiseOrValue::Value(value) => Outcome::json(&value),
+            PromiseOrValue::Promise(_) => Outcome::Promise,
+        }
+    }
+}
+
+/// A contract the chain can run receipts on, methods are called by name with their JSON args
+pub trait Runnable: BorshSerialize + BorshDeserialize {
+    fn run(&mut self, method: &str, args: &Value) -> Outcome;
+}
+
+pub fn arg<T: DeserializeOwned>(args: &Value, name: &str) -> T {
+    serde_json::from_value(args.get(name).cloned().unwrap_or(Value::Null))
+        .unwrap_or_else(|_| env::panic(format!("Invalid argument {}", name).as_bytes()))
+}
+
+fn run_state<T: Runnable>(method: &str, args: &Value) -> Outcome {
+    let mut contract: T = env::state_read().unwrap();
+    let outcome = contract.run(method, args);
+    env::state_write(&contract);
+    outcome
+}
+
+impl Runnable for Contract {
+    fn run(&mut self, method: &str, args: &Value) -> Outcome {
+        match method {
+            "ft_on_transfer" => Outcome::from_promise_or_value(self.ft_on_transfer(
+                arg(args, "sender_id"),
+                arg(args, "amount"),
+                arg(args, "msg"),
+            )),
+            "resolve_player_ft_mint" => {
+                Outcome::json(&self.resolve_player_ft_mint(arg(args, "mint")))
+            }
+            "resolve_ft_withdraw" => {
+                self.resolve_ft_withdraw(arg(args, "event_id"), arg(args, "amount"));
+                Outcome::Value(vec![])
+            }
+            "claim_event_refund" => {
+                self.claim_event_refund(arg(args, "event_id"));
+                Outcome::Promise
+            }
+            "resolve_ft_refund" => {
+                self.resolve_ft_refund(
+                    arg(args, "event_id"),
+                    arg(args, "account_id"),
+                    arg(args, "amount"),
+                );
+                Outcome::Value(vec![])
+            }
+            "marketplace_create_lease" => Outcome::json(&self.marketplace_create_lease(
+                arg(args, "token_id"),
+                arg(args, "borrower_id"),
+                arg(args, "duration_sec"),
+                arg(args, "approval_id"),
+            )),
+            "resolve_lock" => {
+                Outcome::json(&self.resolve_lock(arg(args, "token_id"), arg(args, "locker_id")))
+            }
+            "nft_unlock" => {
+                self.nft_unlock(arg(args, "token_id"));
+                Outcome::Value(vec![])
+            }
+            "nft_transfer_payout" => Outcome::json(&self.nft_transfer_payout(
+                arg(args, "receiver_id"),
+                arg(args, "token_id"),
+                arg(args, "approval_id"),
+                arg(args, "balance"),
+                arg(args, "memo"),
+                arg(args, "max_len_payout"),
+                arg(args, "affiliate_id"),
+            )),
+            _ => env::panic(format!("{} is not a method of the NFT contract", method).as_bytes()),
+        }
+    }
+}
+
+impl Runnable for ft::Contract {
+    fn run(&mut self, method: &str, args: &Value) -> Outcome {
+        match method {
+            "ft_transfer" => {
+                self.ft_transfer(
+                    arg(args, "receiver_id"),
+                    arg(args, "amount"),
+                    arg(args, "memo"),
+                );
+                Outcome::Value(vec![])
+            }
+            "ft_resolve_transfer" => Outcome::json(&self.ft_resolve_transfer(
+                arg(args, "sender_id"),
+                arg(args, "receiver_id"),
+                arg(args, "amount"),
+            )),
+            "nft_on_lock" => Outcome::from_promise_or_value(self.nft_on_lock(
+                arg(args, "owner_id"),
+                arg(args, "token_id"),
+                arg(args, "msg"),
+            )),
+            "resolve_unstake" => {
+                self.resolve_unstake(arg(args, "token_id"), arg(args, "rewards"));
+                Outcome::Value(vec![])
+            }
+            _ => env::panic(format!("{} is not a method of the FT contract", method).as_bytes()),
+        }
+    }
+}
+
+/// A receipt which ran on the chain
+pub struct ExecutedReceipt {
+    pub method_name: String,
+    //the panic message if the receipt failed
+    pub error: Option<String>,
+}
+
+//the receipts of the mocked blockchain, read back from their JSON as their fields are private
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct CreatedReceipt {
+    receipt_indices: Vec<usize>,
+    receiver_id: AccountId,
+    actions: Vec<Action>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+enum Action {
+    FunctionCall {
+        method_name: String,
+        args: String,
+        gas: Gas,
+        deposit: Balance,
+    },
+    Transfer {
+        deposit: Balance,
+    },
+}
+
+struct PendingReceipt {
+    id: usize,
+    predecessor_id: AccountId,
+    receiver_id: AccountId,
+    depends_on: Vec<usize>,
+    actions: Vec<Action>,
+}
+
+//None if the receipt failed
+type ReceiptValue = Option<Vec<u8>>;
+
+enum ReceiptResult {
+    Done(ReceiptValue),
+    Forwarded(usize),
+}
+
+fn promise_result(value: &ReceiptValue) -> PromiseResult {
+    match value {
+        Some(value) => PromiseResult::Successful(value.clone()),
+        None => PromiseResult::Failed,
+    }
+}
+
+#[derive(Default)]
+pub struct Chain {
+    storage: HashMap<AccountId, Storage>,
+    storage_usage: HashMap<AccountId, u64>,
+    runners: HashMap<AccountId, fn(&str, &Value) -> Outcome>,
+    pub block_timestamp: u64,
+    //NEAR received with transfer actions
+    pub received: HashMap<AccountId, Balance>,
+    pub logs: Vec<String>,
+    //receipts executed by the last transaction after the call itself
+    pub receipts: Vec<ExecutedReceipt>,
+}
+
+impl Chain {
+    /// Initializes the contract on the A/c, init runs with the A/c as predecessor
+    pub fn deploy<T: Runnable + 'static>(
+        &mut self,
+        account_id: ValidAccountId,
+        init: impl FnOnce() -> T,
+    ) {
+        let account_id: AccountId = account_id.into();
+        self.runners.insert(account_id.clone(), run_state::<T>);
+        self.execute(
+            &account_id,
+            &account_id,
+            &account_id,
+            0,
+            TX_GAS,
+            vec![],
+            || {
+                env::state_write(&init());
+            },
+        )
+        .unwrap();
+    }
+
+    /// Calls the contract on receiver_id as signer_id like a transaction and executes the
+    /// receipts created by the call, panics of the call itself are propagated. Promises are
+    /// created when they are dropped, so f mustn't return them
+    pub fn call<T: Runnable, R>(
+        &mut self,
+        signer_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        deposit: Balance,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> R {
+        let signer_id: AccountId = signer_id.into();
+        let receiver_id: AccountId = receiver_id.into();
+        self.receipts.clear();
+
+        let (result, receipts) = self
+            .execute(
+                &signer_id,
+                &signer_id,
+                &receiver_id,
+                deposit,
+                TX_GAS,
+                vec![],
+                || {
+                    let mut contract: T = env::state_read().unwrap();
+                    let result = f(&mut contract);
+                    env::state_write(&contract);
+                    result
+                },
+            )
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        self.process(&signer_id, &receiver_id, receipts);
+
+        result
+    }
+
+    /// Reads the state of the contract on account_id
+    pub fn view<T: Runnable, R>(
+        &mut self,
+        account_id: ValidAccountId,
+        f: impl FnOnce(&T) -> R,
+    ) -> R {
+        let account_id: AccountId = account_id.into();
+        self.execute(
+            &account_id,
+            &account_id,
+            &account_id,
+            0,
+            TX_GAS,
+            vec![],
+            || f(&env::state_read().unwrap()),
+        )
+        .unwrap()
+        .0
+    }
+
+    /// The executed receipt calling the method, panics if there is none
+    pub fn receipt(&self, method_name: &str) -> &ExecutedReceipt {
+        self.receipts
+            .iter()
+            .find(|receipt| receipt.method_name == method_name)
+            .unwrap_or_else(|| panic!("{} wasn't called", method_name))
+    }
+
+    //runs f in the env of receiver_id, the storage is only kept if f doesn't panic
+    #[allow(clippy::too_many_arguments)]
+    fn execute<R>(
+        &mut self,
+        signer_id: &AccountId,
+        predecessor_id: &AccountId,
+        receiver_id: &AccountId,
+        deposit: Balance,
+        prepaid_gas: Gas,
+        promise_results: Vec<PromiseResult>,
+        f: impl FnOnce() -> R,
+    ) -> Result<(R, Vec<CreatedReceipt>), String> {
+        let storage = self.storage.get(receiver_id).cloned().unwrap_or_default();
+        let context = VMContext {
+            current_account_id: receiver_id.clone(),
+            signer_account_id: signer_id.clone(),
+            predecessor_account_id: predecessor_id.clone(),
+            attached_deposit: deposit,
+            block_timestamp: self.block_timestamp,
+            prepaid_gas,
+            storage_usage: *self.storage_usage.get(receiver_id).unwrap_or(&10u64.pow(6)),
+            ..get_context(predecessor_id.clone(), deposit)
+        };
+        env::set_blockchain_interface(Box::new(MockedBlockchain::new(
+            context,
+            Default::default(),
+            Default::default(),
+            promise_results,
+            storage,
+            Default::default(),
+            None,
+        )));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let result = f();
+            (result, env::storage_usage())
+        }));
+
+        let mut blockchain_interface = env::take_blockchain_interface().unwrap();
+        let mocked_blockchain = blockchain_interface.as_mut_mocked_blockchain().unwrap();
+        let (result, storage_usage) = match result {
+            Ok(result) => result,
+            Err(error) => {
+                return Err(error
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| error.downcast_ref::<&str>().map(|error| error.to_string()))
+                    .unwrap_or_default())
+            }
+        };
+
+        self.logs.extend(mocked_blockchain.logs());
+        let receipts = mocked_blockchain
+            .created_receipts()
+            .iter()
+            .map(|receipt| serde_json::from_str(&serde_json::to_string(receipt).unwrap()).unwrap())
+            .collect();
+        self.storage
+            .insert(receiver_id.clone(), mocked_blockchain.take_storage());
+        self.storage_usage
+            .insert(receiver_id.clone(), storage_usage);
+
+        Ok((result, receipts))
+    }
+
+    //executes the receipts in the order they were created, a receipt waits for the receipts it depends on
+    fn process(
+        &mut self,
+        signer_id: &AccountId,
+        predecessor_id: &AccountId,
+        receipts: Vec<CreatedReceipt>,
+    ) {
+        let mut results: Vec<Option<ReceiptResult>> = vec![];
+        let mut queue = VecDeque::new();
+        self.enqueue(&mut queue, &mut results, predecessor_id, receipts);
+
+        while let Some(receipt) = queue.pop_front() {
+            let promise_results: Option<Vec<ReceiptValue>> = receipt
+                .depends_on
+                .iter()
+                .map(|id| Self::resolve(&results, *id))
+                .collect();
+            let promise_results = match promise_results {
+                Some(promise_results) => promise_results,
+                None => {
+                    queue.push_back(receipt);
+                    continue;
+                }
+            };
+
+            let mut result = ReceiptResult::Done(Some(vec![]));
+            for action in receipt.actions {
+                let (method_name, args, gas, deposit) = match action {
+                    Action::Transfer { deposit } => {
+                        *self
+                            .received
+                            .entry(receipt.receiver_id.clone())
+                            .or_default() += deposit;
+                        continue;
+                    }
+                    Action::FunctionCall {
+                        method_name,
+                        args,
+                        gas,
+                        deposit,
+                    } => (method_name, args, gas, deposit),
+                };
+
+                let runner = self.runners.get(&receipt.receiver_id).copied();
+                let executed = match runner {
+                    Some(runner) => self.execute(
+                        signer_id,
+                        &receipt.predecessor_id,
+                        &receipt.receiver_id,
+                        deposit,
+                        gas,
+                        promise_results.iter().map(promise_result).collect(),
+                        || {
+                            let args: Value = serde_json::from_str(&args).unwrap_or(Value::Null);
+                            runner(&method_name, &args)
+                        },
+                    ),
+                    None => Err(format!("{} has no contract", &receipt.receiver_id)),
+                };
+
+                self.receipts.push(ExecutedReceipt {
+                    method_name,
+                    error: executed.as_ref().err().cloned(),
+                });
+
+                result = match executed {
+                    Ok((outcome, receipts)) => {
+                        let first_id = results.len();
+                        let created = receipts.len();
+                        self.enqueue(&mut queue, &mut results, &receipt.receiver_id, receipts);
+                        match outcome {
+                            Outcome::Promise if created > 0 => {
+                                ReceiptResult::Forwarded(first_id + created - 1)
+                            }
+                            Outcome::Promise => ReceiptResult::Done(Some(vec![])),
+                            Outcome::Value(value) => ReceiptResult::Done(Some(value)),
+                        }
+                    }
+                    Err(_) => ReceiptResult::Done(None),
+                };
+            }
+
+            results[receipt.id] = Some(result);
+        }
+    }
+
+    //adds the receipts created by predecessor_id, indices in receipt_indices are relative to them
+    fn enqueue(
+        &self,
+        queue: &mut VecDeque<PendingReceipt>,
+        results: &mut Vec<Option<ReceiptResult>>,
+        predecessor_id: &AccountId,
+        receipts: Vec<CreatedReceipt>,
+    ) {
+        let first_id = results.len();
+        for receipt in receipts {
+            queue.push_back(PendingReceipt {
+                id: results.len(),
+                predecessor_id: predecessor_id.clone(),
+                receiver_id: receipt.receiver_id,
+                depends_on: receipt
+                    .receipt_indices
+                    .iter()
+                    .map(|index| first_id + index)
+                    .collect(),
+                actions: receipt.actions,
+            });
+            results.push(None);
+        }
+    }
+
+    fn resolve(results: &[Option<ReceiptResult>], id: usize) -> Option<ReceiptValue> {
+        match results[id].as_ref()? {
+            ReceiptResult::Done(result) => Some(result.clone()),
+            ReceiptResult::Forwarded(id) => Self::resolve(results, *id),
+        }
+    }
+}
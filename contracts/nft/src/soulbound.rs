@@ -0,0 +1,117 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, makes the token class (event_id.token_id) transferable again, e.g. to move
+    /// the tokens of a player to a new account
+    #[payable]
+    pub fn release_soulbound(&mut self, token_id: TokenId) {
+        assert_one_yocto();
+        self.assert_owner();
+
+        let mut token = self
+            .tokens_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic(b"No token"));
+        require!(token.soulbound, "Token is not soulbound");
+
+        token.soulbound = false;
+        self.tokens_by_id.insert(&token_id, &token);
+
+        CatchEventLogVariant::SoulboundReleased(SoulboundLog { token_id }).emit();
+    }
+}
+
+#[cfg(test)]
+mod soulbound_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "kyc.verified.alice.near";
+
+    /// alice holds a soulbound pass of kyc
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        contract.organise_event(
+            "kyc".to_string(),
+            vec![TokenInfo {
+                token_id: "verified".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: true,
+            }],
+            None,
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "kyc.verified".to_string());
+
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is soulbound")]
+    fn transfer_of_soulbound_token_fails() {
+        let mut contract = setup();
+        assert!(contract.nft_token(PASS.to_string()).unwrap().soulbound);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is soulbound")]
+    fn approve_of_soulbound_token_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    fn soulbound_token_can_be_burnt() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn(PASS.to_string());
+
+        assert!(contract.nft_token(PASS.to_string()).is_none());
+    }
+
+    #[test]
+    fn released_token_can_be_transferred() {
+        let mut contract = setup();
+
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.release_soulbound("kyc.verified".to_string());
+        assert_eq!(
+            test_utils::get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"catch","version":"1.1.0","event":"soulbound_released","data":{"token_id":"kyc.verified"}}"#
+            ]
+        );
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+        assert!(contract
+            .nft_token("kyc.verified.bob.near".to_string())
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "It is a owner only method")]
+    fn release_by_other_account_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.release_soulbound("kyc.verified".to_string());
+    }
+}
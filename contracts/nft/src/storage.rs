@@ -0,0 +1,469 @@
+use crate::*;
+
+/// Bytes of the storage record of the longest possible A/c id, 40 bytes of trie overhead
+/// included, paid once on registration
+const STORAGE_REGISTRATION_BYTES: u64 = 40 + 1 + 4 + 64 + 16 + 8;
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    total: U128,
+    available: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+/// NEAR deposited for storage by an A/c and the bytes drawn from it by approvals and leases
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct AccountStorage {
+    pub total: Balance,
+    pub used: u64,
+}
+
+pub trait StorageManager {
+    /// Deposit Near for the purpose of storage costs, the deposit of a registered A/c is topped up
+    fn storage_deposit(&mut self, account_id: Option<ValidAccountId>) -> StorageBalance;
+
+    /// Wallet UX Security -> Attach 1 Yocto,
+    ///
+    /// Withdraws the deposit not used by approvals and leases, all of it if amount is None
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+
+    /// Wallet UX Security -> Attach 1 Yocto,
+    ///
+    /// Removes the A/c and refunds its deposit, not possible while the A/c owns tokens
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool;
+
+    /// Returns min and max NEAR that can be deposited for storage,
+    ///
+    /// min covers the registration, there is no max
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+
+    /// Returns Storage Balance of a given A/c, available is the deposit not used by the registration,
+    /// approvals and leases of the A/c
+    ///
+    /// None is returned for Unregistered A/c
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance>;
+}
+
+/************************************************/
+/*  IMPLEMENTING STORAGE MANAGER FUNCTIONALITY  */
+/************************************************/
+
+#[near_bindgen]
+impl StorageManager for Contract {
+    #[payable]
+    fn storage_deposit(&mut self, account_id: Option<ValidAccountId>) -> StorageBalance {
+        let amount: Balance = env::attached_deposit();
+
+        let account_id: AccountId = match account_id {
+            Some(acc_id) => acc_id.into(),
+            None => env::predecessor_account_id(),
+        };
+
+        let mut account_storage = self.storage_deposits.get(&account_id).unwrap_or_else(|| {
+            let min_balance = self.storage_balance_bounds().min.0;
+            require!(
+                amount >= min_balance,
+                format!("Please Attach a deposit of {} Yocto Near", min_balance)
+            );
+
+            AccountStorage { total: 0, used: 0 }
+        });
+
+        account_storage.total += amount;
+        self.storage_deposits.insert(&account_id, &account_storage);
+
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+
+        let account_id = env::predecessor_account_id();
+        let storage_balance = self
+            .internal_storage_balance_of(&account_id)
+            .unwrap_or_else(|| env::panic(b"The account is not registered"));
+        let available = storage_balance.available.0;
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+
+        require!(
+            amount <= available,
+            "The amount is greater than the available storage balance"
+        );
+
+        if amount > 0 {
+            let mut account_storage = self.storage_deposits.get(&account_id).unwrap();
+            account_storage.total -= amount;
+            self.storage_deposits.insert(&account_id, &account_storage);
+
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+
+        self.internal_storage_balance_of(&account_id).unwrap()
+    }
+
+    /// approvals and leases only exist on owned tokens, so once the A/c owns no tokens
+    /// nothing draws from its deposit anymore and force makes no difference
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let _ = force;
+
+        let account_id = env::predecessor_account_id();
+
+        require!(
+            self.internal_token_count(&account_id) == 0,
+            "Can't unregister the account while it owns tokens"
+        );
+
+        match self.storage_deposits.remove(&account_id) {
+            Some(account_storage) => {
+                Promise::new(account_id).transfer(account_storage.total + 1);
+                true
+            }
+            None => {
+                env::log(format!("The account {} is not registered", &account_id).as_bytes());
+                false
+            }
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: (Balance::from(STORAGE_REGISTRATION_BYTES) * env::storage_byte_cost()).into(),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.internal_storage_balance_of(account_id.as_ref())
+    }
+}
+
+/*********************************************/
+/*  INTERNAL FUNCTIONS - STORAGE MANAGEMENT  */
+/*********************************************/
+
+impl Contract {
+    pub(crate) fn internal_storage_balance_of(
+        &self,
+        account_id: &AccountId,
+    ) -> Option<StorageBalance> {
+        self.storage_deposits
+            .get(account_id)
+            .map(|account_storage| {
+                let locked = Balance::from(STORAGE_REGISTRATION_BYTES + account_storage.used)
+                    * env::storage_byte_cost();
+
+                StorageBalance {
+                    total: account_storage.total.into(),
+                    available: account_storage.total.saturating_sub(locked).into(),
+                }
+            })
+    }
+
+    /// Pays the storage used by the predecessor, registered A/c's draw from their storage balance
+    /// topped up by the attached deposit, others pay with the attached deposit as before
+    pub(crate) fn internal_pay_storage(&mut self, storage_used: u64) {
+        let account_id = env::predecessor_account_id();
+
+        let mut account_storage = match self.storage_deposits.get(&account_id) {
+            Some(account_storage) => account_storage,
+            None => return refund_deposit(storage_used),
+        };
+
+        account_storage.total += env::attached_deposit();
+        account_storage.used += storage_used;
+
+        let locked = Balance::from(STORAGE_REGISTRATION_BYTES + account_storage.used)
+            * env::storage_byte_cost();
+        require!(
+            account_storage.total >= locked,
+            format!(
+                "Not enough storage balance, deposit {} yoctoNEAR with storage_deposit",
+                locked - account_storage.total
+            )
+        );
+
+        self.storage_deposits.insert(&account_id, &account_storage);
+    }
+
+    /// Adds storage the A/c uses without being the caller, like a lease created by a marketplace,
+    /// to the storage the A/c pays for. Released with internal_release_storage like the storage
+    /// paid with internal_pay_storage, panics if the storage balance of the A/c doesn't cover it
+    pub(crate) fn internal_use_storage_balance(
+        &mut self,
+        account_id: &AccountId,
+        storage_used: u64,
+    ) {
+        if storage_used == 0 {
+            return;
+        }
+
+        let mut account_storage = self.storage_deposits.get(account_id).unwrap_or_else(|| {
+            env::panic(format!("{} must register with storage_deposit", account_id).as_bytes())
+        });
+        account_storage.used += storage_used;
+
+        let locked = Balance::from(STORAGE_REGISTRATION_BYTES + account_storage.used)
+            * env::storage_byte_cost();
+        require!(
+            account_storage.total >= locked,
+            format!(
+                "{} must cover {} yoctoNEAR of storage with storage_deposit",
+                account_id,
+                locked - account_storage.total
+            )
+        );
+
+        self.storage_deposits.insert(account_id, &account_storage);
+    }
+
+    /// Spends the storage balance of the A/c on storage which can't be paid with a deposit,
+    /// like mints paid with the payment token, panics if the available balance doesn't cover it
+    pub(crate) fn internal_spend_storage_balance(
+        &mut self,
+        account_id: &AccountId,
+        storage_used: u64,
+    ) -> Balance {
+        let cost = Balance::from(storage_used) * env::storage_byte_cost();
+        if cost == 0 {
+            return 0;
+        }
+
+        let available = self
+            .internal_storage_balance_of(account_id)
+            .map_or(0, |balance| balance.available.0);
+        require!(
+            available >= cost,
+            format!(
+                "{} must cover {} yoctoNEAR of storage with storage_deposit",
+                account_id, cost
+            )
+        );
+
+        let mut account_storage = self.storage_deposits.get(account_id).unwrap();
+        account_storage.total -= cost;
+        self.storage_deposits.insert(account_id, &account_storage);
+
+        cost
+    }
+
+    /// Gives back storage balance spent on a mint which was rolled back, it is transferred if
+    /// the A/c unregistered meanwhile
+    pub(crate) fn internal_return_storage_balance(
+        &mut self,
+        account_id: AccountId,
+        amount: Balance,
+    ) {
+        if amount == 0 {
+            return;
+        }
+
+        match self.storage_deposits.get(&account_id) {
+            Some(mut account_storage) => {
+                account_storage.total += amount;
+                self.storage_deposits.insert(&account_id, &account_storage);
+            }
+            None => {
+                Promise::new(account_id).transfer(amount);
+            }
+        }
+    }
+
+    /// Releases the storage of revoked approvals to the storage balance of the A/c,
+    /// storage which wasn't drawn from the balance is refunded
+    pub(crate) fn internal_release_storage(
+        &mut self,
+        account_id: AccountId,
+        storage_released: u64,
+    ) {
+        let mut refunded = storage_released;
+
+        if let Some(mut account_storage) = self.storage_deposits.get(&account_id) {
+            let credited = std::cmp::min(account_storage.used, storage_released);
+            account_storage.used -= credited;
+            self.storage_deposits.insert(&account_id, &account_storage);
+
+            refunded -= credited;
+        }
+
+        if refunded > 0 {
+            Promise::new(account_id).transfer(Balance::from(refunded) * env::storage_byte_cost());
+        }
+    }
+}
+
+#[cfg(test)]
+mod storage_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+
+    fn min_balance(contract: &Contract) -> Balance {
+        contract.storage_balance_bounds().min.0
+    }
+
+    /// alice holds a gold pass and deposited twice the minimum for storage
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        create_event(&mut contract, "catchcon", &["gold"], 10);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        let deposit = 2 * min_balance(&contract);
+        testing_env!(get_context(alice().to_string(), deposit));
+        contract.storage_deposit(None);
+
+        contract
+    }
+
+    fn available(contract: &Contract) -> Balance {
+        contract.storage_balance_of(alice()).unwrap().available.0
+    }
+
+    #[test]
+    fn deposit_registers_account() {
+        let contract = setup();
+
+        let min = min_balance(&contract);
+        assert_eq!(
+            contract.storage_balance_of(alice()),
+            Some(StorageBalance {
+                total: (2 * min).into(),
+                available: min.into(),
+            })
+        );
+        assert!(contract.storage_balance_of(bob()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Please Attach a deposit of")]
+    fn deposit_below_minimum_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 1));
+        contract.storage_deposit(None);
+    }
+
+    #[test]
+    fn approvals_draw_from_storage_balance() {
+        let mut contract = setup();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+        let before = available(&contract);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        let initial_storage = env::storage_usage();
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        // the first approval also pays for the record of the copy
+        let cost = Balance::from(env::storage_usage() - initial_storage) * env::storage_byte_cost();
+        let entry_cost = Balance::from(bytes_for_approved_account_id(&marketplace().into()))
+            * env::storage_byte_cost();
+        assert!(cost > entry_cost);
+        // the attached yocto is added to the balance
+        assert_eq!(available(&contract), before - cost + 1);
+        assert!(test_utils::get_created_receipts().is_empty());
+
+        // the record stays to keep next_approval_id, only the entry is released
+        contract.nft_revoke(PASS.to_string(), marketplace().into());
+        assert_eq!(available(&contract), before - cost + entry_cost + 1);
+        assert!(test_utils::get_created_receipts().is_empty());
+
+        // approving again only pays for the entry
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+        assert_eq!(available(&contract), before - cost + 2);
+    }
+
+    #[test]
+    fn transfer_of_a_copy_never_approved_stores_no_approval_record() {
+        let mut contract = setup();
+        testing_env!(get_context(bob().to_string(), STORAGE_DEPOSIT));
+        contract.storage_deposit(None);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().to_string(), PASS.to_string(), None, None);
+
+        let bob_pass = "catchcon.gold.bob.near".to_string();
+        assert_eq!(
+            contract.nft_token(bob_pass.clone()).unwrap().owner_id,
+            bob().to_string()
+        );
+        assert!(contract.approvals_by_token_id.get(&bob_pass).is_none());
+    }
+
+    #[test]
+    fn withdraw_returns_available_balance() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.storage_withdraw(None);
+
+        assert_eq!(available(&contract), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't unregister the account while it owns tokens")]
+    fn unregister_with_tokens_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.storage_unregister(None);
+    }
+
+    #[test]
+    fn unregister_without_tokens() {
+        let mut contract = setup();
+
+        let deposit = min_balance(&contract);
+        testing_env!(get_context(bob().to_string(), deposit));
+        contract.storage_deposit(None);
+
+        testing_env!(get_context(bob().to_string(), 1));
+        assert!(contract.storage_unregister(None));
+        assert!(contract.storage_balance_of(bob()).is_none());
+    }
+
+    #[test]
+    fn transfer_after_expiry_releases_the_lease_storage() {
+        let mut contract = setup();
+        let min = min_balance(&contract);
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.storage_deposit(None);
+        let before = available(&contract);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_lease(PASS.to_string(), bob(), 3_600 * 1_000_000_000);
+        assert!(available(&contract) < before);
+
+        let mut context = get_context(alice().to_string(), 1);
+        context.block_timestamp = 3_600 * 1_000_000_000;
+        testing_env!(context);
+        contract.nft_transfer(carol().to_string(), PASS.to_string(), None, None);
+
+        // the yocto attached to nft_lease is added to the balance
+        assert_eq!(available(&contract), before + 1);
+
+        assert_eq!(
+            contract.storage_withdraw(None),
+            StorageBalance {
+                total: min.into(),
+                available: 0.into(),
+            }
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use crate::*;
+
+pub type PlanId = String;
+
+/// Season pass paid every period in the payment token
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubscriptionPlan {
+    pub price: U128,
+    pub period_sec: u64,
+    //TokenId of form event_id.token_id minted to the subscriber
+    pub pass_token_id: TokenId,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Subscription {
+    //Unix epoch in milliseconds until which the pass is valid
+    pub paid_until: u64,
+    //false once expire_subscriptions found it lapsed, a payment reactivates it
+    pub active: bool,
+}
+
+impl Contract {
+    //extends the subscription of the payer by one period from paid_until or from now if it lapsed,
+    //mints the pass on the first payment and returns the excess. No NEAR comes with the FT, the
+    //storage is paid from the storage balance of the payer
+    pub(crate) fn internal_subscribe(
+        &mut self,
+        payer_id: AccountId,
+        plan_id: PlanId,
+        amount: Balance,
+    ) -> PromiseOrValue<U128> {
+        let plan = self
+            .subscription_plans
+            .get(&plan_id)
+            .unwrap_or_else(|| env::panic(b"Invalid plan id"));
+
+        require!(
+            amount >= plan.price.0,
+            format!("Attached amount is less than the price {}", plan.price.0)
+        );
+
+        let initial_storage = env::storage_usage();
+        if !self.internal_owns_token(&payer_id, &plan.pass_token_id) {
+            self.internal_assert_catch_player(&payer_id);
+            self.internal_mint_copy(
+                &payer_id,
+                &plan.pass_token_id,
+                format!("{} subscribed to {}", &payer_id, &plan_id),
+            );
+        }
+
+        let key = (payer_id.clone(), plan_id.clone());
+        let paid_until = self
+            .subscriptions
+            .get(&key)
+            .map_or(0, |subscription| subscription.paid_until);
+
+        let subscription = Subscription {
+            paid_until: std::cmp::max(paid_until, now_ms()) + plan.period_sec * 1_000,
+            active: true,
+        };
+
+        self.subscriptions.insert(&key, &subscription);
+        self.pass_expiry_by_token_id.insert(
+            &build_full_token_id(plan.pass_token_id.clone(), payer_id.clone()),
+            &subscription.paid_until,
+        );
+
+        // the price is escrowed as proceeds of the event of the pass
+        self.internal_record_ft_payment(&plan.pass_token_id, payer_id.clone(), plan.price.0);
+        self.internal_spend_storage_balance(
+            &payer_id,
+            env::storage_usage().saturating_sub(initial_storage),
+        );
+
+        CatchEventLogVariant::SubscriptionPaid(SubscriptionLog {
+            account_id: payer_id,
+            plan_id,
+            paid_until: subscription.paid_until,
+        })
+        .emit();
+
+        PromiseOrValue::Value(U128(amount - plan.price.0))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, creates or updates the plan, subscribers pay with a subscribe
+    /// ft_transfer_call on the payment token
+    pub fn set_subscription_plan(&mut self, plan_id: PlanId, plan: SubscriptionPlan) {
+        self.assert_owner();
+
+        require!(self.payment_token_id.is_some(), "Payment token is not set");
+        require!(
+            self.tokens_by_id.get(&plan.pass_token_id).is_some(),
+            "Token does not exist"
+        );
+        require!(plan.period_sec > 0, "Plan period can't be zero");
+
+        self.subscription_plans.insert(&plan_id, &plan);
+
+        CatchEventLogVariant::SubscriptionPlanUpdated(SubscriptionPlanLog {
+            plan_id,
+            plan: Some(plan),
+        })
+        .emit();
+    }
+
+    /// Owner only, no new payments are accepted, paid periods stay valid
+    pub fn remove_subscription_plan(&mut self, plan_id: PlanId) {
+        self.assert_owner();
+
+        require!(
+            self.subscription_plans.remove(&plan_id).is_some(),
+            "Invalid plan id"
+        );
+
+        CatchEventLogVariant::SubscriptionPlanUpdated(SubscriptionPlanLog {
+            plan_id,
+            plan: None,
+        })
+        .emit();
+    }
+
+    pub fn get_subscription_plans(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<(PlanId, SubscriptionPlan)> {
+        let start = u128::from(from_index.unwrap_or(U128(0)));
+
+        self.subscription_plans
+            .iter()
+            .skip(start as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+
+    pub fn get_subscription(&self, account_id: AccountId, plan_id: PlanId) -> Option<Subscription> {
+        self.subscriptions.get(&(account_id, plan_id))
+    }
+
+    /// Anyone can call it, marks the lapsed subscriptions of the accounts as inactive,
+    /// their passes expire at paid_until, returns the accounts which were downgraded
+    pub fn expire_subscriptions(
+        &mut self,
+        plan_id: PlanId,
+        account_ids: Vec<AccountId>,
+    ) -> Vec<AccountId> {
+        let mut expired = vec![];
+
+        for account_id in account_ids {
+            let key = (account_id.clone(), plan_id.clone());
+
+            let mut subscription = match self.subscriptions.get(&key) {
+                Some(subscription)
+                    if subscription.active && subscription.paid_until <= now_ms() =>
+                {
+                    subscription
+                }
+                _ => continue,
+            };
+
+            subscription.active = false;
+            self.subscriptions.insert(&key, &subscription);
+
+            CatchEventLogVariant::SubscriptionExpired(SubscriptionLog {
+                account_id: account_id.clone(),
+                plan_id: plan_id.clone(),
+                paid_until: subscription.paid_until,
+            })
+            .emit();
+
+            expired.push(account_id);
+        }
+
+        expired
+    }
+}
+
+#[cfg(test)]
+mod subscriptions_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    const MONTH_SEC: u64 = 30 * 86_400;
+    const MONTH_MS: u64 = MONTH_SEC * 1_000;
+    const PASS: &str = "season.pass";
+
+    /// the owner runs a monthly plan for the season pass at 100, alice has no storage balance
+    fn setup_without_storage() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "season", &["pass"], 1_000);
+        contract.set_subscription_plan(
+            "monthly".to_string(),
+            SubscriptionPlan {
+                price: U128(100),
+                period_sec: MONTH_SEC,
+                pass_token_id: PASS.to_string(),
+            },
+        );
+
+        contract
+    }
+
+    /// alice pays the storage of her pass from her storage balance
+    fn setup() -> Contract {
+        let mut contract = setup_without_storage();
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.storage_deposit(None);
+
+        contract
+    }
+
+    fn storage_total(contract: &Contract) -> Balance {
+        contract
+            .storage_deposits
+            .get(alice().as_ref())
+            .unwrap()
+            .total
+    }
+
+    fn pay_at(contract: &mut Contract, timestamp_ms: u64, amount: u128) -> PromiseOrValue<U128> {
+        let mut context = get_context(ft().to_string(), 0);
+        context.block_timestamp = timestamp_ms * 1_000_000;
+        testing_env!(context);
+
+        let msg = r#"{"action":"subscribe","plan_id":"monthly"}"#;
+        contract.ft_on_transfer(alice(), U128(amount), msg.to_string())
+    }
+
+    fn paid_until(contract: &Contract) -> u64 {
+        contract
+            .get_subscription(alice().into(), "monthly".to_string())
+            .unwrap()
+            .paid_until
+    }
+
+    #[test]
+    fn first_payment_mints_pass() {
+        let mut contract = setup();
+
+        let unused = pay_at(&mut contract, 1_000, 150);
+        assert!(matches!(unused, PromiseOrValue::Value(U128(50))));
+
+        assert!(contract.internal_owns_token(&alice().into(), &PASS.to_string()));
+        assert_eq!(paid_until(&contract), 1_000 + MONTH_MS);
+        assert_eq!(contract.get_ft_proceeds("season".to_string()).0, 100);
+    }
+
+    #[test]
+    fn first_payment_spends_the_storage_balance_of_the_payer() {
+        let mut contract = setup();
+        let total = storage_total(&contract);
+
+        pay_at(&mut contract, 1_000, 100);
+        let spent = total - storage_total(&contract);
+        assert!(spent > 0);
+
+        //renewals don't grow the state
+        pay_at(&mut contract, 2_000, 100);
+        assert_eq!(total - storage_total(&contract), spent);
+    }
+
+    #[test]
+    #[should_panic(expected = "alice.near must cover")]
+    fn first_payment_without_storage_balance_fails() {
+        let mut contract = setup_without_storage();
+
+        pay_at(&mut contract, 1_000, 100);
+    }
+
+    #[test]
+    fn renewal_before_expiry_extends_from_paid_until() {
+        let mut contract = setup();
+
+        pay_at(&mut contract, 1_000, 100);
+        pay_at(&mut contract, 1_000 + MONTH_MS / 2, 100);
+
+        assert_eq!(paid_until(&contract), 1_000 + 2 * MONTH_MS);
+        assert_eq!(contract.nft_supply_for_owner(alice().into()).0, 1);
+    }
+
+    #[test]
+    fn renewal_after_gap_extends_from_now() {
+        let mut contract = setup();
+
+        pay_at(&mut contract, 1_000, 100);
+
+        let later = 1_000 + 3 * MONTH_MS;
+        testing_env!({
+            let mut context = get_context(bob().to_string(), 0);
+            context.block_timestamp = later * 1_000_000;
+            context
+        });
+        let expired = contract.expire_subscriptions("monthly".to_string(), vec![alice().into()]);
+        assert_eq!(expired, vec![alice().to_string()]);
+        let token = contract.tokens_by_id.get(&PASS.to_string()).unwrap();
+        assert!(contract.internal_is_copy_expired(
+            &build_full_token_id(PASS.to_string(), alice().into()),
+            &token
+        ));
+
+        pay_at(&mut contract, later, 100);
+        assert_eq!(paid_until(&contract), later + MONTH_MS);
+        assert!(
+            contract
+                .get_subscription(alice().into(), "monthly".to_string())
+                .unwrap()
+                .active
+        );
+    }
+
+    #[test]
+    fn active_subscription_is_not_expired() {
+        let mut contract = setup();
+
+        pay_at(&mut contract, 1_000, 100);
+
+        testing_env!(get_context(bob().to_string(), 0));
+        let expired = contract.expire_subscriptions("monthly".to_string(), vec![alice().into()]);
+        assert!(expired.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached amount is less than the price 100")]
+    fn underpayment_fails() {
+        let mut contract = setup();
+
+        pay_at(&mut contract, 1_000, 99);
+    }
+}
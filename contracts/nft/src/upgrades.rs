@@ -0,0 +1,319 @@
+use crate::*;
+
+const GAS_FOR_UPGRADE_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_UPGRADE: Gas = 10_000_000_000_000;
+
+#[ext_contract(ext_upgrades_self)]
+pub trait UpgradesResolver {
+    fn resolve_upgrade_payment(&mut self, amount: U128);
+}
+
+/// Level-ups of the copies of a token class, costs[n] is the FT cost of going from level n to n + 1
+/// and the level cap is the number of costs
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UpgradeConfig {
+    pub costs: Vec<U128>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MetadataChange {
+    //Unix epoch in milliseconds
+    pub updated_at: u64,
+    pub change: String,
+}
+
+impl Contract {
+    //levels up the token of form event_id.token_id.owner_id owned by the payer, the cost goes to the
+    //upgrade treasury which burns it, returns the excess
+    pub(crate) fn internal_upgrade(
+        &mut self,
+        payer_id: AccountId,
+        token_id: TokenId,
+        amount: Balance,
+    ) -> PromiseOrValue<U128> {
+        let (class_token_id, owner_id) = resolve_token_id(token_id.clone());
+
+        require!(
+            payer_id == owner_id && self.internal_owns_token(&owner_id, &class_token_id),
+            "Only the token owner can upgrade the token"
+        );
+
+        let config = self
+            .upgrade_config_by_token_id
+            .get(&class_token_id)
+            .unwrap_or_else(|| env::panic(b"Token can't be upgraded"));
+
+        let level = self.level_by_token_id.get(&token_id).unwrap_or(0);
+        let cost = config
+            .costs
+            .get(level as usize)
+            .unwrap_or_else(|| env::panic(b"Token is at the max level"))
+            .0;
+
+        require!(
+            amount >= cost,
+            format!("Attached amount is less than the cost {}", cost)
+        );
+
+        let level = level + 1;
+        self.level_by_token_id.insert(&token_id, &level);
+
+        let mut history = self
+            .metadata_history_by_token_id
+            .get(&token_id)
+            .unwrap_or_default();
+        history.push(MetadataChange {
+            updated_at: env::block_timestamp() / 1_000_000,
+            change: format!("level {} -> {}", level - 1, level),
+        });
+        self.metadata_history_by_token_id
+            .insert(&token_id, &history);
+
+        self.ft_liabilities += cost;
+        self.internal_route_upgrade_payment(cost);
+
+        CatchEventLogVariant::CatchUpgrade(UpgradeLog {
+            token_id,
+            owner_id,
+            level,
+            cost: cost.into(),
+        })
+        .emit();
+
+        PromiseOrValue::Value(U128(amount - cost))
+    }
+
+    fn internal_route_upgrade_payment(&self, amount: Balance) {
+        ext_ft::ft_transfer(
+            self.upgrade_treasury_id.clone().unwrap(),
+            amount.into(),
+            Some("Token upgrade".to_string()),
+            self.payment_token_id.as_ref().unwrap(),
+            1,
+            GAS_FOR_UPGRADE_TRANSFER,
+        )
+        .then(ext_upgrades_self::resolve_upgrade_payment(
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_UPGRADE,
+        ));
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Owner only, FT paid for upgrades is sent to the treasury A/c of the payment token,
+    /// which burns it with treasury_burn
+    pub fn set_upgrade_treasury(&mut self, treasury_id: ValidAccountId) {
+        self.assert_owner();
+
+        self.upgrade_treasury_id = Some(treasury_id.into());
+    }
+
+    /// Owner only, None stops upgrades of the token class (event_id.token_id), levels reached stay
+    pub fn set_upgrade_config(&mut self, token_id: TokenId, config: Option<UpgradeConfig>) {
+        self.assert_owner();
+
+        require!(
+            self.tokens_by_id.get(&token_id).is_some(),
+            "Token does not exist"
+        );
+
+        match config {
+            Some(config) => {
+                require!(self.payment_token_id.is_some(), "Payment token is not set");
+                require!(
+                    self.upgrade_treasury_id.is_some(),
+                    "Upgrade treasury is not set"
+                );
+                require!(config.costs.len() <= u16::MAX as usize, "Too many levels");
+
+                self.upgrade_config_by_token_id.insert(&token_id, &config);
+            }
+            None => {
+                self.upgrade_config_by_token_id.remove(&token_id);
+            }
+        }
+    }
+
+    pub fn get_upgrade_config(&self, token_id: TokenId) -> Option<UpgradeConfig> {
+        self.upgrade_config_by_token_id.get(&token_id)
+    }
+
+    //level of the token of form event_id.token_id.owner_id
+    pub fn get_token_level(&self, token_id: TokenId) -> u16 {
+        self.level_by_token_id.get(&token_id).unwrap_or(0)
+    }
+
+    pub fn get_metadata_history(&self, token_id: TokenId) -> Vec<MetadataChange> {
+        self.metadata_history_by_token_id
+            .get(&token_id)
+            .unwrap_or_default()
+    }
+
+    //upgrade payments which couldn't be sent to the treasury
+    pub fn get_unrouted_upgrade_payments(&self) -> U128 {
+        self.unrouted_upgrade_payments.into()
+    }
+
+    /// Owner only, sends the upgrade payments which couldn't be sent to the treasury again
+    pub fn route_upgrade_payments(&mut self) {
+        self.assert_owner();
+
+        let amount = std::mem::take(&mut self.unrouted_upgrade_payments);
+        require!(amount > 0, "No upgrade payments to route");
+
+        self.internal_route_upgrade_payment(amount);
+    }
+
+    //keeps the payment for route_upgrade_payments if the transfer to the treasury failed
+    #[private]
+    pub fn resolve_upgrade_payment(&mut self, amount: U128) {
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            self.internal_settle_ft_liability(amount.0);
+            return;
+        }
+
+        env::log(
+            format!(
+                "Routing {} to the upgrade treasury failed, it can be routed again",
+                amount.0
+            )
+            .as_bytes(),
+        );
+
+        self.unrouted_upgrade_payments += amount.0;
+    }
+}
+
+#[cfg(test)]
+mod upgrades_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const HERO: &str = "genesis.hero.alice.near";
+
+    /// alice holds a genesis hero, which levels up twice for 100 and 250
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        testing_env!(get_context(nft().to_string(), 1));
+        contract.set_payment_token(ft());
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        contract.set_upgrade_treasury("treasury.near".try_into().unwrap());
+        create_event(&mut contract, "genesis", &["hero"], 10);
+        contract.set_upgrade_config(
+            "genesis.hero".to_string(),
+            Some(UpgradeConfig {
+                costs: vec![U128(100), U128(250)],
+            }),
+        );
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "genesis.hero".to_string());
+
+        contract
+    }
+
+    fn upgrade(contract: &mut Contract, sender_id: ValidAccountId, amount: u128) -> u128 {
+        testing_env!(get_context(ft().to_string(), 0));
+        let msg = format!(r#"{{"action":"upgrade","token_id":"{}"}}"#, HERO);
+
+        match contract.ft_on_transfer(sender_id, U128(amount), msg) {
+            PromiseOrValue::Value(unused) => unused.0,
+            PromiseOrValue::Promise(_) => panic!("Unexpected promise"),
+        }
+    }
+
+    #[test]
+    fn upgrade_levels_up_and_returns_excess() {
+        let mut contract = setup();
+
+        assert_eq!(upgrade(&mut contract, alice(), 150), 50);
+
+        assert_eq!(contract.get_token_level(HERO.to_string()), 1);
+        assert_eq!(contract.nft_token(HERO.to_string()).unwrap().level, 1);
+        assert_eq!(
+            contract.get_metadata_history(HERO.to_string()),
+            vec![MetadataChange {
+                updated_at: 0,
+                change: "level 0 -> 1".to_string(),
+            }]
+        );
+        assert!(test_utils::get_logs()[0].contains(r#""event":"catch_upgrade""#));
+
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(receipts.contains("treasury.near"));
+        assert!(receipts.contains("resolve_upgrade_payment"));
+
+        assert_eq!(upgrade(&mut contract, alice(), 250), 0);
+        assert_eq!(contract.get_token_level(HERO.to_string()), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token is at the max level")]
+    fn upgrade_past_cap_fails() {
+        let mut contract = setup();
+
+        upgrade(&mut contract, alice(), 100);
+        upgrade(&mut contract, alice(), 250);
+        upgrade(&mut contract, alice(), 1_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the token owner can upgrade the token")]
+    fn upgrade_of_other_token_fails() {
+        let mut contract = setup();
+
+        upgrade(&mut contract, bob(), 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "Attached amount is less than the cost 100")]
+    fn underpaid_upgrade_fails() {
+        let mut contract = setup();
+
+        upgrade(&mut contract, alice(), 99);
+    }
+
+    #[test]
+    fn failed_routing_is_kept() {
+        let mut contract = setup();
+
+        testing_env!(
+            get_context(nft().to_string(), 0),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        contract.resolve_upgrade_payment(U128(100));
+        assert_eq!(contract.get_unrouted_upgrade_payments().0, 100);
+
+        testing_env!(get_context(nft().to_string(), 0));
+        contract.route_upgrade_payments();
+        assert_eq!(contract.get_unrouted_upgrade_payments().0, 0);
+    }
+
+    #[test]
+    fn level_moves_with_the_token() {
+        let mut contract = setup();
+
+        upgrade(&mut contract, alice(), 100);
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_transfer(bob().into(), HERO.to_string(), None, None);
+
+        assert_eq!(contract.get_token_level(HERO.to_string()), 0);
+        assert_eq!(
+            contract.get_token_level("genesis.hero.bob.near".to_string()),
+            1
+        );
+    }
+}
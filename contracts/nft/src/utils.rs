@@ -23,44 +23,42 @@ impl Contract {
         account_id: &AccountId,
         token: &Token,
     ) -> bool {
-        let has_dependency =
-            token.token_dependency_by_id.len() > 0 || token.event_dependency_by_id.len() > 0;
-
-        if has_dependency {
-            let token_set = if let Some(token_set) = self.tokens_per_owner.get(&account_id) {
-                token_set
-            } else {
-                return false;
-            };
-
-            if !token
-                .token_dependency_by_id
-                .iter()
-                .all(|token_id| token_set.contains(&token_id))
-            {
-                return false;
-            } else {
-                let mut event_passes: Vec<TokenId>;
-
-                for event_id in &token.event_dependency_by_id {
-                    event_passes = if let Some(event) = self.events_by_id.get(&event_id) {
-                        event.event_passes
-                    } else {
-                        return false;
-                    };
-
-                    if !event_passes
-                        .iter()
-                        .any(|token_id| token_set.contains(token_id))
-                    {
-                        return false;
-                    }
-                }
-                true
-            }
-        } else {
-            true
-        }
+        let (missing_token_ids, missing_event_ids) =
+            self.internal_missing_dependencies(account_id, token);
+
+        missing_token_ids.is_empty() && missing_event_ids.is_empty()
+    }
+
+    /// Tokens of token_dependency_by_id the account neither owns nor borrows and events of
+    /// event_dependency_by_id it holds no valid pass of
+    pub(crate) fn internal_missing_dependencies(
+        &self,
+        account_id: &AccountId,
+        token: &Token,
+    ) -> (Vec<TokenId>, Vec<EventId>) {
+        let token_set = self.tokens_per_owner.get(account_id);
+        let owns = |token_id: &TokenId| {
+            token_set
+                .as_ref()
+                .is_some_and(|token_set| token_set.contains(token_id))
+                || self.internal_borrowed_copy(account_id, token_id).is_some()
+        };
+
+        let missing_token_ids = token
+            .token_dependency_by_id
+            .iter()
+            .filter(|token_id| !owns(token_id))
+            .cloned()
+            .collect();
+
+        let missing_event_ids = token
+            .event_dependency_by_id
+            .iter()
+            .filter(|event_id| !self.internal_holds_valid_pass(account_id, event_id))
+            .cloned()
+            .collect();
+
+        (missing_token_ids, missing_event_ids)
     }
 }
 
@@ -73,19 +71,60 @@ pub(crate) fn assert_valid_id(id: &str) {
     );
 }
 
+/// TokenId of a minted token, at most MAX_TOKEN_ID_LEN bytes of Ascii alphanumerics and - . _ :
+/// of form prefix.name, token ids end up in storage keys, logs and urls of the marketplace
+pub(crate) fn assert_valid_token_id(token_id: &str) {
+    require!(!token_id.is_empty(), "TokenId is empty");
+    require!(
+        token_id.len() <= MAX_TOKEN_ID_LEN,
+        format!("TokenId is longer than {} bytes", MAX_TOKEN_ID_LEN)
+    );
+    require!(
+        token_id
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'.' | b'_' | b':')),
+        "TokenId can only contain Ascii alphanumerics and - . _ :"
+    );
+    require!(
+        token_id.matches('.').count() == 1,
+        "TokenId must be of form prefix.name"
+    );
+}
+
+/// Max length of a transfer memo in bytes
+pub const MAX_MEMO_LEN: usize = 256;
+
+/// The memo of a transfer is only logged, it is capped at MAX_MEMO_LEN bytes to keep logs small
+pub(crate) fn assert_valid_memo(memo: &Option<String>) {
+    require!(
+        memo.as_ref().is_none_or(|memo| memo.len() <= MAX_MEMO_LEN),
+        format!("Memo can't be longer than {} bytes", MAX_MEMO_LEN)
+    );
+}
+
+/// panics unless the account is a direct sub-account of this contract, as created at mint
+pub(crate) fn assert_catch_sub_account(account_id: &AccountId) {
+    let is_sub_account = account_id
+        .strip_suffix(&format!(".{}", env::current_account_id()))
+        .is_some_and(|name| !name.is_empty() && !name.contains('.'));
+
+    require!(
+        is_sub_account,
+        format!("{} is not a Catch sub-account", account_id)
+    );
+}
+
 /// Resolve token_id of form event_id.token_id.owner_id to Full TokenId and OwnerId
 pub(crate) fn resolve_token_id(token_id: TokenId) -> (TokenId, AccountId) {
-    let (event_id, token_id_and_owner_id) = token_id
-        .split_once(".")
-        .unwrap_or_else(|| env::panic(b"Invalid TokenId"));
-
-    let (token_id, owner_id) = token_id_and_owner_id
-        .split_once(".")
-        .unwrap_or_else(|| env::panic(b"Invalid TokenId"));
+    try_resolve_token_id(&token_id).unwrap_or_else(|| env::panic(b"Invalid TokenId"))
+}
 
-    let token_id = format!("{}.{}", event_id, token_id);
+/// resolve_token_id for ids which may be malformed, None instead of a panic
+pub(crate) fn try_resolve_token_id(token_id: &str) -> Option<(TokenId, AccountId)> {
+    let (event_id, token_id_and_owner_id) = token_id.split_once('.')?;
+    let (token_id, owner_id) = token_id_and_owner_id.split_once('.')?;
 
-    return (token_id, owner_id.to_string());
+    Some((format!("{}.{}", event_id, token_id), owner_id.to_string()))
 }
 
 /// Build token_id of form event_id.token_id.owner_id from TokenId and OwnerId
@@ -93,12 +132,35 @@ pub(crate) fn build_full_token_id(token_id: TokenId, owner_id: AccountId) -> Tok
     format!("{}.{}", token_id, owner_id)
 }
 
+/// Catch marketplace of the network the contract is deployed on, told by the suffix of
+/// the contract account, panics for accounts of other networks
+pub(crate) fn catch_marketplace_of_network() -> AccountId {
+    let contract_id = env::current_account_id();
+
+    let marketplace = if contract_id.ends_with(".test.near") {
+        CATCH_MARKETPLACE_CONTRACT_LOCAL_NET
+    } else if contract_id.ends_with(".near") {
+        CATCH_MARKETPLACE_CONTRACT
+    } else if contract_id.ends_with(".testnet") {
+        CATCH_MARKETPLACE_CONTRACT_TESTNET
+    } else {
+        env::panic(format!("Can't tell the network of {}", contract_id).as_bytes())
+    };
+
+    marketplace.to_string()
+}
+
+/// current block time in milliseconds, like the expiry of tokens
+pub(crate) fn now_ms() -> u64 {
+    env::block_timestamp() / 1_000_000
+}
+
 /// returns true if token has expired
 pub(crate) fn internal_is_token_expired(token: &Token) -> bool {
     if let Some(t) = token.expires_at {
         if let Some(t) = t.checked_mul(1_000_000) {
             // Multiply by 1_000_000 to convert milli to nano seconds
-            return t < env::block_timestamp();
+            return t <= env::block_timestamp();
         } else {
             env::panic(b"Time Stamp Overflow, Invalid ");
         }
@@ -160,6 +222,11 @@ pub mod test_utils {
     pub fn marketplace() -> ValidAccountId {
         ValidAccountId::try_from("marketplace.near").unwrap()
     }
+    pub fn ft() -> ValidAccountId {
+        ValidAccountId::try_from("ft.catchlabs.near").unwrap()
+    }
+
+    pub const STORAGE_DEPOSIT: Balance = 10u128.pow(24); // 1 Near, excess is refunded
 
     pub fn get_context(predecessor_account_id: AccountId, attached_deposit: Balance) -> VMContext {
         VMContext {
@@ -183,7 +250,44 @@ pub mod test_utils {
     }
 
     pub fn create_contract() -> Contract {
-        todo!()
+        Contract::new_default_meta(nft(), None)
+    }
+
+    pub fn token_metadata(copies: u64) -> TokenMetadata {
+        TokenMetadata {
+            title: "Catch Pass".to_string(),
+            description: None,
+            media: "ipfs://media".to_string(),
+            media_hash: Base64VecU8::from([5_u8; 32].to_vec()),
+            copies: Some(copies),
+            issued_at: None,
+            expires_at: None,
+            starts_at: None,
+            updated_at: None,
+            lat: None,
+            lng: None,
+            extra: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    /// Organises the event as the predecessor with the given token ids and no dependencies
+    pub fn create_event(contract: &mut Contract, event_id: &str, token_ids: &[&str], copies: u64) {
+        let tokens = token_ids
+            .iter()
+            .map(|token_id| TokenInfo {
+                token_id: token_id.to_string(),
+                token_metadata: token_metadata(copies),
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            })
+            .collect();
+
+        contract.organise_event(event_id.to_string(), tokens, None);
     }
 }
 
@@ -211,6 +315,45 @@ mod test_utility_fn {
         assert_valid_id(&id);
     }
 
+    #[test]
+    fn success_assert_valid_token_id() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        assert_valid_token_id("genesis.hero_1:gold-edition");
+    }
+
+    #[test]
+    #[should_panic(expected = "TokenId can only contain Ascii alphanumerics and - . _ :")]
+    fn panic_assert_valid_token_id_with_slash() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        assert_valid_token_id("genesis.hero/1");
+    }
+
+    #[test]
+    #[should_panic(expected = "TokenId can only contain Ascii alphanumerics and - . _ :")]
+    fn panic_assert_valid_token_id_with_unicode() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        assert_valid_token_id("genesis.héro");
+    }
+
+    #[test]
+    #[should_panic(expected = "TokenId is longer than 64 bytes")]
+    fn panic_assert_valid_token_id_too_long() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        assert_valid_token_id(&format!("genesis.{}", "a".repeat(57)));
+    }
+
+    #[test]
+    #[should_panic(expected = "TokenId must be of form prefix.name")]
+    fn panic_assert_valid_token_id_without_prefix() {
+        testing_env!(get_context(carol().to_string(), 0));
+
+        assert_valid_token_id("hero");
+    }
+
     #[test]
     fn success_resolve_token_id() {
         testing_env!(get_context(carol().to_string(), 0));
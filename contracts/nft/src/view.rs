@@ -2,6 +2,93 @@
 
 use crate::*;
 
+pub const MAX_TOKENS_BATCH: usize = 100;
+
+/// Dependencies of a token class the account has and lacks, error is set instead for
+/// unknown tokens
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DependencyReport {
+    pub owned_token_ids: Vec<TokenId>,
+    pub missing_token_ids: Vec<TokenId>,
+    //events the account holds a valid pass of
+    pub attended_event_ids: Vec<EventId>,
+    pub missing_event_ids: Vec<EventId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DependencyEntry {
+    pub token_id: TokenId,
+    //1 for the direct dependencies of the token
+    pub depth: u8,
+    //None if no account was given
+    pub owned: Option<bool>,
+}
+
+/// Token dependencies of a token class, transitively and each token once
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DependencyTree {
+    pub entries: Vec<DependencyEntry>,
+    pub cycle_detected: bool,
+    //tokens of the first cycle found, in dependency order
+    pub cycle_token_ids: Vec<TokenId>,
+}
+
+impl Contract {
+    //walks the dependencies of the last token of the path depth first, a dependency already on
+    //the path closes a cycle and isn't walked again
+    fn internal_walk_dependencies(
+        &self,
+        path: &mut Vec<TokenId>,
+        max_depth: u8,
+        account_id: &Option<AccountId>,
+        tree: &mut DependencyTree,
+    ) {
+        let depth = path.len() as u8;
+        if depth > max_depth {
+            return;
+        }
+
+        let dependencies = match self.tokens_by_id.get(path.last().unwrap()) {
+            Some(token) => token.token_dependency_by_id,
+            None => return,
+        };
+
+        for token_id in dependencies {
+            if let Some(start) = path
+                .iter()
+                .position(|path_token_id| path_token_id == &token_id)
+            {
+                if !tree.cycle_detected {
+                    tree.cycle_detected = true;
+                    tree.cycle_token_ids = path[start..].to_vec();
+                }
+                continue;
+            }
+
+            if tree.entries.iter().any(|entry| entry.token_id == token_id) {
+                continue;
+            }
+
+            tree.entries.push(DependencyEntry {
+                token_id: token_id.clone(),
+                depth,
+                owned: account_id
+                    .as_ref()
+                    .map(|account_id| self.internal_owns_token(account_id, &token_id)),
+            });
+
+            path.push(token_id);
+            self.internal_walk_dependencies(path, max_depth, account_id, tree);
+            path.pop();
+        }
+    }
+}
+
 #[near_bindgen]
 impl Contract {
     pub fn is_eligible_to_mint_token(&self, account_id: AccountId, token_id: TokenId) -> bool {
@@ -23,6 +110,95 @@ impl Contract {
         internal_is_token_expired(&token)
     }
 
+    //expiry of the copy of form event_id.token_id.owner_id, renewals included,
+    //tokens without expires_at never expire
+    pub fn nft_is_expired(&self, token_id: TokenId) -> bool {
+        let (class_token_id, _) = resolve_token_id(token_id.clone());
+
+        let token = self
+            .tokens_by_id
+            .get(&class_token_id)
+            .unwrap_or_else(|| env::panic(b"No Token"));
+
+        self.internal_is_copy_expired(&token_id, &token)
+    }
+
+    //false before starts_at of the copy of form event_id.token_id.owner_id, inactive copies
+    //can't be transferred, approved or used as a dependency
+    pub fn nft_is_active(&self, token_id: TokenId) -> bool {
+        let (class_token_id, _) = resolve_token_id(token_id);
+
+        require!(self.tokens_by_id.get(&class_token_id).is_some(), "No Token");
+
+        self.internal_is_active(&class_token_id)
+    }
+
+    //nft_token for up to MAX_TOKENS_BATCH copies of form event_id.token_id.owner_id, in the
+    //order given with None for the copies which don't exist
+    pub fn nft_tokens_batch(&self, token_ids: Vec<TokenId>) -> Vec<Option<JsonToken>> {
+        require!(
+            token_ids.len() <= MAX_TOKENS_BATCH,
+            format!("Can't view more than {} tokens at once", MAX_TOKENS_BATCH)
+        );
+
+        token_ids
+            .into_iter()
+            .map(|token_id| self.nft_token(token_id))
+            .collect()
+    }
+
+    //dependencies of the token class of form event_id.token_id the account has and lacks,
+    //a missing token doesn't panic so a batch of reports can be rendered
+    pub fn check_dependencies(&self, account_id: AccountId, token_id: TokenId) -> DependencyReport {
+        let token = match self.tokens_by_id.get(&token_id) {
+            Some(token) => token,
+            None => {
+                return DependencyReport {
+                    error: Some(format!("No token {}", token_id)),
+                    ..Default::default()
+                }
+            }
+        };
+
+        let (missing_token_ids, missing_event_ids) =
+            self.internal_missing_dependencies(&account_id, &token);
+
+        DependencyReport {
+            owned_token_ids: token
+                .token_dependency_by_id
+                .into_iter()
+                .filter(|token_id| !missing_token_ids.contains(token_id))
+                .collect(),
+            missing_token_ids,
+            attended_event_ids: token
+                .event_dependency_by_id
+                .into_iter()
+                .filter(|event_id| !missing_event_ids.contains(event_id))
+                .collect(),
+            missing_event_ids,
+            error: None,
+        }
+    }
+
+    //token dependencies of the token class of form event_id.token_id down to max_depth, owned
+    //tells whether the account holds each of them
+    pub fn resolve_dependency_tree(
+        &self,
+        token_id: TokenId,
+        max_depth: u8,
+        account_id: Option<AccountId>,
+    ) -> DependencyTree {
+        let mut tree = DependencyTree {
+            entries: vec![],
+            cycle_detected: false,
+            cycle_token_ids: vec![],
+        };
+
+        self.internal_walk_dependencies(&mut vec![token_id], max_depth, &account_id, &mut tree);
+
+        tree
+    }
+
     // General Purpose fn
     pub fn can_token_be_minted(&self, token_id: TokenId) -> bool {
         let token = self
@@ -33,3 +209,277 @@ impl Contract {
         token.copies_minted < token.max_copies
     }
 }
+
+#[cfg(test)]
+mod view_tests {
+    use super::*;
+    use crate::approval::NonFungibleTokenCore as _;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{testing_env, VMContext};
+
+    const PASS: &str = "catchcon.gold.alice.near";
+    // Unix epoch in milliseconds
+    const STARTS_AT: u64 = 1_000;
+
+    fn context_at(predecessor: ValidAccountId, deposit: Balance, timestamp: u64) -> VMContext {
+        let mut context = get_context(predecessor.to_string(), deposit);
+        context.block_timestamp = timestamp;
+        context
+    }
+
+    /// alice holds a pass of catchcon which starts at STARTS_AT, the owner holds one too
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), STORAGE_DEPOSIT));
+        let mut contract = create_contract();
+        contract.internal_approve_marketplace(marketplace().into(), None);
+
+        let mut token_metadata = token_metadata(10);
+        token_metadata.starts_at = Some(STARTS_AT);
+        contract.organise_event(
+            "catchcon".to_string(),
+            vec![TokenInfo {
+                token_id: "gold".to_string(),
+                token_metadata,
+                token_dependency_by_id: vec![],
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+        contract.nft_event_register(nft().to_string(), "catchcon.gold".to_string());
+
+        testing_env!(get_context(alice().to_string(), STORAGE_DEPOSIT));
+        contract.nft_event_register(alice().to_string(), "catchcon.gold".to_string());
+
+        contract
+    }
+
+    #[test]
+    fn token_is_active_from_starts_at() {
+        let mut contract = setup();
+        let start = STARTS_AT * 1_000_000;
+
+        testing_env!(context_at(alice(), 1, start - 1));
+        assert!(!contract.nft_is_active(PASS.to_string()));
+
+        testing_env!(context_at(alice(), 1, start));
+        assert!(contract.nft_is_active(PASS.to_string()));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    fn token_view_has_the_details() {
+        let mut contract = setup();
+
+        testing_env!(context_at(alice(), STORAGE_DEPOSIT, STARTS_AT * 1_000_000));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+
+        let token = contract.nft_token(PASS.to_string()).unwrap();
+        assert_eq!(token.owner_id, alice().to_string());
+        assert_eq!(token.metadata.starts_at, Some(STARTS_AT));
+        assert_eq!(
+            token.approved_account_ids,
+            HashMap::from([(marketplace().to_string(), 0)])
+        );
+        assert_eq!((token.copies_minted, token.max_copies), (2, 10));
+        assert!(token.royalty.is_empty());
+        assert_eq!(token.expires_at, None);
+
+        // speculative ids are None, malformed ones included
+        for token_id in [
+            "catchcon.gold.carol.near",
+            "catchcon.silver.alice.near",
+            "catchcon",
+            "",
+        ] {
+            assert!(contract.nft_token(token_id.to_string()).is_none());
+        }
+    }
+
+    #[test]
+    fn batch_keeps_the_order_and_missing_slots() {
+        let contract = setup();
+
+        let tokens = contract.nft_tokens_batch(vec![
+            "catchcon.gold.carol.near".to_string(),
+            PASS.to_string(),
+            "catchcon.gold.nft.catchlabs.near".to_string(),
+        ]);
+        let owners: Vec<Option<AccountId>> = tokens
+            .into_iter()
+            .map(|token| token.map(|token| token.owner_id))
+            .collect();
+        assert_eq!(
+            owners,
+            vec![None, Some(alice().to_string()), Some(nft().to_string())]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't view more than 100 tokens at once")]
+    fn batch_over_the_cap_fails() {
+        let contract = setup();
+
+        contract.nft_tokens_batch(vec![PASS.to_string(); 101]);
+    }
+
+    #[test]
+    fn dependency_report_lists_owned_and_missing() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        create_event(&mut contract, "arena", &["entry"], 10);
+        contract.organise_event(
+            "finals".to_string(),
+            vec![TokenInfo {
+                token_id: "seat".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: vec![
+                    "catchcon.gold".to_string(),
+                    "arena.entry".to_string(),
+                ],
+                event_dependency_by_id: vec!["catchcon".to_string(), "arena".to_string()],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+
+        testing_env!(context_at(alice(), 0, STARTS_AT * 1_000_000));
+        assert_eq!(
+            contract.check_dependencies(alice().into(), "finals.seat".to_string()),
+            DependencyReport {
+                owned_token_ids: vec!["catchcon.gold".to_string()],
+                missing_token_ids: vec!["arena.entry".to_string()],
+                attended_event_ids: vec!["catchcon".to_string()],
+                missing_event_ids: vec!["arena".to_string()],
+                error: None,
+            }
+        );
+
+        assert_eq!(
+            contract
+                .check_dependencies(alice().into(), "finals.box".to_string())
+                .error,
+            Some("No token finals.box".to_string())
+        );
+    }
+
+    fn organise_with_dependencies(contract: &mut Contract, event_id: &str, dependencies: &[&str]) {
+        contract.organise_event(
+            event_id.to_string(),
+            vec![TokenInfo {
+                token_id: "pass".to_string(),
+                token_metadata: token_metadata(10),
+                token_dependency_by_id: dependencies.iter().map(|id| id.to_string()).collect(),
+                event_dependency_by_id: vec![],
+                external_dependencies: vec![],
+                royalty: Default::default(),
+                soulbound: false,
+            }],
+            None,
+        );
+    }
+
+    #[test]
+    fn dependency_tree_is_walked_transitively() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        organise_with_dependencies(&mut contract, "badge", &["catchcon.gold"]);
+        organise_with_dependencies(&mut contract, "item", &["badge.pass", "catchcon.gold"]);
+
+        let tree =
+            contract.resolve_dependency_tree("item.pass".to_string(), 5, Some(alice().into()));
+        assert!(!tree.cycle_detected);
+        assert_eq!(
+            tree.entries,
+            vec![
+                DependencyEntry {
+                    token_id: "badge.pass".to_string(),
+                    depth: 1,
+                    owned: Some(false),
+                },
+                DependencyEntry {
+                    token_id: "catchcon.gold".to_string(),
+                    depth: 2,
+                    owned: Some(true),
+                },
+            ]
+        );
+
+        // the walk stops at max_depth
+        let tree = contract.resolve_dependency_tree("item.pass".to_string(), 1, None);
+        assert_eq!(tree.entries.len(), 2);
+        assert_eq!(tree.entries[1].depth, 1);
+        assert_eq!(tree.entries[1].owned, None);
+    }
+
+    #[test]
+    fn dependency_cycle_is_reported() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        organise_with_dependencies(&mut contract, "first", &["second.pass"]);
+        organise_with_dependencies(&mut contract, "second", &["first.pass"]);
+
+        let tree = contract.resolve_dependency_tree("first.pass".to_string(), 10, None);
+        assert!(tree.cycle_detected);
+        assert_eq!(
+            tree.cycle_token_ids,
+            vec!["first.pass".to_string(), "second.pass".to_string()]
+        );
+        assert_eq!(tree.entries.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token loop.pass can't depend on itself")]
+    fn token_depending_on_itself_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        organise_with_dependencies(&mut contract, "loop", &["loop.pass"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not yet active")]
+    fn transfer_before_start_fails() {
+        let mut contract = setup();
+
+        testing_env!(context_at(alice(), 1, STARTS_AT * 1_000_000 - 1));
+        contract.nft_transfer(bob().into(), PASS.to_string(), None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not yet active")]
+    fn approve_before_start_fails() {
+        let mut contract = setup();
+
+        testing_env!(context_at(
+            alice(),
+            STORAGE_DEPOSIT,
+            STARTS_AT * 1_000_000 - 1
+        ));
+        contract.nft_approve(PASS.to_string(), marketplace().into(), None);
+    }
+
+    #[test]
+    fn owner_transfers_before_start() {
+        let mut contract = setup();
+
+        testing_env!(context_at(nft(), 1, 0));
+        contract.nft_transfer(
+            bob().into(),
+            "catchcon.gold.nft.catchlabs.near".to_string(),
+            None,
+            None,
+        );
+        assert!(contract
+            .nft_token("catchcon.gold.bob.near".to_string())
+            .is_some());
+    }
+}
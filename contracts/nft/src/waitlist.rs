@@ -0,0 +1,329 @@
+use crate::*;
+
+impl Contract {
+    //queues the caller for a pass of the sold out event, the deposit less the storage of the
+    //entry is held in escrow until the account is promoted or leaves the waitlist
+    pub(crate) fn internal_join_waitlist(
+        &mut self,
+        event_id: EventId,
+        event: Event,
+        price: Balance,
+    ) {
+        let metadata = event
+            .metadata
+            .unwrap_or_else(|| env::panic(b"Passes are issued for events of create_event"));
+        require!(!event.cancelled, "Event is cancelled");
+        require!(now_ms() < metadata.starts_at, "Event has already started");
+
+        let account_id = env::predecessor_account_id();
+        self.internal_assert_catch_player(&account_id);
+        self.assert_not_blacklisted(&account_id);
+        require!(
+            !self.internal_is_registered(&event_id, &account_id),
+            "Already registered for the event"
+        );
+
+        let key = (event_id, account_id);
+        require!(
+            self.waitlist_escrow.get(&key).is_none(),
+            "Already on the waitlist"
+        );
+
+        let initial_storage = env::storage_usage();
+
+        let mut waitlist = self.waitlists.get(&key.0).unwrap_or_else(|| {
+            Vector::new(
+                StorageKey::WaitlistsInner {
+                    event_id_hash: hash_id(&key.0),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        waitlist.push(&key.1);
+        self.waitlists.insert(&key.0, &waitlist);
+        self.waitlist_escrow.insert(&key, &0);
+
+        let storage_cost =
+            env::storage_byte_cost() * Balance::from(env::storage_usage() - initial_storage);
+        let deposit = env::attached_deposit();
+        require!(
+            deposit >= price + storage_cost,
+            format!(
+                "Must attach {} yoctoNEAR to cover the price and storage",
+                price + storage_cost
+            )
+        );
+
+        self.waitlist_escrow.insert(&key, &(deposit - storage_cost));
+    }
+
+    //takes the entry at index off the waitlist keeping the order of the accounts behind it,
+    //returns the account and its escrow
+    fn internal_remove_from_waitlist(
+        &mut self,
+        event_id: &EventId,
+        index: u64,
+    ) -> (AccountId, Balance) {
+        let mut waitlist = self.waitlists.get(event_id).unwrap();
+        let account_id = waitlist.get(index).unwrap();
+
+        for i in index..waitlist.len() - 1 {
+            waitlist.replace(i, &waitlist.get(i + 1).unwrap());
+        }
+        waitlist.pop();
+        self.waitlists.insert(event_id, &waitlist);
+
+        let escrow = self
+            .waitlist_escrow
+            .remove(&(event_id.clone(), account_id.clone()))
+            .unwrap();
+
+        (account_id, escrow)
+    }
+
+    //gives the seat freed by a burnt pass to the head of the waitlist, the price and the storage
+    //of the pass are paid from its escrow and the rest is refunded. Accounts which can't take the
+    //pass anymore are refunded and skipped. Returns the storage cost the escrow didn't cover
+    pub(crate) fn internal_promote_from_waitlist(&mut self, event_id: &EventId) -> Balance {
+        let event = match self.events_by_id.get(event_id) {
+            Some(event) => event,
+            None => return 0,
+        };
+        let token_id = format!("{}.{}", event_id, EVENT_PASS_TOKEN);
+
+        while self
+            .waitlists
+            .get(event_id)
+            .is_some_and(|waitlist| !waitlist.is_empty())
+        {
+            let initial_storage = env::storage_usage();
+            let (account_id, escrow) = self.internal_remove_from_waitlist(event_id, 0);
+
+            let price = match event.price {
+                Some(price)
+                    if price.0 <= escrow
+                        && !self.blacklist.contains(&account_id)
+                        && !self.internal_is_registered(event_id, &account_id) =>
+                {
+                    price.0
+                }
+                _ => {
+                    env::log(
+                        format!(
+                            "{} can't take the pass anymore, the escrow is refunded",
+                            &account_id
+                        )
+                        .as_bytes(),
+                    );
+                    let storage_freed = initial_storage.saturating_sub(env::storage_usage());
+                    Promise::new(account_id)
+                        .transfer(escrow + Balance::from(storage_freed) * env::storage_byte_cost());
+                    continue;
+                }
+            };
+
+            self.internal_mint_copy(
+                &account_id,
+                &token_id,
+                format!(
+                    "{} was promoted from the waitlist of {}",
+                    &account_id, event_id
+                ),
+            );
+            self.internal_escrow_pass_price(event_id, &account_id, price);
+
+            // the storage of the entry is freed along, it was paid when joining
+            let storage_cost = Balance::from(env::storage_usage().saturating_sub(initial_storage))
+                * env::storage_byte_cost();
+            let storage_freed = Balance::from(initial_storage.saturating_sub(env::storage_usage()))
+                * env::storage_byte_cost();
+            let charged = std::cmp::min(price + storage_cost, escrow);
+
+            if escrow - charged + storage_freed > 0 {
+                Promise::new(account_id).transfer(escrow - charged + storage_freed);
+            }
+
+            return price + storage_cost - charged;
+        }
+
+        0
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Takes the caller off the waitlist of the event and refunds the deposit held for it
+    pub fn leave_waitlist(&mut self, event_id: EventId) -> Promise {
+        let account_id = env::predecessor_account_id();
+
+        let index = self
+            .waitlists
+            .get(&event_id)
+            .and_then(|waitlist| waitlist.iter().position(|queued| queued == account_id))
+            .unwrap_or_else(|| env::panic(b"Not on the waitlist"));
+
+        let initial_storage = env::storage_usage();
+        let (account_id, escrow) = self.internal_remove_from_waitlist(&event_id, index as u64);
+        let storage_freed = initial_storage.saturating_sub(env::storage_usage());
+
+        Promise::new(account_id)
+            .transfer(escrow + Balance::from(storage_freed) * env::storage_byte_cost())
+    }
+
+    /// Position of the account on the waitlist of the event, 1 is promoted next
+    pub fn waitlist_position(&self, event_id: EventId, account_id: AccountId) -> Option<u64> {
+        self.waitlists
+            .get(&event_id)?
+            .iter()
+            .position(|queued| queued == account_id)
+            .map(|index| index as u64 + 1)
+    }
+
+    pub fn waitlist_length(&self, event_id: EventId) -> u64 {
+        self.waitlists
+            .get(&event_id)
+            .map_or(0, |waitlist| waitlist.len())
+    }
+}
+
+#[cfg(test)]
+mod waitlist_tests {
+    use super::*;
+    use crate::utils::test_utils::*;
+    use near_sdk::MockedBlockchain;
+    use near_sdk::{test_utils, testing_env};
+
+    const EVENT: &str = "catchcon";
+    const PRICE: Balance = 10u128.pow(24);
+
+    fn dave() -> AccountId {
+        "dave.near".to_string()
+    }
+
+    fn setup() -> Contract {
+        testing_env!(get_context(nft().to_string(), 0));
+        let mut contract = create_contract();
+
+        testing_env!(get_context(carol().to_string(), STORAGE_DEPOSIT));
+        contract.create_event(
+            EVENT.to_string(),
+            EventMetadata {
+                title: "Catch Con".to_string(),
+                description: None,
+                venue: None,
+                starts_at: 1_000,
+                ends_at: 2_000,
+                max_capacity: 1,
+                media: None,
+                media_hash: None,
+                reference: None,
+                reference_hash: None,
+            },
+            None,
+        );
+
+        testing_env!(get_context(carol().to_string(), 1));
+        contract.set_event_price(EVENT.to_string(), Some(U128(PRICE)));
+
+        testing_env!(get_context(alice().to_string(), PRICE + STORAGE_DEPOSIT));
+        contract.register_for_event(EVENT.to_string(), None);
+
+        contract
+    }
+
+    fn join(contract: &mut Contract, account_id: AccountId) {
+        testing_env!(get_context(account_id, PRICE + STORAGE_DEPOSIT));
+        contract.register_for_event(EVENT.to_string(), Some(true));
+    }
+
+    #[test]
+    fn burn_promotes_the_head_of_the_waitlist() {
+        let mut contract = setup();
+
+        join(&mut contract, bob().into());
+        join(&mut contract, dave());
+        assert_eq!(contract.waitlist_length(EVENT.to_string()), 2);
+        assert_eq!(
+            contract.waitlist_position(EVENT.to_string(), dave()),
+            Some(2)
+        );
+
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn("catchcon.pass.alice.near".to_string());
+
+        assert!(contract
+            .nft_token("catchcon.pass.bob.near".to_string())
+            .is_some());
+        assert_eq!(
+            contract
+                .events_by_id
+                .get(&EVENT.to_string())
+                .unwrap()
+                .passes_issued,
+            1
+        );
+        assert_eq!(contract.waitlist_length(EVENT.to_string()), 1);
+        assert_eq!(
+            contract.waitlist_position(EVENT.to_string(), dave()),
+            Some(1)
+        );
+        assert!(contract
+            .waitlist_position(EVENT.to_string(), bob().into())
+            .is_none());
+
+        // the price is paid from the escrow and held until the event ends
+        let receipts =
+            near_sdk::serde_json::to_string(&test_utils::get_created_receipts()).unwrap();
+        assert!(!receipts.contains(r#""receiver_id":"carol.near""#));
+        assert!(receipts.contains(r#""receiver_id":"bob.near""#));
+        assert_eq!(
+            contract.get_near_paid(EVENT.to_string(), bob().into()).0,
+            PRICE
+        );
+        assert_eq!(contract.get_near_proceeds(EVENT.to_string()).0, 2 * PRICE);
+    }
+
+    #[test]
+    fn leave_waitlist_refunds_the_escrow() {
+        let mut contract = setup();
+
+        join(&mut contract, bob().into());
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.leave_waitlist(EVENT.to_string());
+
+        assert_eq!(contract.waitlist_length(EVENT.to_string()), 0);
+        assert_eq!(test_utils::get_created_receipts().len(), 1);
+
+        // nobody is left to take the seat
+        testing_env!(get_context(alice().to_string(), 1));
+        contract.nft_burn("catchcon.pass.alice.near".to_string());
+        assert_eq!(
+            contract
+                .events_by_id
+                .get(&EVENT.to_string())
+                .unwrap()
+                .passes_issued,
+            0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Already on the waitlist")]
+    fn second_join_fails() {
+        let mut contract = setup();
+
+        join(&mut contract, bob().into());
+        join(&mut contract, bob().into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Not on the waitlist")]
+    fn leave_without_joining_fails() {
+        let mut contract = setup();
+
+        testing_env!(get_context(bob().to_string(), 0));
+        contract.leave_waitlist(EVENT.to_string());
+    }
+}